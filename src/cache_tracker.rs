@@ -8,6 +8,7 @@ use std::time;
 use crate::cache::{BoundedMemoryCache, CachePutResponse, KeySpace};
 use crate::env;
 use crate::rdd::Rdd;
+use crate::scheduler::{fail_task, task_failed};
 use crate::serializable_traits::Data;
 use crate::serialized_data_capnp::serialized_data;
 use crate::split::Split;
@@ -344,7 +345,18 @@ impl CacheTracker {
             }
             self.loading.insert(key);
 
-            let res: Vec<_> = rdd.compute(split.clone()).unwrap().collect();
+            let res: Vec<_> = match rdd.compute(split.clone()) {
+                Ok(iter) => iter.collect(),
+                Err(error) => {
+                    fail_task(error);
+                    vec![]
+                }
+            };
+            if task_failed() {
+                // The partition is incomplete, the task fails without caching it.
+                self.loading.remove(&key);
+                return Box::new(res.into_iter());
+            }
             let res_bytes = bincode::serialize(&res).unwrap();
             let put_response = self
                 .cache
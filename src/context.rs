@@ -1,5 +1,7 @@
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::fs;
+use std::future::Future;
 use std::io::Write;
 use std::net::{Ipv4Addr, SocketAddrV4, TcpStream};
 use std::ops::Range;
@@ -11,17 +13,22 @@ use std::sync::{
 };
 use std::time::{Duration, Instant};
 
+use crate::dataframe::{DataFrame, PlanError, RddTable, Row, Schema, TableProvider};
 use crate::error::{Error, Result};
 use crate::executor::{Executor, Signal};
-use crate::io::ReaderConfiguration;
+use crate::io::{CsvFile, CsvOptions, FileListing, ObjectFileRdd};
+use crate::io::{JsonLinesFile, JsonLinesOptions, ParquetTable, ReaderConfiguration, TextFileRdd};
 use crate::partial::{ApproximateEvaluator, PartialResult};
 use crate::rdd::{ParallelCollection, Rdd, RddBase, UnionRdd};
-use crate::scheduler::{DistributedScheduler, LocalScheduler, NativeScheduler, TaskContext};
+use crate::scheduler::{
+    DistributedScheduler, JobCancellation, LocalScheduler, NativeScheduler, TaskContext,
+};
 use crate::serializable_traits::{Data, SerFunc};
 use crate::serialized_data_capnp::serialized_data;
 use crate::{env, hosts, utils, Fn, SerArc};
 use log::error;
 use once_cell::sync::OnceCell;
+use parking_lot::RwLock;
 use simplelog::*;
 use uuid::Uuid;
 use Schedulers::*;
@@ -85,6 +92,39 @@ impl Schedulers {
         }
     }
 
+    async fn run_job_async<T: Data, U: Data, F>(
+        self,
+        func: Arc<F>,
+        final_rdd: Arc<dyn Rdd<Item = T>>,
+        partitions: Vec<usize>,
+        cancellation: Arc<JobCancellation>,
+    ) -> Result<Vec<U>>
+    where
+        F: SerFunc((TaskContext, Box<dyn Iterator<Item = T>>)) -> U,
+    {
+        let op_name = final_rdd.get_op_name();
+        log::info!("starting `{}` job", op_name);
+        let start = Instant::now();
+        let res = match self {
+            Distributed(distributed) => {
+                distributed
+                    .run_job_async(func, final_rdd, partitions, false, Some(cancellation))
+                    .await
+            }
+            Local(local) => {
+                local
+                    .run_job_async(func, final_rdd, partitions, false, Some(cancellation))
+                    .await
+            }
+        };
+        log::info!(
+            "`{}` job finished, took {}s",
+            op_name,
+            start.elapsed().as_secs()
+        );
+        res
+    }
+
     fn run_approximate_job<T: Data, U: Data, R, F, E>(
         &self,
         func: Arc<F>,
@@ -126,6 +166,8 @@ pub struct Context {
     distributed_driver: bool,
     /// this context/session temp work dir
     work_dir: PathBuf,
+    /// tables which can be queried with SQL
+    tables: RwLock<HashMap<String, Arc<dyn TableProvider>>>,
 }
 
 impl Drop for Context {
@@ -198,6 +240,7 @@ impl Context {
             address_map: vec![SocketAddrV4::new(Ipv4Addr::LOCALHOST, 0)],
             distributed_driver: false,
             work_dir: job_work_dir,
+            tables: RwLock::new(HashMap::new()),
         }))
     }
 
@@ -299,6 +342,7 @@ impl Context {
             address_map,
             distributed_driver: true,
             work_dir: job_work_dir,
+            tables: RwLock::new(HashMap::new()),
         }))
     }
 
@@ -442,6 +486,10 @@ impl Context {
     }
 
     /// Load from a distributed source and turns it into a parallel collection.
+    ///
+    /// The source is either the configuration of a built-in reader, such as
+    /// `LocalFsReaderConfig`, or any `io::DataSource`, which is read with one partition per
+    /// split.
     pub fn read_source<F, C, I: Data, O: Data>(
         self: &Arc<Self>,
         config: C,
@@ -454,6 +502,149 @@ impl Context {
         config.make_reader(self.clone(), func)
     }
 
+    /// Read the lines of the text files of a path: a single file, a directory or a glob
+    /// pattern such as `logs/2026-*/part-*`.
+    ///
+    /// Every host reads the files from its local file system, cut into byte ranges so that
+    /// each host gets at least `min_partitions` partitions. Lines are read lazily and a line
+    /// crossing the boundary of two ranges is read once, by the range where it starts.
+    ///
+    /// Like all the readers and writers, the path may be the URI of a file system of the
+    /// `fs::FileSystemRegistry`, such as `mem:///logs`.
+    pub fn text_file<P: Into<PathBuf>>(
+        self: &Arc<Self>,
+        path: P,
+        min_partitions: usize,
+    ) -> SerArc<dyn Rdd<Item = String>> {
+        let listing = FileListing::new(vec![path.into()]);
+        SerArc::new(TextFileRdd::<String>::new(
+            self.clone(),
+            listing,
+            min_partitions,
+        ))
+    }
+
+    /// Read the object files written by `Rdd::save_as_object_file` to a path, a directory or a
+    /// glob pattern.
+    ///
+    /// The files, which must be readable from the driver, are indexed there and their headers
+    /// checked to hold items of type `T`. As the check relies on `std::any::type_name`, the
+    /// files should be read by a binary built with the same compiler. Their blocks are dealt
+    /// among the partitions of all the hosts, which read them from their own file system.
+    pub fn object_file<T: Data, P: Into<PathBuf>>(
+        self: &Arc<Self>,
+        path: P,
+    ) -> Result<SerArc<dyn Rdd<Item = T>>> {
+        let listing = FileListing::new(vec![path.into()]);
+        let rdd = ObjectFileRdd::<T>::try_new(self.clone(), listing, num_cpus::get())?;
+        Ok(SerArc::new(rdd))
+    }
+
+    /// Read the CSV files of a path, a directory or a glob pattern.
+    ///
+    /// The header and a sample of the rows are read from the driver to infer the types of the
+    /// columns, then the files are read from the local file system of every host like text
+    /// files. The rows can be read as typed values, deserialized into structs, or as a
+    /// DataFrame.
+    pub fn read_csv<P: Into<PathBuf>>(
+        self: &Arc<Self>,
+        path: P,
+        options: CsvOptions,
+    ) -> Result<CsvFile> {
+        CsvFile::try_new(self.clone(), path.into(), options)
+    }
+
+    /// Read the JSON Lines files of a path, a directory or a glob pattern, parsing each line
+    /// into a `T`.
+    ///
+    /// The files are split on line boundaries and read from the local file system of every
+    /// host like text files. Lines which can't be parsed are handled as set in the options.
+    pub fn read_json_lines<T: Data, P: Into<PathBuf>>(
+        self: &Arc<Self>,
+        path: P,
+        options: JsonLinesOptions,
+    ) -> Result<JsonLinesFile<T>> {
+        JsonLinesFile::try_new(self.clone(), path.into(), options)
+    }
+
+    /// Read the Parquet files of a path, either a single file or a directory of `.parquet`
+    /// files, as a DataFrame.
+    ///
+    /// The schema is taken from the first file, which must be readable from the driver. The
+    /// files are read from the local file system of every host, with their row groups dealt
+    /// among the partitions of the host. Only the columns used by the query are decoded and
+    /// row groups are skipped when their statistics show that no row matches the filters.
+    pub fn read_parquet<P: Into<PathBuf>>(self: &Arc<Self>, path: P) -> Result<DataFrame> {
+        let path = path.into();
+        let name = format!("parquet:{}", path.display());
+        let table = ParquetTable::try_new(self.clone(), path)?;
+        Ok(DataFrame::scan(&name, Arc::new(table)))
+    }
+
+    /// Create a DataFrame from an RDD of rows matching the schema.
+    pub fn create_dataframe(
+        self: &Arc<Self>,
+        rdd: SerArc<dyn Rdd<Item = Row>>,
+        schema: Schema,
+    ) -> DataFrame {
+        let name = format!("rdd_{}", rdd.get_rdd_id());
+        DataFrame::scan(&name, Arc::new(RddTable::new(rdd, schema)))
+    }
+
+    /// Create a DataFrame reading the rows of a table.
+    pub fn read_table(self: &Arc<Self>, name: &str, source: Arc<dyn TableProvider>) -> DataFrame {
+        DataFrame::scan(name, source)
+    }
+
+    /// Register a table under the given name, replacing any table with the same name.
+    pub fn register_table(&self, name: &str, source: Arc<dyn TableProvider>) {
+        self.tables.write().insert(name.to_owned(), source);
+    }
+
+    /// Register an RDD of rows matching the schema as a table.
+    pub fn register_rdd(&self, name: &str, rdd: SerArc<dyn Rdd<Item = Row>>, schema: Schema) {
+        self.register_table(name, Arc::new(RddTable::new(rdd, schema)));
+    }
+
+    /// Register as a table the rows decoded from each item of a distributed source.
+    pub fn register_source<F, C, I: Data>(
+        self: &Arc<Self>,
+        name: &str,
+        config: C,
+        decoder: F,
+        schema: Schema,
+    ) where
+        F: SerFunc(I) -> Vec<Row>,
+        C: ReaderConfiguration<I>,
+    {
+        let rdd = config
+            .make_reader(self.clone(), decoder)
+            .flat_map(Fn!(|rows: Vec<Row>| {
+                Box::new(rows.into_iter()) as Box<dyn Iterator<Item = Row>>
+            }));
+        self.register_rdd(name, rdd, schema);
+    }
+
+    /// Remove a table, returning it if it was registered.
+    pub fn deregister_table(&self, name: &str) -> Option<Arc<dyn TableProvider>> {
+        self.tables.write().remove(name)
+    }
+
+    /// Returns a DataFrame reading the rows of a registered table.
+    pub fn table(&self, name: &str) -> Result<DataFrame> {
+        match self.tables.read().get(name) {
+            Some(source) => Ok(DataFrame::scan(name, source.clone())),
+            None => Err(PlanError::UnknownTable(name.to_owned()).into()),
+        }
+    }
+
+    /// Returns a DataFrame computing the result of a SQL query over the registered tables.
+    ///
+    /// See the [sql module](dataframe/sql/index.html) for the supported syntax.
+    pub fn sql(&self, query: &str) -> Result<DataFrame> {
+        crate::dataframe::sql::plan_query(query, |name| self.table(name))
+    }
+
     pub fn run_job<T: Data, U: Data, F>(
         self: &Arc<Self>,
         rdd: Arc<dyn Rdd<Item = T>>,
@@ -505,6 +696,24 @@ impl Context {
         )
     }
 
+    /// Run a job on the given partitions from the async runtime, for the asynchronous actions.
+    /// The job is stopped once `cancellation` is cancelled.
+    pub(crate) fn run_job_async<T: Data, U: Data, F>(
+        self: &Arc<Self>,
+        rdd: Arc<dyn Rdd<Item = T>>,
+        func: F,
+        partitions: Vec<usize>,
+        cancellation: Arc<JobCancellation>,
+    ) -> impl Future<Output = Result<Vec<U>>> + Send
+    where
+        F: SerFunc(Box<dyn Iterator<Item = T>>) -> U,
+    {
+        let cl = Fn!(move |(_task_context, iter)| (func)(iter));
+        self.scheduler
+            .clone()
+            .run_job_async(Arc::new(cl), rdd, partitions, cancellation)
+    }
+
     /// Run a job that can return approximate results. Returns a partial result
     /// (how partial depends on whether the job was finished before or after timeout).
     pub(crate) fn run_approximate_job<T: Data, U: Data, R, F, E>(
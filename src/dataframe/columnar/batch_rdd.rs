@@ -0,0 +1,332 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use crate::dataframe::columnar::{batch_from_columns, batch_to_rows, kernels, rows_to_batch};
+use crate::dataframe::columnar::{from_arrow_schema, ArrowBatch};
+use crate::dataframe::{DataFrame, DataType, Expr, PlanError, Row, Schema, TableProvider};
+use crate::error::{Error, Result, StdResult};
+use crate::partitioner::HashPartitioner;
+use crate::rdd::{PairRdd, Rdd, ResultRdd};
+use crate::scheduler::fail_task;
+use crate::{Fn, SerArc};
+use arrow::array::{ArrayRef, BooleanArray};
+use arrow::datatypes::Schema as ArrowSchema;
+use arrow::record_batch::RecordBatch;
+use serde_derive::{Deserialize, Serialize};
+
+/// Error computing a record batch in a task, such as an Arrow error evaluating an
+/// expression.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BatchError {
+    pub message: String,
+}
+
+impl From<Error> for BatchError {
+    fn from(error: Error) -> Self {
+        BatchError {
+            message: error.to_string(),
+        }
+    }
+}
+
+impl From<BatchError> for Error {
+    fn from(error: BatchError) -> Self {
+        Error::RecordBatch(error.message)
+    }
+}
+
+type BatchResult = StdResult<ArrowBatch, BatchError>;
+
+/// RDD of Arrow record batches holding rows of a known schema.
+///
+/// The columns of the batches may use any Arrow type which converts to the type of the
+/// schema, e.g. `Int32` for an `Int64` column. A batch which can't be computed is an error,
+/// which the actions return.
+#[derive(Clone)]
+pub struct RecordBatchRdd {
+    rdd: SerArc<dyn Rdd<Item = BatchResult>>,
+    schema: Schema,
+}
+
+impl RecordBatchRdd {
+    pub fn new(rdd: SerArc<dyn Rdd<Item = ArrowBatch>>, schema: Schema) -> Self {
+        let rdd = rdd.map(Fn!(|batch: ArrowBatch| -> BatchResult { Ok(batch) }));
+        RecordBatchRdd::from_results(rdd, schema)
+    }
+
+    fn from_results(rdd: SerArc<dyn Rdd<Item = BatchResult>>, schema: Schema) -> Self {
+        RecordBatchRdd { rdd, schema }
+    }
+
+    /// Wrap batches with the given Arrow schema, failing if some column type is not supported.
+    pub fn from_arrow(
+        rdd: SerArc<dyn Rdd<Item = ArrowBatch>>,
+        schema: &ArrowSchema,
+    ) -> Result<Self> {
+        Ok(RecordBatchRdd::new(rdd, from_arrow_schema(schema)?))
+    }
+
+    /// Group the rows of each partition into batches of at most `batch_size` rows.
+    pub fn from_rows(rdd: SerArc<dyn Rdd<Item = Row>>, schema: Schema, batch_size: usize) -> Self {
+        assert!(batch_size > 0, "batch size must be positive");
+        let batch_schema = schema.clone();
+        let rdd = rdd.map_partitions(Fn!(move |rows: Box<dyn Iterator<Item = Row>>| {
+            let (mut rows, schema) = (rows, batch_schema.clone());
+            Box::new(std::iter::from_fn(move || {
+                let rows = rows.by_ref().take(batch_size).collect::<Vec<_>>();
+                if rows.is_empty() {
+                    return None;
+                }
+                let batch = rows_to_batch(&rows, &schema).map_err(BatchError::from);
+                Some(batch.map(ArrowBatch::new))
+            })) as Box<dyn Iterator<Item = BatchResult>>
+        }));
+        RecordBatchRdd::from_results(rdd, schema)
+    }
+
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// The batches, or the errors computing them.
+    pub fn rdd(&self) -> SerArc<dyn Rdd<Item = BatchResult>> {
+        self.rdd.clone()
+    }
+
+    /// Returns the batches with only the columns at the given indices, in that order.
+    pub fn project(&self, columns: &[usize]) -> Result<Self> {
+        if columns.is_empty() {
+            return Err(PlanError::Unsupported("projection without columns".to_owned()).into());
+        }
+        if let Some(&i) = columns.iter().find(|&&i| i >= self.schema.len()) {
+            return Err(PlanError::UnknownColumn(format!("#{}", i)).into());
+        }
+        let schema = self.schema.project(columns);
+        let batch_schema = schema.clone();
+        let columns = columns.to_vec();
+        let rdd = self.rdd.map(Fn!(move |batch: BatchResult| -> BatchResult {
+            let batch = batch?;
+            let arrays = columns
+                .iter()
+                .map(|&i| batch.column(i).clone())
+                .collect::<Vec<_>>();
+            Ok(ArrowBatch::new(batch_from_columns(&batch_schema, arrays)?))
+        }));
+        Ok(RecordBatchRdd::from_results(rdd, schema))
+    }
+
+    /// Returns the rows for which the predicate is true.
+    pub fn filter(&self, predicate: Expr) -> Result<Self> {
+        let predicate = self.resolve(predicate)?;
+        match predicate.data_type(&self.schema)? {
+            DataType::Boolean | DataType::Null => {}
+            _ => return Err(PlanError::TypeMismatch(predicate.to_string()).into()),
+        }
+        Ok(RecordBatchRdd::from_results(
+            filter_batches(&self.rdd, vec![predicate], self.schema.clone()),
+            self.schema.clone(),
+        ))
+    }
+
+    /// Returns batches with a column computed by each expression.
+    pub fn select(&self, exprs: Vec<Expr>) -> Result<Self> {
+        let exprs = exprs
+            .into_iter()
+            .map(|expr| self.resolve(expr))
+            .collect::<Result<Vec<_>>>()?;
+        let fields = exprs
+            .iter()
+            .map(|expr| expr.to_field(&self.schema))
+            .collect::<Result<_>>()?;
+        let schema = Schema::new(fields);
+        let (input_schema, output_schema) = (self.schema.clone(), schema.clone());
+        let rdd = self.rdd.map(Fn!(move |batch: BatchResult| -> BatchResult {
+            let batch = batch?;
+            let arrays = exprs
+                .iter()
+                .map(|expr| kernels::evaluate(expr, &input_schema, &batch))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(ArrowBatch::new(batch_from_columns(&output_schema, arrays)?))
+        }));
+        Ok(RecordBatchRdd::from_results(rdd, schema))
+    }
+
+    /// Redistribute the rows into `num_partitions` partitions by the hash of the given
+    /// columns, so that rows with equal keys end up in the same partition. The batches are
+    /// shuffled in the Arrow IPC format.
+    pub fn repartition_by(&self, columns: &[usize], num_partitions: usize) -> Result<Self> {
+        if let Some(&i) = columns.iter().find(|&&i| i >= self.schema.len()) {
+            return Err(PlanError::UnknownColumn(format!("#{}", i)).into());
+        }
+        let columns = columns.to_vec();
+        let buckets = self.rdd.flat_map(Fn!(move |batch: BatchResult| {
+            // An error goes to the first partition, to be returned by the actions.
+            let buckets = batch.and_then(|batch| {
+                split_by_hash(&batch, &columns, num_partitions).map_err(BatchError::from)
+            });
+            let iter: Box<dyn Iterator<Item = (usize, BatchResult)>> = match buckets {
+                Ok(buckets) => Box::new(buckets.into_iter().map(|(i, batch)| (i, Ok(batch)))),
+                Err(error) => Box::new(std::iter::once((0, Err(error)))),
+            };
+            iter
+        }));
+        let rdd = buckets.partition_by_key(Box::new(HashPartitioner::<usize>::new(num_partitions)));
+        Ok(RecordBatchRdd::from_results(rdd, self.schema.clone()))
+    }
+
+    /// Returns the rows of the batches, or the errors computing them.
+    pub fn to_rows(&self) -> SerArc<dyn Rdd<Item = StdResult<Row, BatchError>>> {
+        self.rdd.flat_map(Fn!(|batch: BatchResult| {
+            let iter: Box<dyn Iterator<Item = StdResult<Row, BatchError>>> = match batch {
+                Ok(batch) => Box::new(batch_to_rows(&batch).into_iter().map(Ok)),
+                Err(error) => Box::new(std::iter::once(Err(error))),
+            };
+            iter
+        }))
+    }
+
+    /// Returns a DataFrame over the batches. Projections and filters pushed down to the scan
+    /// are evaluated with the vectorized kernels.
+    pub fn to_dataframe(&self) -> DataFrame {
+        let name = format!("record_batches_{}", self.rdd.get_rdd_id());
+        DataFrame::scan(&name, Arc::new(RecordBatchTable::new(self.clone())))
+    }
+
+    pub fn num_rows(&self) -> Result<u64> {
+        let counts = self
+            .rdd
+            .map(Fn!(|batch: BatchResult| -> StdResult<u64, BatchError> {
+                Ok(batch?.num_rows() as u64)
+            }));
+        Ok(counts.try_collect()?.into_iter().sum())
+    }
+
+    pub fn collect(&self) -> Result<Vec<RecordBatch>> {
+        Ok(self
+            .rdd
+            .try_collect()?
+            .into_iter()
+            .map(ArrowBatch::into_inner)
+            .collect())
+    }
+
+    fn resolve(&self, expr: Expr) -> Result<Expr> {
+        if expr.contains_aggregate() {
+            return Err(PlanError::InvalidAggregate(expr.to_string()).into());
+        }
+        expr.resolve(&self.schema)
+    }
+}
+
+/// Keep the rows of the batches matching all the predicates.
+fn filter_batches(
+    rdd: &SerArc<dyn Rdd<Item = BatchResult>>,
+    predicates: Vec<Expr>,
+    schema: Schema,
+) -> SerArc<dyn Rdd<Item = BatchResult>> {
+    rdd.map(Fn!(move |batch: BatchResult| -> BatchResult {
+        let mut batch = batch?.into_inner();
+        for predicate in &predicates {
+            let mask = kernels::evaluate(predicate, &schema, &batch)?;
+            batch = kernels::filter(&batch, &mask)?;
+        }
+        Ok(ArrowBatch::new(batch))
+    }))
+}
+
+/// Split a batch into one batch per non empty bucket of the hashes of the key columns.
+fn split_by_hash(
+    batch: &RecordBatch,
+    columns: &[usize],
+    num_partitions: usize,
+) -> Result<Vec<(usize, ArrowBatch)>> {
+    let buckets = (0..batch.num_rows())
+        .map(|i| {
+            let mut hasher = DefaultHasher::new();
+            for &c in columns {
+                kernels::value_at(batch.column(c).as_ref(), i).hash(&mut hasher);
+            }
+            hasher.finish() as usize % num_partitions
+        })
+        .collect::<Vec<_>>();
+    let mut present = buckets.clone();
+    present.sort_unstable();
+    present.dedup();
+    present
+        .into_iter()
+        .map(|bucket| {
+            let mask: ArrayRef = Arc::new(BooleanArray::from(
+                buckets.iter().map(|&b| b == bucket).collect::<Vec<_>>(),
+            ));
+            Ok((bucket, ArrowBatch::new(kernels::filter(batch, &mask)?)))
+        })
+        .collect()
+}
+
+/// Table backed by a [RecordBatchRdd](struct.RecordBatchRdd.html).
+pub struct RecordBatchTable {
+    batches: RecordBatchRdd,
+}
+
+impl RecordBatchTable {
+    pub fn new(batches: RecordBatchRdd) -> Self {
+        RecordBatchTable { batches }
+    }
+}
+
+impl TableProvider for RecordBatchTable {
+    fn schema(&self) -> Schema {
+        self.batches.schema.clone()
+    }
+
+    fn scan(
+        &self,
+        projection: Option<&[usize]>,
+        filters: &[Expr],
+    ) -> Result<SerArc<dyn Rdd<Item = Row>>> {
+        let batches = match projection {
+            // Only the number of rows is needed, the filters can't reference any column.
+            Some(projection) if projection.is_empty() => {
+                return Ok(scan_rows(&self.batches.rdd, true));
+            }
+            Some(projection) => self.batches.project(projection)?,
+            None => self.batches.clone(),
+        };
+        let rdd = if filters.is_empty() {
+            batches.rdd
+        } else {
+            filter_batches(&batches.rdd, filters.to_vec(), batches.schema.clone())
+        };
+        Ok(scan_rows(&rdd, false))
+    }
+}
+
+/// The rows of the batches scanned by a DataFrame, which are empty when only the number of
+/// rows is needed. DataFrame rows can't hold errors, so the first batch which failed ends its
+/// partition and fails the task, whose action returns the error.
+fn scan_rows(
+    rdd: &SerArc<dyn Rdd<Item = BatchResult>>,
+    count_only: bool,
+) -> SerArc<dyn Rdd<Item = Row>> {
+    rdd.map_partitions(Fn!(move |batches: Box<
+        dyn Iterator<Item = BatchResult>,
+    >| {
+        let batches = batches.scan((), |_, batch| match batch {
+            Ok(batch) => Some(batch),
+            Err(error) => {
+                fail_task(error.into());
+                None
+            }
+        });
+        Box::new(
+            batches.flat_map(move |batch| -> Box<dyn Iterator<Item = Row>> {
+                if count_only {
+                    Box::new(std::iter::repeat(vec![]).take(batch.num_rows()))
+                } else {
+                    Box::new(batch_to_rows(&batch).into_iter())
+                }
+            }),
+        ) as Box<dyn Iterator<Item = Row>>
+    }))
+}
@@ -0,0 +1,498 @@
+//! Vectorized evaluation of expressions over record batches.
+//!
+//! Columns, literals, arithmetic, comparisons and boolean operators are computed a column at
+//! a time with arrow's compute kernels, with the same null semantics as the row by row
+//! evaluation. The other expressions fall back to evaluating each row, only materializing the
+//! columns they reference.
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+use crate::dataframe::value::cmp_f64;
+use crate::dataframe::{DataType, Expr, Operator, Row, Schema, Value};
+use crate::error::Result;
+use arrow::array::{
+    Array, ArrayRef, BooleanArray, BooleanBuilder, Float32Array, Float64Array, Float64Builder,
+    Int16Array, Int32Array, Int64Array, Int64Builder, Int8Array, StringArray, StringBuilder,
+    UInt16Array, UInt32Array, UInt64Array, UInt8Array,
+};
+use arrow::compute;
+use arrow::datatypes::DataType as ArrowType;
+use arrow::record_batch::RecordBatch;
+
+macro_rules! downcast {
+    ($array:expr, $ty:ty) => {
+        $array
+            .as_any()
+            .downcast_ref::<$ty>()
+            .expect("array of its declared type")
+    };
+}
+
+/// Collect the values of a typed array, with `None` for nulls.
+macro_rules! options {
+    ($array:expr) => {{
+        let array = $array;
+        (0..array.len())
+            .map(|i| {
+                if array.is_null(i) {
+                    None
+                } else {
+                    Some(array.value(i))
+                }
+            })
+            .collect()
+    }};
+}
+
+/// Returns the value at the given index of an array.
+pub(crate) fn value_at(array: &dyn Array, i: usize) -> Value {
+    if array.is_null(i) {
+        return Value::Null;
+    }
+    match array.data_type() {
+        ArrowType::Boolean => Value::Boolean(downcast!(array, BooleanArray).value(i)),
+        ArrowType::Int8 => Value::Int(downcast!(array, Int8Array).value(i) as i64),
+        ArrowType::Int16 => Value::Int(downcast!(array, Int16Array).value(i) as i64),
+        ArrowType::Int32 => Value::Int(downcast!(array, Int32Array).value(i) as i64),
+        ArrowType::Int64 => Value::Int(downcast!(array, Int64Array).value(i)),
+        ArrowType::UInt8 => Value::Int(downcast!(array, UInt8Array).value(i) as i64),
+        ArrowType::UInt16 => Value::Int(downcast!(array, UInt16Array).value(i) as i64),
+        ArrowType::UInt32 => Value::Int(downcast!(array, UInt32Array).value(i) as i64),
+        ArrowType::UInt64 => Value::Int(downcast!(array, UInt64Array).value(i) as i64),
+        ArrowType::Float32 => Value::Float(downcast!(array, Float32Array).value(i) as f64),
+        ArrowType::Float64 => Value::Float(downcast!(array, Float64Array).value(i)),
+        ArrowType::Utf8 => Value::Utf8(downcast!(array, StringArray).value(i).to_owned()),
+        _ => Value::Null,
+    }
+}
+
+/// Build an array of the given type, casting the values to it.
+pub(crate) fn array_from_values<I: IntoIterator<Item = Value>>(
+    values: I,
+    data_type: DataType,
+    capacity: usize,
+) -> Result<ArrayRef> {
+    let array: ArrayRef = match data_type {
+        DataType::Null | DataType::Boolean => {
+            let mut builder = BooleanBuilder::new(capacity);
+            for value in values {
+                builder.append_option(value.cast(DataType::Boolean).as_bool())?;
+            }
+            Arc::new(builder.finish())
+        }
+        DataType::Int64 => {
+            let mut builder = Int64Builder::new(capacity);
+            for value in values {
+                builder.append_option(value.cast(DataType::Int64).as_i64())?;
+            }
+            Arc::new(builder.finish())
+        }
+        DataType::Float64 => {
+            let mut builder = Float64Builder::new(capacity);
+            for value in values {
+                builder.append_option(value.cast(DataType::Float64).as_f64())?;
+            }
+            Arc::new(builder.finish())
+        }
+        DataType::Utf8 => {
+            let mut builder = StringBuilder::new(capacity);
+            for value in values {
+                match value.cast(DataType::Utf8) {
+                    Value::Utf8(v) => builder.append_value(&v)?,
+                    _ => builder.append_null()?,
+                }
+            }
+            Arc::new(builder.finish())
+        }
+    };
+    Ok(array)
+}
+
+fn int_values(array: &dyn Array) -> Vec<Option<i64>> {
+    match array.data_type() {
+        ArrowType::Int64 => options!(downcast!(array, Int64Array)),
+        _ => (0..array.len())
+            .map(|i| value_at(array, i).as_i64())
+            .collect(),
+    }
+}
+
+fn float_values(array: &dyn Array) -> Vec<Option<f64>> {
+    match array.data_type() {
+        ArrowType::Float64 => options!(downcast!(array, Float64Array)),
+        _ => (0..array.len())
+            .map(|i| value_at(array, i).as_f64())
+            .collect(),
+    }
+}
+
+fn bool_values(array: &dyn Array) -> Vec<Option<bool>> {
+    match array.data_type() {
+        ArrowType::Boolean => options!(downcast!(array, BooleanArray)),
+        _ => (0..array.len())
+            .map(|i| value_at(array, i).as_bool())
+            .collect(),
+    }
+}
+
+/// The array as an `Int64Array`, converting the values of other types.
+fn ints(array: &ArrayRef) -> ArrayRef {
+    match array.data_type() {
+        ArrowType::Int64 => array.clone(),
+        _ => Arc::new(Int64Array::from(int_values(array.as_ref()))),
+    }
+}
+
+/// The array as a `Float64Array`, converting the values of other types.
+fn floats(array: &ArrayRef) -> ArrayRef {
+    match array.data_type() {
+        ArrowType::Float64 => array.clone(),
+        _ => Arc::new(Float64Array::from(float_values(array.as_ref()))),
+    }
+}
+
+/// The array as a `BooleanArray`, converting the values of other types.
+fn booleans(array: &ArrayRef) -> ArrayRef {
+    match array.data_type() {
+        ArrowType::Boolean => array.clone(),
+        _ => Arc::new(BooleanArray::from(bool_values(array.as_ref()))),
+    }
+}
+
+fn has_nan(array: &Float64Array) -> bool {
+    (0..array.len()).any(|i| !array.is_null(i) && array.value(i).is_nan())
+}
+
+/// Compare two arrays with the arrow kernel of the comparison operator, given the kernels for
+/// `=`, `!=`, `<`, `<=`, `>` and `>=`.
+macro_rules! compare_with {
+    ($op:expr, $left:expr, $right:expr, $eq:path, $neq:path, $lt:path, $lt_eq:path, $gt:path, $gt_eq:path) => {
+        match $op {
+            Operator::Eq => $eq($left, $right),
+            Operator::NotEq => $neq($left, $right),
+            Operator::Lt => $lt($left, $right),
+            Operator::LtEq => $lt_eq($left, $right),
+            Operator::Gt => $gt($left, $right),
+            _ => $gt_eq($left, $right),
+        }
+    };
+}
+
+fn compare<T, F>(op: Operator, left: Vec<Option<T>>, right: Vec<Option<T>>, cmp: F) -> ArrayRef
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    let result = left
+        .iter()
+        .zip(right.iter())
+        .map(|(a, b)| match (a, b) {
+            (Some(a), Some(b)) => {
+                let ordering = cmp(a, b);
+                Some(match op {
+                    Operator::Eq => ordering == Ordering::Equal,
+                    Operator::NotEq => ordering != Ordering::Equal,
+                    Operator::Lt => ordering == Ordering::Less,
+                    Operator::LtEq => ordering != Ordering::Greater,
+                    Operator::Gt => ordering == Ordering::Greater,
+                    _ => ordering != Ordering::Less,
+                })
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    Arc::new(BooleanArray::from(result))
+}
+
+fn comparison(
+    op: Operator,
+    left: &ArrayRef,
+    right: &ArrayRef,
+    types: (DataType, DataType),
+) -> Result<ArrayRef> {
+    let result = match types {
+        (DataType::Int64, DataType::Int64) => {
+            let (left, right) = (ints(left), ints(right));
+            let (left, right) = (downcast!(left, Int64Array), downcast!(right, Int64Array));
+            compare_with!(
+                op,
+                left,
+                right,
+                compute::eq,
+                compute::neq,
+                compute::lt,
+                compute::lt_eq,
+                compute::gt,
+                compute::gt_eq
+            )?
+        }
+        (l, r) if l.is_numeric() && r.is_numeric() => {
+            let (left, right) = (floats(left), floats(right));
+            let (left, right) = (
+                downcast!(left, Float64Array),
+                downcast!(right, Float64Array),
+            );
+            // NaN is greater than all other floats, while arrow compares floats as IEEE 754 does.
+            if has_nan(left) || has_nan(right) {
+                return Ok(compare(
+                    op,
+                    options!(left),
+                    options!(right),
+                    |a: &f64, b: &f64| cmp_f64(*a, *b),
+                ));
+            }
+            compare_with!(
+                op,
+                left,
+                right,
+                compute::eq,
+                compute::neq,
+                compute::lt,
+                compute::lt_eq,
+                compute::gt,
+                compute::gt_eq
+            )?
+        }
+        // arrow has no kernels comparing booleans
+        (DataType::Boolean, DataType::Boolean) => {
+            return Ok(compare(
+                op,
+                bool_values(left.as_ref()),
+                bool_values(right.as_ref()),
+                Ord::cmp,
+            ))
+        }
+        (DataType::Utf8, DataType::Utf8) => {
+            let (left, right) = (downcast!(left, StringArray), downcast!(right, StringArray));
+            compare_with!(
+                op,
+                left,
+                right,
+                compute::eq_utf8,
+                compute::neq_utf8,
+                compute::lt_utf8,
+                compute::lt_eq_utf8,
+                compute::gt_utf8,
+                compute::gt_eq_utf8
+            )?
+        }
+        _ => BooleanArray::from(vec![None::<bool>; left.len()]),
+    };
+    Ok(Arc::new(result))
+}
+
+fn arithmetic(
+    op: Operator,
+    left: &ArrayRef,
+    right: &ArrayRef,
+    types: (DataType, DataType),
+) -> Result<ArrayRef> {
+    if types == (DataType::Int64, DataType::Int64) && op != Operator::Divide {
+        // An overflow gives null, while arrow's kernels wrap or panic on overflow.
+        let (left, right) = (int_values(left.as_ref()), int_values(right.as_ref()));
+        let result = left
+            .iter()
+            .zip(right.iter())
+            .map(|(a, b)| match (a, b) {
+                (Some(a), Some(b)) => match op {
+                    Operator::Plus => a.checked_add(*b),
+                    Operator::Minus => a.checked_sub(*b),
+                    Operator::Multiply => a.checked_mul(*b),
+                    _ => a.checked_rem(*b),
+                },
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        return Ok(Arc::new(Int64Array::from(result)));
+    }
+    let (left, right) = (floats(left), floats(right));
+    let (left, right) = (
+        downcast!(left, Float64Array),
+        downcast!(right, Float64Array),
+    );
+    let result = match op {
+        Operator::Plus => compute::add(left, right)?,
+        Operator::Minus => compute::subtract(left, right)?,
+        Operator::Multiply => compute::multiply(left, right)?,
+        Operator::Divide => {
+            // Dividing by zero gives null, while arrow's kernel fails.
+            let divisors: Vec<Option<f64>> = options!(right);
+            let divisors = divisors
+                .into_iter()
+                .map(|b| b.filter(|b| *b != 0.0))
+                .collect::<Vec<_>>();
+            compute::divide(left, &Float64Array::from(divisors))?
+        }
+        // arrow has no modulo kernel
+        _ => {
+            let (left, right): (Vec<Option<f64>>, Vec<Option<f64>>) =
+                (options!(left), options!(right));
+            let result = left
+                .iter()
+                .zip(right.iter())
+                .map(|(a, b)| match (a, b) {
+                    (Some(a), Some(b)) if *b != 0.0 => Some(a % b),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            Float64Array::from(result)
+        }
+    };
+    Ok(Arc::new(result))
+}
+
+fn logical(op: Operator, left: &ArrayRef, right: &ArrayRef) -> Result<ArrayRef> {
+    let (left, right) = (booleans(left), booleans(right));
+    let (left, right) = (
+        downcast!(left, BooleanArray),
+        downcast!(right, BooleanArray),
+    );
+    // arrow's kernels give null when either side is null, while `false AND null` is false and
+    // `true OR null` is true.
+    if left.null_count() == 0 && right.null_count() == 0 {
+        let result = match op {
+            Operator::And => compute::and(left, right)?,
+            _ => compute::or(left, right)?,
+        };
+        return Ok(Arc::new(result));
+    }
+    let (left, right): (Vec<Option<bool>>, Vec<Option<bool>>) = (options!(left), options!(right));
+    let result = left
+        .iter()
+        .zip(right.iter())
+        .map(|(a, b)| match (op, a, b) {
+            (Operator::And, Some(false), _) | (Operator::And, _, Some(false)) => Some(false),
+            (Operator::And, Some(true), Some(true)) => Some(true),
+            (Operator::Or, Some(true), _) | (Operator::Or, _, Some(true)) => Some(true),
+            (Operator::Or, Some(false), Some(false)) => Some(false),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    Ok(Arc::new(BooleanArray::from(result)))
+}
+
+/// Evaluate an expression bound to the columns of the schema over all the rows of the batch.
+pub(crate) fn evaluate(expr: &Expr, schema: &Schema, batch: &RecordBatch) -> Result<ArrayRef> {
+    let num_rows = batch.num_rows();
+    let array = match expr {
+        Expr::Column(column) => match column.index {
+            Some(index) => batch.column(index).clone(),
+            None => return evaluate_rows(expr, schema, batch),
+        },
+        Expr::Literal(value) => array_from_values(
+            std::iter::repeat(value.clone()).take(num_rows),
+            value.data_type(),
+            num_rows,
+        )?,
+        Expr::Alias(expr, _) => evaluate(expr, schema, batch)?,
+        Expr::BinaryExpr { left, op, right }
+            if *op != Operator::Like && *op != Operator::NotLike =>
+        {
+            let types = (left.data_type(schema)?, right.data_type(schema)?);
+            let (left, right) = (
+                evaluate(left, schema, batch)?,
+                evaluate(right, schema, batch)?,
+            );
+            match op {
+                Operator::And | Operator::Or => logical(*op, &left, &right)?,
+                op if op.is_comparison() => comparison(*op, &left, &right, types)?,
+                op => arithmetic(*op, &left, &right, types)?,
+            }
+        }
+        Expr::Not(expr) => {
+            let array = booleans(&evaluate(expr, schema, batch)?);
+            Arc::new(compute::not(downcast!(array, BooleanArray))?)
+        }
+        Expr::IsNull(inner) | Expr::IsNotNull(inner) => {
+            let is_null = match expr {
+                Expr::IsNull(_) => true,
+                _ => false,
+            };
+            let array = evaluate(inner, schema, batch)?;
+            let result = (0..num_rows)
+                .map(|i| array.is_null(i) == is_null)
+                .collect::<Vec<_>>();
+            Arc::new(BooleanArray::from(result))
+        }
+        _ => return evaluate_rows(expr, schema, batch),
+    };
+    Ok(array)
+}
+
+/// Evaluate an expression row by row, filling only the referenced columns of each row.
+fn evaluate_rows(expr: &Expr, schema: &Schema, batch: &RecordBatch) -> Result<ArrayRef> {
+    let columns = expr.columns();
+    let data_type = expr.data_type(schema)?;
+    let values = (0..batch.num_rows()).map(|i| {
+        let row = (0..batch.num_columns())
+            .map(|c| {
+                if columns.contains(&c) {
+                    value_at(batch.column(c).as_ref(), i)
+                } else {
+                    Value::Null
+                }
+            })
+            .collect::<Row>();
+        expr.evaluate(&row)
+    });
+    array_from_values(values, data_type, batch.num_rows())
+}
+
+/// Returns the rows of the batch for which the mask is true; nulls are dropped.
+pub(crate) fn filter(batch: &RecordBatch, mask: &ArrayRef) -> Result<RecordBatch> {
+    let mask = BooleanArray::from(
+        bool_values(mask.as_ref())
+            .into_iter()
+            .map(|v| v == Some(true))
+            .collect::<Vec<_>>(),
+    );
+    let columns = batch
+        .columns()
+        .iter()
+        .map(|column| compute::filter(column.as_ref(), &mask))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(RecordBatch::try_new(batch.schema().clone(), columns)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataframe::columnar::rows_to_batch;
+    use crate::dataframe::{col, lit, Field};
+
+    #[test]
+    fn evaluate_matches_rows() {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Float64, true),
+            Field::new("c", DataType::Utf8, true),
+        ]);
+        let rows: Vec<Row> = vec![
+            vec![1.into(), 2.5.into(), "x".into()],
+            vec![Value::Null, 0.0.into(), "yy".into()],
+            vec![i64::MAX.into(), Value::Null, Value::Null],
+            vec![(-4).into(), (-1.0).into(), "zzz".into()],
+        ];
+        let batch = rows_to_batch(&rows, &schema).unwrap();
+        let exprs = vec![
+            col("a") + lit(1),
+            col("a") / col("b"),
+            col("a") % lit(3),
+            col("a").gt(col("b")).or(col("c").is_null()),
+            col("c").lt_eq(lit("yy")).and(col("b").not_eq(lit(0))),
+            col("c").like(lit("%y%")).not(),
+            col("a").cast(DataType::Utf8),
+        ];
+        for expr in exprs {
+            let expr = expr.resolve(&schema).unwrap();
+            let array = evaluate(&expr, &schema, &batch).unwrap();
+            let expected = rows
+                .iter()
+                .map(|row| expr.evaluate(row))
+                .collect::<Vec<_>>();
+            let actual = (0..rows.len())
+                .map(|i| value_at(array.as_ref(), i))
+                .collect::<Vec<_>>();
+            assert_eq!(actual, expected, "{}", expr);
+        }
+    }
+}
@@ -0,0 +1,225 @@
+//! Columnar processing of DataFrame rows stored in Arrow record batches.
+//!
+//! Moving rows one by one through bincode is slow for wide numeric data. A
+//! [RecordBatchRdd](struct.RecordBatchRdd.html) instead holds blocks of rows laid out by column,
+//! which are filtered, projected and mapped with vectorized kernels. The batches are written
+//! as Arrow IPC streams whenever they are serialized, so the shuffle and cache blocks keep the
+//! columnar layout as well.
+use std::fmt::{self, Debug};
+use std::io::Cursor;
+use std::ops::Deref;
+use std::sync::Arc;
+
+use crate::dataframe::{DataType, Field, PlanError, Row, Schema};
+use crate::error::Result;
+use arrow::array::ArrayRef;
+use arrow::datatypes::{
+    DataType as ArrowType, Field as ArrowField, Schema as ArrowSchema, SchemaRef,
+};
+use arrow::error::ArrowError;
+use arrow::ipc::reader::StreamReader;
+use arrow::ipc::writer::StreamWriter;
+use arrow::record_batch::RecordBatch;
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{self, Serialize, Serializer};
+
+mod batch_rdd;
+pub(crate) mod kernels;
+
+pub use batch_rdd::{BatchError, RecordBatchRdd, RecordBatchTable};
+
+/// A record batch which can be moved across the cluster, serialized as an Arrow IPC stream.
+#[derive(Clone)]
+pub struct ArrowBatch(RecordBatch);
+
+impl ArrowBatch {
+    pub fn new(batch: RecordBatch) -> Self {
+        ArrowBatch(batch)
+    }
+
+    pub fn into_inner(self) -> RecordBatch {
+        self.0
+    }
+
+    /// Encode the batch as an Arrow IPC stream holding its schema and a single batch.
+    pub fn to_ipc(&self) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        {
+            let mut writer = StreamWriter::try_new(&mut bytes, &self.0.schema())?;
+            writer.write(&self.0)?;
+            writer.finish()?;
+        }
+        Ok(bytes)
+    }
+
+    /// Decode the first batch of an Arrow IPC stream.
+    pub fn from_ipc(bytes: &[u8]) -> Result<Self> {
+        let mut reader = StreamReader::try_new(Cursor::new(bytes))?;
+        match reader.next_batch()? {
+            Some(batch) => Ok(ArrowBatch(batch)),
+            None => {
+                Err(ArrowError::ParseError("IPC stream without record batch".to_owned()).into())
+            }
+        }
+    }
+}
+
+impl Deref for ArrowBatch {
+    type Target = RecordBatch;
+
+    fn deref(&self) -> &RecordBatch {
+        &self.0
+    }
+}
+
+impl From<RecordBatch> for ArrowBatch {
+    fn from(batch: RecordBatch) -> Self {
+        ArrowBatch(batch)
+    }
+}
+
+impl Debug for ArrowBatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ArrowBatch")
+            .field("schema", &self.0.schema())
+            .field("num_rows", &self.0.num_rows())
+            .finish()
+    }
+}
+
+impl Serialize for ArrowBatch {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let bytes = self.to_ipc().map_err(ser::Error::custom)?;
+        serializer.serialize_bytes(&bytes)
+    }
+}
+
+impl<'de> Deserialize<'de> for ArrowBatch {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        struct BytesVisitor;
+
+        impl<'de> Visitor<'de> for BytesVisitor {
+            type Value = Vec<u8>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "an Arrow IPC stream")
+            }
+
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> std::result::Result<Vec<u8>, E> {
+                Ok(v.to_vec())
+            }
+
+            fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> std::result::Result<Vec<u8>, E> {
+                Ok(v)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> std::result::Result<Vec<u8>, A::Error> {
+                let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                while let Some(byte) = seq.next_element()? {
+                    bytes.push(byte);
+                }
+                Ok(bytes)
+            }
+        }
+
+        let bytes = deserializer.deserialize_byte_buf(BytesVisitor)?;
+        ArrowBatch::from_ipc(&bytes).map_err(de::Error::custom)
+    }
+}
+
+/// Arrow type used to store the values of a DataFrame type. Columns of nulls are stored as
+/// booleans.
+pub fn to_arrow_type(data_type: DataType) -> ArrowType {
+    match data_type {
+        DataType::Null | DataType::Boolean => ArrowType::Boolean,
+        DataType::Int64 => ArrowType::Int64,
+        DataType::Float64 => ArrowType::Float64,
+        DataType::Utf8 => ArrowType::Utf8,
+    }
+}
+
+/// DataFrame type of the values of an Arrow type. Integers are widened to `Int64` and floats
+/// to `Float64`.
+pub fn from_arrow_type(data_type: &ArrowType) -> Result<DataType> {
+    match data_type {
+        ArrowType::Boolean => Ok(DataType::Boolean),
+        ArrowType::Int8
+        | ArrowType::Int16
+        | ArrowType::Int32
+        | ArrowType::Int64
+        | ArrowType::UInt8
+        | ArrowType::UInt16
+        | ArrowType::UInt32
+        | ArrowType::UInt64 => Ok(DataType::Int64),
+        ArrowType::Float32 | ArrowType::Float64 => Ok(DataType::Float64),
+        ArrowType::Utf8 => Ok(DataType::Utf8),
+        other => Err(PlanError::UnsupportedType(format!("{:?}", other)).into()),
+    }
+}
+
+pub fn to_arrow_schema(schema: &Schema) -> ArrowSchema {
+    ArrowSchema::new(
+        schema
+            .fields()
+            .iter()
+            .map(|field| ArrowField::new(field.name(), to_arrow_type(field.data_type()), true))
+            .collect(),
+    )
+}
+
+pub fn from_arrow_schema(schema: &ArrowSchema) -> Result<Schema> {
+    let fields = schema
+        .fields()
+        .iter()
+        .map(|field| {
+            let data_type = from_arrow_type(field.data_type())?;
+            Ok(Field::new(field.name(), data_type, field.is_nullable()))
+        })
+        .collect::<Result<_>>()?;
+    Ok(Schema::new(fields))
+}
+
+/// Build a record batch from rows, casting the values to the types of the schema.
+pub fn rows_to_batch(rows: &[Row], schema: &Schema) -> Result<RecordBatch> {
+    let columns = schema
+        .fields()
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let values = rows.iter().map(|row| row[i].clone());
+            kernels::array_from_values(values, field.data_type(), rows.len())
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let arrow_schema: SchemaRef = Arc::new(to_arrow_schema(schema));
+    Ok(RecordBatch::try_new(arrow_schema, columns)?)
+}
+
+/// Returns the rows of a record batch.
+pub fn batch_to_rows(batch: &RecordBatch) -> Vec<Row> {
+    let columns = batch.columns();
+    (0..batch.num_rows())
+        .map(|i| {
+            columns
+                .iter()
+                .map(|column| kernels::value_at(column.as_ref(), i))
+                .collect()
+        })
+        .collect()
+}
+
+/// Build a record batch from computed columns, taking the field types from the arrays.
+pub(crate) fn batch_from_columns(schema: &Schema, columns: Vec<ArrayRef>) -> Result<RecordBatch> {
+    let fields = schema
+        .fields()
+        .iter()
+        .zip(columns.iter())
+        .map(|(field, column)| ArrowField::new(field.name(), column.data_type().clone(), true))
+        .collect();
+    Ok(RecordBatch::try_new(
+        Arc::new(ArrowSchema::new(fields)),
+        columns,
+    )?)
+}
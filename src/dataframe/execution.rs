@@ -0,0 +1,424 @@
+//! Compiles a logical plan down to RDD operators.
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+use crate::aggregator::Aggregator;
+use crate::dataframe::expr::evaluate_binary;
+use crate::dataframe::optimizer::conjunction;
+use crate::dataframe::{
+    AggregateFunction, DataType, Expr, JoinType, LogicalPlan, Operator, Row, SortExpr, Value,
+};
+use crate::error::Result;
+use crate::partitioner::{HashPartitioner, Partitioner, RangePartitioner};
+use crate::rdd::{PairRdd, Rdd};
+use crate::{Fn, SerArc};
+use serde_derive::{Deserialize, Serialize};
+
+/// Number of sampled sort keys per partition used to compute the range bounds of a sort.
+const SORT_SAMPLES_PER_PARTITION: f64 = 20.0;
+
+/// Returns the RDD computing the rows of the plan.
+///
+/// Sorts and limits run jobs right away: a sort samples its input to compute the ranges of its
+/// output partitions and a limit takes the first rows of its input.
+pub(crate) fn execute(plan: &LogicalPlan) -> Result<SerArc<dyn Rdd<Item = Row>>> {
+    match plan {
+        LogicalPlan::Scan {
+            source,
+            projection,
+            filters,
+            ..
+        } => {
+            let rdd = source.scan(projection.as_deref(), filters)?;
+            Ok(match conjunction(filters.clone()) {
+                Some(predicate) => filter_rows(rdd, predicate),
+                None => rdd,
+            })
+        }
+        LogicalPlan::Projection { exprs, input, .. } => {
+            let exprs = exprs.clone();
+            Ok(execute(input)?.map(Fn!(move |row: Row| {
+                exprs
+                    .iter()
+                    .map(|expr| expr.evaluate(&row))
+                    .collect::<Row>()
+            })))
+        }
+        LogicalPlan::Filter { predicate, input } => {
+            Ok(filter_rows(execute(input)?, predicate.clone()))
+        }
+        LogicalPlan::Aggregate {
+            group_exprs,
+            aggr_exprs,
+            input,
+            ..
+        } => execute_aggregate(execute(input)?, group_exprs.clone(), aggr_exprs),
+        LogicalPlan::Join {
+            left,
+            right,
+            on,
+            join_type,
+            ..
+        } => execute_join(left, right, on, *join_type),
+        LogicalPlan::Sort { exprs, input } => execute_sort(execute(input)?, exprs.clone()),
+        LogicalPlan::Limit { n, input } => {
+            let input = execute(input)?;
+            let rows = input.take(*n)?;
+            Ok(input.get_context().parallelize(rows, 1))
+        }
+        LogicalPlan::Alias { input, .. } => execute(input),
+    }
+}
+
+fn filter_rows(rdd: SerArc<dyn Rdd<Item = Row>>, predicate: Expr) -> SerArc<dyn Rdd<Item = Row>> {
+    rdd.map_partitions(Fn!(move |rows: Box<dyn Iterator<Item = Row>>| {
+        let predicate = predicate.clone();
+        Box::new(rows.filter(move |row| predicate.evaluate(row).as_bool() == Some(true)))
+            as Box<dyn Iterator<Item = Row>>
+    }))
+}
+
+/// Intermediate state of an aggregate function.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) enum Accumulator {
+    Count(i64),
+    CountDistinct(HashSet<Value>),
+    /// An integer sum which overflowed stays null, even if later values would fit.
+    Sum {
+        sum: Value,
+        overflowed: bool,
+    },
+    Avg {
+        sum: f64,
+        count: i64,
+    },
+    Min(Value),
+    Max(Value),
+}
+
+impl Accumulator {
+    pub(crate) fn new(func: AggregateFunction) -> Self {
+        match func {
+            AggregateFunction::Count => Accumulator::Count(0),
+            AggregateFunction::CountDistinct => Accumulator::CountDistinct(HashSet::new()),
+            AggregateFunction::Sum => Accumulator::Sum {
+                sum: Value::Null,
+                overflowed: false,
+            },
+            AggregateFunction::Avg => Accumulator::Avg { sum: 0.0, count: 0 },
+            AggregateFunction::Min => Accumulator::Min(Value::Null),
+            AggregateFunction::Max => Accumulator::Max(Value::Null),
+        }
+    }
+
+    /// Add a value to the state, nulls are ignored.
+    pub(crate) fn update(&mut self, value: Value) {
+        if value.is_null() {
+            return;
+        }
+        match self {
+            Accumulator::Count(count) => *count += 1,
+            Accumulator::CountDistinct(values) => {
+                values.insert(value);
+            }
+            Accumulator::Sum {
+                overflowed: true, ..
+            } => {}
+            Accumulator::Sum { sum, overflowed } => {
+                if sum.is_null() {
+                    *sum = value;
+                } else {
+                    // the sum of numeric values is only null on integer overflow
+                    *sum = evaluate_binary(Operator::Plus, sum, &value);
+                    *overflowed = sum.is_null();
+                }
+            }
+            Accumulator::Avg { sum, count } => {
+                if let Some(value) = value.as_f64() {
+                    *sum += value;
+                    *count += 1;
+                }
+            }
+            Accumulator::Min(min) => {
+                if min.is_null() || value < *min {
+                    *min = value;
+                }
+            }
+            Accumulator::Max(max) => {
+                if max.is_null() || value > *max {
+                    *max = value;
+                }
+            }
+        }
+    }
+
+    pub(crate) fn merge(&mut self, other: Accumulator) {
+        match (self, other) {
+            (Accumulator::Count(count), Accumulator::Count(other)) => *count += other,
+            (Accumulator::CountDistinct(values), Accumulator::CountDistinct(other)) => {
+                values.extend(other)
+            }
+            (Accumulator::Avg { sum, count }, Accumulator::Avg { sum: s, count: c }) => {
+                *sum += s;
+                *count += c;
+            }
+            (
+                Accumulator::Sum { sum, overflowed },
+                Accumulator::Sum {
+                    overflowed: true, ..
+                },
+            ) => {
+                *sum = Value::Null;
+                *overflowed = true;
+            }
+            (this, Accumulator::Sum { sum: value, .. })
+            | (this, Accumulator::Min(value))
+            | (this, Accumulator::Max(value)) => this.update(value),
+            _ => unreachable!("merging accumulators of different aggregate functions"),
+        }
+    }
+
+    pub(crate) fn finish(self) -> Value {
+        match self {
+            Accumulator::Count(count) => Value::Int(count),
+            Accumulator::CountDistinct(values) => Value::Int(values.len() as i64),
+            Accumulator::Avg { count: 0, .. } => Value::Null,
+            Accumulator::Avg { sum, count } => Value::Float(sum / count as f64),
+            Accumulator::Sum { sum: value, .. }
+            | Accumulator::Min(value)
+            | Accumulator::Max(value) => value,
+        }
+    }
+}
+
+/// Returns the function and the argument of an aggregate expression.
+pub(crate) fn split_aggregate(expr: &Expr) -> (AggregateFunction, Expr) {
+    match expr {
+        Expr::Alias(expr, _) => split_aggregate(expr),
+        Expr::AggregateFunction { func, arg } => (*func, (**arg).clone()),
+        expr => unreachable!("{} is not an aggregate expression", expr),
+    }
+}
+
+fn update_state(mut state: Vec<Accumulator>, values: Row) -> Vec<Accumulator> {
+    state
+        .iter_mut()
+        .zip(values)
+        .for_each(|(accumulator, value)| accumulator.update(value));
+    state
+}
+
+fn execute_aggregate(
+    input: SerArc<dyn Rdd<Item = Row>>,
+    group_exprs: Vec<Expr>,
+    aggr_exprs: &[Expr],
+) -> Result<SerArc<dyn Rdd<Item = Row>>> {
+    let (funcs, args): (Vec<_>, Vec<_>) = aggr_exprs.iter().map(split_aggregate).unzip();
+    let global = group_exprs.is_empty();
+    let num_partitions = input.number_of_splits().max(1);
+    let context = input.get_context();
+    let num_args = args.len();
+    // Each row is turned into its grouping key and the values of the aggregated arguments.
+    let keyed = input.map(Fn!(move |row: Row| {
+        let key = group_exprs
+            .iter()
+            .map(|expr| expr.evaluate(&row))
+            .collect::<Row>();
+        let values = args.iter().map(|arg| arg.evaluate(&row)).collect::<Row>();
+        (key, values)
+    }));
+    // A global aggregation returns a row even if there is no input row. Accumulators ignore
+    // nulls, so a row of nulls only makes sure the group exists.
+    let keyed = if global {
+        keyed.union(
+            context
+                .parallelize(vec![(vec![], vec![Value::Null; num_args])], 1)
+                .get_rdd(),
+        )?
+    } else {
+        keyed
+    };
+    let create_combiner = Box::new(Fn!(move |values: Row| {
+        let state = funcs
+            .iter()
+            .map(|func| Accumulator::new(*func))
+            .collect::<Vec<_>>();
+        update_state(state, values)
+    }));
+    let merge_value = Box::new(Fn!(|(state, values): (Vec<Accumulator>, Row)| {
+        update_state(state, values)
+    }));
+    let merge_combiners = Box::new(Fn!(|(mut state, other): (
+        Vec<Accumulator>,
+        Vec<Accumulator>
+    )| {
+        state
+            .iter_mut()
+            .zip(other)
+            .for_each(|(accumulator, other)| accumulator.merge(other));
+        state
+    }));
+    let aggregator = Aggregator::new(create_combiner, merge_value, merge_combiners);
+    let partitioner = Box::new(HashPartitioner::<Row>::new(num_partitions)) as Box<dyn Partitioner>;
+    let combined = keyed.combine_by_key(aggregator, partitioner);
+    Ok(combined.map(Fn!(|grouped: (Row, Vec<Accumulator>)| {
+        let (mut row, state) = grouped;
+        row.extend(state.into_iter().map(Accumulator::finish));
+        row
+    })))
+}
+
+fn join_key(row: &[Value], columns: &[usize], types: &[Option<DataType>]) -> Row {
+    columns
+        .iter()
+        .zip(types)
+        .map(|(&i, data_type)| match data_type {
+            Some(data_type) => row[i].cast(*data_type),
+            None => row[i].clone(),
+        })
+        .collect()
+}
+
+fn execute_join(
+    left: &LogicalPlan,
+    right: &LogicalPlan,
+    on: &[(usize, usize)],
+    join_type: JoinType,
+) -> Result<SerArc<dyn Rdd<Item = Row>>> {
+    let (left_schema, right_schema) = (left.schema(), right.schema());
+    let (left_width, right_width) = (left_schema.len(), right_schema.len());
+    // Keys of different numeric types are compared as floats.
+    let key_types = on
+        .iter()
+        .map(|&(l, r)| {
+            let (l, r) = (
+                left_schema.field(l).data_type(),
+                right_schema.field(r).data_type(),
+            );
+            if l == r {
+                None
+            } else {
+                l.common_type(r)
+            }
+        })
+        .collect::<Vec<_>>();
+    let left_keys = on.iter().map(|&(l, _)| l).collect::<Vec<_>>();
+    let right_keys = on.iter().map(|&(_, r)| r).collect::<Vec<_>>();
+    let left = execute(left)?;
+    let right = execute(right)?;
+    let num_partitions = left.number_of_splits().max(right.number_of_splits()).max(1);
+
+    let types = key_types.clone();
+    let left = left.map(Fn!(move |row: Row| (
+        join_key(&row, &left_keys, &types),
+        row
+    )));
+    let right = right.map(Fn!(move |row: Row| (
+        join_key(&row, &right_keys, &key_types),
+        row
+    )));
+    let partitioner = Box::new(HashPartitioner::<Row>::new(num_partitions)) as Box<dyn Partitioner>;
+    let joined =
+        left.cogroup(right, partitioner).flat_map(Fn!(
+            move |grouped: (Row, (Vec<Row>, Vec<Row>))| -> Box<dyn Iterator<Item = Row>> {
+                let (key, (lefts, rights)) = grouped;
+                let mut rows = Vec::new();
+                // Null keys never match.
+                if !key.iter().any(Value::is_null) && !lefts.is_empty() && !rights.is_empty() {
+                    for l in &lefts {
+                        for r in &rights {
+                            rows.push(l.iter().chain(r.iter()).cloned().collect());
+                        }
+                    }
+                } else {
+                    if join_type.preserves_left() {
+                        rows.extend(lefts.into_iter().map(|mut row| {
+                            row.resize(left_width + right_width, Value::Null);
+                            row
+                        }));
+                    }
+                    if join_type.preserves_right() {
+                        rows.extend(rights.into_iter().map(|row| {
+                            let mut padded = vec![Value::Null; left_width];
+                            padded.extend(row);
+                            padded
+                        }));
+                    }
+                }
+                Box::new(rows.into_iter())
+            }
+        ));
+    Ok(joined)
+}
+
+/// Key of a row in a sort. Nulls are the smallest values, so they come first in ascending
+/// order and last in descending order.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub(crate) struct SortKey(Vec<(Value, bool)>);
+
+impl SortKey {
+    fn new(exprs: &[SortExpr], row: &[Value]) -> Self {
+        SortKey(
+            exprs
+                .iter()
+                .map(|sort| (sort.expr.evaluate(row), sort.ascending))
+                .collect(),
+        )
+    }
+}
+
+impl PartialOrd for SortKey {
+    fn partial_cmp(&self, other: &SortKey) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SortKey {
+    fn cmp(&self, other: &SortKey) -> Ordering {
+        self.0
+            .iter()
+            .zip(other.0.iter())
+            .map(
+                |((a, ascending), (b, _))| {
+                    if *ascending {
+                        a.cmp(b)
+                    } else {
+                        b.cmp(a)
+                    }
+                },
+            )
+            .find(|ordering| *ordering != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+fn execute_sort(
+    input: SerArc<dyn Rdd<Item = Row>>,
+    exprs: Vec<SortExpr>,
+) -> Result<SerArc<dyn Rdd<Item = Row>>> {
+    let count = input.count()?;
+    if count == 0 {
+        return Ok(input);
+    }
+    let num_partitions = input.number_of_splits().max(1);
+    let key_exprs = exprs.clone();
+    let keyed = input.map(Fn!(move |row: Row| (SortKey::new(&key_exprs, &row), row)));
+    let partitioner = if num_partitions == 1 {
+        RangePartitioner::new(vec![])
+    } else {
+        let fraction = (SORT_SAMPLES_PER_PARTITION * num_partitions as f64 / count as f64).min(1.0);
+        let sample = keyed
+            .map(Fn!(|keyed: (SortKey, Row)| keyed.0))
+            .sample(false, fraction)
+            .collect()?;
+        RangePartitioner::from_sample(num_partitions, sample)
+    };
+    let partitioned = keyed.partition_by_key(Box::new(partitioner));
+    Ok(
+        partitioned.map_partitions(Fn!(move |rows: Box<dyn Iterator<Item = Row>>| {
+            let mut rows = rows.collect::<Vec<_>>();
+            rows.sort_by_cached_key(|row| SortKey::new(&exprs, row));
+            Box::new(rows.into_iter()) as Box<dyn Iterator<Item = Row>>
+        })),
+    )
+}
@@ -0,0 +1,998 @@
+use std::collections::BTreeSet;
+use std::fmt::{self, Display};
+use std::ops;
+
+use crate::dataframe::value::cmp_f64;
+use crate::dataframe::{DataType, Field, PlanError, Schema, Value};
+use crate::error::Result;
+use serde_derive::{Deserialize, Serialize};
+
+/// Binary operators of the expressions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Operator {
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    And,
+    Or,
+    Plus,
+    Minus,
+    Multiply,
+    Divide,
+    Modulo,
+    /// Matches a string against a pattern where `%` matches any sequence of characters and `_`
+    /// any single character.
+    Like,
+    NotLike,
+}
+
+impl Operator {
+    pub(crate) fn is_comparison(self) -> bool {
+        match self {
+            Operator::Eq
+            | Operator::NotEq
+            | Operator::Lt
+            | Operator::LtEq
+            | Operator::Gt
+            | Operator::GtEq => true,
+            _ => false,
+        }
+    }
+}
+
+impl Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let symbol = match self {
+            Operator::Eq => "=",
+            Operator::NotEq => "!=",
+            Operator::Lt => "<",
+            Operator::LtEq => "<=",
+            Operator::Gt => ">",
+            Operator::GtEq => ">=",
+            Operator::And => "AND",
+            Operator::Or => "OR",
+            Operator::Plus => "+",
+            Operator::Minus => "-",
+            Operator::Multiply => "*",
+            Operator::Divide => "/",
+            Operator::Modulo => "%",
+            Operator::Like => "LIKE",
+            Operator::NotLike => "NOT LIKE",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+/// Aggregate functions which can be used in [agg](struct.GroupedData.html#method.agg).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AggregateFunction {
+    /// Number of non null values.
+    Count,
+    /// Number of distinct non null values.
+    CountDistinct,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl AggregateFunction {
+    /// Returns the aggregate function with the given case insensitive SQL name.
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        let func = match name.to_uppercase().as_str() {
+            "COUNT" => AggregateFunction::Count,
+            "SUM" => AggregateFunction::Sum,
+            "AVG" => AggregateFunction::Avg,
+            "MIN" => AggregateFunction::Min,
+            "MAX" => AggregateFunction::Max,
+            _ => return None,
+        };
+        Some(func)
+    }
+}
+
+impl Display for AggregateFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            AggregateFunction::Count | AggregateFunction::CountDistinct => "COUNT",
+            AggregateFunction::Sum => "SUM",
+            AggregateFunction::Avg => "AVG",
+            AggregateFunction::Min => "MIN",
+            AggregateFunction::Max => "MAX",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Scalar functions, evaluated for each row.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScalarFunction {
+    Abs,
+    /// Rounds to the nearest integer, or to the number of decimals given as second argument.
+    Round,
+    Floor,
+    Ceil,
+    Sqrt,
+    Upper,
+    Lower,
+    Trim,
+    /// Number of characters of a string.
+    Length,
+    /// Substring from a 1-based character position, with an optional length.
+    Substr,
+    /// Concatenation of strings, null if any of them is null.
+    Concat,
+    /// First non null argument.
+    Coalesce,
+}
+
+impl ScalarFunction {
+    /// Returns the scalar function with the given case insensitive SQL name.
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        let func = match name.to_uppercase().as_str() {
+            "ABS" => ScalarFunction::Abs,
+            "ROUND" => ScalarFunction::Round,
+            "FLOOR" => ScalarFunction::Floor,
+            "CEIL" | "CEILING" => ScalarFunction::Ceil,
+            "SQRT" => ScalarFunction::Sqrt,
+            "UPPER" => ScalarFunction::Upper,
+            "LOWER" => ScalarFunction::Lower,
+            "TRIM" => ScalarFunction::Trim,
+            "LENGTH" => ScalarFunction::Length,
+            "SUBSTR" | "SUBSTRING" => ScalarFunction::Substr,
+            "CONCAT" => ScalarFunction::Concat,
+            "COALESCE" => ScalarFunction::Coalesce,
+            _ => return None,
+        };
+        Some(func)
+    }
+
+    /// Returns the expression applying the function to the arguments.
+    pub fn call(self, args: Vec<Expr>) -> Expr {
+        Expr::ScalarFunction { func: self, args }
+    }
+
+    /// Returns the type of the result for arguments of the given types, None if the function
+    /// can't be applied to them.
+    fn return_type(self, args: &[DataType]) -> Option<DataType> {
+        let is = |data_type: DataType, expected: DataType| {
+            data_type == expected || data_type == DataType::Null
+        };
+        let numeric = |data_type: DataType| data_type.is_numeric() || data_type == DataType::Null;
+        match (self, args) {
+            (ScalarFunction::Abs, &[arg])
+            | (ScalarFunction::Floor, &[arg])
+            | (ScalarFunction::Ceil, &[arg])
+            | (ScalarFunction::Round, &[arg])
+                if numeric(arg) =>
+            {
+                Some(arg)
+            }
+            (ScalarFunction::Round, &[arg, digits])
+                if numeric(arg) && is(digits, DataType::Int64) =>
+            {
+                Some(arg)
+            }
+            (ScalarFunction::Sqrt, &[arg]) if numeric(arg) => Some(DataType::Float64),
+            (ScalarFunction::Upper, &[arg])
+            | (ScalarFunction::Lower, &[arg])
+            | (ScalarFunction::Trim, &[arg])
+                if is(arg, DataType::Utf8) =>
+            {
+                Some(DataType::Utf8)
+            }
+            (ScalarFunction::Length, &[arg]) if is(arg, DataType::Utf8) => Some(DataType::Int64),
+            (ScalarFunction::Substr, &[arg, start])
+            | (ScalarFunction::Substr, &[arg, start, _])
+                if is(arg, DataType::Utf8)
+                    && is(start, DataType::Int64)
+                    && args[2..].iter().all(|len| is(*len, DataType::Int64)) =>
+            {
+                Some(DataType::Utf8)
+            }
+            (ScalarFunction::Concat, args) if !args.is_empty() => Some(DataType::Utf8),
+            (ScalarFunction::Coalesce, args) if !args.is_empty() => args
+                .iter()
+                .try_fold(DataType::Null, |common, arg| common.common_type(*arg)),
+            _ => None,
+        }
+    }
+
+    fn evaluate(self, args: Vec<Value>) -> Value {
+        if self == ScalarFunction::Coalesce {
+            return args
+                .into_iter()
+                .find(|arg| !arg.is_null())
+                .unwrap_or(Value::Null);
+        }
+        if args.iter().any(Value::is_null) {
+            return Value::Null;
+        }
+        let float = |f: fn(f64) -> f64, value: &Value| match value {
+            Value::Float(v) => Value::Float(f(*v)),
+            value => value.clone(),
+        };
+        match (self, args.as_slice()) {
+            (ScalarFunction::Abs, [Value::Int(v)]) => {
+                v.checked_abs().map_or(Value::Null, Value::Int)
+            }
+            (ScalarFunction::Abs, [v]) => float(f64::abs, v),
+            (ScalarFunction::Floor, [v]) => float(f64::floor, v),
+            (ScalarFunction::Ceil, [v]) => float(f64::ceil, v),
+            (ScalarFunction::Round, [v]) => float(f64::round, v),
+            (ScalarFunction::Round, [Value::Float(v), Value::Int(digits)]) => {
+                let scale = 10f64.powi(*digits as i32);
+                Value::Float((v * scale).round() / scale)
+            }
+            (ScalarFunction::Round, [Value::Int(v), Value::Int(digits)]) if *digits < 0 => {
+                let scale = 10f64.powi(-*digits as i32);
+                Value::Int(((*v as f64 / scale).round() * scale) as i64)
+            }
+            (ScalarFunction::Round, [v, _]) => v.clone(),
+            (ScalarFunction::Sqrt, [v]) => {
+                v.as_f64().map_or(Value::Null, |v| Value::Float(v.sqrt()))
+            }
+            (ScalarFunction::Upper, [Value::Utf8(v)]) => Value::Utf8(v.to_uppercase()),
+            (ScalarFunction::Lower, [Value::Utf8(v)]) => Value::Utf8(v.to_lowercase()),
+            (ScalarFunction::Trim, [Value::Utf8(v)]) => Value::Utf8(v.trim().to_owned()),
+            (ScalarFunction::Length, [Value::Utf8(v)]) => Value::Int(v.chars().count() as i64),
+            (ScalarFunction::Substr, [Value::Utf8(v), Value::Int(start), rest @ ..]) => {
+                let len = match rest {
+                    [Value::Int(len)] => (*len).max(0) as usize,
+                    _ => usize::MAX,
+                };
+                let start = (*start).max(1) as usize - 1;
+                Value::Utf8(v.chars().skip(start).take(len).collect())
+            }
+            (ScalarFunction::Concat, args) => {
+                Value::Utf8(args.iter().map(ToString::to_string).collect())
+            }
+            _ => Value::Null,
+        }
+    }
+}
+
+impl Display for ScalarFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            ScalarFunction::Abs => "ABS",
+            ScalarFunction::Round => "ROUND",
+            ScalarFunction::Floor => "FLOOR",
+            ScalarFunction::Ceil => "CEIL",
+            ScalarFunction::Sqrt => "SQRT",
+            ScalarFunction::Upper => "UPPER",
+            ScalarFunction::Lower => "LOWER",
+            ScalarFunction::Trim => "TRIM",
+            ScalarFunction::Length => "LENGTH",
+            ScalarFunction::Substr => "SUBSTR",
+            ScalarFunction::Concat => "CONCAT",
+            ScalarFunction::Coalesce => "COALESCE",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Reference to a column by name. Once the expression is part of a plan the column is also
+/// bound to its index in the input rows.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Column {
+    pub relation: Option<String>,
+    pub name: String,
+    pub(crate) index: Option<usize>,
+}
+
+impl Column {
+    pub fn flat_name(&self) -> String {
+        match self.relation {
+            Some(ref relation) => format!("{}.{}", relation, self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// Expression over the columns of a row.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Expr {
+    Column(Column),
+    Literal(Value),
+    BinaryExpr {
+        left: Box<Expr>,
+        op: Operator,
+        right: Box<Expr>,
+    },
+    Not(Box<Expr>),
+    Negative(Box<Expr>),
+    IsNull(Box<Expr>),
+    IsNotNull(Box<Expr>),
+    Cast {
+        expr: Box<Expr>,
+        data_type: DataType,
+    },
+    Alias(Box<Expr>, String),
+    AggregateFunction {
+        func: AggregateFunction,
+        arg: Box<Expr>,
+    },
+    ScalarFunction {
+        func: ScalarFunction,
+        args: Vec<Expr>,
+    },
+}
+
+/// Returns an expression referring to the column with the given name, which can be qualified
+/// with the relation name as in `table.column`.
+pub fn col(name: &str) -> Expr {
+    let (relation, name) = match name.rfind('.') {
+        Some(pos) => (Some(name[..pos].to_owned()), &name[pos + 1..]),
+        None => (None, name),
+    };
+    Expr::Column(Column {
+        relation,
+        name: name.to_owned(),
+        index: None,
+    })
+}
+
+/// Returns a literal expression.
+pub fn lit<T: Into<Value>>(value: T) -> Expr {
+    Expr::Literal(value.into())
+}
+
+fn aggregate(func: AggregateFunction, arg: Expr) -> Expr {
+    Expr::AggregateFunction {
+        func,
+        arg: Box::new(arg),
+    }
+}
+
+/// Number of rows for which the expression is not null.
+pub fn count(expr: Expr) -> Expr {
+    aggregate(AggregateFunction::Count, expr)
+}
+
+/// Number of distinct non null values of the expression.
+pub fn count_distinct(expr: Expr) -> Expr {
+    aggregate(AggregateFunction::CountDistinct, expr)
+}
+
+pub fn sum(expr: Expr) -> Expr {
+    aggregate(AggregateFunction::Sum, expr)
+}
+
+pub fn avg(expr: Expr) -> Expr {
+    aggregate(AggregateFunction::Avg, expr)
+}
+
+pub fn min(expr: Expr) -> Expr {
+    aggregate(AggregateFunction::Min, expr)
+}
+
+pub fn max(expr: Expr) -> Expr {
+    aggregate(AggregateFunction::Max, expr)
+}
+
+impl Expr {
+    fn binary(self, op: Operator, other: Expr) -> Expr {
+        Expr::BinaryExpr {
+            left: Box::new(self),
+            op,
+            right: Box::new(other),
+        }
+    }
+
+    pub fn eq(self, other: Expr) -> Expr {
+        self.binary(Operator::Eq, other)
+    }
+
+    pub fn not_eq(self, other: Expr) -> Expr {
+        self.binary(Operator::NotEq, other)
+    }
+
+    pub fn lt(self, other: Expr) -> Expr {
+        self.binary(Operator::Lt, other)
+    }
+
+    pub fn lt_eq(self, other: Expr) -> Expr {
+        self.binary(Operator::LtEq, other)
+    }
+
+    pub fn gt(self, other: Expr) -> Expr {
+        self.binary(Operator::Gt, other)
+    }
+
+    pub fn gt_eq(self, other: Expr) -> Expr {
+        self.binary(Operator::GtEq, other)
+    }
+
+    pub fn and(self, other: Expr) -> Expr {
+        self.binary(Operator::And, other)
+    }
+
+    pub fn or(self, other: Expr) -> Expr {
+        self.binary(Operator::Or, other)
+    }
+
+    /// Whether the string matches the SQL pattern, see [Operator::Like](enum.Operator.html).
+    pub fn like(self, pattern: Expr) -> Expr {
+        self.binary(Operator::Like, pattern)
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(self) -> Expr {
+        Expr::Not(Box::new(self))
+    }
+
+    pub fn is_null(self) -> Expr {
+        Expr::IsNull(Box::new(self))
+    }
+
+    pub fn is_not_null(self) -> Expr {
+        Expr::IsNotNull(Box::new(self))
+    }
+
+    pub fn cast(self, data_type: DataType) -> Expr {
+        Expr::Cast {
+            expr: Box::new(self),
+            data_type,
+        }
+    }
+
+    pub fn alias(self, name: &str) -> Expr {
+        Expr::Alias(Box::new(self), name.to_owned())
+    }
+
+    pub fn asc(self) -> SortExpr {
+        SortExpr {
+            expr: self,
+            ascending: true,
+        }
+    }
+
+    pub fn desc(self) -> SortExpr {
+        SortExpr {
+            expr: self,
+            ascending: false,
+        }
+    }
+
+    /// Name of the column produced by the expression.
+    pub fn name(&self) -> String {
+        match self {
+            Expr::Alias(_, name) => name.clone(),
+            Expr::Column(column) => column.name.clone(),
+            other => other.to_string(),
+        }
+    }
+
+    /// Returns the type of the values produced by the expression over rows of the schema.
+    pub fn data_type(&self, schema: &Schema) -> Result<DataType> {
+        let mismatch = || PlanError::TypeMismatch(self.to_string());
+        let data_type = match self {
+            Expr::Column(column) => match column.index {
+                Some(index) => schema.field(index).data_type(),
+                None => schema
+                    .field(schema.index_of(&column.flat_name())?)
+                    .data_type(),
+            },
+            Expr::Literal(value) => value.data_type(),
+            Expr::BinaryExpr { left, op, right } => {
+                let left = left.data_type(schema)?;
+                let right = right.data_type(schema)?;
+                match op {
+                    Operator::And | Operator::Or => {
+                        for data_type in &[left, right] {
+                            if !(*data_type == DataType::Boolean || *data_type == DataType::Null) {
+                                return Err(mismatch().into());
+                            }
+                        }
+                        DataType::Boolean
+                    }
+                    Operator::Like | Operator::NotLike => {
+                        for data_type in &[left, right] {
+                            if !(*data_type == DataType::Utf8 || *data_type == DataType::Null) {
+                                return Err(mismatch().into());
+                            }
+                        }
+                        DataType::Boolean
+                    }
+                    op if op.is_comparison() => {
+                        if left.common_type(right).is_none() {
+                            return Err(mismatch().into());
+                        }
+                        DataType::Boolean
+                    }
+                    op => {
+                        if !left.is_numeric() || !right.is_numeric() {
+                            return Err(mismatch().into());
+                        }
+                        match (op, left, right) {
+                            (Operator::Divide, _, _) => DataType::Float64,
+                            (_, DataType::Float64, _) | (_, _, DataType::Float64) => {
+                                DataType::Float64
+                            }
+                            _ => DataType::Int64,
+                        }
+                    }
+                }
+            }
+            Expr::Not(expr) => match expr.data_type(schema)? {
+                DataType::Boolean | DataType::Null => DataType::Boolean,
+                _ => return Err(mismatch().into()),
+            },
+            Expr::Negative(expr) => match expr.data_type(schema)? {
+                data_type if data_type.is_numeric() => data_type,
+                _ => return Err(mismatch().into()),
+            },
+            Expr::IsNull(expr) | Expr::IsNotNull(expr) => {
+                expr.data_type(schema)?;
+                DataType::Boolean
+            }
+            Expr::Cast { expr, data_type } => {
+                expr.data_type(schema)?;
+                *data_type
+            }
+            Expr::Alias(expr, _) => expr.data_type(schema)?,
+            Expr::AggregateFunction { func, arg } => {
+                let arg = arg.data_type(schema)?;
+                match func {
+                    AggregateFunction::Count | AggregateFunction::CountDistinct => DataType::Int64,
+                    AggregateFunction::Sum | AggregateFunction::Avg if !arg.is_numeric() => {
+                        return Err(mismatch().into())
+                    }
+                    AggregateFunction::Sum if arg == DataType::Float64 => DataType::Float64,
+                    AggregateFunction::Sum => DataType::Int64,
+                    AggregateFunction::Avg => DataType::Float64,
+                    AggregateFunction::Min | AggregateFunction::Max => arg,
+                }
+            }
+            Expr::ScalarFunction { func, args } => {
+                let args = args
+                    .iter()
+                    .map(|arg| arg.data_type(schema))
+                    .collect::<Result<Vec<_>>>()?;
+                func.return_type(&args).ok_or_else(mismatch)?
+            }
+        };
+        Ok(data_type)
+    }
+
+    fn is_nullable(&self, schema: &Schema) -> bool {
+        match self {
+            Expr::Column(column) => column
+                .index
+                .map_or(true, |index| schema.field(index).is_nullable()),
+            Expr::Literal(value) => value.is_null(),
+            Expr::IsNull(_) | Expr::IsNotNull(_) => false,
+            Expr::Alias(expr, _) => expr.is_nullable(schema),
+            Expr::AggregateFunction {
+                func: AggregateFunction::Count,
+                ..
+            }
+            | Expr::AggregateFunction {
+                func: AggregateFunction::CountDistinct,
+                ..
+            } => false,
+            _ => true,
+        }
+    }
+
+    /// Returns the field of the column produced by the expression over rows of the schema.
+    pub(crate) fn to_field(&self, schema: &Schema) -> Result<Field> {
+        let data_type = self.data_type(schema)?;
+        let field = Field::new(&self.name(), data_type, self.is_nullable(schema));
+        Ok(match self {
+            // Columns which are passed through keep their relation.
+            Expr::Column(column) => {
+                let qualifier = column
+                    .index
+                    .and_then(|index| schema.field(index).qualifier());
+                field.with_qualifier(qualifier)
+            }
+            _ => field,
+        })
+    }
+
+    /// Bind the columns of the expression to their index in the schema and check its types.
+    pub(crate) fn resolve(&self, schema: &Schema) -> Result<Expr> {
+        let mut failure = None;
+        let resolved = self.clone().rewrite(&mut |expr| match expr {
+            Expr::Column(mut column) => {
+                match schema.index_of(&column.flat_name()) {
+                    Ok(index) => column.index = Some(index),
+                    Err(err) => failure = Some(err),
+                }
+                Expr::Column(column)
+            }
+            expr => expr,
+        });
+        if let Some(err) = failure {
+            return Err(err);
+        }
+        // The arguments of coalesce are cast to their common type, so that the result has the
+        // same type whichever argument is returned.
+        let resolved = resolved.rewrite(&mut |expr| match expr {
+            Expr::ScalarFunction {
+                func: ScalarFunction::Coalesce,
+                args,
+            } => {
+                let types = args
+                    .iter()
+                    .map(|arg| arg.data_type(schema).ok())
+                    .collect::<Option<Vec<_>>>();
+                let common = types
+                    .as_ref()
+                    .and_then(|types| ScalarFunction::Coalesce.return_type(types));
+                let args = match (types, common) {
+                    (Some(types), Some(common)) => args
+                        .into_iter()
+                        .zip(types)
+                        .map(|(arg, data_type)| match data_type {
+                            DataType::Null => arg,
+                            data_type if data_type == common => arg,
+                            _ => arg.cast(common),
+                        })
+                        .collect(),
+                    _ => args,
+                };
+                ScalarFunction::Coalesce.call(args)
+            }
+            expr => expr,
+        });
+        resolved.data_type(schema)?;
+        Ok(resolved)
+    }
+
+    /// Rewrite the expression bottom-up, applying the function to every node after its children.
+    pub(crate) fn rewrite<F: FnMut(Expr) -> Expr>(self, f: &mut F) -> Expr {
+        let expr = match self {
+            Expr::BinaryExpr { left, op, right } => Expr::BinaryExpr {
+                left: Box::new(left.rewrite(f)),
+                op,
+                right: Box::new(right.rewrite(f)),
+            },
+            Expr::Not(expr) => Expr::Not(Box::new(expr.rewrite(f))),
+            Expr::Negative(expr) => Expr::Negative(Box::new(expr.rewrite(f))),
+            Expr::IsNull(expr) => Expr::IsNull(Box::new(expr.rewrite(f))),
+            Expr::IsNotNull(expr) => Expr::IsNotNull(Box::new(expr.rewrite(f))),
+            Expr::Cast { expr, data_type } => Expr::Cast {
+                expr: Box::new(expr.rewrite(f)),
+                data_type,
+            },
+            Expr::Alias(expr, name) => Expr::Alias(Box::new(expr.rewrite(f)), name),
+            Expr::AggregateFunction { func, arg } => Expr::AggregateFunction {
+                func,
+                arg: Box::new(arg.rewrite(f)),
+            },
+            Expr::ScalarFunction { func, args } => Expr::ScalarFunction {
+                func,
+                args: args.into_iter().map(|arg| arg.rewrite(f)).collect(),
+            },
+            leaf => leaf,
+        };
+        f(expr)
+    }
+
+    pub(crate) fn children(&self) -> Vec<&Expr> {
+        match self {
+            Expr::BinaryExpr { left, right, .. } => vec![&**left, &**right],
+            Expr::Not(expr)
+            | Expr::Negative(expr)
+            | Expr::IsNull(expr)
+            | Expr::IsNotNull(expr)
+            | Expr::Cast { expr, .. }
+            | Expr::Alias(expr, _)
+            | Expr::AggregateFunction { arg: expr, .. } => vec![&**expr],
+            Expr::ScalarFunction { args, .. } => args.iter().collect(),
+            Expr::Column(_) | Expr::Literal(_) => vec![],
+        }
+    }
+
+    /// Add the indices of the columns referenced by the expression to the set.
+    pub(crate) fn collect_columns(&self, columns: &mut BTreeSet<usize>) {
+        if let Expr::Column(Column {
+            index: Some(index), ..
+        }) = self
+        {
+            columns.insert(*index);
+        }
+        self.children()
+            .into_iter()
+            .for_each(|child| child.collect_columns(columns));
+    }
+
+    pub(crate) fn columns(&self) -> BTreeSet<usize> {
+        let mut columns = BTreeSet::new();
+        self.collect_columns(&mut columns);
+        columns
+    }
+
+    pub(crate) fn contains_aggregate(&self) -> bool {
+        match self {
+            Expr::AggregateFunction { .. } => true,
+            expr => expr.children().into_iter().any(Expr::contains_aggregate),
+        }
+    }
+
+    /// Change the index of the referenced columns.
+    pub(crate) fn remap_columns<F: Fn(usize) -> usize>(self, remap: F) -> Expr {
+        self.rewrite(&mut |expr| match expr {
+            Expr::Column(mut column) => {
+                column.index = column.index.map(&remap);
+                Expr::Column(column)
+            }
+            expr => expr,
+        })
+    }
+
+    /// Replace the referenced columns by the expressions producing them.
+    pub(crate) fn substitute_columns(self, exprs: &[Expr]) -> Expr {
+        self.rewrite(&mut |expr| match expr {
+            Expr::Column(Column {
+                index: Some(index), ..
+            }) => match exprs[index] {
+                Expr::Alias(ref expr, _) => (**expr).clone(),
+                ref expr => expr.clone(),
+            },
+            expr => expr,
+        })
+    }
+
+    /// Evaluate the expression over a row. Aggregate functions are evaluated by the aggregation
+    /// itself, here they evaluate their argument.
+    pub(crate) fn evaluate(&self, row: &[Value]) -> Value {
+        match self {
+            Expr::Column(column) => column
+                .index
+                .and_then(|index| row.get(index).cloned())
+                .unwrap_or(Value::Null),
+            Expr::Literal(value) => value.clone(),
+            Expr::BinaryExpr { left, op, right } => match op {
+                // Evaluate the right side lazily, as in three-valued logic.
+                Operator::And => match left.evaluate(row).as_bool() {
+                    Some(false) => Value::Boolean(false),
+                    left => match (left, right.evaluate(row).as_bool()) {
+                        (_, Some(false)) => Value::Boolean(false),
+                        (Some(true), Some(true)) => Value::Boolean(true),
+                        _ => Value::Null,
+                    },
+                },
+                Operator::Or => match left.evaluate(row).as_bool() {
+                    Some(true) => Value::Boolean(true),
+                    left => match (left, right.evaluate(row).as_bool()) {
+                        (_, Some(true)) => Value::Boolean(true),
+                        (Some(false), Some(false)) => Value::Boolean(false),
+                        _ => Value::Null,
+                    },
+                },
+                op => evaluate_binary(*op, &left.evaluate(row), &right.evaluate(row)),
+            },
+            Expr::Not(expr) => match expr.evaluate(row) {
+                Value::Boolean(v) => Value::Boolean(!v),
+                _ => Value::Null,
+            },
+            Expr::Negative(expr) => match expr.evaluate(row) {
+                Value::Int(v) => v.checked_neg().map_or(Value::Null, Value::Int),
+                Value::Float(v) => Value::Float(-v),
+                _ => Value::Null,
+            },
+            Expr::IsNull(expr) => Value::Boolean(expr.evaluate(row).is_null()),
+            Expr::IsNotNull(expr) => Value::Boolean(!expr.evaluate(row).is_null()),
+            Expr::Cast { expr, data_type } => expr.evaluate(row).cast(*data_type),
+            Expr::Alias(expr, _) => expr.evaluate(row),
+            Expr::AggregateFunction { arg, .. } => arg.evaluate(row),
+            Expr::ScalarFunction { func, args } => {
+                func.evaluate(args.iter().map(|arg| arg.evaluate(row)).collect())
+            }
+        }
+    }
+}
+
+/// Evaluate a comparison or arithmetic operator. Nulls and values of incompatible types
+/// produce null, as well as integer overflows and divisions by zero.
+pub(crate) fn evaluate_binary(op: Operator, left: &Value, right: &Value) -> Value {
+    use std::cmp::Ordering;
+    if op.is_comparison() {
+        let ordering = match (left, right) {
+            (Value::Null, _) | (_, Value::Null) => return Value::Null,
+            (Value::Int(a), Value::Int(b)) => a.cmp(b),
+            (a, b) if a.data_type().is_numeric() && b.data_type().is_numeric() => {
+                cmp_f64(a.as_f64().unwrap(), b.as_f64().unwrap())
+            }
+            (a, b) if a.data_type() == b.data_type() => a.cmp(b),
+            _ => return Value::Null,
+        };
+        let result = match op {
+            Operator::Eq => ordering == Ordering::Equal,
+            Operator::NotEq => ordering != Ordering::Equal,
+            Operator::Lt => ordering == Ordering::Less,
+            Operator::LtEq => ordering != Ordering::Greater,
+            Operator::Gt => ordering == Ordering::Greater,
+            _ => ordering != Ordering::Less,
+        };
+        return Value::Boolean(result);
+    }
+    if op == Operator::Like || op == Operator::NotLike {
+        return match (left, right) {
+            (Value::Utf8(value), Value::Utf8(pattern)) => {
+                Value::Boolean(like(value, pattern) == (op == Operator::Like))
+            }
+            _ => Value::Null,
+        };
+    }
+    match (left, right) {
+        (Value::Int(a), Value::Int(b)) => {
+            let result = match op {
+                Operator::Plus => a.checked_add(*b),
+                Operator::Minus => a.checked_sub(*b),
+                Operator::Multiply => a.checked_mul(*b),
+                Operator::Divide if *b != 0 => return Value::Float(*a as f64 / *b as f64),
+                Operator::Modulo => a.checked_rem(*b),
+                _ => None,
+            };
+            result.map_or(Value::Null, Value::Int)
+        }
+        (a, b) => match (a.as_f64(), b.as_f64()) {
+            (Some(a), Some(b)) => match op {
+                Operator::Plus => Value::Float(a + b),
+                Operator::Minus => Value::Float(a - b),
+                Operator::Multiply => Value::Float(a * b),
+                Operator::Divide if b != 0.0 => Value::Float(a / b),
+                Operator::Modulo if b != 0.0 => Value::Float(a % b),
+                _ => Value::Null,
+            },
+            _ => Value::Null,
+        },
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expr::Column(column) => write!(f, "{}", column.flat_name()),
+            Expr::Literal(Value::Utf8(value)) => write!(f, "'{}'", value),
+            Expr::Literal(value) => write!(f, "{}", value),
+            Expr::BinaryExpr { left, op, right } => write!(f, "({} {} {})", left, op, right),
+            Expr::Not(expr) => write!(f, "NOT {}", expr),
+            Expr::Negative(expr) => write!(f, "-{}", expr),
+            Expr::IsNull(expr) => write!(f, "{} IS NULL", expr),
+            Expr::IsNotNull(expr) => write!(f, "{} IS NOT NULL", expr),
+            Expr::Cast { expr, data_type } => write!(f, "CAST({} AS {})", expr, data_type),
+            Expr::Alias(expr, name) => write!(f, "{} AS {}", expr, name),
+            Expr::AggregateFunction {
+                func: AggregateFunction::CountDistinct,
+                arg,
+            } => write!(f, "COUNT(DISTINCT {})", arg),
+            Expr::AggregateFunction { func, arg } => write!(f, "{}({})", func, arg),
+            Expr::ScalarFunction { func, args } => {
+                let args = args.iter().map(ToString::to_string).collect::<Vec<_>>();
+                write!(f, "{}({})", func, args.join(", "))
+            }
+        }
+    }
+}
+
+macro_rules! impl_binary_operator {
+    ($trait:ident, $method:ident, $op:expr) => {
+        impl ops::$trait for Expr {
+            type Output = Expr;
+
+            fn $method(self, other: Expr) -> Expr {
+                self.binary($op, other)
+            }
+        }
+    };
+}
+
+impl_binary_operator!(Add, add, Operator::Plus);
+impl_binary_operator!(Sub, sub, Operator::Minus);
+impl_binary_operator!(Mul, mul, Operator::Multiply);
+impl_binary_operator!(Div, div, Operator::Divide);
+impl_binary_operator!(Rem, rem, Operator::Modulo);
+
+impl ops::Neg for Expr {
+    type Output = Expr;
+
+    fn neg(self) -> Expr {
+        Expr::Negative(Box::new(self))
+    }
+}
+
+/// Sort key of [order_by](struct.DataFrame.html#method.order_by). Nulls come first in ascending
+/// order and last in descending order.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SortExpr {
+    pub expr: Expr,
+    pub ascending: bool,
+}
+
+impl Display for SortExpr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let order = if self.ascending { "ASC" } else { "DESC" };
+        write!(f, "{} {}", self.expr, order)
+    }
+}
+
+/// Whether the value matches the LIKE pattern. A `%` in the pattern matches any sequence of
+/// characters and a `_` any single character.
+fn like(value: &str, pattern: &str) -> bool {
+    let value = value.chars().collect::<Vec<_>>();
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let (mut v, mut p) = (0, 0);
+    // Position of the last `%` in the pattern and of the value when it was reached, to
+    // backtrack when the rest of the pattern doesn't match.
+    let mut backtrack = None;
+    while v < value.len() {
+        match pattern.get(p) {
+            Some('%') => {
+                backtrack = Some((p, v));
+                p += 1;
+            }
+            Some(&c) if c == '_' || c == value[v] => {
+                p += 1;
+                v += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    v = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '%')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn like_patterns() {
+        assert!(like("vega", "vega"));
+        assert!(like("vega", "v%"));
+        assert!(like("vega", "%g_"));
+        assert!(like("", "%"));
+        assert!(like("abcabd", "%ab_"));
+        assert!(!like("vega", "v_g"));
+        assert!(!like("vega", "%x%"));
+    }
+
+    #[test]
+    fn scalar_functions() {
+        let eval = |func: ScalarFunction, args: Vec<Value>| func.evaluate(args);
+        assert_eq!(eval(ScalarFunction::Abs, vec![(-3).into()]), Value::Int(3));
+        assert_eq!(
+            eval(ScalarFunction::Round, vec![2.567.into(), 1.into()]),
+            Value::Float(2.6)
+        );
+        assert_eq!(
+            eval(ScalarFunction::Round, vec![1234.into(), (-2).into()]),
+            Value::Int(1200)
+        );
+        assert_eq!(
+            eval(
+                ScalarFunction::Substr,
+                vec!["dataframe".into(), 5.into(), 3.into()]
+            ),
+            Value::from("fra")
+        );
+        assert_eq!(
+            eval(ScalarFunction::Concat, vec!["a".into(), Value::Null]),
+            Value::Null
+        );
+        assert_eq!(
+            eval(ScalarFunction::Coalesce, vec![Value::Null, 2.into()]),
+            Value::Int(2)
+        );
+        assert_eq!(
+            ScalarFunction::Coalesce.return_type(&[DataType::Int64, DataType::Float64]),
+            Some(DataType::Float64)
+        );
+        assert_eq!(ScalarFunction::Upper.return_type(&[DataType::Int64]), None);
+    }
+}
@@ -0,0 +1,369 @@
+//! Relational API over RDDs of rows.
+//!
+//! A [DataFrame](struct.DataFrame.html) is a lazily evaluated query over rows with a known
+//! [Schema](struct.Schema.html). Instead of opaque closures its operators take
+//! [expressions](enum.Expr.html), so that the query can be optimized before running: filters
+//! are pushed down to the sources, unused columns are pruned and constant expressions are
+//! folded. The optimized plan is then compiled down to the usual RDD operators.
+//!
+//! ```no_run
+//! use vega::dataframe::*;
+//! # use vega::*;
+//! # fn main() -> Result<()> {
+//! # let sc = Context::new()?;
+//! let schema = Schema::new(vec![
+//!     Field::new("name", DataType::Utf8, false),
+//!     Field::new("age", DataType::Int64, true),
+//! ]);
+//! let rows = vec![vec![Value::from("alice"), Value::from(31)]];
+//! let people = sc.create_dataframe(sc.parallelize(rows, 1), schema);
+//! let adults = people
+//!     .filter(col("age").gt_eq(lit(18)))?
+//!     .group_by(vec![col("name")])?
+//!     .agg(vec![count(col("age")).alias("n")])?
+//!     .collect()?;
+//! # Ok(())
+//! # }
+//! ```
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::error::Result;
+use crate::io::ParquetWriterConfig;
+use crate::rdd::Rdd;
+use crate::SerArc;
+use thiserror::Error;
+
+pub mod columnar;
+mod execution;
+mod expr;
+mod optimizer;
+mod plan;
+mod schema;
+pub mod sql;
+mod value;
+
+pub(crate) use expr::evaluate_binary;
+pub use expr::{
+    avg, col, count, count_distinct, lit, max, min, sum, AggregateFunction, Column, Expr, Operator,
+    ScalarFunction, SortExpr,
+};
+pub use optimizer::{
+    ConstantFolding, Optimizer, OptimizerRule, PredicatePushdown, ProjectionPruning,
+};
+pub use plan::{JoinType, LogicalPlan, RddTable, TableProvider};
+pub use schema::{DataType, Field, Schema};
+pub use value::{Row, Value};
+
+#[derive(Debug, Error)]
+pub enum PlanError {
+    #[error("unknown column {0}")]
+    UnknownColumn(String),
+
+    #[error("ambiguous column {0}")]
+    AmbiguousColumn(String),
+
+    #[error("invalid types in expression {0}")]
+    TypeMismatch(String),
+
+    #[error("invalid aggregate expression {0}")]
+    InvalidAggregate(String),
+
+    #[error("unknown table {0}")]
+    UnknownTable(String),
+
+    #[error("SQL syntax error: {0}")]
+    Syntax(String),
+
+    #[error("unsupported SQL: {0}")]
+    Unsupported(String),
+
+    #[error("unsupported column type {0}")]
+    UnsupportedType(String),
+}
+
+/// A query over rows with a known schema. See the [module documentation](index.html).
+#[derive(Clone)]
+pub struct DataFrame {
+    plan: LogicalPlan,
+}
+
+impl DataFrame {
+    pub(crate) fn new(plan: LogicalPlan) -> Self {
+        DataFrame { plan }
+    }
+
+    /// Returns a DataFrame reading all the rows of the table.
+    pub(crate) fn scan(name: &str, source: Arc<dyn TableProvider>) -> Self {
+        let schema = source.schema();
+        DataFrame::new(LogicalPlan::Scan {
+            name: name.to_owned(),
+            source,
+            projection: None,
+            filters: vec![],
+            schema,
+        })
+    }
+
+    pub fn schema(&self) -> &Schema {
+        self.plan.schema()
+    }
+
+    pub fn logical_plan(&self) -> &LogicalPlan {
+        &self.plan
+    }
+
+    fn resolve_all(&self, exprs: &[Expr]) -> Result<Vec<Expr>> {
+        exprs
+            .iter()
+            .map(|expr| {
+                if expr.contains_aggregate() {
+                    return Err(PlanError::InvalidAggregate(expr.to_string()).into());
+                }
+                expr.resolve(self.schema())
+            })
+            .collect()
+    }
+
+    /// Returns the DataFrame with a column for each expression.
+    pub fn select(&self, exprs: Vec<Expr>) -> Result<DataFrame> {
+        let exprs = self.resolve_all(&exprs)?;
+        let fields = exprs
+            .iter()
+            .map(|expr| expr.to_field(self.schema()))
+            .collect::<Result<_>>()?;
+        Ok(DataFrame::new(LogicalPlan::Projection {
+            exprs,
+            input: Box::new(self.plan.clone()),
+            schema: Schema::new(fields),
+        }))
+    }
+
+    /// Returns the rows for which the predicate is true.
+    pub fn filter(&self, predicate: Expr) -> Result<DataFrame> {
+        let predicate = self.resolve_all(&[predicate])?.remove(0);
+        match predicate.data_type(self.schema())? {
+            DataType::Boolean | DataType::Null => {}
+            _ => return Err(PlanError::TypeMismatch(predicate.to_string()).into()),
+        }
+        Ok(DataFrame::new(LogicalPlan::Filter {
+            predicate,
+            input: Box::new(self.plan.clone()),
+        }))
+    }
+
+    /// Group the rows by the given expressions, to compute aggregates with
+    /// [agg](struct.GroupedData.html#method.agg).
+    pub fn group_by(&self, exprs: Vec<Expr>) -> Result<GroupedData> {
+        Ok(GroupedData {
+            input: self.clone(),
+            group_exprs: self.resolve_all(&exprs)?,
+        })
+    }
+
+    /// Compute aggregates over all the rows, as a single group.
+    pub fn agg(&self, aggr_exprs: Vec<Expr>) -> Result<DataFrame> {
+        self.group_by(vec![])?.agg(aggr_exprs)
+    }
+
+    /// Join with another DataFrame on the equality of pairs of left and right columns.
+    ///
+    /// The rows of the result hold the columns of this DataFrame followed by the columns of
+    /// the other one. Rows with null keys never match.
+    pub fn join(
+        &self,
+        right: &DataFrame,
+        on: &[(&str, &str)],
+        join_type: JoinType,
+    ) -> Result<DataFrame> {
+        let (left_schema, right_schema) = (self.schema(), right.schema());
+        let on = on
+            .iter()
+            .map(|(l, r)| {
+                let (l, r) = (left_schema.index_of(l)?, right_schema.index_of(r)?);
+                let (l_type, r_type) = (
+                    left_schema.field(l).data_type(),
+                    right_schema.field(r).data_type(),
+                );
+                if l_type.common_type(r_type).is_none() {
+                    let keys = format!(
+                        "{} = {}",
+                        left_schema.field(l).qualified_name(),
+                        right_schema.field(r).qualified_name()
+                    );
+                    return Err(PlanError::TypeMismatch(keys).into());
+                }
+                Ok((l, r))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        // The columns of the side which may have no match become nullable.
+        let nullable = |schema: &Schema, nullable: bool| {
+            Schema::new(
+                schema
+                    .fields()
+                    .iter()
+                    .map(|field| {
+                        let is_nullable = field.is_nullable() || nullable;
+                        field.clone().with_nullable(is_nullable)
+                    })
+                    .collect(),
+            )
+        };
+        let schema = nullable(left_schema, join_type.preserves_right())
+            .join(&nullable(right_schema, join_type.preserves_left()));
+        Ok(DataFrame::new(LogicalPlan::Join {
+            left: Box::new(self.plan.clone()),
+            right: Box::new(right.plan.clone()),
+            on,
+            join_type,
+            schema,
+        }))
+    }
+
+    /// Sort the rows by the given keys.
+    pub fn order_by(&self, exprs: Vec<SortExpr>) -> Result<DataFrame> {
+        let exprs = exprs
+            .into_iter()
+            .map(|sort| {
+                Ok(SortExpr {
+                    expr: self.resolve_all(&[sort.expr])?.remove(0),
+                    ascending: sort.ascending,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(DataFrame::new(LogicalPlan::Sort {
+            exprs,
+            input: Box::new(self.plan.clone()),
+        }))
+    }
+
+    /// Returns at most `n` rows.
+    pub fn limit(&self, n: usize) -> DataFrame {
+        DataFrame::new(LogicalPlan::Limit {
+            n,
+            input: Box::new(self.plan.clone()),
+        })
+    }
+
+    /// Returns the DataFrame without duplicate rows.
+    pub fn distinct(&self) -> DataFrame {
+        let group_exprs = self
+            .schema()
+            .fields()
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                Expr::Column(Column {
+                    relation: field.qualifier().map(ToOwned::to_owned),
+                    name: field.name().to_owned(),
+                    index: Some(i),
+                })
+            })
+            .collect();
+        DataFrame::new(LogicalPlan::Aggregate {
+            group_exprs,
+            aggr_exprs: vec![],
+            input: Box::new(self.plan.clone()),
+            schema: self.schema().clone(),
+        })
+    }
+
+    /// Qualify all the columns with the given relation name, so that they can be referred to
+    /// as `alias.column`.
+    pub fn alias(&self, alias: &str) -> DataFrame {
+        DataFrame::new(LogicalPlan::Alias {
+            alias: alias.to_owned(),
+            schema: self.schema().with_qualifier(alias),
+            input: Box::new(self.plan.clone()),
+        })
+    }
+
+    /// Returns the plan after applying the default optimizer rules.
+    pub fn optimized_plan(&self) -> Result<LogicalPlan> {
+        Optimizer::default().optimize(self.plan.clone())
+    }
+
+    /// Returns a description of the logical and the optimized plans of the query.
+    pub fn explain(&self) -> Result<String> {
+        Ok(format!(
+            "== Logical Plan ==\n{}== Optimized Plan ==\n{}",
+            self.plan,
+            self.optimized_plan()?
+        ))
+    }
+
+    /// Compile the optimized plan down to an RDD of rows.
+    pub fn to_rdd(&self) -> Result<SerArc<dyn Rdd<Item = Row>>> {
+        execution::execute(&self.optimized_plan()?)
+    }
+
+    /// Compile the optimized plan and group the rows of each partition into Arrow record
+    /// batches of at most `batch_size` rows.
+    pub fn to_record_batches(&self, batch_size: usize) -> Result<columnar::RecordBatchRdd> {
+        Ok(columnar::RecordBatchRdd::from_rows(
+            self.to_rdd()?,
+            self.schema().clone(),
+            batch_size,
+        ))
+    }
+
+    /// Write the rows to a directory, as one Parquet file per partition.
+    pub fn save_as_parquet<P: Into<PathBuf>>(
+        &self,
+        path: P,
+        config: ParquetWriterConfig,
+    ) -> Result<()> {
+        crate::io::save_as_parquet(self, path.into(), config)
+    }
+
+    pub fn collect(&self) -> Result<Vec<Row>> {
+        self.to_rdd()?.collect()
+    }
+
+    pub fn count(&self) -> Result<u64> {
+        self.to_rdd()?.count()
+    }
+}
+
+/// Rows of a DataFrame grouped by some expressions, returned by
+/// [group_by](struct.DataFrame.html#method.group_by).
+pub struct GroupedData {
+    input: DataFrame,
+    group_exprs: Vec<Expr>,
+}
+
+impl GroupedData {
+    /// Compute aggregates for each group. The rows of the result hold the grouping expressions
+    /// followed by the aggregates.
+    pub fn agg(&self, aggr_exprs: Vec<Expr>) -> Result<DataFrame> {
+        let schema = self.input.schema();
+        let aggr_exprs = aggr_exprs
+            .iter()
+            .map(|expr| {
+                let invalid = || PlanError::InvalidAggregate(expr.to_string());
+                let aggregate = match expr {
+                    Expr::Alias(inner, _) => &**inner,
+                    expr => expr,
+                };
+                match aggregate {
+                    Expr::AggregateFunction { arg, .. } if !arg.contains_aggregate() => {
+                        expr.resolve(schema)
+                    }
+                    _ => Err(invalid().into()),
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let fields = self
+            .group_exprs
+            .iter()
+            .chain(aggr_exprs.iter())
+            .map(|expr| expr.to_field(schema))
+            .collect::<Result<_>>()?;
+        Ok(DataFrame::new(LogicalPlan::Aggregate {
+            group_exprs: self.group_exprs.clone(),
+            aggr_exprs,
+            input: Box::new(self.input.plan.clone()),
+            schema: Schema::new(fields),
+        }))
+    }
+}
@@ -0,0 +1,634 @@
+//! Rule-based optimizer rewriting the logical plan of a DataFrame before it is compiled down
+//! to RDD operators.
+use std::collections::BTreeSet;
+
+use crate::dataframe::{Expr, LogicalPlan, Operator, SortExpr, Value};
+use crate::error::Result;
+
+/// A rewrite of a logical plan which doesn't change its result.
+pub trait OptimizerRule {
+    fn name(&self) -> &'static str;
+
+    fn optimize(&self, plan: LogicalPlan) -> Result<LogicalPlan>;
+}
+
+/// Applies a list of rules to a plan, in order.
+pub struct Optimizer {
+    rules: Vec<Box<dyn OptimizerRule>>,
+}
+
+impl Optimizer {
+    pub fn new(rules: Vec<Box<dyn OptimizerRule>>) -> Self {
+        Optimizer { rules }
+    }
+
+    pub fn optimize(&self, plan: LogicalPlan) -> Result<LogicalPlan> {
+        self.rules.iter().try_fold(plan, |plan, rule| {
+            log::debug!("applying optimizer rule {}", rule.name());
+            rule.optimize(plan)
+        })
+    }
+}
+
+impl Default for Optimizer {
+    fn default() -> Self {
+        Optimizer::new(vec![
+            Box::new(ConstantFolding),
+            Box::new(PredicatePushdown),
+            Box::new(ProjectionPruning),
+        ])
+    }
+}
+
+/// Rebuild the plan with its inputs transformed by the function.
+fn map_inputs<F>(plan: LogicalPlan, mut f: F) -> Result<LogicalPlan>
+where
+    F: FnMut(LogicalPlan) -> Result<LogicalPlan>,
+{
+    let mut f = |input: Box<LogicalPlan>| f(*input).map(Box::new);
+    let plan = match plan {
+        LogicalPlan::Projection {
+            exprs,
+            input,
+            schema,
+        } => LogicalPlan::Projection {
+            exprs,
+            input: f(input)?,
+            schema,
+        },
+        LogicalPlan::Filter { predicate, input } => LogicalPlan::Filter {
+            predicate,
+            input: f(input)?,
+        },
+        LogicalPlan::Aggregate {
+            group_exprs,
+            aggr_exprs,
+            input,
+            schema,
+        } => LogicalPlan::Aggregate {
+            group_exprs,
+            aggr_exprs,
+            input: f(input)?,
+            schema,
+        },
+        LogicalPlan::Join {
+            left,
+            right,
+            on,
+            join_type,
+            schema,
+        } => LogicalPlan::Join {
+            left: f(left)?,
+            right: f(right)?,
+            on,
+            join_type,
+            schema,
+        },
+        LogicalPlan::Sort { exprs, input } => LogicalPlan::Sort {
+            exprs,
+            input: f(input)?,
+        },
+        LogicalPlan::Limit { n, input } => LogicalPlan::Limit {
+            n,
+            input: f(input)?,
+        },
+        LogicalPlan::Alias {
+            alias,
+            input,
+            schema,
+        } => LogicalPlan::Alias {
+            alias,
+            input: f(input)?,
+            schema,
+        },
+        scan @ LogicalPlan::Scan { .. } => scan,
+    };
+    Ok(plan)
+}
+
+/// Split a predicate into the expressions of its conjunction.
+pub(crate) fn split_conjunction(predicate: Expr, conjuncts: &mut Vec<Expr>) {
+    match predicate {
+        Expr::BinaryExpr {
+            left,
+            op: Operator::And,
+            right,
+        } => {
+            split_conjunction(*left, conjuncts);
+            split_conjunction(*right, conjuncts);
+        }
+        predicate => conjuncts.push(predicate),
+    }
+}
+
+/// Returns the conjunction of the predicates, None if there is none.
+pub(crate) fn conjunction(predicates: Vec<Expr>) -> Option<Expr> {
+    predicates
+        .into_iter()
+        .fold(None, |acc, predicate| match acc {
+            Some(acc) => Some(acc.and(predicate)),
+            None => Some(predicate),
+        })
+}
+
+/// Evaluates the expressions which don't depend on the rows and simplifies boolean
+/// expressions with a constant side. Filters which are always true are removed.
+pub struct ConstantFolding;
+
+fn is_literal(expr: &Expr) -> bool {
+    match expr {
+        Expr::Literal(_) => true,
+        _ => false,
+    }
+}
+
+impl ConstantFolding {
+    fn fold(expr: Expr) -> Expr {
+        expr.rewrite(&mut |expr| match expr {
+            Expr::BinaryExpr { left, op, right } if op == Operator::And || op == Operator::Or => {
+                Self::simplify_logical(*left, op, *right)
+            }
+            Expr::Column(_)
+            | Expr::Literal(_)
+            | Expr::Alias(..)
+            | Expr::AggregateFunction { .. } => expr,
+            expr if expr.children().into_iter().all(is_literal) => {
+                Expr::Literal(expr.evaluate(&[]))
+            }
+            expr => expr,
+        })
+    }
+
+    fn simplify_logical(left: Expr, op: Operator, right: Expr) -> Expr {
+        let absorbing = Value::Boolean(op == Operator::Or);
+        let neutral = Value::Boolean(op == Operator::And);
+        let is_value = |expr: &Expr, value: &Value| match expr {
+            Expr::Literal(literal) => literal == value,
+            _ => false,
+        };
+        if is_value(&left, &absorbing) || is_value(&right, &absorbing) {
+            Expr::Literal(absorbing)
+        } else if is_value(&left, &neutral) {
+            right
+        } else if is_value(&right, &neutral) {
+            left
+        } else {
+            let expr = Expr::BinaryExpr {
+                left: Box::new(left),
+                op,
+                right: Box::new(right),
+            };
+            if expr.children().into_iter().all(is_literal) {
+                Expr::Literal(expr.evaluate(&[]))
+            } else {
+                expr
+            }
+        }
+    }
+
+    fn fold_sort(sort: SortExpr) -> SortExpr {
+        SortExpr {
+            expr: Self::fold(sort.expr),
+            ascending: sort.ascending,
+        }
+    }
+}
+
+impl OptimizerRule for ConstantFolding {
+    fn name(&self) -> &'static str {
+        "constant_folding"
+    }
+
+    fn optimize(&self, plan: LogicalPlan) -> Result<LogicalPlan> {
+        let fold_all = |exprs: Vec<Expr>| exprs.into_iter().map(Self::fold).collect::<Vec<_>>();
+        let plan = match map_inputs(plan, |input| self.optimize(input))? {
+            LogicalPlan::Scan {
+                name,
+                source,
+                projection,
+                filters,
+                schema,
+            } => LogicalPlan::Scan {
+                name,
+                source,
+                projection,
+                filters: fold_all(filters),
+                schema,
+            },
+            LogicalPlan::Projection {
+                exprs,
+                input,
+                schema,
+            } => LogicalPlan::Projection {
+                exprs: fold_all(exprs),
+                input,
+                schema,
+            },
+            LogicalPlan::Filter { predicate, input } => match Self::fold(predicate) {
+                Expr::Literal(Value::Boolean(true)) => *input,
+                predicate => LogicalPlan::Filter { predicate, input },
+            },
+            LogicalPlan::Aggregate {
+                group_exprs,
+                aggr_exprs,
+                input,
+                schema,
+            } => LogicalPlan::Aggregate {
+                group_exprs: fold_all(group_exprs),
+                aggr_exprs: fold_all(aggr_exprs),
+                input,
+                schema,
+            },
+            LogicalPlan::Sort { exprs, input } => LogicalPlan::Sort {
+                exprs: exprs.into_iter().map(Self::fold_sort).collect(),
+                input,
+            },
+            plan => plan,
+        };
+        Ok(plan)
+    }
+}
+
+/// Moves the filters as close to the sources as possible, down to the scans, so that fewer
+/// rows go through the other operators and the sources can skip data.
+pub struct PredicatePushdown;
+
+impl PredicatePushdown {
+    fn push_down(plan: LogicalPlan) -> LogicalPlan {
+        match plan {
+            LogicalPlan::Filter { predicate, input } => {
+                let mut conjuncts = Vec::new();
+                split_conjunction(predicate, &mut conjuncts);
+                Self::push_filters(Self::push_down(*input), conjuncts)
+            }
+            plan => map_inputs(plan, |input| Ok(Self::push_down(input))).unwrap(),
+        }
+    }
+
+    fn filter(plan: LogicalPlan, conjuncts: Vec<Expr>) -> LogicalPlan {
+        match conjunction(conjuncts) {
+            Some(predicate) => LogicalPlan::Filter {
+                predicate,
+                input: Box::new(plan),
+            },
+            None => plan,
+        }
+    }
+
+    /// Returns the plan with the predicates applied at the lowest possible point.
+    fn push_filters(plan: LogicalPlan, conjuncts: Vec<Expr>) -> LogicalPlan {
+        if conjuncts.is_empty() {
+            return plan;
+        }
+        match plan {
+            LogicalPlan::Filter { predicate, input } => {
+                let mut conjuncts = conjuncts;
+                split_conjunction(predicate, &mut conjuncts);
+                Self::push_filters(*input, conjuncts)
+            }
+            LogicalPlan::Projection {
+                exprs,
+                input,
+                schema,
+            } => {
+                let conjuncts = conjuncts
+                    .into_iter()
+                    .map(|conjunct| conjunct.substitute_columns(&exprs))
+                    .collect();
+                LogicalPlan::Projection {
+                    input: Box::new(Self::push_filters(*input, conjuncts)),
+                    exprs,
+                    schema,
+                }
+            }
+            LogicalPlan::Sort { exprs, input } => LogicalPlan::Sort {
+                exprs,
+                input: Box::new(Self::push_filters(*input, conjuncts)),
+            },
+            LogicalPlan::Alias {
+                alias,
+                input,
+                schema,
+            } => LogicalPlan::Alias {
+                alias,
+                input: Box::new(Self::push_filters(*input, conjuncts)),
+                schema,
+            },
+            LogicalPlan::Join {
+                left,
+                right,
+                on,
+                join_type,
+                schema,
+            } => {
+                let left_len = left.schema().len();
+                let (mut to_left, mut to_right, mut kept) = (vec![], vec![], vec![]);
+                for conjunct in conjuncts {
+                    let columns = conjunct.columns();
+                    if columns.is_empty() {
+                        kept.push(conjunct);
+                    } else if columns.iter().all(|&i| i < left_len) && !join_type.preserves_right()
+                    {
+                        to_left.push(conjunct);
+                    } else if columns.iter().all(|&i| i >= left_len) && !join_type.preserves_left()
+                    {
+                        to_right.push(conjunct.remap_columns(|i| i - left_len));
+                    } else {
+                        kept.push(conjunct);
+                    }
+                }
+                let join = LogicalPlan::Join {
+                    left: Box::new(Self::push_filters(*left, to_left)),
+                    right: Box::new(Self::push_filters(*right, to_right)),
+                    on,
+                    join_type,
+                    schema,
+                };
+                Self::filter(join, kept)
+            }
+            LogicalPlan::Aggregate {
+                group_exprs,
+                aggr_exprs,
+                input,
+                schema,
+            } => {
+                let (pushed, kept): (Vec<_>, Vec<_>) =
+                    conjuncts.into_iter().partition(|conjunct| {
+                        let columns = conjunct.columns();
+                        !columns.is_empty() && columns.iter().all(|&i| i < group_exprs.len())
+                    });
+                let pushed = pushed
+                    .into_iter()
+                    .map(|conjunct| conjunct.substitute_columns(&group_exprs))
+                    .collect();
+                let aggregate = LogicalPlan::Aggregate {
+                    input: Box::new(Self::push_filters(*input, pushed)),
+                    group_exprs,
+                    aggr_exprs,
+                    schema,
+                };
+                Self::filter(aggregate, kept)
+            }
+            LogicalPlan::Scan {
+                name,
+                source,
+                projection,
+                mut filters,
+                schema,
+            } => {
+                for conjunct in conjuncts {
+                    if !filters.contains(&conjunct) {
+                        filters.push(conjunct);
+                    }
+                }
+                LogicalPlan::Scan {
+                    name,
+                    source,
+                    projection,
+                    filters,
+                    schema,
+                }
+            }
+            // The rows kept by a limit depend on the rows which reach it.
+            plan @ LogicalPlan::Limit { .. } => Self::filter(plan, conjuncts),
+        }
+    }
+}
+
+impl OptimizerRule for PredicatePushdown {
+    fn name(&self) -> &'static str {
+        "predicate_pushdown"
+    }
+
+    fn optimize(&self, plan: LogicalPlan) -> Result<LogicalPlan> {
+        Ok(Self::push_down(plan))
+    }
+}
+
+/// Removes the columns which are not used by the query, down to the scans so that the sources
+/// only read the columns they need.
+pub struct ProjectionPruning;
+
+/// For each column of the original output of a plan, its index in the pruned output if kept.
+type Remap = Vec<Option<usize>>;
+
+impl ProjectionPruning {
+    fn remap_of(kept: &[usize], len: usize) -> Remap {
+        let mut remap = vec![None; len];
+        kept.iter()
+            .enumerate()
+            .for_each(|(new, &old)| remap[old] = Some(new));
+        remap
+    }
+
+    fn rewrite(expr: Expr, remap: &[Option<usize>]) -> Expr {
+        expr.remap_columns(|i| remap[i].expect("pruned a column which is still referenced"))
+    }
+
+    fn kept(remap: &[Option<usize>]) -> Vec<usize> {
+        remap
+            .iter()
+            .enumerate()
+            .filter_map(|(old, new)| new.map(|_| old))
+            .collect()
+    }
+
+    /// Prune the columns of the plan which are not required by its parent. The columns which
+    /// are kept stay in the same order.
+    fn prune(plan: LogicalPlan, required: &BTreeSet<usize>) -> (LogicalPlan, Remap) {
+        match plan {
+            LogicalPlan::Scan {
+                name,
+                source,
+                projection,
+                filters,
+                schema,
+            } => {
+                let mut needed = required.clone();
+                filters
+                    .iter()
+                    .for_each(|filter| filter.collect_columns(&mut needed));
+                let kept = needed.into_iter().collect::<Vec<_>>();
+                let remap = Self::remap_of(&kept, schema.len());
+                let new_projection = kept
+                    .iter()
+                    .map(|&i| projection.as_ref().map_or(i, |projection| projection[i]))
+                    .collect();
+                let filters = filters
+                    .into_iter()
+                    .map(|filter| Self::rewrite(filter, &remap))
+                    .collect();
+                let plan = LogicalPlan::Scan {
+                    name,
+                    source,
+                    projection: Some(new_projection),
+                    filters,
+                    schema: schema.project(&kept),
+                };
+                (plan, remap)
+            }
+            LogicalPlan::Projection {
+                exprs,
+                input,
+                schema,
+            } => {
+                let kept = required.iter().copied().collect::<Vec<_>>();
+                let mut input_required = BTreeSet::new();
+                kept.iter()
+                    .for_each(|&i| exprs[i].collect_columns(&mut input_required));
+                let (input, input_remap) = Self::prune(*input, &input_required);
+                let exprs = kept
+                    .iter()
+                    .map(|&i| Self::rewrite(exprs[i].clone(), &input_remap))
+                    .collect();
+                let plan = LogicalPlan::Projection {
+                    exprs,
+                    input: Box::new(input),
+                    schema: schema.project(&kept),
+                };
+                (plan, Self::remap_of(&kept, schema.len()))
+            }
+            LogicalPlan::Filter { predicate, input } => {
+                let mut input_required = required.clone();
+                predicate.collect_columns(&mut input_required);
+                let (input, remap) = Self::prune(*input, &input_required);
+                let plan = LogicalPlan::Filter {
+                    predicate: Self::rewrite(predicate, &remap),
+                    input: Box::new(input),
+                };
+                (plan, remap)
+            }
+            LogicalPlan::Sort { exprs, input } => {
+                let mut input_required = required.clone();
+                exprs
+                    .iter()
+                    .for_each(|sort| sort.expr.collect_columns(&mut input_required));
+                let (input, remap) = Self::prune(*input, &input_required);
+                let exprs = exprs
+                    .into_iter()
+                    .map(|sort| SortExpr {
+                        expr: Self::rewrite(sort.expr, &remap),
+                        ascending: sort.ascending,
+                    })
+                    .collect();
+                let plan = LogicalPlan::Sort {
+                    exprs,
+                    input: Box::new(input),
+                };
+                (plan, remap)
+            }
+            LogicalPlan::Limit { n, input } => {
+                let (input, remap) = Self::prune(*input, required);
+                let plan = LogicalPlan::Limit {
+                    n,
+                    input: Box::new(input),
+                };
+                (plan, remap)
+            }
+            LogicalPlan::Alias {
+                alias,
+                input,
+                schema,
+            } => {
+                let (input, remap) = Self::prune(*input, required);
+                let plan = LogicalPlan::Alias {
+                    alias,
+                    input: Box::new(input),
+                    schema: schema.project(&Self::kept(&remap)),
+                };
+                (plan, remap)
+            }
+            LogicalPlan::Aggregate {
+                group_exprs,
+                aggr_exprs,
+                input,
+                schema,
+            } => {
+                // All the grouping expressions are kept, they define the groups.
+                let num_groups = group_exprs.len();
+                let kept = (0..num_groups)
+                    .chain(required.iter().copied().filter(|&i| i >= num_groups))
+                    .collect::<Vec<_>>();
+                let aggr_exprs = kept[num_groups..]
+                    .iter()
+                    .map(|&i| aggr_exprs[i - num_groups].clone())
+                    .collect::<Vec<_>>();
+                let mut input_required = BTreeSet::new();
+                group_exprs
+                    .iter()
+                    .chain(aggr_exprs.iter())
+                    .for_each(|expr| expr.collect_columns(&mut input_required));
+                let (input, input_remap) = Self::prune(*input, &input_required);
+                let rewrite_all = |exprs: Vec<Expr>| {
+                    exprs
+                        .into_iter()
+                        .map(|expr| Self::rewrite(expr, &input_remap))
+                        .collect()
+                };
+                let plan = LogicalPlan::Aggregate {
+                    group_exprs: rewrite_all(group_exprs),
+                    aggr_exprs: rewrite_all(aggr_exprs),
+                    input: Box::new(input),
+                    schema: schema.project(&kept),
+                };
+                (plan, Self::remap_of(&kept, schema.len()))
+            }
+            LogicalPlan::Join {
+                left,
+                right,
+                on,
+                join_type,
+                schema,
+            } => {
+                let left_len = left.schema().len();
+                let mut left_required = BTreeSet::new();
+                let mut right_required = BTreeSet::new();
+                for &i in required {
+                    if i < left_len {
+                        left_required.insert(i);
+                    } else {
+                        right_required.insert(i - left_len);
+                    }
+                }
+                for &(l, r) in &on {
+                    left_required.insert(l);
+                    right_required.insert(r);
+                }
+                let (left, left_remap) = Self::prune(*left, &left_required);
+                let (right, right_remap) = Self::prune(*right, &right_required);
+                let new_left_len = left.schema().len();
+                let on = on
+                    .into_iter()
+                    .map(|(l, r)| (left_remap[l].unwrap(), right_remap[r].unwrap()))
+                    .collect();
+                let remap = left_remap
+                    .into_iter()
+                    .chain(
+                        right_remap
+                            .into_iter()
+                            .map(|new| new.map(|i| i + new_left_len)),
+                    )
+                    .collect::<Remap>();
+                let plan = LogicalPlan::Join {
+                    left: Box::new(left),
+                    right: Box::new(right),
+                    on,
+                    join_type,
+                    schema: schema.project(&Self::kept(&remap)),
+                };
+                (plan, remap)
+            }
+        }
+    }
+}
+
+impl OptimizerRule for ProjectionPruning {
+    fn name(&self) -> &'static str {
+        "projection_pruning"
+    }
+
+    fn optimize(&self, plan: LogicalPlan) -> Result<LogicalPlan> {
+        let required = (0..plan.schema().len()).collect();
+        Ok(Self::prune(plan, &required).0)
+    }
+}
@@ -0,0 +1,249 @@
+use std::fmt::{self, Debug, Display};
+use std::sync::Arc;
+
+use crate::dataframe::{Expr, Row, Schema, SortExpr};
+use crate::error::Result;
+use crate::rdd::Rdd;
+use crate::{Fn, SerArc};
+use serde_derive::{Deserialize, Serialize};
+
+/// A source of rows which can be scanned by a DataFrame.
+///
+/// The optimizer pushes the projection and the filters of the query into the scan, so that the
+/// provider can avoid reading the columns and the data which are not needed.
+pub trait TableProvider: Send + Sync {
+    /// Schema of the rows of the table.
+    fn schema(&self) -> Schema;
+
+    /// Returns the rows of the table.
+    ///
+    /// If a projection is given, the rows must only hold the columns at the given indices, in
+    /// that order. The filters only reference the projected columns; they are evaluated again
+    /// on the returned rows, so the provider is free to use them only to skip data.
+    fn scan(
+        &self,
+        projection: Option<&[usize]>,
+        filters: &[Expr],
+    ) -> Result<SerArc<dyn Rdd<Item = Row>>>;
+}
+
+/// Table backed by an RDD of rows.
+pub struct RddTable {
+    rdd: SerArc<dyn Rdd<Item = Row>>,
+    schema: Schema,
+}
+
+impl RddTable {
+    pub fn new(rdd: SerArc<dyn Rdd<Item = Row>>, schema: Schema) -> Self {
+        RddTable { rdd, schema }
+    }
+}
+
+impl TableProvider for RddTable {
+    fn schema(&self) -> Schema {
+        self.schema.clone()
+    }
+
+    fn scan(
+        &self,
+        projection: Option<&[usize]>,
+        _filters: &[Expr],
+    ) -> Result<SerArc<dyn Rdd<Item = Row>>> {
+        match projection {
+            Some(projection) => {
+                let projection = projection.to_vec();
+                Ok(self.rdd.map(Fn!(move |row: Row| {
+                    projection.iter().map(|&i| row[i].clone()).collect::<Row>()
+                })))
+            }
+            None => Ok(self.rdd.clone()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JoinType {
+    Inner,
+    Left,
+    Right,
+    Full,
+}
+
+impl JoinType {
+    /// Whether the rows of the left side without a match are kept.
+    pub(crate) fn preserves_left(self) -> bool {
+        self == JoinType::Left || self == JoinType::Full
+    }
+
+    /// Whether the rows of the right side without a match are kept.
+    pub(crate) fn preserves_right(self) -> bool {
+        self == JoinType::Right || self == JoinType::Full
+    }
+}
+
+/// Relational operators of a DataFrame query. The columns of the expressions are bound to the
+/// output of the input plan.
+#[derive(Clone)]
+pub enum LogicalPlan {
+    Scan {
+        name: String,
+        source: Arc<dyn TableProvider>,
+        /// Indices of the columns read from the source, all of them if None.
+        projection: Option<Vec<usize>>,
+        /// Predicates over the projected columns which the scanned rows must satisfy.
+        filters: Vec<Expr>,
+        schema: Schema,
+    },
+    Projection {
+        exprs: Vec<Expr>,
+        input: Box<LogicalPlan>,
+        schema: Schema,
+    },
+    Filter {
+        predicate: Expr,
+        input: Box<LogicalPlan>,
+    },
+    /// Rows made of the grouping expressions followed by the aggregate expressions.
+    Aggregate {
+        group_exprs: Vec<Expr>,
+        aggr_exprs: Vec<Expr>,
+        input: Box<LogicalPlan>,
+        schema: Schema,
+    },
+    /// Equi-join on pairs of left and right column indices.
+    Join {
+        left: Box<LogicalPlan>,
+        right: Box<LogicalPlan>,
+        on: Vec<(usize, usize)>,
+        join_type: JoinType,
+        schema: Schema,
+    },
+    Sort {
+        exprs: Vec<SortExpr>,
+        input: Box<LogicalPlan>,
+    },
+    Limit {
+        n: usize,
+        input: Box<LogicalPlan>,
+    },
+    /// Qualifies the columns of the input with a relation name.
+    Alias {
+        alias: String,
+        input: Box<LogicalPlan>,
+        schema: Schema,
+    },
+}
+
+impl LogicalPlan {
+    pub fn schema(&self) -> &Schema {
+        match self {
+            LogicalPlan::Scan { schema, .. }
+            | LogicalPlan::Projection { schema, .. }
+            | LogicalPlan::Aggregate { schema, .. }
+            | LogicalPlan::Join { schema, .. }
+            | LogicalPlan::Alias { schema, .. } => schema,
+            LogicalPlan::Filter { input, .. }
+            | LogicalPlan::Sort { input, .. }
+            | LogicalPlan::Limit { input, .. } => input.schema(),
+        }
+    }
+
+    pub fn inputs(&self) -> Vec<&LogicalPlan> {
+        match self {
+            LogicalPlan::Scan { .. } => vec![],
+            LogicalPlan::Join { left, right, .. } => vec![&**left, &**right],
+            LogicalPlan::Projection { input, .. }
+            | LogicalPlan::Filter { input, .. }
+            | LogicalPlan::Aggregate { input, .. }
+            | LogicalPlan::Sort { input, .. }
+            | LogicalPlan::Limit { input, .. }
+            | LogicalPlan::Alias { input, .. } => vec![&**input],
+        }
+    }
+
+    fn fmt_node(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let list = |exprs: &[Expr]| {
+            exprs
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match self {
+            LogicalPlan::Scan {
+                name,
+                projection,
+                filters,
+                ..
+            } => {
+                write!(f, "Scan: {}", name)?;
+                if let Some(projection) = projection {
+                    write!(f, " projection={:?}", projection)?;
+                }
+                if !filters.is_empty() {
+                    write!(f, " filters=[{}]", list(filters))?;
+                }
+                Ok(())
+            }
+            LogicalPlan::Projection { exprs, .. } => write!(f, "Projection: {}", list(exprs)),
+            LogicalPlan::Filter { predicate, .. } => write!(f, "Filter: {}", predicate),
+            LogicalPlan::Aggregate {
+                group_exprs,
+                aggr_exprs,
+                ..
+            } => write!(
+                f,
+                "Aggregate: groupBy=[{}], aggr=[{}]",
+                list(group_exprs),
+                list(aggr_exprs)
+            ),
+            LogicalPlan::Join {
+                left,
+                right,
+                on,
+                join_type,
+                ..
+            } => {
+                let on = on
+                    .iter()
+                    .map(|&(l, r)| {
+                        format!(
+                            "{} = {}",
+                            left.schema().field(l).qualified_name(),
+                            right.schema().field(r).qualified_name()
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                write!(f, "Join: {:?} on {}", join_type, on.join(", "))
+            }
+            LogicalPlan::Sort { exprs, .. } => {
+                let exprs = exprs.iter().map(ToString::to_string).collect::<Vec<_>>();
+                write!(f, "Sort: {}", exprs.join(", "))
+            }
+            LogicalPlan::Limit { n, .. } => write!(f, "Limit: {}", n),
+            LogicalPlan::Alias { alias, .. } => write!(f, "Alias: {}", alias),
+        }
+    }
+
+    fn fmt_indented(&self, f: &mut fmt::Formatter, indent: usize) -> fmt::Result {
+        write!(f, "{:width$}", "", width = indent * 2)?;
+        self.fmt_node(f)?;
+        writeln!(f)?;
+        self.inputs()
+            .into_iter()
+            .try_for_each(|input| input.fmt_indented(f, indent + 1))
+    }
+}
+
+/// Tree representation of the plan, one operator per line.
+impl Display for LogicalPlan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
+
+impl Debug for LogicalPlan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Display::fmt(self, f)
+    }
+}
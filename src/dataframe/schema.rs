@@ -0,0 +1,193 @@
+use std::fmt::{self, Display};
+
+use crate::dataframe::PlanError;
+use crate::error::Result;
+use serde_derive::{Deserialize, Serialize};
+
+/// Type of a DataFrame column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DataType {
+    /// Type of the null literal, it can be coerced to any other type.
+    Null,
+    Boolean,
+    Int64,
+    Float64,
+    Utf8,
+}
+
+impl DataType {
+    pub fn is_numeric(self) -> bool {
+        match self {
+            DataType::Int64 | DataType::Float64 | DataType::Null => true,
+            _ => false,
+        }
+    }
+
+    /// Returns the type both types can be converted to, if any.
+    pub fn common_type(self, other: DataType) -> Option<DataType> {
+        match (self, other) {
+            (a, b) if a == b => Some(a),
+            (DataType::Null, t) | (t, DataType::Null) => Some(t),
+            (DataType::Int64, DataType::Float64) | (DataType::Float64, DataType::Int64) => {
+                Some(DataType::Float64)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Display for DataType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            DataType::Null => "null",
+            DataType::Boolean => "boolean",
+            DataType::Int64 => "bigint",
+            DataType::Float64 => "double",
+            DataType::Utf8 => "string",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A named and typed column of a schema. The column can be qualified with the name of the
+/// relation it comes from, so that columns of joined tables can be told apart.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Field {
+    qualifier: Option<String>,
+    name: String,
+    data_type: DataType,
+    nullable: bool,
+}
+
+impl Field {
+    pub fn new(name: &str, data_type: DataType, nullable: bool) -> Self {
+        Field {
+            qualifier: None,
+            name: name.to_owned(),
+            data_type,
+            nullable,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn qualifier(&self) -> Option<&str> {
+        self.qualifier.as_deref()
+    }
+
+    /// Returns the name of the field prefixed by its qualifier, if any.
+    pub fn qualified_name(&self) -> String {
+        match self.qualifier {
+            Some(ref qualifier) => format!("{}.{}", qualifier, self.name),
+            None => self.name.clone(),
+        }
+    }
+
+    pub fn data_type(&self) -> DataType {
+        self.data_type
+    }
+
+    pub fn is_nullable(&self) -> bool {
+        self.nullable
+    }
+
+    pub(crate) fn with_qualifier(mut self, qualifier: Option<&str>) -> Self {
+        self.qualifier = qualifier.map(ToOwned::to_owned);
+        self
+    }
+
+    pub(crate) fn with_nullable(mut self, nullable: bool) -> Self {
+        self.nullable = nullable;
+        self
+    }
+
+    fn matches(&self, qualifier: Option<&str>, name: &str) -> bool {
+        self.name == name && (qualifier.is_none() || self.qualifier() == qualifier)
+    }
+}
+
+/// The ordered list of columns of a DataFrame.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Schema {
+    fields: Vec<Field>,
+}
+
+impl Schema {
+    pub fn new(fields: Vec<Field>) -> Self {
+        Schema { fields }
+    }
+
+    pub fn empty() -> Self {
+        Schema { fields: Vec::new() }
+    }
+
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
+    pub fn field(&self, index: usize) -> &Field {
+        &self.fields[index]
+    }
+
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Returns the index of the column with the given name. The name can be qualified with the
+    /// relation name as in `table.column`.
+    pub fn index_of(&self, name: &str) -> Result<usize> {
+        let (qualifier, column) = match name.rfind('.') {
+            Some(pos) => (Some(&name[..pos]), &name[pos + 1..]),
+            None => (None, name),
+        };
+        let mut matches = self
+            .fields
+            .iter()
+            .enumerate()
+            .filter(|(_, field)| field.matches(qualifier, column));
+        match (matches.next(), matches.next()) {
+            (Some((index, _)), None) => Ok(index),
+            (Some(_), Some(_)) => Err(PlanError::AmbiguousColumn(name.to_owned()).into()),
+            (None, _) => Err(PlanError::UnknownColumn(name.to_owned()).into()),
+        }
+    }
+
+    /// Returns the schema made of the columns at the given indices.
+    pub fn project(&self, indices: &[usize]) -> Schema {
+        Schema::new(indices.iter().map(|&i| self.fields[i].clone()).collect())
+    }
+
+    /// Returns the schema with the columns of both schemas.
+    pub fn join(&self, other: &Schema) -> Schema {
+        let mut fields = self.fields.clone();
+        fields.extend(other.fields.iter().cloned());
+        Schema::new(fields)
+    }
+
+    /// Returns the schema with all the columns qualified by the given relation name.
+    pub fn with_qualifier(&self, qualifier: &str) -> Schema {
+        Schema::new(
+            self.fields
+                .iter()
+                .map(|field| field.clone().with_qualifier(Some(qualifier)))
+                .collect(),
+        )
+    }
+}
+
+impl Display for Schema {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let fields = self
+            .fields
+            .iter()
+            .map(|field| format!("{}: {}", field.qualified_name(), field.data_type))
+            .collect::<Vec<_>>();
+        write!(f, "[{}]", fields.join(", "))
+    }
+}
@@ -0,0 +1,389 @@
+//! SQL front-end of the DataFrame API.
+//!
+//! Queries are parsed and translated to the same logical plans as the DataFrame operators, so
+//! they are optimized and executed the same way. The supported subset of SQL is:
+//!
+//! ```text
+//! SELECT [DISTINCT] { * | table.* | expr [[AS] alias] }, ...
+//! FROM table [[AS] alias]
+//! [{ [INNER] | LEFT [OUTER] | RIGHT [OUTER] | FULL [OUTER] } JOIN table [[AS] alias] ON condition]...
+//! [WHERE condition]
+//! [GROUP BY expr, ...]
+//! [HAVING condition]
+//! [ORDER BY { expr | alias | position } [ASC | DESC], ...]
+//! [LIMIT n]
+//! ```
+//!
+//! Expressions support the arithmetic, comparison and boolean operators, `||`, `IS [NOT] NULL`,
+//! `[NOT] BETWEEN`, `[NOT] IN (...)`, `[NOT] LIKE`, `CAST(expr AS type)`, the aggregate
+//! functions `COUNT(*)`, `COUNT([DISTINCT] expr)`, `SUM`, `AVG`, `MIN` and `MAX` and the
+//! [scalar functions](../enum.ScalarFunction.html). Keywords are case insensitive, identifiers
+//! are case sensitive and can be double quoted.
+//!
+//! The conditions of outer joins must be conjunctions of equalities between a column of each
+//! side.
+use crate::dataframe::optimizer::split_conjunction;
+use crate::dataframe::{Column, DataFrame, Expr, JoinType, Operator, PlanError, SortExpr, Value};
+use crate::error::Result;
+
+mod parser;
+mod tokenizer;
+
+use parser::{Join, Parser, Query, SelectItem, TableRef};
+
+/// Returns the DataFrame computing the result of the query. The tables are looked up by name
+/// with the given function.
+pub(crate) fn plan_query<F>(sql: &str, table: F) -> Result<DataFrame>
+where
+    F: Fn(&str) -> Result<DataFrame>,
+{
+    let Query {
+        distinct,
+        projection,
+        from,
+        joins,
+        selection,
+        group_by,
+        having,
+        order_by,
+        limit,
+    } = Parser::parse_query(sql)?;
+
+    let scan = |table_ref: &TableRef| -> Result<DataFrame> {
+        let alias = table_ref.alias.as_ref().unwrap_or(&table_ref.name);
+        Ok(table(&table_ref.name)?.alias(alias))
+    };
+    let mut df = scan(&from)?;
+    for join in joins {
+        let right = scan(&join.table)?;
+        df = plan_join(df, right, join)?;
+    }
+    if let Some(selection) = selection {
+        df = df.filter(selection)?;
+    }
+
+    let select = expand_projection(&df, projection)?;
+    let order_by = order_by
+        .into_iter()
+        .map(|sort| {
+            Ok(SortExpr {
+                expr: substitute_output(sort.expr, &select)?,
+                ascending: sort.ascending,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let is_aggregate = !group_by.is_empty()
+        || having.is_some()
+        || select.iter().any(|(expr, _)| expr.contains_aggregate());
+    let (mut df, select, order_by) = if is_aggregate {
+        plan_aggregate(df, group_by, select, having, order_by)?
+    } else {
+        (df, select, order_by)
+    };
+
+    if distinct {
+        // The rows are sorted after removing the duplicates, so only by the selected columns.
+        let order_by = order_by
+            .into_iter()
+            .map(|sort| {
+                let position = select
+                    .iter()
+                    .position(|(expr, name)| *expr == sort.expr || *name == sort.expr.name());
+                match position {
+                    Some(i) => Ok(SortExpr {
+                        expr: output_column(&select[i].1),
+                        ascending: sort.ascending,
+                    }),
+                    None => {
+                        let message =
+                            format!("ORDER BY {} not in the SELECT DISTINCT list", sort.expr);
+                        Err(PlanError::Unsupported(message).into())
+                    }
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+        df = df.select(output_exprs(select))?.distinct();
+        if !order_by.is_empty() {
+            df = df.order_by(order_by)?;
+        }
+    } else {
+        // Sorting before the projection allows to sort by columns which are not selected.
+        if !order_by.is_empty() {
+            df = df.order_by(order_by)?;
+        }
+        df = df.select(output_exprs(select))?;
+    }
+    Ok(match limit {
+        Some(n) => df.limit(n),
+        None => df,
+    })
+}
+
+fn output_column(name: &str) -> Expr {
+    Expr::Column(Column {
+        relation: None,
+        name: name.to_owned(),
+        index: None,
+    })
+}
+
+/// Returns the selected expressions, named after the output columns.
+fn output_exprs(select: Vec<(Expr, String)>) -> Vec<Expr> {
+    select
+        .into_iter()
+        .map(|(expr, name)| match expr {
+            Expr::Column(ref column) if column.name == name => expr,
+            expr => expr.alias(&name),
+        })
+        .collect()
+}
+
+/// Returns the side of the join of the column, true for the left one.
+fn join_side(left: &DataFrame, right: &DataFrame, expr: &Expr) -> Option<bool> {
+    match expr {
+        Expr::Column(column) => {
+            let name = column.flat_name();
+            match (
+                left.schema().index_of(&name).is_ok(),
+                right.schema().index_of(&name).is_ok(),
+            ) {
+                (true, false) => Some(true),
+                (false, true) => Some(false),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Split the condition of the join into equalities of columns of each side, used as keys, and
+/// a residual predicate evaluated after an inner join.
+fn plan_join(left: DataFrame, right: DataFrame, join: Join) -> Result<DataFrame> {
+    let mut conjuncts = vec![];
+    split_conjunction(join.on, &mut conjuncts);
+    let mut keys = vec![];
+    let mut residual = vec![];
+    for conjunct in conjuncts {
+        if let Expr::BinaryExpr {
+            left: ref l,
+            op: Operator::Eq,
+            right: ref r,
+        } = conjunct
+        {
+            match (join_side(&left, &right, l), join_side(&left, &right, r)) {
+                (Some(true), Some(false)) => {
+                    keys.push((l.to_string(), r.to_string()));
+                    continue;
+                }
+                (Some(false), Some(true)) => {
+                    keys.push((r.to_string(), l.to_string()));
+                    continue;
+                }
+                _ => {}
+            }
+        }
+        if join.join_type != JoinType::Inner {
+            let message = format!("condition {} of an outer join", conjunct);
+            return Err(PlanError::Unsupported(message).into());
+        }
+        residual.push(conjunct);
+    }
+    let keys = keys
+        .iter()
+        .map(|(l, r)| (l.as_str(), r.as_str()))
+        .collect::<Vec<_>>();
+    let mut df = left.join(&right, &keys, join.join_type)?;
+    for predicate in residual {
+        df = df.filter(predicate)?;
+    }
+    Ok(df)
+}
+
+/// Returns the selected expressions with the names of the output columns.
+fn expand_projection(df: &DataFrame, projection: Vec<SelectItem>) -> Result<Vec<(Expr, String)>> {
+    let mut select = vec![];
+    for item in projection {
+        match item {
+            SelectItem::Wildcard(relation) => {
+                let fields = df
+                    .schema()
+                    .fields()
+                    .iter()
+                    .filter(|field| relation.is_none() || field.qualifier() == relation.as_deref())
+                    .collect::<Vec<_>>();
+                if fields.is_empty() {
+                    let relation = relation.unwrap_or_default();
+                    return Err(PlanError::UnknownTable(relation).into());
+                }
+                select.extend(fields.into_iter().map(|field| {
+                    let column = Column {
+                        relation: field.qualifier().map(ToOwned::to_owned),
+                        name: field.name().to_owned(),
+                        index: None,
+                    };
+                    (Expr::Column(column), field.name().to_owned())
+                }));
+            }
+            SelectItem::Expr { expr, alias } => {
+                let name = alias.unwrap_or_else(|| expr.name());
+                select.push((expr, name));
+            }
+        }
+    }
+    Ok(select)
+}
+
+/// Replace a reference to a selected column, by position or by alias, with its expression.
+fn substitute_output(expr: Expr, select: &[(Expr, String)]) -> Result<Expr> {
+    match expr {
+        Expr::Literal(Value::Int(position)) => {
+            match select.get((position as usize).wrapping_sub(1)) {
+                Some((expr, _)) if position > 0 => Ok(expr.clone()),
+                _ => Err(PlanError::UnknownColumn(format!("at position {}", position)).into()),
+            }
+        }
+        Expr::Column(Column {
+            relation: None,
+            ref name,
+            ..
+        }) => Ok(select
+            .iter()
+            .find(|(_, alias)| alias == name)
+            .map_or(expr.clone(), |(selected, _)| selected.clone())),
+        expr => Ok(expr),
+    }
+}
+
+/// Returns the expression with its columns bound to the schema of the DataFrame and without
+/// their names, to compare expressions referring to the same columns differently.
+fn normalize(df: &DataFrame, expr: &Expr) -> Option<Expr> {
+    let resolved = expr.resolve(df.schema()).ok()?;
+    Some(resolved.rewrite(&mut |expr| match expr {
+        Expr::Column(column) => Expr::Column(Column {
+            relation: None,
+            name: String::new(),
+            index: column.index,
+        }),
+        expr => expr,
+    }))
+}
+
+/// Replace the sub-expressions matching one of the given expressions by a reference to the
+/// column of the same index, named with the prefix.
+fn replace_matching(df: &DataFrame, expr: Expr, matching: &[Expr], prefix: &str) -> Expr {
+    expr.rewrite(&mut |expr| {
+        let normalized = normalize(df, &expr);
+        match matching
+            .iter()
+            .position(|other| normalized.as_ref() == Some(other))
+        {
+            Some(i) => Expr::Column(Column {
+                relation: None,
+                name: format!("{}{}", prefix, i),
+                index: None,
+            }),
+            None => expr,
+        }
+    })
+}
+
+const GROUP_PREFIX: &str = "__group";
+const AGGREGATE_PREFIX: &str = "__agg";
+
+fn collect_aggregates(expr: &Expr, aggregates: &mut Vec<Expr>) {
+    match expr {
+        Expr::AggregateFunction { .. } => {
+            if !aggregates.contains(expr) {
+                aggregates.push(expr.clone());
+            }
+        }
+        expr => expr
+            .children()
+            .into_iter()
+            .for_each(|child| collect_aggregates(child, aggregates)),
+    }
+}
+
+/// Whether the expression references columns which are not computed by the aggregation.
+fn references_input(expr: &Expr) -> bool {
+    match expr {
+        Expr::Column(column) => {
+            !(column.name.starts_with(GROUP_PREFIX) || column.name.starts_with(AGGREGATE_PREFIX))
+        }
+        expr => expr.children().into_iter().any(references_input),
+    }
+}
+
+type AggregatePlan = (DataFrame, Vec<(Expr, String)>, Vec<SortExpr>);
+
+/// Aggregate the rows by the grouping expressions, computing all the aggregates used by the
+/// query. The selected expressions, the `HAVING` condition and the sort keys are rewritten to
+/// refer to the columns of the aggregation.
+fn plan_aggregate(
+    df: DataFrame,
+    group_by: Vec<Expr>,
+    select: Vec<(Expr, String)>,
+    having: Option<Expr>,
+    order_by: Vec<SortExpr>,
+) -> Result<AggregatePlan> {
+    let mut aggregates = vec![];
+    select
+        .iter()
+        .map(|(expr, _)| expr)
+        .chain(having.iter())
+        .chain(order_by.iter().map(|sort| &sort.expr))
+        .for_each(|expr| collect_aggregates(expr, &mut aggregates));
+
+    let normalize_all = |exprs: &[Expr]| {
+        exprs
+            .iter()
+            .map(|expr| match normalize(&df, expr) {
+                Some(expr) => Ok(expr),
+                // Report the error of the expression.
+                None => expr.resolve(df.schema()),
+            })
+            .collect::<Result<Vec<_>>>()
+    };
+    let normalized_groups = normalize_all(&group_by)?;
+    let normalized_aggregates = normalize_all(&aggregates)?;
+    let rewrite = |expr: Expr| -> Result<Expr> {
+        let original = expr.to_string();
+        // Aggregates first, so that their arguments are not replaced by grouping columns.
+        let expr = replace_matching(&df, expr, &normalized_aggregates, AGGREGATE_PREFIX);
+        let expr = replace_matching(&df, expr, &normalized_groups, GROUP_PREFIX);
+        if references_input(&expr) {
+            let message = format!("{} is neither grouped nor aggregated", original);
+            return Err(PlanError::InvalidAggregate(message).into());
+        }
+        Ok(expr)
+    };
+
+    let group_exprs = group_by
+        .into_iter()
+        .enumerate()
+        .map(|(i, expr)| expr.alias(&format!("{}{}", GROUP_PREFIX, i)))
+        .collect();
+    let aggr_exprs = aggregates
+        .into_iter()
+        .enumerate()
+        .map(|(i, expr)| expr.alias(&format!("{}{}", AGGREGATE_PREFIX, i)))
+        .collect();
+    let mut aggregated = df.group_by(group_exprs)?.agg(aggr_exprs)?;
+    if let Some(having) = having {
+        aggregated = aggregated.filter(rewrite(having)?)?;
+    }
+    let select = select
+        .into_iter()
+        .map(|(expr, name)| Ok((rewrite(expr)?, name)))
+        .collect::<Result<_>>()?;
+    let order_by = order_by
+        .into_iter()
+        .map(|sort| {
+            Ok(SortExpr {
+                expr: rewrite(sort.expr)?,
+                ascending: sort.ascending,
+            })
+        })
+        .collect::<Result<_>>()?;
+    Ok((aggregated, select, order_by))
+}
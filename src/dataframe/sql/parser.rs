@@ -0,0 +1,611 @@
+use crate::dataframe::sql::tokenizer::{tokenize, Token};
+use crate::dataframe::{
+    count, lit, AggregateFunction, Column, DataType, Expr, JoinType, PlanError, ScalarFunction,
+    SortExpr, Value,
+};
+use crate::error::Result;
+
+/// A parsed `SELECT` statement.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Query {
+    pub distinct: bool,
+    pub projection: Vec<SelectItem>,
+    pub from: TableRef,
+    pub joins: Vec<Join>,
+    pub selection: Option<Expr>,
+    pub group_by: Vec<Expr>,
+    pub having: Option<Expr>,
+    pub order_by: Vec<SortExpr>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum SelectItem {
+    /// All the columns, or only those of the given relation.
+    Wildcard(Option<String>),
+    Expr {
+        expr: Expr,
+        alias: Option<String>,
+    },
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct TableRef {
+    pub name: String,
+    pub alias: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct Join {
+    pub table: TableRef,
+    pub join_type: JoinType,
+    pub on: Expr,
+}
+
+/// Words which can't be used as unquoted aliases, because they start the next clause.
+const RESERVED: &[&str] = &[
+    "SELECT", "FROM", "WHERE", "GROUP", "HAVING", "ORDER", "LIMIT", "JOIN", "INNER", "LEFT",
+    "RIGHT", "FULL", "OUTER", "ON", "AND", "OR", "NOT", "AS", "BY", "ASC", "DESC", "IS", "NULL",
+    "IN", "LIKE", "BETWEEN", "DISTINCT", "TRUE", "FALSE", "CAST",
+];
+
+/// Recursive descent parser of the supported subset of SQL.
+pub(crate) struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+fn syntax_error<T>(message: String) -> Result<T> {
+    Err(PlanError::Syntax(message).into())
+}
+
+impl Parser {
+    pub(crate) fn parse_query(query: &str) -> Result<Query> {
+        let mut parser = Parser {
+            tokens: tokenize(query)?,
+            pos: 0,
+        };
+        let query = parser.parse_select()?;
+        parser.consume(&Token::SemiColon);
+        match parser.peek() {
+            None => Ok(query),
+            Some(token) => syntax_error(format!("unexpected {} after the query", token)),
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        self.peek().map_or(false, |token| token.is_keyword(keyword))
+    }
+
+    /// Consume the next token if it is the given keyword.
+    fn parse_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.peek_keyword(keyword);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn parse_keywords(&mut self, keywords: &[&str]) -> bool {
+        let start = self.pos;
+        if keywords.iter().all(|keyword| self.parse_keyword(keyword)) {
+            true
+        } else {
+            self.pos = start;
+            false
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<()> {
+        if self.parse_keyword(keyword) {
+            Ok(())
+        } else {
+            self.unexpected(keyword)
+        }
+    }
+
+    /// Consume the next token if it is the given one.
+    fn consume(&mut self, token: &Token) -> bool {
+        let found = self.peek() == Some(token);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, token: &Token) -> Result<()> {
+        if self.consume(token) {
+            Ok(())
+        } else {
+            self.unexpected(&token.to_string())
+        }
+    }
+
+    fn unexpected<T>(&self, expected: &str) -> Result<T> {
+        match self.peek() {
+            Some(token) => syntax_error(format!("expected {}, found {}", expected, token)),
+            None => syntax_error(format!("expected {}, found end of query", expected)),
+        }
+    }
+
+    fn parse_identifier(&mut self) -> Result<String> {
+        match self.peek() {
+            Some(Token::Word { value, quoted })
+                if *quoted || !RESERVED.contains(&value.to_uppercase().as_str()) =>
+            {
+                let value = value.clone();
+                self.pos += 1;
+                Ok(value)
+            }
+            _ => self.unexpected("an identifier"),
+        }
+    }
+
+    /// Parse an optional alias, introduced by `AS` or not.
+    fn parse_alias(&mut self) -> Result<Option<String>> {
+        if self.parse_keyword("AS") {
+            return self.parse_identifier().map(Some);
+        }
+        match self.peek() {
+            Some(Token::Word { .. }) => Ok(self.parse_identifier().ok()),
+            _ => Ok(None),
+        }
+    }
+
+    fn parse_comma_separated<T, F>(&mut self, mut f: F) -> Result<Vec<T>>
+    where
+        F: FnMut(&mut Parser) -> Result<T>,
+    {
+        let mut values = vec![f(self)?];
+        while self.consume(&Token::Comma) {
+            values.push(f(self)?);
+        }
+        Ok(values)
+    }
+
+    fn parse_select(&mut self) -> Result<Query> {
+        self.expect_keyword("SELECT")?;
+        let distinct = self.parse_keyword("DISTINCT");
+        let projection = self.parse_comma_separated(Parser::parse_select_item)?;
+        self.expect_keyword("FROM")?;
+        let from = self.parse_table_ref()?;
+        let mut joins = vec![];
+        while let Some(join_type) = self.parse_join_type()? {
+            let table = self.parse_table_ref()?;
+            self.expect_keyword("ON")?;
+            let on = self.parse_expr()?;
+            joins.push(Join {
+                table,
+                join_type,
+                on,
+            });
+        }
+        let selection = if self.parse_keyword("WHERE") {
+            Some(self.parse_expr()?)
+        } else {
+            None
+        };
+        let group_by = if self.parse_keywords(&["GROUP", "BY"]) {
+            self.parse_comma_separated(Parser::parse_expr)?
+        } else {
+            vec![]
+        };
+        let having = if self.parse_keyword("HAVING") {
+            Some(self.parse_expr()?)
+        } else {
+            None
+        };
+        let order_by = if self.parse_keywords(&["ORDER", "BY"]) {
+            self.parse_comma_separated(Parser::parse_sort_expr)?
+        } else {
+            vec![]
+        };
+        let limit = if self.parse_keyword("LIMIT") {
+            match self.next() {
+                Some(Token::Number(n)) => match n.parse() {
+                    Ok(n) => Some(n),
+                    Err(_) => return syntax_error(format!("invalid limit {}", n)),
+                },
+                _ => {
+                    self.pos -= 1;
+                    return self.unexpected("a number of rows");
+                }
+            }
+        } else {
+            None
+        };
+        Ok(Query {
+            distinct,
+            projection,
+            from,
+            joins,
+            selection,
+            group_by,
+            having,
+            order_by,
+            limit,
+        })
+    }
+
+    fn parse_select_item(&mut self) -> Result<SelectItem> {
+        if self.consume(&Token::Star) {
+            return Ok(SelectItem::Wildcard(None));
+        }
+        // `relation.*`
+        if let (Some(Token::Word { value, .. }), Some(Token::Period), Some(Token::Star)) = (
+            self.tokens.get(self.pos),
+            self.tokens.get(self.pos + 1),
+            self.tokens.get(self.pos + 2),
+        ) {
+            let relation = value.clone();
+            self.pos += 3;
+            return Ok(SelectItem::Wildcard(Some(relation)));
+        }
+        let expr = self.parse_expr()?;
+        let alias = self.parse_alias()?;
+        Ok(SelectItem::Expr { expr, alias })
+    }
+
+    fn parse_table_ref(&mut self) -> Result<TableRef> {
+        let name = self.parse_identifier()?;
+        let alias = self.parse_alias()?;
+        Ok(TableRef { name, alias })
+    }
+
+    fn parse_join_type(&mut self) -> Result<Option<JoinType>> {
+        let join_type = if self.parse_keyword("JOIN") || self.parse_keywords(&["INNER", "JOIN"]) {
+            JoinType::Inner
+        } else if self.parse_keyword("LEFT") {
+            JoinType::Left
+        } else if self.parse_keyword("RIGHT") {
+            JoinType::Right
+        } else if self.parse_keyword("FULL") {
+            JoinType::Full
+        } else {
+            return Ok(None);
+        };
+        if join_type != JoinType::Inner {
+            self.parse_keyword("OUTER");
+            self.expect_keyword("JOIN")?;
+        }
+        Ok(Some(join_type))
+    }
+
+    fn parse_sort_expr(&mut self) -> Result<SortExpr> {
+        let expr = self.parse_expr()?;
+        let ascending = !self.parse_keyword("DESC");
+        if ascending {
+            self.parse_keyword("ASC");
+        }
+        Ok(SortExpr { expr, ascending })
+    }
+
+    pub(crate) fn parse_expr(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_and()?;
+        while self.parse_keyword("OR") {
+            expr = expr.or(self.parse_and()?);
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_not()?;
+        while self.parse_keyword("AND") {
+            expr = expr.and(self.parse_not()?);
+        }
+        Ok(expr)
+    }
+
+    fn parse_not(&mut self) -> Result<Expr> {
+        if self.parse_keyword("NOT") {
+            Ok(self.parse_not()?.not())
+        } else {
+            self.parse_comparison()
+        }
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr> {
+        let expr = self.parse_additive()?;
+        let op = match self.peek() {
+            Some(Token::Eq) => Expr::eq,
+            Some(Token::NotEq) => Expr::not_eq,
+            Some(Token::Lt) => Expr::lt,
+            Some(Token::LtEq) => Expr::lt_eq,
+            Some(Token::Gt) => Expr::gt,
+            Some(Token::GtEq) => Expr::gt_eq,
+            _ => return self.parse_postfix_predicate(expr),
+        };
+        self.pos += 1;
+        Ok(op(expr, self.parse_additive()?))
+    }
+
+    /// Parse the predicates following an expression: `IS [NOT] NULL`, `[NOT] BETWEEN`,
+    /// `[NOT] IN` and `[NOT] LIKE`.
+    fn parse_postfix_predicate(&mut self, expr: Expr) -> Result<Expr> {
+        if self.parse_keyword("IS") {
+            let negated = self.parse_keyword("NOT");
+            self.expect_keyword("NULL")?;
+            return Ok(if negated {
+                expr.is_not_null()
+            } else {
+                expr.is_null()
+            });
+        }
+        let start = self.pos;
+        let negated = self.parse_keyword("NOT");
+        let predicate = if self.parse_keyword("BETWEEN") {
+            let low = self.parse_additive()?;
+            self.expect_keyword("AND")?;
+            let high = self.parse_additive()?;
+            expr.clone().gt_eq(low).and(expr.lt_eq(high))
+        } else if self.parse_keyword("IN") {
+            self.expect(&Token::LParen)?;
+            let list = self.parse_comma_separated(Parser::parse_expr)?;
+            self.expect(&Token::RParen)?;
+            list.into_iter()
+                .map(|value| expr.clone().eq(value))
+                .fold(None, |any: Option<Expr>, eq| match any {
+                    Some(any) => Some(any.or(eq)),
+                    None => Some(eq),
+                })
+                .unwrap()
+        } else if self.parse_keyword("LIKE") {
+            expr.like(self.parse_additive()?)
+        } else {
+            self.pos = start;
+            return Ok(expr);
+        };
+        Ok(if negated { predicate.not() } else { predicate })
+    }
+
+    fn parse_additive(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_multiplicative()?;
+        loop {
+            expr = match self.peek() {
+                Some(Token::Plus) => {
+                    self.pos += 1;
+                    expr + self.parse_multiplicative()?
+                }
+                Some(Token::Minus) => {
+                    self.pos += 1;
+                    expr - self.parse_multiplicative()?
+                }
+                Some(Token::Concat) => {
+                    self.pos += 1;
+                    ScalarFunction::Concat.call(vec![expr, self.parse_multiplicative()?])
+                }
+                _ => return Ok(expr),
+            }
+        }
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_unary()?;
+        loop {
+            expr = match self.peek() {
+                Some(Token::Star) => {
+                    self.pos += 1;
+                    expr * self.parse_unary()?
+                }
+                Some(Token::Slash) => {
+                    self.pos += 1;
+                    expr / self.parse_unary()?
+                }
+                Some(Token::Percent) => {
+                    self.pos += 1;
+                    expr % self.parse_unary()?
+                }
+                _ => return Ok(expr),
+            }
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr> {
+        if self.consume(&Token::Minus) {
+            return Ok(match self.parse_unary()? {
+                Expr::Literal(Value::Int(v)) => lit(-v),
+                Expr::Literal(Value::Float(v)) => lit(-v),
+                expr => -expr,
+            });
+        }
+        self.consume(&Token::Plus);
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr> {
+        let token = match self.next() {
+            Some(token) => token,
+            None => {
+                self.pos -= 1;
+                return self.unexpected("an expression");
+            }
+        };
+        match token {
+            Token::Number(n) => match n.parse::<i64>() {
+                Ok(v) => Ok(lit(v)),
+                Err(_) => match n.parse::<f64>() {
+                    Ok(v) => Ok(lit(v)),
+                    Err(_) => syntax_error(format!("invalid number {}", n)),
+                },
+            },
+            Token::String(s) => Ok(lit(s)),
+            Token::LParen => {
+                let expr = self.parse_expr()?;
+                self.expect(&Token::RParen)?;
+                Ok(expr)
+            }
+            ref word if word.is_keyword("NULL") => Ok(Expr::Literal(Value::Null)),
+            ref word if word.is_keyword("TRUE") => Ok(lit(true)),
+            ref word if word.is_keyword("FALSE") => Ok(lit(false)),
+            ref word if word.is_keyword("CAST") => {
+                self.expect(&Token::LParen)?;
+                let expr = self.parse_expr()?;
+                self.expect_keyword("AS")?;
+                let data_type = self.parse_data_type()?;
+                self.expect(&Token::RParen)?;
+                Ok(expr.cast(data_type))
+            }
+            Token::Word { value, quoted } => {
+                if !quoted && self.peek() == Some(&Token::LParen) {
+                    self.pos += 1;
+                    return self.parse_function(&value);
+                }
+                if !quoted && RESERVED.contains(&value.to_uppercase().as_str()) {
+                    self.pos -= 1;
+                    return self.unexpected("an expression");
+                }
+                if self.consume(&Token::Period) {
+                    let name = self.parse_identifier()?;
+                    return Ok(Expr::Column(Column {
+                        relation: Some(value),
+                        name,
+                        index: None,
+                    }));
+                }
+                Ok(Expr::Column(Column {
+                    relation: None,
+                    name: value,
+                    index: None,
+                }))
+            }
+            _ => {
+                self.pos -= 1;
+                self.unexpected("an expression")
+            }
+        }
+    }
+
+    /// Parse the arguments of a function call, after the opening parenthesis.
+    fn parse_function(&mut self, name: &str) -> Result<Expr> {
+        if let Some(func) = AggregateFunction::from_name(name) {
+            let expr = if func == AggregateFunction::Count && self.consume(&Token::Star) {
+                // Literals are never null, so this counts all the rows.
+                count(lit(1))
+            } else if func == AggregateFunction::Count && self.parse_keyword("DISTINCT") {
+                Expr::AggregateFunction {
+                    func: AggregateFunction::CountDistinct,
+                    arg: Box::new(self.parse_expr()?),
+                }
+            } else {
+                Expr::AggregateFunction {
+                    func,
+                    arg: Box::new(self.parse_expr()?),
+                }
+            };
+            self.expect(&Token::RParen)?;
+            return Ok(expr);
+        }
+        match ScalarFunction::from_name(name) {
+            Some(func) => {
+                let args = if self.peek() == Some(&Token::RParen) {
+                    vec![]
+                } else {
+                    self.parse_comma_separated(Parser::parse_expr)?
+                };
+                self.expect(&Token::RParen)?;
+                Ok(func.call(args))
+            }
+            None => Err(PlanError::Unsupported(format!("function {}", name)).into()),
+        }
+    }
+
+    fn parse_data_type(&mut self) -> Result<DataType> {
+        let name = match self.next() {
+            Some(Token::Word { value, .. }) => value.to_uppercase(),
+            _ => {
+                self.pos -= 1;
+                return self.unexpected("a type");
+            }
+        };
+        let data_type = match name.as_str() {
+            "BOOLEAN" | "BOOL" => DataType::Boolean,
+            "INT" | "INTEGER" | "BIGINT" | "SMALLINT" => DataType::Int64,
+            "DOUBLE" | "FLOAT" | "REAL" => DataType::Float64,
+            "STRING" | "TEXT" | "VARCHAR" => DataType::Utf8,
+            _ => return Err(PlanError::Unsupported(format!("type {}", name)).into()),
+        };
+        Ok(data_type)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataframe::col;
+
+    #[test]
+    fn parse_select() {
+        let query = Parser::parse_query(
+            "SELECT DISTINCT p.name AS n, count(*) total FROM people p \
+             LEFT OUTER JOIN orders o ON p.id = o.person_id \
+             WHERE age BETWEEN 18 AND 65 AND city IN ('paris', 'berlin') \
+             GROUP BY p.name HAVING sum(amount) > 10 ORDER BY total DESC, n LIMIT 5;",
+        )
+        .unwrap();
+        assert!(query.distinct);
+        assert_eq!(
+            query.projection,
+            vec![
+                SelectItem::Expr {
+                    expr: col("p.name"),
+                    alias: Some("n".to_owned())
+                },
+                SelectItem::Expr {
+                    expr: count(lit(1)),
+                    alias: Some("total".to_owned())
+                },
+            ]
+        );
+        assert_eq!(
+            query.from,
+            TableRef {
+                name: "people".to_owned(),
+                alias: Some("p".to_owned())
+            }
+        );
+        assert_eq!(query.joins[0].join_type, JoinType::Left);
+        assert_eq!(query.joins[0].on, col("p.id").eq(col("o.person_id")));
+        assert_eq!(
+            query.selection.unwrap().to_string(),
+            "(((age >= 18) AND (age <= 65)) AND ((city = 'paris') OR (city = 'berlin')))"
+        );
+        assert_eq!(query.group_by, vec![col("p.name")]);
+        assert_eq!(query.having.unwrap().to_string(), "(SUM(amount) > 10)");
+        assert_eq!(query.order_by, vec![col("total").desc(), col("n").asc()]);
+        assert_eq!(query.limit, Some(5));
+    }
+
+    #[test]
+    fn parse_expressions() {
+        let parse = |sql: &str| {
+            let mut parser = Parser {
+                tokens: tokenize(sql).unwrap(),
+                pos: 0,
+            };
+            parser.parse_expr().unwrap().to_string()
+        };
+        assert_eq!(parse("1 + 2 * -x"), "(1 + (2 * -x))");
+        assert_eq!(parse("NOT a = 1 OR b"), "(NOT (a = 1) OR b)");
+        assert_eq!(parse("name NOT LIKE 'a%'"), "NOT (name LIKE 'a%')");
+        assert_eq!(parse("a || 'x'"), "CONCAT(a, 'x')");
+        assert_eq!(
+            parse("CAST(x AS int) IS NOT NULL"),
+            "CAST(x AS bigint) IS NOT NULL"
+        );
+        assert_eq!(parse("upper(\"select\")"), "UPPER(select)");
+        assert!(Parser::parse_query("SELECT FROM t").is_err());
+        assert!(Parser::parse_query("SELECT a FROM t WHERE").is_err());
+        assert!(Parser::parse_query("SELECT a FROM t LIMIT x").is_err());
+        assert!(Parser::parse_query("SELECT unknown(a) FROM t").is_err());
+    }
+}
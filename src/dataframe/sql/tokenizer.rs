@@ -0,0 +1,223 @@
+use std::fmt::{self, Display};
+
+use crate::dataframe::PlanError;
+use crate::error::Result;
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Token {
+    /// Keyword or identifier. Quoted identifiers are never keywords.
+    Word {
+        value: String,
+        quoted: bool,
+    },
+    Number(String),
+    String(String),
+    Comma,
+    Period,
+    LParen,
+    RParen,
+    Star,
+    Plus,
+    Minus,
+    Slash,
+    Percent,
+    Concat,
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    SemiColon,
+}
+
+impl Token {
+    /// Whether the token is the given keyword, ignoring the case.
+    pub(crate) fn is_keyword(&self, keyword: &str) -> bool {
+        match self {
+            Token::Word {
+                value,
+                quoted: false,
+            } => value.eq_ignore_ascii_case(keyword),
+            _ => false,
+        }
+    }
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Word {
+                value,
+                quoted: true,
+            } => write!(f, "\"{}\"", value),
+            Token::Word { value, .. } | Token::Number(value) => write!(f, "{}", value),
+            Token::String(value) => write!(f, "'{}'", value),
+            Token::Comma => write!(f, ","),
+            Token::Period => write!(f, "."),
+            Token::LParen => write!(f, "("),
+            Token::RParen => write!(f, ")"),
+            Token::Star => write!(f, "*"),
+            Token::Plus => write!(f, "+"),
+            Token::Minus => write!(f, "-"),
+            Token::Slash => write!(f, "/"),
+            Token::Percent => write!(f, "%"),
+            Token::Concat => write!(f, "||"),
+            Token::Eq => write!(f, "="),
+            Token::NotEq => write!(f, "<>"),
+            Token::Lt => write!(f, "<"),
+            Token::LtEq => write!(f, "<="),
+            Token::Gt => write!(f, ">"),
+            Token::GtEq => write!(f, ">="),
+            Token::SemiColon => write!(f, ";"),
+        }
+    }
+}
+
+fn syntax_error(message: String) -> crate::error::Error {
+    PlanError::Syntax(message).into()
+}
+
+/// Split a query into tokens, skipping whitespaces and `--` comments.
+pub(crate) fn tokenize(query: &str) -> Result<Vec<Token>> {
+    let mut chars = query.chars().peekable();
+    let mut tokens = vec![];
+    while let Some(&c) = chars.peek() {
+        let token = match c {
+            c if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut value = String::new();
+                while let Some(&c) = chars.peek() {
+                    if !(c.is_alphanumeric() || c == '_') {
+                        break;
+                    }
+                    value.push(c);
+                    chars.next();
+                }
+                Token::Word {
+                    value,
+                    quoted: false,
+                }
+            }
+            c if c.is_ascii_digit() => {
+                let mut value = String::new();
+                while let Some(&c) = chars.peek() {
+                    let is_exponent_sign =
+                        (c == '+' || c == '-') && value.ends_with(|c: char| c == 'e' || c == 'E');
+                    if !(c.is_ascii_digit() || c == '.' || c == 'e' || c == 'E' || is_exponent_sign)
+                    {
+                        break;
+                    }
+                    value.push(c);
+                    chars.next();
+                }
+                Token::Number(value)
+            }
+            '\'' | '"' => {
+                chars.next();
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        // A doubled quote stands for the quote character itself.
+                        Some(q) if q == c && chars.peek() == Some(&c) => {
+                            chars.next();
+                            value.push(c);
+                        }
+                        Some(q) if q == c => break,
+                        Some(other) => value.push(other),
+                        None => return Err(syntax_error(format!("unterminated quote {}", c))),
+                    }
+                }
+                if c == '\'' {
+                    Token::String(value)
+                } else {
+                    Token::Word {
+                        value,
+                        quoted: true,
+                    }
+                }
+            }
+            _ => {
+                chars.next();
+                let next = chars.peek().cloned();
+                let (token, two_chars) = match (c, next) {
+                    ('-', Some('-')) => {
+                        chars.find(|&c| c == '\n');
+                        continue;
+                    }
+                    ('|', Some('|')) => (Token::Concat, true),
+                    ('!', Some('=')) | ('<', Some('>')) => (Token::NotEq, true),
+                    ('<', Some('=')) => (Token::LtEq, true),
+                    ('>', Some('=')) => (Token::GtEq, true),
+                    ('<', _) => (Token::Lt, false),
+                    ('>', _) => (Token::Gt, false),
+                    ('=', _) => (Token::Eq, false),
+                    (',', _) => (Token::Comma, false),
+                    ('.', _) => (Token::Period, false),
+                    ('(', _) => (Token::LParen, false),
+                    (')', _) => (Token::RParen, false),
+                    ('*', _) => (Token::Star, false),
+                    ('+', _) => (Token::Plus, false),
+                    ('-', _) => (Token::Minus, false),
+                    ('/', _) => (Token::Slash, false),
+                    ('%', _) => (Token::Percent, false),
+                    (';', _) => (Token::SemiColon, false),
+                    (c, _) => return Err(syntax_error(format!("unexpected character {}", c))),
+                };
+                if two_chars {
+                    chars.next();
+                }
+                token
+            }
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(value: &str) -> Token {
+        Token::Word {
+            value: value.to_owned(),
+            quoted: false,
+        }
+    }
+
+    #[test]
+    fn tokenize_query() {
+        let tokens =
+            tokenize("SELECT \"a b\", x1 FROM t -- comment\n WHERE y <> 'it''s' AND z >= 1.5e-3;")
+                .unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                word("SELECT"),
+                Token::Word {
+                    value: "a b".to_owned(),
+                    quoted: true
+                },
+                Token::Comma,
+                word("x1"),
+                word("FROM"),
+                word("t"),
+                word("WHERE"),
+                word("y"),
+                Token::NotEq,
+                Token::String("it's".to_owned()),
+                word("AND"),
+                word("z"),
+                Token::GtEq,
+                Token::Number("1.5e-3".to_owned()),
+                Token::SemiColon,
+            ]
+        );
+        assert!(tokenize("SELECT 'abc").is_err());
+        assert!(tokenize("SELECT #").is_err());
+    }
+}
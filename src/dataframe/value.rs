@@ -0,0 +1,285 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Display};
+use std::hash::{Hash, Hasher};
+
+use crate::dataframe::DataType;
+use serde_derive::{Deserialize, Serialize};
+
+/// A row of a DataFrame, holding one value per column of its schema.
+pub type Row = Vec<Value>;
+
+/// A single dynamically typed value of a DataFrame column.
+///
+/// Values are totally ordered so they can be used as grouping and sorting keys: nulls sort
+/// first, then booleans, numbers and strings. Integers and floats compare numerically and NaN
+/// is greater than any other number.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Value {
+    Null,
+    Boolean(bool),
+    Int(i64),
+    Float(f64),
+    Utf8(String),
+}
+
+impl Value {
+    /// Returns the type of the value, `DataType::Null` for nulls.
+    pub fn data_type(&self) -> DataType {
+        match self {
+            Value::Null => DataType::Null,
+            Value::Boolean(_) => DataType::Boolean,
+            Value::Int(_) => DataType::Int64,
+            Value::Float(_) => DataType::Float64,
+            Value::Utf8(_) => DataType::Utf8,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        match self {
+            Value::Null => true,
+            _ => false,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Boolean(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Value::Int(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a float if it is numeric.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Int(v) => Some(*v as f64),
+            Value::Float(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Utf8(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Converts the value to the given type, returning null if it can't be represented.
+    pub fn cast(&self, data_type: DataType) -> Value {
+        match (self, data_type) {
+            (Value::Null, _) | (_, DataType::Null) => Value::Null,
+            (Value::Boolean(v), DataType::Boolean) => Value::Boolean(*v),
+            (Value::Boolean(v), DataType::Int64) => Value::Int(*v as i64),
+            (Value::Boolean(v), DataType::Float64) => Value::Float(*v as i64 as f64),
+            (Value::Int(v), DataType::Boolean) => Value::Boolean(*v != 0),
+            (Value::Int(v), DataType::Int64) => Value::Int(*v),
+            (Value::Int(v), DataType::Float64) => Value::Float(*v as f64),
+            (Value::Float(v), DataType::Boolean) => Value::Boolean(*v != 0.0),
+            (Value::Float(v), DataType::Int64) if v.is_finite() => Value::Int(*v as i64),
+            (Value::Float(_), DataType::Int64) => Value::Null,
+            (Value::Float(v), DataType::Float64) => Value::Float(*v),
+            (Value::Utf8(v), DataType::Boolean) => match v.trim().to_lowercase().as_str() {
+                "true" => Value::Boolean(true),
+                "false" => Value::Boolean(false),
+                _ => Value::Null,
+            },
+            (Value::Utf8(v), DataType::Int64) => {
+                v.trim().parse().map(Value::Int).unwrap_or(Value::Null)
+            }
+            (Value::Utf8(v), DataType::Float64) => {
+                v.trim().parse().map(Value::Float).unwrap_or(Value::Null)
+            }
+            (v, DataType::Utf8) => Value::Utf8(v.to_string()),
+        }
+    }
+
+    fn type_rank(&self) -> u8 {
+        match self {
+            Value::Null => 0,
+            Value::Boolean(_) => 1,
+            Value::Int(_) | Value::Float(_) => 2,
+            Value::Utf8(_) => 3,
+        }
+    }
+}
+
+/// Total order of floats, with NaN greater than any other number.
+pub(crate) fn cmp_f64(a: f64, b: f64) -> Ordering {
+    a.partial_cmp(&b)
+        .unwrap_or_else(|| a.is_nan().cmp(&b.is_nan()))
+}
+
+/// Bits of a float where all NaNs and both zeros are normalized, so that equal values hash
+/// the same.
+fn normalized_bits(v: f64) -> u64 {
+    if v.is_nan() {
+        std::f64::NAN.to_bits()
+    } else if v == 0.0 {
+        0
+    } else {
+        v.to_bits()
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Null, Value::Null) => true,
+            (Value::Boolean(a), Value::Boolean(b)) => a == b,
+            (Value::Int(a), Value::Int(b)) => a == b,
+            (Value::Float(a), Value::Float(b)) => normalized_bits(*a) == normalized_bits(*b),
+            (Value::Utf8(a), Value::Utf8(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Value {}
+
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Value::Null => {}
+            Value::Boolean(v) => v.hash(state),
+            Value::Int(v) => v.hash(state),
+            Value::Float(v) => normalized_bits(*v).hash(state),
+            Value::Utf8(v) => v.hash(state),
+        }
+    }
+}
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Value) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Value {
+    fn cmp(&self, other: &Value) -> Ordering {
+        match (self, other) {
+            (Value::Boolean(a), Value::Boolean(b)) => a.cmp(b),
+            (Value::Int(a), Value::Int(b)) => a.cmp(b),
+            (Value::Float(a), Value::Float(b)) => cmp_f64(*a, *b),
+            // Ties between an int and a float are broken by the type to stay consistent with Eq.
+            (Value::Int(a), Value::Float(b)) => cmp_f64(*a as f64, *b).then(Ordering::Less),
+            (Value::Float(a), Value::Int(b)) => cmp_f64(*a, *b as f64).then(Ordering::Greater),
+            (Value::Utf8(a), Value::Utf8(b)) => a.cmp(b),
+            _ => self.type_rank().cmp(&other.type_rank()),
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Null => write!(f, "NULL"),
+            Value::Boolean(v) => write!(f, "{}", v),
+            Value::Int(v) => write!(f, "{}", v),
+            Value::Float(v) => write!(f, "{}", v),
+            Value::Utf8(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(v: bool) -> Value {
+        Value::Boolean(v)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(v: i32) -> Value {
+        Value::Int(v as i64)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(v: i64) -> Value {
+        Value::Int(v)
+    }
+}
+
+impl From<u32> for Value {
+    fn from(v: u32) -> Value {
+        Value::Int(v as i64)
+    }
+}
+
+impl From<f32> for Value {
+    fn from(v: f32) -> Value {
+        Value::Float(v as f64)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(v: f64) -> Value {
+        Value::Float(v)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(v: &str) -> Value {
+        Value::Utf8(v.to_owned())
+    }
+}
+
+impl From<String> for Value {
+    fn from(v: String) -> Value {
+        Value::Utf8(v)
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(v: Option<T>) -> Value {
+        v.map(Into::into).unwrap_or(Value::Null)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn value_ordering() {
+        let mut values = vec![
+            Value::Utf8("a".to_owned()),
+            Value::Float(std::f64::NAN),
+            Value::Int(2),
+            Value::Float(1.5),
+            Value::Null,
+            Value::Boolean(true),
+            Value::Int(1),
+        ];
+        values.sort();
+        assert_eq!(
+            values[..6],
+            [
+                Value::Null,
+                Value::Boolean(true),
+                Value::Int(1),
+                Value::Float(1.5),
+                Value::Int(2),
+                Value::Float(std::f64::NAN),
+            ]
+        );
+        assert_eq!(Value::Float(0.0), Value::Float(-0.0));
+        assert_ne!(Value::Int(1), Value::Float(1.0));
+        assert_eq!(Value::Int(1).cmp(&Value::Float(1.0)), Ordering::Less);
+    }
+
+    #[test]
+    fn value_cast() {
+        assert_eq!(Value::from("12").cast(DataType::Int64), Value::Int(12));
+        assert_eq!(Value::from("x").cast(DataType::Float64), Value::Null);
+        assert_eq!(Value::Int(3).cast(DataType::Utf8), Value::from("3"));
+        assert_eq!(Value::Float(2.7).cast(DataType::Int64), Value::Int(2));
+    }
+}
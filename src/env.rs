@@ -8,6 +8,7 @@ use crate::cache_tracker::CacheTracker;
 use crate::error::Error;
 use crate::hosts::Hosts;
 use crate::map_output_tracker::MapOutputTracker;
+use crate::scheduler::OutputCommitCoordinator;
 use crate::shuffle::{ShuffleFetcher, ShuffleManager};
 use dashmap::DashMap;
 use log::LevelFilter;
@@ -32,6 +33,7 @@ pub(crate) struct Env {
     pub shuffle_manager: ShuffleManager,
     pub shuffle_fetcher: ShuffleFetcher,
     pub cache_tracker: Arc<CacheTracker>,
+    pub output_commit_coordinator: Arc<OutputCommitCoordinator>,
 }
 
 impl Env {
@@ -91,6 +93,10 @@ impl Env {
                     &BOUNDED_MEM_CACHE,
                 )
                 .expect("fatal error: failed creating cache tracker"),
+                output_commit_coordinator: OutputCommitCoordinator::new(
+                    conf.is_driver,
+                    master_addr,
+                ),
             }
         })
     }
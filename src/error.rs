@@ -8,6 +8,15 @@ pub type StdResult<T, E> = std::result::Result<T, E>;
 
 #[derive(Debug, Error)]
 pub enum Error {
+    #[error(transparent)]
+    Arrow(#[from] arrow::error::ArrowError),
+
+    #[error(transparent)]
+    Csv(#[from] csv::Error),
+
+    #[error("async action terminated without a result")]
+    AsyncActionFailed,
+
     #[error(transparent)]
     AsyncJoinError(#[from] tokio::task::JoinError),
 
@@ -17,6 +26,12 @@ pub enum Error {
         command: String,
     },
 
+    #[error("{command} exited with {status}")]
+    CommandExit {
+        command: String,
+        status: std::process::ExitStatus,
+    },
+
     #[error("failed to create the log file")]
     CreateLogFile(#[source] std::io::Error),
 
@@ -29,6 +44,9 @@ pub enum Error {
     #[error("couldn't determine the path to the current binary")]
     CurrentBinaryPath,
 
+    #[error("attempt {attempt_id} of task {split_id} was denied committing its output")]
+    CommitDenied { split_id: usize, attempt_id: usize },
+
     #[error("failed trying converting to type {0}")]
     ConversionError(&'static str),
 
@@ -47,6 +65,15 @@ pub enum Error {
     #[error("configuration failure: {0}")]
     GetOrCreateConfig(&'static str),
 
+    #[error("invalid glob pattern")]
+    GlobPattern(#[from] glob::PatternError),
+
+    #[error("job was cancelled")]
+    JobCancelled,
+
+    #[error("task failed: {0}")]
+    TaskFailed(String),
+
     #[error("partitioner not set")]
     LackingPartitioner,
 
@@ -71,9 +98,15 @@ pub enum Error {
     #[error("failed to convert {:?} to a String", .0)]
     OsStringToString(OsString),
 
+    #[error("output path {} already exists", .0.display())]
+    OutputExists(PathBuf),
+
     #[error("failed writing to output destination")]
     OutputWrite(#[source] std::io::Error),
 
+    #[error(transparent)]
+    Parquet(#[from] parquet::errors::ParquetError),
+
     #[error("failed to parse hosts file at {}", path.display())]
     ParseHosts {
         source: toml::de::Error,
@@ -86,21 +119,48 @@ pub enum Error {
     #[error("failed to convert {} to a String", .0.display())]
     PathToString(PathBuf),
 
+    #[error(transparent)]
+    PlanError(#[from] crate::dataframe::PlanError),
+
     #[error("failed to parse slave address {0}")]
     ParseHostAddress(String),
 
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    #[error("invalid CSV header {header:?}: {message}")]
+    InvalidCsvHeader { header: String, message: String },
+
+    #[error("invalid object file {}: {reason}", path.display())]
+    InvalidObjectFile { path: PathBuf, reason: String },
+
     #[error("failed reading from input source")]
     InputRead(#[source] std::io::Error),
 
+    #[error("malformed record {line:?}: {message}")]
+    MalformedRecord { line: String, message: String },
+
+    #[error("failed computing a record batch: {0}")]
+    RecordBatch(String),
+
     #[error(transparent)]
     ShuffleError(#[from] crate::shuffle::ShuffleError),
 
     #[error("operation not supported: {0}")]
     UnsupportedOperation(&'static str),
 
+    #[error("no file system registered for scheme {0}")]
+    UnsupportedScheme(String),
+
+    #[error("file system URIs can't name a host, found authority {0}")]
+    UnsupportedAuthority(String),
+
     #[error("no files for the given path")]
     NoFilesFound,
 
+    #[error("can't zip RDDs with {first} and {second} partitions")]
+    UnequalPartitionCounts { first: usize, second: usize },
+
     #[error("unrecognized error (todo!)")]
     Other,
 }
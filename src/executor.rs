@@ -145,8 +145,8 @@ impl Executor {
         let result: Result<Vec<u8>> = {
             let start = Instant::now();
             log::debug!("executing the task from server port {}", self.port);
-            // TODO: change attempt id from 0 to proper value
-            let result = des_task.run(0);
+            // Tasks get a new id every time they are submitted, which identifies the attempt.
+            let result = des_task.run(des_task.get_task_id());
             log::debug!(
                 "time taken @{} executor running task #{}: {}ms",
                 self.port,
@@ -1,21 +1,221 @@
-// WIP
-pub trait FileSystem {
+use std::collections::HashMap;
+use std::io::{self, Read, Seek, Write};
+use std::path::{Path as StdPath, PathBuf};
+use std::sync::Arc;
+
+use crate::error::{Error, Result};
+use crate::fs::path::Path;
+use crate::fs::{LocalFileSystem, MemoryFileSystem};
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use regex::Regex;
+
+static URI_SCHEME: Lazy<Regex> =
+    Lazy::new(|| Regex::new("^([a-zA-Z][a-zA-Z0-9+.-]*)://([^/]*)").unwrap());
+
+static REGISTRY: Lazy<FileSystemRegistry> = Lazy::new(|| {
+    let registry = FileSystemRegistry {
+        file_systems: RwLock::new(HashMap::new()),
+    };
+    registry.register(Arc::new(LocalFileSystem));
+    registry.register(Arc::new(MemoryFileSystem::new()));
+    registry
+});
+
+/// Split a path into the scheme of its URI, if any, and the path within the file system.
+///
+/// `mem:///data/part-0` gives `(Some("mem"), "/data/part-0")`, while paths without a
+/// `scheme://` prefix are local paths. File systems only hold the paths of their own host, so
+/// a URI with an authority, such as `file://host/data`, is not split and the registry rejects
+/// it.
+pub fn split_scheme(path: &StdPath) -> (Option<&str>, &StdPath) {
+    let text = match path.to_str() {
+        Some(text) => text,
+        None => return (None, path),
+    };
+    match URI_SCHEME.captures(text) {
+        Some(captures) if captures[2].is_empty() => {
+            let scheme = captures.get(1).unwrap().as_str();
+            (Some(scheme), StdPath::new(&text[scheme.len() + 3..]))
+        }
+        _ => (None, path),
+    }
+}
+
+/// File system holding a path, from the global registry.
+pub fn get_file_system(path: &StdPath) -> Result<Arc<dyn FileSystem>> {
+    FileSystemRegistry::global().get_for(path)
+}
+
+/// Status of a file or directory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileStatus {
+    /// Path of the entry, with the scheme of the path it was listed from.
+    pub path: PathBuf,
+    /// Length of a file in bytes, 0 for directories.
+    pub len: u64,
+    pub is_dir: bool,
+}
+
+pub trait ReadSeek: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> ReadSeek for T {}
+
+/// A storage backend holding files and directories, such as the local file system.
+///
+/// Paths given to a file system may start with its `scheme://` prefix, which is kept in the
+/// paths it lists. Errors are I/O errors, with the usual kinds: `NotFound` for a missing
+/// path and `AlreadyExists` when creating a file which exists.
+pub trait FileSystem: Send + Sync {
+    /// Scheme of the URIs of the paths held by the file system, such as `file`.
+    fn scheme(&self) -> &str;
+
     fn get_default_port(&self) -> u16 {
         0
     }
 
-    //    fn canonicalize_url(&self, mut url: Url) -> Url {
-    //        if url.port().is_none() && self.get_default_port() > 0 {
-    //            url.set_port(Some(self.get_default_port()));
-    //        }
-    //        return url;
-    //    }
-    //
-    //    fn get_canonical_uri(&self) -> Url {
-    //        self.canonicalize_url(self.get_uri().clone())
-    //    }
+    fn open(&self, path: &StdPath) -> io::Result<Box<dyn ReadSeek>>;
+
+    /// Create a file and its missing parent directories. When `overwrite` is false, creating
+    /// fails if the file exists, atomically so that only one of concurrent callers succeeds.
+    ///
+    /// The content of the file is only guaranteed to be visible once the writer is flushed.
+    fn create(&self, path: &StdPath, overwrite: bool) -> io::Result<Box<dyn Write + Send>>;
+
+    /// Statuses of the entries of a directory, in no particular order, or the status of the
+    /// path itself if it is a file.
+    fn list_status(&self, path: &StdPath) -> io::Result<Vec<FileStatus>>;
+
+    fn get_file_status(&self, path: &StdPath) -> io::Result<FileStatus>;
+
+    /// Move a file or a directory, replacing the destination if it is a file.
+    fn rename(&self, from: &StdPath, to: &StdPath) -> io::Result<()>;
+
+    /// Delete a file or a directory, with its content if `recursive` is true. Returns false
+    /// if the path doesn't exist.
+    fn delete(&self, path: &StdPath, recursive: bool) -> io::Result<bool>;
+
+    /// Create a directory and its missing parents.
+    fn mkdirs(&self, path: &StdPath) -> io::Result<()>;
+
+    fn exists(&self, path: &StdPath) -> bool {
+        self.get_file_status(path).is_ok()
+    }
+
+    /// Path of the file on the local file system, for the readers and writers which need a
+    /// local file. `None` when the file system is not local.
+    fn to_local_path(&self, _path: &StdPath) -> Option<PathBuf> {
+        None
+    }
+}
+
+/// File systems by scheme. Paths without a scheme are held by the `file` file system.
+///
+/// The global registry holds the local file system under `file` and an in-memory file system
+/// under `mem`. Note that the files of the `mem` file system only live in the memory of the
+/// process, so in distributed mode every host sees its own files.
+pub struct FileSystemRegistry {
+    file_systems: RwLock<HashMap<String, Arc<dyn FileSystem>>>,
+}
 
-    //fn get_uri(&self) -> &Url;
+impl FileSystemRegistry {
+    pub fn global() -> &'static FileSystemRegistry {
+        &REGISTRY
+    }
+
+    /// Register a file system under its scheme, replacing any file system with the same
+    /// scheme.
+    pub fn register(&self, file_system: Arc<dyn FileSystem>) {
+        let scheme = file_system.scheme().to_ascii_lowercase();
+        self.file_systems.write().insert(scheme, file_system);
+    }
+
+    /// File system of the scheme of a path, which must not have an authority.
+    pub fn get(&self, path: &Path) -> Result<Arc<dyn FileSystem>> {
+        let url = path.to_url();
+        self.get_uri(
+            url.scheme().as_str(),
+            url.authority().map(|a| a.to_string()),
+        )
+    }
 
-    //fn get_fs_of_path(Path abs_or_fq_path);
+    /// File system of a path given as a `scheme://` URI or as a local path.
+    pub fn get_for(&self, path: &StdPath) -> Result<Arc<dyn FileSystem>> {
+        let captures = path.to_str().and_then(|text| URI_SCHEME.captures(text));
+        match captures {
+            Some(captures) => self.get_uri(&captures[1], Some(captures[2].to_owned())),
+            None => self.get_scheme("file"),
+        }
+    }
+
+    fn get_uri(&self, scheme: &str, authority: Option<String>) -> Result<Arc<dyn FileSystem>> {
+        let file_system = self.get_scheme(scheme)?;
+        match authority {
+            Some(authority) if !authority.is_empty() => Err(Error::UnsupportedAuthority(authority)),
+            _ => Ok(file_system),
+        }
+    }
+
+    fn get_scheme(&self, scheme: &str) -> Result<Arc<dyn FileSystem>> {
+        self.file_systems
+            .read()
+            .get(&scheme.to_ascii_lowercase())
+            .cloned()
+            .ok_or_else(|| Error::UnsupportedScheme(scheme.to_owned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_schemes() {
+        assert_eq!(
+            split_scheme(StdPath::new("mem:///data/a")),
+            (Some("mem"), StdPath::new("/data/a"))
+        );
+        assert_eq!(
+            split_scheme(StdPath::new("data/a")),
+            (None, StdPath::new("data/a"))
+        );
+
+        let registry = FileSystemRegistry::global();
+        assert_eq!(
+            registry.get_for(StdPath::new("/tmp")).unwrap().scheme(),
+            "file"
+        );
+        assert_eq!(
+            registry.get_for(StdPath::new("MEM:///x")).unwrap().scheme(),
+            "mem"
+        );
+        let path = Path::from_path_string("mem:///data/a");
+        assert_eq!(registry.get(&path).unwrap().scheme(), "mem");
+        assert_eq!(
+            registry
+                .get(&Path::from_path_string("/tmp"))
+                .unwrap()
+                .scheme(),
+            "file"
+        );
+        // A URI naming a host is not resolved to the local file system.
+        assert_eq!(
+            split_scheme(StdPath::new("file://host/tmp/x")),
+            (None, StdPath::new("file://host/tmp/x"))
+        );
+        for path in &["file://host/tmp/x", "mem://host/x"] {
+            match registry.get_for(StdPath::new(path)) {
+                Err(Error::UnsupportedAuthority(authority)) => assert_eq!(authority, "host"),
+                _ => panic!("expected an unsupported authority for {}", path),
+            }
+            match registry.get(&Path::from_path_string(path)) {
+                Err(Error::UnsupportedAuthority(authority)) => assert_eq!(authority, "host"),
+                _ => panic!("expected an unsupported authority for {}", path),
+            }
+        }
+        match registry.get_for(StdPath::new("hdfs://host/data")) {
+            Err(Error::UnsupportedScheme(scheme)) => assert_eq!(scheme, "hdfs"),
+            _ => panic!("expected an unsupported scheme"),
+        }
+    }
 }
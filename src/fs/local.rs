@@ -0,0 +1,98 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::fs::{split_scheme, FileStatus, FileSystem, ReadSeek};
+
+/// The file system of the host, for local paths and `file://` URIs.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LocalFileSystem;
+
+impl LocalFileSystem {
+    fn local(path: &Path) -> &Path {
+        split_scheme(path).1
+    }
+}
+
+impl FileSystem for LocalFileSystem {
+    fn scheme(&self) -> &str {
+        "file"
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn ReadSeek>> {
+        Ok(Box::new(File::open(Self::local(path))?))
+    }
+
+    fn create(&self, path: &Path, overwrite: bool) -> io::Result<Box<dyn Write + Send>> {
+        let local = Self::local(path);
+        if let Some(parent) = local
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new()
+            .write(true)
+            .create(overwrite)
+            .truncate(overwrite)
+            .create_new(!overwrite)
+            .open(local)?;
+        Ok(Box::new(file))
+    }
+
+    fn list_status(&self, path: &Path) -> io::Result<Vec<FileStatus>> {
+        let status = self.get_file_status(path)?;
+        if !status.is_dir {
+            return Ok(vec![status]);
+        }
+        let mut statuses = vec![];
+        for entry in fs::read_dir(Self::local(path))? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            statuses.push(FileStatus {
+                path: path.join(entry.file_name()),
+                len: if metadata.is_dir() { 0 } else { metadata.len() },
+                is_dir: metadata.is_dir(),
+            });
+        }
+        Ok(statuses)
+    }
+
+    fn get_file_status(&self, path: &Path) -> io::Result<FileStatus> {
+        let metadata = fs::metadata(Self::local(path))?;
+        Ok(FileStatus {
+            path: path.to_owned(),
+            len: if metadata.is_dir() { 0 } else { metadata.len() },
+            is_dir: metadata.is_dir(),
+        })
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        fs::rename(Self::local(from), Self::local(to))
+    }
+
+    fn delete(&self, path: &Path, recursive: bool) -> io::Result<bool> {
+        let local = Self::local(path);
+        let metadata = match fs::symlink_metadata(local) {
+            Ok(metadata) => metadata,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(false),
+            Err(err) => return Err(err),
+        };
+        if !metadata.is_dir() {
+            fs::remove_file(local)?;
+        } else if recursive {
+            fs::remove_dir_all(local)?;
+        } else {
+            fs::remove_dir(local)?;
+        }
+        Ok(true)
+    }
+
+    fn mkdirs(&self, path: &Path) -> io::Result<()> {
+        fs::create_dir_all(Self::local(path))
+    }
+
+    fn to_local_path(&self, path: &Path) -> Option<PathBuf> {
+        Some(Self::local(path).to_owned())
+    }
+}
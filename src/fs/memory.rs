@@ -0,0 +1,355 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, Cursor, ErrorKind, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+use crate::fs::{split_scheme, FileStatus, FileSystem, ReadSeek};
+use parking_lot::RwLock;
+
+const MEMORY_SCHEME: &str = "mem";
+
+/// Content of a file, shared with the writers of the file.
+type Content = Arc<RwLock<Vec<u8>>>;
+
+#[derive(Debug, Default)]
+struct MemoryTree {
+    files: BTreeMap<PathBuf, Content>,
+    /// Every directory, with all its ancestors.
+    dirs: BTreeSet<PathBuf>,
+}
+
+impl MemoryTree {
+    fn add_dirs(&mut self, dir: &Path) -> io::Result<()> {
+        for ancestor in dir.ancestors() {
+            if self.files.contains_key(ancestor) {
+                return Err(io::Error::new(
+                    ErrorKind::AlreadyExists,
+                    format!("{} is a file", ancestor.display()),
+                ));
+            }
+            self.dirs.insert(ancestor.to_owned());
+        }
+        Ok(())
+    }
+}
+
+/// A file system holding its files in memory, for `mem://` URIs such as
+/// `mem:///data/part-0`.
+///
+/// Clones share the same files. Files are only visible to the process which wrote them.
+#[derive(Clone, Debug)]
+pub struct MemoryFileSystem {
+    tree: Arc<RwLock<MemoryTree>>,
+}
+
+impl MemoryFileSystem {
+    pub fn new() -> Self {
+        let mut tree = MemoryTree::default();
+        tree.dirs.insert(PathBuf::from("/"));
+        MemoryFileSystem {
+            tree: Arc::new(RwLock::new(tree)),
+        }
+    }
+
+    /// Key of a path in the tree: an absolute path without `.` or `..` components.
+    fn key(path: &Path) -> PathBuf {
+        let mut key = PathBuf::from("/");
+        for component in split_scheme(path).1.components() {
+            match component {
+                Component::Normal(name) => key.push(name),
+                Component::ParentDir => {
+                    key.pop();
+                }
+                _ => {}
+            }
+        }
+        key
+    }
+
+    fn not_found(path: &Path) -> io::Error {
+        io::Error::new(ErrorKind::NotFound, format!("{} not found", path.display()))
+    }
+
+    fn status(path: PathBuf, tree: &MemoryTree, key: &Path) -> Option<FileStatus> {
+        if let Some(content) = tree.files.get(key) {
+            Some(FileStatus {
+                path,
+                len: content.read().len() as u64,
+                is_dir: false,
+            })
+        } else if tree.dirs.contains(key) {
+            Some(FileStatus {
+                path,
+                len: 0,
+                is_dir: true,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for MemoryFileSystem {
+    fn default() -> Self {
+        MemoryFileSystem::new()
+    }
+}
+
+/// Writes a file of a memory file system, storing its content on flush and on drop.
+///
+/// Like a file handle, the writer keeps writing to its file once it is renamed, and doesn't
+/// bring it back once it is deleted.
+struct MemoryWriter {
+    content: Content,
+    buf: Vec<u8>,
+}
+
+impl Write for MemoryWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        *self.content.write() = self.buf.clone();
+        Ok(())
+    }
+}
+
+impl Drop for MemoryWriter {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+impl FileSystem for MemoryFileSystem {
+    fn scheme(&self) -> &str {
+        MEMORY_SCHEME
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn ReadSeek>> {
+        let tree = self.tree.read();
+        let content = tree
+            .files
+            .get(&Self::key(path))
+            .ok_or_else(|| Self::not_found(path))?;
+        Ok(Box::new(Cursor::new(content.read().clone())))
+    }
+
+    fn create(&self, path: &Path, overwrite: bool) -> io::Result<Box<dyn Write + Send>> {
+        let key = Self::key(path);
+        let mut tree = self.tree.write();
+        if tree.dirs.contains(&key) {
+            return Err(io::Error::new(
+                ErrorKind::Other,
+                format!("{} is a directory", path.display()),
+            ));
+        }
+        if !overwrite && tree.files.contains_key(&key) {
+            return Err(io::Error::new(
+                ErrorKind::AlreadyExists,
+                format!("{} already exists", path.display()),
+            ));
+        }
+        if let Some(parent) = key.parent() {
+            tree.add_dirs(parent)?;
+        }
+        let content = Content::default();
+        tree.files.insert(key, content.clone());
+        Ok(Box::new(MemoryWriter {
+            content,
+            buf: vec![],
+        }))
+    }
+
+    fn list_status(&self, path: &Path) -> io::Result<Vec<FileStatus>> {
+        let key = Self::key(path);
+        let tree = self.tree.read();
+        let status =
+            Self::status(path.to_owned(), &tree, &key).ok_or_else(|| Self::not_found(path))?;
+        if !status.is_dir {
+            return Ok(vec![status]);
+        }
+        let children = tree
+            .files
+            .keys()
+            .chain(tree.dirs.iter())
+            .filter(|child| child.parent() == Some(key.as_path()))
+            .filter_map(|child| {
+                let name = child.file_name()?;
+                Self::status(path.join(name), &tree, child)
+            })
+            .collect();
+        Ok(children)
+    }
+
+    fn get_file_status(&self, path: &Path) -> io::Result<FileStatus> {
+        let tree = self.tree.read();
+        Self::status(path.to_owned(), &tree, &Self::key(path)).ok_or_else(|| Self::not_found(path))
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let (from_key, to_key) = (Self::key(from), Self::key(to));
+        let mut tree = self.tree.write();
+        if tree.dirs.contains(&to_key) {
+            return Err(io::Error::new(
+                ErrorKind::AlreadyExists,
+                format!("{} is a directory", to.display()),
+            ));
+        }
+        if let Some(parent) = to_key.parent() {
+            if !tree.dirs.contains(parent) {
+                return Err(Self::not_found(parent));
+            }
+        }
+        if let Some(content) = tree.files.remove(&from_key) {
+            tree.files.insert(to_key, content);
+            return Ok(());
+        }
+        if !tree.dirs.contains(&from_key) {
+            return Err(Self::not_found(from));
+        }
+        if tree.files.contains_key(&to_key) || to_key.starts_with(&from_key) {
+            return Err(io::Error::new(
+                ErrorKind::Other,
+                format!("can't move {} to {}", from.display(), to.display()),
+            ));
+        }
+        let moved = |key: &PathBuf| match key.strip_prefix(&from_key).unwrap() {
+            relative if relative.as_os_str().is_empty() => to_key.clone(),
+            relative => to_key.join(relative),
+        };
+        let files = tree
+            .files
+            .keys()
+            .filter(|key| key.starts_with(&from_key))
+            .cloned()
+            .collect::<Vec<_>>();
+        for key in files {
+            let content = tree.files.remove(&key).unwrap();
+            tree.files.insert(moved(&key), content);
+        }
+        let dirs = tree
+            .dirs
+            .iter()
+            .filter(|key| key.starts_with(&from_key))
+            .cloned()
+            .collect::<Vec<_>>();
+        for key in dirs {
+            tree.dirs.remove(&key);
+            tree.dirs.insert(moved(&key));
+        }
+        Ok(())
+    }
+
+    fn delete(&self, path: &Path, recursive: bool) -> io::Result<bool> {
+        let key = Self::key(path);
+        let mut tree = self.tree.write();
+        if tree.files.remove(&key).is_some() {
+            return Ok(true);
+        }
+        if !tree.dirs.contains(&key) {
+            return Ok(false);
+        }
+        let is_descendant = |child: &PathBuf| child != &key && child.starts_with(&key);
+        let is_empty =
+            !tree.files.keys().any(is_descendant) && !tree.dirs.iter().any(is_descendant);
+        if !is_empty && !recursive {
+            return Err(io::Error::new(
+                ErrorKind::Other,
+                format!("directory {} is not empty", path.display()),
+            ));
+        }
+        tree.files.retain(|child, _| !child.starts_with(&key));
+        tree.dirs.retain(|child| !child.starts_with(&key));
+        tree.dirs.insert(PathBuf::from("/"));
+        Ok(true)
+    }
+
+    fn mkdirs(&self, path: &Path) -> io::Result<()> {
+        self.tree.write().add_dirs(&Self::key(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn read(fs: &MemoryFileSystem, path: &str) -> String {
+        let mut text = String::new();
+        fs.open(Path::new(path))
+            .unwrap()
+            .read_to_string(&mut text)
+            .unwrap();
+        text
+    }
+
+    fn names(fs: &MemoryFileSystem, path: &str) -> Vec<String> {
+        let mut names = fs
+            .list_status(Path::new(path))
+            .unwrap()
+            .into_iter()
+            .map(|status| status.path.to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn files_and_directories() {
+        let fs = MemoryFileSystem::new();
+        let mut writer = fs.create(Path::new("mem:///a/b/one"), false).unwrap();
+        writer.write_all(b"first").unwrap();
+        drop(writer);
+        assert_eq!(read(&fs, "mem:///a/b/one"), "first");
+        assert!(fs.get_file_status(Path::new("mem:///a")).unwrap().is_dir);
+        match fs.create(Path::new("mem:///a/b/one"), false) {
+            Err(err) => assert_eq!(err.kind(), ErrorKind::AlreadyExists),
+            Ok(_) => panic!("expected an existing file"),
+        }
+
+        fs.mkdirs(Path::new("mem:///a/c")).unwrap();
+        fs.create(Path::new("mem:///a/two"), true)
+            .unwrap()
+            .write_all(b"second")
+            .unwrap();
+        assert_eq!(
+            names(&fs, "mem:///a"),
+            vec!["mem:///a/b", "mem:///a/c", "mem:///a/two"]
+        );
+        assert_eq!(
+            fs.get_file_status(Path::new("mem:///a/two")).unwrap().len,
+            6
+        );
+
+        fs.rename(Path::new("mem:///a/b"), Path::new("mem:///a/c/d"))
+            .unwrap();
+        assert_eq!(read(&fs, "mem:///a/c/d/one"), "first");
+        assert!(!fs.exists(Path::new("mem:///a/b")));
+
+        assert!(fs.delete(Path::new("mem:///a/c"), false).is_err());
+        assert!(fs.delete(Path::new("mem:///a/c"), true).unwrap());
+        assert!(!fs.delete(Path::new("mem:///a/c"), true).unwrap());
+        assert_eq!(names(&fs, "mem:///a"), vec!["mem:///a/two"]);
+        assert!(fs.mkdirs(Path::new("mem:///a/two/x")).is_err());
+    }
+
+    #[test]
+    fn writers_follow_their_file() {
+        let fs = MemoryFileSystem::new();
+        let mut writer = fs.create(Path::new("mem:///work/a"), false).unwrap();
+        writer.write_all(b"moved").unwrap();
+        fs.rename(Path::new("mem:///work/a"), Path::new("mem:///out/a"))
+            .unwrap();
+        drop(writer);
+        assert!(!fs.exists(Path::new("mem:///work/a")));
+        assert_eq!(read(&fs, "mem:///out/a"), "moved");
+
+        let mut writer = fs.create(Path::new("mem:///work/b"), false).unwrap();
+        writer.write_all(b"deleted").unwrap();
+        assert!(fs.delete(Path::new("mem:///work"), true).unwrap());
+        drop(writer);
+        assert!(!fs.exists(Path::new("mem:///work/b")));
+    }
+}
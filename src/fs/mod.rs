@@ -1,3 +1,10 @@
 pub mod common_config_keys;
 pub mod file_system;
 pub mod path;
+pub use file_system::{
+    get_file_system, split_scheme, FileStatus, FileSystem, FileSystemRegistry, ReadSeek,
+};
+mod local;
+pub use local::LocalFileSystem;
+mod memory;
+pub use memory::MemoryFileSystem;
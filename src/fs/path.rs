@@ -1,6 +1,6 @@
 use once_cell::sync::Lazy;
 use regex::Regex;
-use uriparse::{Authority, Fragment, Query, URI};
+use uriparse::{Fragment, Query, URI};
 
 pub const SEPARATOR: char = '/';
 pub const SEPARATOR_CHAR: char = '/';
@@ -30,7 +30,7 @@ impl Path {
             path_string = format!("/{:?}", path_string);
         }
         let mut scheme = None;
-        let mut _authority = None;
+        let mut authority = None;
         let mut index = 0;
 
         // parse uri scheme if any present
@@ -42,16 +42,12 @@ impl Path {
         }
 
         if path_string.get(index..).unwrap().starts_with("//") && path_string.len() - index > 2 {
-            let next_slash = path_string[index + 2..]
+            let auth_end = path_string[index + 2..]
                 .find('/')
-                .map(|fi| index + 2 + fi)
-                .unwrap();
-            let auth_end = if next_slash > 0 {
-                next_slash
-            } else {
-                path_string.len()
-            };
-            _authority = Some(path_string.get(index + 2..auth_end).unwrap());
+                .map_or(path_string.len(), |fi| index + 2 + fi);
+            // `scheme:///path` has an empty authority, which is the same as none.
+            authority =
+                Some(path_string.get(index + 2..auth_end).unwrap()).filter(|a| !a.is_empty());
             index = auth_end;
         }
 
@@ -63,7 +59,7 @@ impl Path {
         let path = Self::normalize_path(scheme.unwrap().to_string(), path.unwrap().to_string());
         let mut url = URI::from_parts(
             scheme.unwrap(),
-            None::<Authority>,
+            authority,
             path.as_str(),
             None::<Query>,
             None::<Fragment>,
@@ -214,6 +210,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn authority() {
+        let authority = |path: &str| {
+            Path::from_path_string(path)
+                .to_url()
+                .authority()
+                .map(|authority| authority.to_string())
+        };
+        assert_eq!(
+            authority("hdfs://host:9000/foo"),
+            Some("host:9000".to_owned())
+        );
+        assert_eq!(authority("file://host"), Some("host".to_owned()));
+        assert_eq!(authority("file:///foo"), None);
+        assert_eq!(authority("/foo"), None);
+    }
+
     #[test]
     fn is_absolute() {
         assert!(Path::from_path_string("/").is_absolute());
@@ -0,0 +1,79 @@
+//! Support for actions which run in the background and can be awaited or cancelled.
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context as TaskCx, Poll};
+
+use crate::env;
+use crate::error::{Error, Result};
+use crate::scheduler::JobCancellation;
+use futures::channel::oneshot;
+
+/// A future for the result of an action, returned by the `*_async` methods of
+/// [Rdd](rdd/trait.Rdd.html).
+///
+/// The jobs of the action are submitted in the background as soon as it is created, and run
+/// on the async runtime, so no thread is blocked while waiting for their tasks. Awaiting it
+/// yields the result of the action.
+///
+/// Cancelling the action stops its running job, whose tasks which did not start yet are
+/// skipped, and no further jobs of the action are submitted. The future then resolves with
+/// `Error::JobCancelled`. Tasks which are already running are not interrupted; their results
+/// are discarded.
+pub struct FutureAction<T> {
+    result: oneshot::Receiver<Result<T>>,
+    cancellation: Arc<JobCancellation>,
+}
+
+impl<T: Send + 'static> FutureAction<T> {
+    /// Spawn the future of an action on the async runtime. The action receives the
+    /// cancellation of the action, which it passes to the jobs it runs.
+    pub(crate) fn spawn<F, Fut>(action: F) -> Self
+    where
+        F: FnOnce(Arc<JobCancellation>) -> Fut,
+        Fut: Future<Output = Result<T>> + Send + 'static,
+    {
+        let (sender, receiver) = oneshot::channel();
+        let cancellation = Arc::new(JobCancellation::new());
+        let action = action(cancellation.clone());
+        env::Env::run_in_async_rt(|| {
+            tokio::spawn(async move {
+                // the receiver is gone if the future was dropped, nobody wants the result
+                let _ = sender.send(action.await);
+            })
+        });
+        FutureAction {
+            result: receiver,
+            cancellation,
+        }
+    }
+}
+
+impl<T> FutureAction<T> {
+    /// Cancel the execution of this action.
+    pub fn cancel(&self) {
+        self.cancellation.cancel();
+    }
+
+    /// Returns whether the action has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation.is_cancelled()
+    }
+}
+
+impl<T> Future for FutureAction<T> {
+    type Output = Result<T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut TaskCx<'_>) -> Poll<Self::Output> {
+        match Pin::new(&mut self.result).poll(cx) {
+            // a cancelled action may still complete if its last job finished in the meantime
+            Poll::Ready(Ok(_)) if self.cancellation.is_cancelled() => {
+                Poll::Ready(Err(Error::JobCancelled))
+            }
+            Poll::Ready(Ok(result)) => Poll::Ready(result),
+            // the action panicked before sending its result
+            Poll::Ready(Err(oneshot::Canceled)) => Poll::Ready(Err(Error::AsyncActionFailed)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
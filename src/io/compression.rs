@@ -0,0 +1,165 @@
+use std::io::{Read, Write};
+use std::path::Path;
+
+use crate::error::{Error, Result};
+use crate::fs;
+use serde_derive::{Deserialize, Serialize};
+
+/// Compression codec of input files.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Codec {
+    Gzip,
+    Zstd,
+    Bzip2,
+    Xz,
+}
+
+impl Codec {
+    /// Returns the codec matching the extension of the path, if any.
+    pub fn from_path(path: &Path) -> Option<Codec> {
+        match path.extension()?.to_str()? {
+            "gz" | "gzip" => Some(Codec::Gzip),
+            "zst" | "zstd" => Some(Codec::Zstd),
+            "bz2" => Some(Codec::Bzip2),
+            "xz" => Some(Codec::Xz),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Codec::Gzip => "gz",
+            Codec::Zstd => "zst",
+            Codec::Bzip2 => "bz2",
+            Codec::Xz => "xz",
+        }
+    }
+
+    /// Wrap a reader of compressed data into a reader of the decompressed stream.
+    pub fn decoder<R: Read + 'static>(self, reader: R) -> Result<Box<dyn Read>> {
+        let decoder: Box<dyn Read> = match self {
+            Codec::Gzip => Box::new(flate2::read::MultiGzDecoder::new(reader)),
+            Codec::Zstd => {
+                Box::new(zstd::stream::read::Decoder::new(reader).map_err(Error::InputRead)?)
+            }
+            Codec::Bzip2 => Box::new(bzip2::read::BzDecoder::new(reader)),
+            Codec::Xz => Box::new(xz2::read::XzDecoder::new(reader)),
+        };
+        Ok(decoder)
+    }
+
+    /// Compress a buffer.
+    pub fn compress(self, data: &[u8]) -> Result<Vec<u8>> {
+        let compressed = match self {
+            Codec::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+                encoder.write_all(data).and_then(|_| encoder.finish())
+            }
+            Codec::Zstd => zstd::stream::encode_all(data, 0),
+            Codec::Bzip2 => {
+                let mut encoder = bzip2::write::BzEncoder::new(vec![], bzip2::Compression::Default);
+                encoder.write_all(data).and_then(|_| encoder.finish())
+            }
+            Codec::Xz => {
+                let mut encoder = xz2::write::XzEncoder::new(vec![], 6);
+                encoder.write_all(data).and_then(|_| encoder.finish())
+            }
+        };
+        compressed.map_err(Error::OutputWrite)
+    }
+
+    /// Decompress a buffer compressed with `compress`.
+    pub fn decompress(self, data: &[u8]) -> Result<Vec<u8>> {
+        let mut decompressed = vec![];
+        self.decoder(std::io::Cursor::new(data.to_vec()))?
+            .read_to_end(&mut decompressed)
+            .map_err(Error::InputRead)?;
+        Ok(decompressed)
+    }
+}
+
+/// How the codec of an input file is chosen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CodecSelection {
+    /// Detect the codec from the extension of each file, read unknown extensions as is.
+    FromExtension,
+    /// Never decompress.
+    Uncompressed,
+    /// Decompress every file with the given codec.
+    Codec(Codec),
+}
+
+impl Default for CodecSelection {
+    fn default() -> Self {
+        CodecSelection::FromExtension
+    }
+}
+
+impl CodecSelection {
+    /// Codec of the file, `None` if it is not compressed.
+    pub fn codec_for(self, path: &Path) -> Option<Codec> {
+        match self {
+            CodecSelection::FromExtension => Codec::from_path(path),
+            CodecSelection::Uncompressed => None,
+            CodecSelection::Codec(codec) => Some(codec),
+        }
+    }
+
+    /// Open a file, decompressing it if needed. Compressed files can't be split and must be
+    /// read from the beginning.
+    pub fn open(self, path: &Path) -> Result<Box<dyn Read>> {
+        let file = fs::get_file_system(path)?
+            .open(path)
+            .map_err(Error::InputRead)?;
+        match self.codec_for(path) {
+            Some(codec) => codec.decoder(file),
+            None => Ok(Box::new(file)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn decode_by_extension() {
+        let text = b"line 1\nline 2\n".to_vec();
+        let mut gz = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+        gz.write_all(&text).unwrap();
+        let gz = gz.finish().unwrap();
+        let zst = zstd::stream::encode_all(&text[..], 0).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        for (name, bytes) in &[("a.txt.gz", &gz), ("b.zst", &zst), ("c.txt", &text)] {
+            let path = dir.path().join(name);
+            std::fs::write(&path, bytes).unwrap();
+            let mut decoded = vec![];
+            CodecSelection::FromExtension
+                .open(&path)
+                .unwrap()
+                .read_to_end(&mut decoded)
+                .unwrap();
+            assert_eq!(decoded, text);
+        }
+        assert_eq!(
+            CodecSelection::Codec(Codec::Xz).codec_for(Path::new("a.txt")),
+            Some(Codec::Xz)
+        );
+        assert_eq!(
+            CodecSelection::Uncompressed.codec_for(Path::new("a.gz")),
+            None
+        );
+    }
+
+    #[test]
+    fn compress_buffers() {
+        let data = b"some data, some data, some data".to_vec();
+        for codec in &[Codec::Gzip, Codec::Zstd, Codec::Bzip2, Codec::Xz] {
+            let compressed = codec.compress(&data).unwrap();
+            assert_eq!(codec.decompress(&compressed).unwrap(), data);
+        }
+    }
+}
@@ -0,0 +1,499 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::context::Context;
+use crate::dataframe::{DataFrame, DataType, Field, Row, Schema, Value};
+use crate::error::{Error, Result, StdResult};
+use crate::fs;
+use crate::io::{write_partitions, CodecSelection, FileListing, FileOutputCommitter};
+use crate::io::{FileRange, LineReader, SaveMode, TextFileRdd};
+use crate::rdd::Rdd;
+use crate::scheduler::fail_task;
+use crate::serializable_traits::Data;
+use crate::{Fn, SerArc};
+use csv::{ReaderBuilder, StringRecord, WriterBuilder};
+use serde::de::DeserializeOwned;
+use serde_derive::{Deserialize, Serialize};
+
+const CSV_EXTENSION: &str = "csv";
+
+/// Options of the CSV reader and writer.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CsvOptions {
+    header: bool,
+    delimiter: u8,
+    quote: u8,
+    escape: Option<u8>,
+    null_values: Vec<String>,
+    permissive: bool,
+    sample_size: usize,
+    partitions_per_host: usize,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            header: true,
+            delimiter: b',',
+            quote: b'"',
+            escape: None,
+            null_values: vec![String::new()],
+            permissive: false,
+            sample_size: 1000,
+            partitions_per_host: num_cpus::get(),
+        }
+    }
+}
+
+impl CsvOptions {
+    pub fn new() -> Self {
+        CsvOptions::default()
+    }
+
+    /// Whether the first line of the files holds the names of the columns, true by default.
+    /// Otherwise columns are named `_c0`, `_c1`...
+    pub fn header(mut self, header: bool) -> Self {
+        self.header = header;
+        self
+    }
+
+    pub fn delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    pub fn quote(mut self, quote: u8) -> Self {
+        self.quote = quote;
+        self
+    }
+
+    /// Character escaping quotes within quoted fields. By default quotes are escaped by
+    /// doubling them.
+    pub fn escape(mut self, escape: Option<u8>) -> Self {
+        self.escape = escape;
+        self
+    }
+
+    /// Add a token read as null, besides empty fields.
+    pub fn null_value<T: Into<String>>(mut self, token: T) -> Self {
+        self.null_values.push(token.into());
+        self
+    }
+
+    /// Skip malformed rows instead of failing, they can then be read with
+    /// `CsvFile::bad_rows`.
+    pub fn permissive(mut self, permissive: bool) -> Self {
+        self.permissive = permissive;
+        self
+    }
+
+    /// Number of rows read to infer the types of the columns.
+    pub fn sample_size(mut self, num_rows: usize) -> Self {
+        self.sample_size = num_rows.max(1);
+        self
+    }
+
+    /// Number of partitions reading the files of each host.
+    pub fn num_partitions_per_host(mut self, num: usize) -> Self {
+        self.partitions_per_host = num.max(1);
+        self
+    }
+
+    fn reader_builder(&self) -> ReaderBuilder {
+        let mut builder = ReaderBuilder::new();
+        builder
+            .has_headers(false)
+            .flexible(true)
+            .delimiter(self.delimiter)
+            .quote(self.quote)
+            .escape(self.escape)
+            .double_quote(self.escape.is_none());
+        builder
+    }
+
+    fn writer_builder(&self) -> WriterBuilder {
+        let mut builder = WriterBuilder::new();
+        builder
+            .has_headers(self.header)
+            .delimiter(self.delimiter)
+            .quote(self.quote)
+            .escape(self.escape.unwrap_or(b'"'))
+            .double_quote(self.escape.is_none());
+        builder
+    }
+
+    fn is_null(&self, field: &str) -> bool {
+        self.null_values.iter().any(|token| token == field)
+    }
+}
+
+/// A row of a CSV file which could not be read.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BadRow {
+    pub line: String,
+    pub error: String,
+}
+
+impl From<BadRow> for Error {
+    fn from(row: BadRow) -> Self {
+        Error::MalformedRecord {
+            line: row.line,
+            message: row.error,
+        }
+    }
+}
+
+fn parse_record(builder: &ReaderBuilder, line: &str) -> StdResult<StringRecord, String> {
+    let mut record = StringRecord::new();
+    builder
+        .from_reader(line.as_bytes())
+        .read_record(&mut record)
+        .map_err(|err| err.to_string())?;
+    Ok(record)
+}
+
+fn infer_type(field: &str) -> DataType {
+    if field.parse::<i64>().is_ok() {
+        DataType::Int64
+    } else if field.parse::<f64>().is_ok() {
+        DataType::Float64
+    } else if field.eq_ignore_ascii_case("true") || field.eq_ignore_ascii_case("false") {
+        DataType::Boolean
+    } else {
+        DataType::Utf8
+    }
+}
+
+fn parse_value(field: &str, data_type: DataType) -> Option<Value> {
+    match data_type {
+        DataType::Null => Some(Value::Null),
+        DataType::Boolean if field.eq_ignore_ascii_case("true") => Some(Value::Boolean(true)),
+        DataType::Boolean if field.eq_ignore_ascii_case("false") => Some(Value::Boolean(false)),
+        DataType::Boolean => None,
+        DataType::Int64 => field.parse().ok().map(Value::Int),
+        DataType::Float64 => field.parse().ok().map(Value::Float),
+        DataType::Utf8 => Some(Value::Utf8(field.to_owned())),
+    }
+}
+
+fn parse_row(
+    builder: &ReaderBuilder,
+    options: &CsvOptions,
+    schema: &Schema,
+    line: &str,
+) -> StdResult<Row, String> {
+    let record = parse_record(builder, line)?;
+    if record.len() != schema.len() {
+        return Err(format!(
+            "expected {} fields, found {}",
+            schema.len(),
+            record.len()
+        ));
+    }
+    record
+        .iter()
+        .zip(schema.fields())
+        .map(|(value, field)| {
+            if options.is_null(value) {
+                return Ok(Value::Null);
+            }
+            parse_value(value, field.data_type()).ok_or_else(|| {
+                format!(
+                    "value {:?} of column {} is not a {}",
+                    value,
+                    field.name(),
+                    field.data_type()
+                )
+            })
+        })
+        .collect()
+}
+
+/// Deserialize a row into a struct, matching the fields of the struct to the columns by name.
+fn deserialize_row<T: DeserializeOwned>(
+    builder: &ReaderBuilder,
+    options: &CsvOptions,
+    header: &StringRecord,
+    line: &str,
+) -> StdResult<T, String> {
+    let record = parse_record(builder, line)?;
+    // Null tokens are read as empty fields, which deserialize to `None`.
+    let record = record
+        .iter()
+        .map(|value| if options.is_null(value) { "" } else { value })
+        .collect::<StringRecord>();
+    record
+        .deserialize(Some(header))
+        .map_err(|err| err.to_string())
+}
+
+/// Rows read from CSV files, with the types of the columns inferred from a sample.
+///
+/// The files are read as lines, so fields can't hold line breaks even when quoted. When the
+/// files have a header, every line equal to the header of the first file is skipped.
+#[derive(Clone)]
+pub struct CsvFile {
+    context: Arc<Context>,
+    listing: FileListing,
+    options: CsvOptions,
+    header: Option<String>,
+    schema: Schema,
+}
+
+impl CsvFile {
+    /// Read the header and a sample of the rows from the driver to build the schema.
+    pub(crate) fn try_new(
+        context: Arc<Context>,
+        path: PathBuf,
+        options: CsvOptions,
+    ) -> Result<Self> {
+        let listing = FileListing::new(vec![path]);
+        let builder = options.reader_builder();
+        let mut header = None;
+        let mut names = vec![];
+        let mut types: Vec<Option<DataType>> = vec![];
+        let mut num_rows = 0;
+        'files: for file in listing.list()? {
+            let range = FileRange {
+                path: file.path,
+                start: 0,
+                end: u64::MAX,
+                partition_values: vec![],
+            };
+            for line in LineReader::open(&range, CodecSelection::FromExtension)? {
+                if line.is_empty() {
+                    continue;
+                }
+                if options.header && header.is_none() {
+                    let record = parse_record(&builder, &line).map_err(|message| {
+                        Error::InvalidCsvHeader {
+                            header: line.clone(),
+                            message,
+                        }
+                    })?;
+                    names = record.iter().map(ToOwned::to_owned).collect();
+                    header = Some(line);
+                    continue;
+                }
+                if header.as_ref() == Some(&line) {
+                    continue;
+                }
+                let record = match parse_record(&builder, &line) {
+                    Ok(record) => record,
+                    Err(_) => continue,
+                };
+                if types.len() < record.len() {
+                    types.resize(record.len(), None);
+                }
+                for (value, data_type) in record.iter().zip(types.iter_mut()) {
+                    if options.is_null(value) {
+                        continue;
+                    }
+                    let value_type = infer_type(value);
+                    *data_type = Some(match data_type {
+                        Some(current) => current.common_type(value_type).unwrap_or(DataType::Utf8),
+                        None => value_type,
+                    });
+                }
+                num_rows += 1;
+                if num_rows == options.sample_size {
+                    break 'files;
+                }
+            }
+        }
+        if !options.header {
+            names = (0..types.len()).map(|i| format!("_c{}", i)).collect();
+        }
+        let fields = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let data_type = types.get(i).cloned().flatten().unwrap_or(DataType::Utf8);
+                Field::new(name, data_type, true)
+            })
+            .collect();
+        Ok(CsvFile {
+            context,
+            listing,
+            options,
+            header,
+            schema: Schema::new(fields),
+        })
+    }
+
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// The data lines of the files, without the headers and empty lines.
+    fn lines(&self) -> SerArc<dyn Rdd<Item = String>> {
+        let lines = TextFileRdd::<String>::new(
+            self.context.clone(),
+            self.listing.clone(),
+            self.options.partitions_per_host,
+        );
+        let header = self.header.clone();
+        lines.map_partitions(Fn!(move |lines: Box<dyn Iterator<Item = String>>| {
+            let header = header.clone();
+            Box::new(lines.filter(move |line| !line.is_empty() && header.as_ref() != Some(line)))
+                as Box<dyn Iterator<Item = _>>
+        }))
+    }
+
+    /// The rows of the files, with values of the types of the schema and nulls for the null
+    /// tokens.
+    ///
+    /// A row with a wrong number of fields or with a value which doesn't match the type of
+    /// its column is an error, which the actions of [ResultRdd](../rdd/trait.ResultRdd.html)
+    /// return. With permissive options such rows are skipped instead.
+    pub fn rows(&self) -> SerArc<dyn Rdd<Item = StdResult<Row, BadRow>>> {
+        let options = self.options.clone();
+        let schema = self.schema.clone();
+        self.lines()
+            .map_partitions(Fn!(move |lines: Box<dyn Iterator<Item = String>>| {
+                let builder = options.reader_builder();
+                let options = options.clone();
+                let schema = schema.clone();
+                Box::new(lines.filter_map(move |line| {
+                    match parse_row(&builder, &options, &schema, &line) {
+                        Ok(row) => Some(Ok(row)),
+                        Err(_) if options.permissive => None,
+                        Err(error) => Some(Err(BadRow { line, error })),
+                    }
+                })) as Box<dyn Iterator<Item = _>>
+            }))
+    }
+
+    /// The rows of the files deserialized into structs, whose fields are matched with the
+    /// columns by name. Null tokens and empty fields deserialize to `None`.
+    ///
+    /// A row which can't be deserialized is an error, unless the options are permissive.
+    pub fn deserialize<T: Data + DeserializeOwned>(
+        &self,
+    ) -> SerArc<dyn Rdd<Item = StdResult<T, BadRow>>> {
+        let options = self.options.clone();
+        let names = self
+            .schema
+            .fields()
+            .iter()
+            .map(|field| field.name().to_owned())
+            .collect::<Vec<_>>();
+        self.lines()
+            .map_partitions(Fn!(move |lines: Box<dyn Iterator<Item = String>>| {
+                let builder = options.reader_builder();
+                let options = options.clone();
+                let header = StringRecord::from(names.clone());
+                Box::new(lines.filter_map(move |line| {
+                    match deserialize_row(&builder, &options, &header, &line) {
+                        Ok(item) => Some(Ok(item)),
+                        Err(_) if options.permissive => None,
+                        Err(error) => Some(Err(BadRow { line, error })),
+                    }
+                })) as Box<dyn Iterator<Item = _>>
+            }))
+    }
+
+    /// The rows which don't match the schema, along with the reason. With permissive options
+    /// these are the rows skipped by `rows`.
+    pub fn bad_rows(&self) -> SerArc<dyn Rdd<Item = BadRow>> {
+        let options = self.options.clone();
+        let schema = self.schema.clone();
+        self.lines()
+            .map_partitions(Fn!(move |lines: Box<dyn Iterator<Item = String>>| {
+                let builder = options.reader_builder();
+                let options = options.clone();
+                let schema = schema.clone();
+                Box::new(lines.filter_map(move |line| {
+                    match parse_row(&builder, &options, &schema, &line) {
+                        Ok(_) => None,
+                        Err(error) => Some(BadRow { line, error }),
+                    }
+                })) as Box<dyn Iterator<Item = _>>
+            }))
+    }
+
+    /// A dataframe of the rows. Unless the options are permissive, the first malformed row
+    /// read by a task fails it, and the action of the dataframe returns the error.
+    pub fn to_dataframe(&self) -> Result<DataFrame> {
+        let rows = self.rows().map_partitions(Fn!(|rows: Box<
+            dyn Iterator<Item = StdResult<Row, BadRow>>,
+        >| {
+            Box::new(rows.scan((), |_, row| match row {
+                Ok(row) => Some(row),
+                Err(row) => {
+                    fail_task(row.into());
+                    None
+                }
+            })) as Box<dyn Iterator<Item = Row>>
+        }));
+        Ok(self.context.create_dataframe(rows, self.schema.clone()))
+    }
+}
+
+fn write_csv_file<T: Data>(
+    path: PathBuf,
+    items: Box<dyn Iterator<Item = T>>,
+    options: &CsvOptions,
+) -> Result<()> {
+    let file = fs::get_file_system(&path)?
+        .create(&path, true)
+        .map_err(Error::OutputWrite)?;
+    let mut writer = options.writer_builder().from_writer(file);
+    for item in items {
+        writer.serialize(item)?;
+    }
+    writer.flush().map_err(Error::OutputWrite)
+}
+
+/// Write the items of an RDD to a directory, as one CSV file per partition named
+/// `part-NNNNN.csv`, with a header holding the names of the fields when the options have one.
+pub(crate) fn save_as_csv<T: Data>(
+    rdd: Arc<dyn Rdd<Item = T>>,
+    path: PathBuf,
+    options: CsvOptions,
+    mode: SaveMode,
+) -> Result<()> {
+    let committer = FileOutputCommitter::new(path, mode);
+    let write = Fn!(
+        move |file: PathBuf, items: Box<dyn Iterator<Item = T>>| write_csv_file(
+            file, items, &options
+        )
+    );
+    write_partitions(rdd, committer, Some(CSV_EXTENSION), write)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_fields() {
+        let options = CsvOptions::new()
+            .delimiter(b';')
+            .escape(Some(b'\\'))
+            .null_value("NA");
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int64, true),
+            Field::new("name", DataType::Utf8, true),
+            Field::new("score", DataType::Float64, true),
+        ]);
+        let builder = options.reader_builder();
+        let row = parse_row(&builder, &options, &schema, r#"1;"a \"b\"; c";NA"#).unwrap();
+        assert_eq!(
+            row,
+            vec![
+                Value::Int(1),
+                Value::Utf8("a \"b\"; c".to_owned()),
+                Value::Null
+            ]
+        );
+        assert!(parse_row(&builder, &options, &schema, "x;a;1.5").is_err());
+        assert!(parse_row(&builder, &options, &schema, "1;a").is_err());
+
+        assert_eq!(infer_type("-12"), DataType::Int64);
+        assert_eq!(infer_type("1e3"), DataType::Float64);
+        assert_eq!(infer_type("TRUE"), DataType::Boolean);
+        assert_eq!(infer_type("12a"), DataType::Utf8);
+    }
+}
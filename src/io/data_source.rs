@@ -0,0 +1,182 @@
+use std::net::Ipv4Addr;
+use std::sync::Arc;
+
+use crate::context::Context;
+use crate::dependency::Dependency;
+use crate::error::Result;
+use crate::io::ReaderConfiguration;
+use crate::rdd::{MapperRdd, Rdd, RddBase};
+use crate::serializable_traits::{AnyData, Data, SerFunc};
+use crate::split::Split;
+use crate::SerArc;
+use log::debug;
+use serde_derive::{Deserialize, Serialize};
+
+/// A unit of work of a data source, read by one task.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SourceSplit<S> {
+    /// What the reader of the source needs to read the split, such as a file range or a key
+    /// range of a table.
+    pub split: S,
+    /// Estimated size of the split in bytes, if known.
+    pub size: Option<u64>,
+    /// Hosts where the split is cheaper to read, such as the hosts holding its data. The
+    /// scheduler runs the task of the split on one of them when it can.
+    pub preferred_locations: Vec<Ipv4Addr>,
+}
+
+impl<S> SourceSplit<S> {
+    pub fn new(split: S) -> Self {
+        SourceSplit {
+            split,
+            size: None,
+            preferred_locations: vec![],
+        }
+    }
+
+    pub fn size(mut self, size: u64) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    pub fn preferred_location(mut self, host: Ipv4Addr) -> Self {
+        self.preferred_locations.push(host);
+        self
+    }
+}
+
+/// A source of items read in parallel, one partition per split.
+///
+/// The splits are listed once from the driver, then the source is shipped with every task
+/// which reads its split with `read`. Any data source can be read with `Context::read_source`,
+/// along with a function decoding its items.
+pub trait DataSource: Data {
+    /// Description of a split, shipped to the task reading it.
+    type Split: Data;
+    type Item: Data;
+
+    fn splits(&self) -> Vec<SourceSplit<Self::Split>>;
+
+    fn read(&self, split: Self::Split) -> Result<Box<dyn Iterator<Item = Self::Item>>>;
+}
+
+impl<S: DataSource> ReaderConfiguration<S::Item> for S {
+    fn make_reader<F, O>(self, context: Arc<Context>, decoder: F) -> SerArc<dyn Rdd<Item = O>>
+    where
+        O: Data,
+        F: SerFunc(S::Item) -> O,
+    {
+        let source = Arc::new(DataSourceRdd::new(context, self));
+        let decoder = MapperRdd::new(source, decoder);
+        decoder.register_op_name("data_source");
+        SerArc::new(decoder)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+struct DataSourceSplit<S: Data> {
+    idx: usize,
+    split: SourceSplit<S>,
+}
+
+impl<S: Data> Split for DataSourceSplit<S> {
+    fn get_index(&self) -> usize {
+        self.idx
+    }
+}
+
+/// Items of a data source, with one partition per split of the source.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct DataSourceRdd<S: DataSource> {
+    id: usize,
+    source: S,
+    splits: Vec<SourceSplit<S::Split>>,
+    #[serde(skip_serializing, skip_deserializing)]
+    context: Arc<Context>,
+}
+
+impl<S: DataSource> DataSourceRdd<S> {
+    pub fn new(context: Arc<Context>, source: S) -> Self {
+        let splits = source.splits();
+        debug!(
+            "data source with {} splits, {} bytes known",
+            splits.len(),
+            splits.iter().filter_map(|split| split.size).sum::<u64>()
+        );
+        DataSourceRdd {
+            id: context.new_rdd_id(),
+            source,
+            splits,
+            context,
+        }
+    }
+}
+
+impl<S: DataSource> RddBase for DataSourceRdd<S> {
+    fn get_rdd_id(&self) -> usize {
+        self.id
+    }
+
+    fn get_context(&self) -> Arc<Context> {
+        self.context.clone()
+    }
+
+    fn get_op_name(&self) -> String {
+        "data_source".to_owned()
+    }
+
+    fn get_dependencies(&self) -> Vec<Dependency> {
+        vec![]
+    }
+
+    fn preferred_locations(&self, split: Box<dyn Split>) -> Vec<Ipv4Addr> {
+        let split = split.downcast_ref::<DataSourceSplit<S::Split>>().unwrap();
+        split.split.preferred_locations.clone()
+    }
+
+    fn splits(&self) -> Vec<Box<dyn Split>> {
+        self.splits
+            .iter()
+            .enumerate()
+            .map(|(idx, split)| {
+                Box::new(DataSourceSplit {
+                    idx,
+                    split: split.clone(),
+                }) as Box<dyn Split>
+            })
+            .collect()
+    }
+
+    fn number_of_splits(&self) -> usize {
+        self.splits.len()
+    }
+
+    fn iterator_any(
+        &self,
+        split: Box<dyn Split>,
+    ) -> Result<Box<dyn Iterator<Item = Box<dyn AnyData>>>> {
+        Ok(Box::new(
+            self.iterator(split)?
+                .map(|x| Box::new(x) as Box<dyn AnyData>),
+        ))
+    }
+}
+
+impl<S: DataSource> Rdd for DataSourceRdd<S> {
+    type Item = S::Item;
+
+    fn get_rdd(&self) -> Arc<dyn Rdd<Item = Self::Item>> {
+        Arc::new(self.clone())
+    }
+
+    fn get_rdd_base(&self) -> Arc<dyn RddBase> {
+        Arc::new(self.clone()) as Arc<dyn RddBase>
+    }
+
+    fn compute(&self, split: Box<dyn Split>) -> Result<Box<dyn Iterator<Item = Self::Item>>> {
+        let split = split.downcast_ref::<DataSourceSplit<S::Split>>().unwrap();
+        self.source.read(split.split.split.clone())
+    }
+}
@@ -0,0 +1,257 @@
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::{Component, Path, PathBuf};
+
+use crate::error::{Error, Result};
+use crate::fs::{self, FileSystem};
+use glob::Pattern;
+use serde_derive::{Deserialize, Serialize};
+
+/// Values of the Hive-style `key=value` directories holding a file, from the outermost one.
+pub type PartitionValues = Vec<(String, String)>;
+
+/// A file found by a listing.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InputFile {
+    pub path: PathBuf,
+    pub size: u64,
+    pub partition_values: PartitionValues,
+}
+
+/// Where to look for input files.
+///
+/// Each root is a file, a directory or a glob pattern such as `logs/2026-*/part-*`, on the
+/// file system of its scheme, such as `mem:///logs`. Files and directories whose name starts
+/// with `.` or `_` are skipped unless they are named explicitly, so that markers such as
+/// `_SUCCESS` are not read as data.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct FileListing {
+    pub roots: Vec<PathBuf>,
+    pub recursive: bool,
+    pub filter_ext: Option<OsString>,
+    /// Whether a root which doesn't exist is ignored rather than an error.
+    pub allow_missing: bool,
+}
+
+impl FileListing {
+    pub(crate) fn new(roots: Vec<PathBuf>) -> Self {
+        FileListing {
+            roots,
+            recursive: false,
+            filter_ext: None,
+            allow_missing: false,
+        }
+    }
+
+    /// Returns the files of all the roots, sorted by path and without duplicates.
+    pub(crate) fn list(&self) -> Result<Vec<InputFile>> {
+        let mut files = BTreeMap::new();
+        for root in &self.roots {
+            let file_system = fs::get_file_system(root)?;
+            let (base, patterns) = split_glob(root)?;
+            if !file_system.exists(&base) && (self.allow_missing || !patterns.is_empty()) {
+                continue;
+            }
+            self.expand(&*file_system, &base, &base, &patterns, &mut files)?;
+        }
+        Ok(files.into_iter().map(|(_, file)| file).collect())
+    }
+
+    fn expand(
+        &self,
+        file_system: &dyn FileSystem,
+        base: &Path,
+        path: &Path,
+        patterns: &[Pattern],
+        files: &mut BTreeMap<PathBuf, InputFile>,
+    ) -> Result<()> {
+        let status = file_system
+            .get_file_status(path)
+            .map_err(Error::InputRead)?;
+        match patterns.split_first() {
+            None if !status.is_dir => {
+                // A file given explicitly is read whatever its extension.
+                let has_extension = self
+                    .filter_ext
+                    .as_ref()
+                    .map_or(true, |ext| path.extension() == Some(ext.as_ref()));
+                if path == base || has_extension {
+                    let file = InputFile {
+                        path: path.to_owned(),
+                        size: status.len,
+                        partition_values: partition_values(base, path),
+                    };
+                    files.insert(file.path.clone(), file);
+                }
+            }
+            None => {
+                for (_, entry, is_dir) in read_dir_sorted(file_system, path)? {
+                    if !is_dir || self.recursive {
+                        self.expand(file_system, base, &entry, &[], files)?;
+                    }
+                }
+            }
+            Some((pattern, rest)) if status.is_dir => {
+                for (name, entry, _) in read_dir_sorted(file_system, path)? {
+                    if pattern.matches(&name) {
+                        self.expand(file_system, base, &entry, rest, files)?;
+                    }
+                }
+            }
+            Some(_) => {}
+        }
+        Ok(())
+    }
+}
+
+/// Split a path into its leading directory without glob characters and the patterns
+/// matching each of the following components. The scheme of the path is kept in the
+/// directory.
+fn split_glob(path: &Path) -> Result<(PathBuf, Vec<Pattern>)> {
+    let (scheme, path) = fs::split_scheme(path);
+    let mut base = PathBuf::new();
+    let mut patterns = vec![];
+    for component in path.components() {
+        let text = component.as_os_str().to_string_lossy();
+        let is_glob = text.contains(|c| c == '*' || c == '?' || c == '[');
+        if patterns.is_empty() && !is_glob {
+            base.push(component);
+        } else {
+            patterns.push(Pattern::new(&text)?);
+        }
+    }
+    if base.as_os_str().is_empty() {
+        base.push(Component::CurDir);
+    }
+    if let Some(scheme) = scheme {
+        base = format!("{}://{}", scheme, base.display()).into();
+    }
+    Ok((base, patterns))
+}
+
+/// Entries of a directory sorted by name, as name, path and whether it is a directory.
+/// Hidden entries are skipped.
+fn read_dir_sorted(
+    file_system: &dyn FileSystem,
+    path: &Path,
+) -> Result<Vec<(String, PathBuf, bool)>> {
+    let mut entries = vec![];
+    for status in file_system.list_status(path).map_err(Error::InputRead)? {
+        let name = match status.path.file_name() {
+            Some(name) => name.to_string_lossy().into_owned(),
+            None => continue,
+        };
+        if name.starts_with('.') || name.starts_with('_') {
+            continue;
+        }
+        entries.push((name, status.path, status.is_dir));
+    }
+    entries.sort();
+    Ok(entries)
+}
+
+/// Values of the `key=value` directories between the base directory and the file.
+fn partition_values(base: &Path, file: &Path) -> PartitionValues {
+    let relative = match file.parent().and_then(|dir| dir.strip_prefix(base).ok()) {
+        Some(relative) => relative,
+        None => return vec![],
+    };
+    relative
+        .components()
+        .filter_map(|component| {
+            let segment = component.as_os_str().to_str()?;
+            let pos = segment.find('=').filter(|&pos| pos > 0)?;
+            Some((segment[..pos].to_owned(), segment[pos + 1..].to_owned()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn touch(root: &Path, path: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "data").unwrap();
+    }
+
+    fn names(root: &Path, files: Vec<InputFile>) -> Vec<String> {
+        files
+            .into_iter()
+            .map(|file| {
+                let path = file.path.strip_prefix(root).unwrap();
+                path.to_string_lossy().into_owned()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn list_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        touch(root, "logs/2026-01/part-0");
+        touch(root, "logs/2026-01/part-1.txt");
+        touch(root, "logs/2026-01/_SUCCESS");
+        touch(root, "logs/2026-02/part-0");
+        touch(root, "logs/2025-12/part-0");
+        touch(root, "logs/top");
+
+        let listing = FileListing::new(vec![root.join("logs/2026-*/part-*")]);
+        assert_eq!(
+            names(root, listing.list().unwrap()),
+            vec![
+                "logs/2026-01/part-0",
+                "logs/2026-01/part-1.txt",
+                "logs/2026-02/part-0"
+            ]
+        );
+
+        let mut listing = FileListing::new(vec![root.join("logs")]);
+        assert_eq!(names(root, listing.list().unwrap()), vec!["logs/top"]);
+        listing.recursive = true;
+        assert_eq!(listing.list().unwrap().len(), 5);
+        listing.filter_ext = Some("txt".into());
+        assert_eq!(
+            names(root, listing.list().unwrap()),
+            vec!["logs/2026-01/part-1.txt"]
+        );
+
+        let listing = FileListing::new(vec![
+            root.join("logs/2025-12"),
+            root.join("logs/2026-02/part-0"),
+            root.join("logs/2025-*"),
+        ]);
+        assert_eq!(listing.list().unwrap().len(), 2);
+        assert!(FileListing::new(vec![root.join("missing")]).list().is_err());
+        assert!(FileListing::new(vec![root.join("missing/*")])
+            .list()
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn discover_partitions() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        touch(root, "table/date=2026-01-01/country=fr/part-0");
+        touch(root, "table/date=2026-01-02/country=de/part-0");
+        let mut listing = FileListing::new(vec![root.join("table")]);
+        listing.recursive = true;
+        let files = listing.list().unwrap();
+        assert_eq!(
+            files[0].partition_values,
+            vec![
+                ("date".to_owned(), "2026-01-01".to_owned()),
+                ("country".to_owned(), "fr".to_owned())
+            ]
+        );
+        let listing = FileListing::new(vec![root.join("table/date=2026-01-02/*/part-0")]);
+        let files = listing.list().unwrap();
+        assert_eq!(
+            files[0].partition_values,
+            vec![("country".to_owned(), "de".to_owned())]
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use std::net::{Ipv4Addr, SocketAddrV4};
+
+use crate::split::Split;
+use serde_derive::{Deserialize, Serialize};
+
+/// Split of an RDD reading the files of the local file system of every host. The files of
+/// each host are divided among the `num_parts` partitions of the host.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct HostSplit {
+    idx: usize,
+    pub host: Ipv4Addr,
+    pub part: usize,
+    pub num_parts: usize,
+}
+
+impl Split for HostSplit {
+    fn get_index(&self) -> usize {
+        self.idx
+    }
+}
+
+impl HostSplit {
+    /// The splits of `partitions_per_host` partitions on every host.
+    pub(crate) fn for_hosts(
+        hosts: &[SocketAddrV4],
+        partitions_per_host: usize,
+    ) -> Vec<Box<dyn Split>> {
+        hosts
+            .iter()
+            .flat_map(|host| (0..partitions_per_host).map(move |part| (*host.ip(), part)))
+            .enumerate()
+            .map(|(idx, (host, part))| {
+                Box::new(HostSplit {
+                    idx,
+                    host,
+                    part,
+                    num_parts: partitions_per_host,
+                }) as Box<dyn Split>
+            })
+            .collect()
+    }
+
+    /// The units of work of the split when they are dealt among the partitions of the host,
+    /// one after the other.
+    pub(crate) fn deal<T>(&self, units: Vec<T>) -> Vec<T> {
+        units
+            .into_iter()
+            .enumerate()
+            .filter(|(k, _)| k % self.num_parts == self.part)
+            .map(|(_, unit)| unit)
+            .collect()
+    }
+
+    /// The contiguous run of units of work of the split, so that reading the partitions of
+    /// the host in order reads the units in order.
+    pub(crate) fn run<T>(&self, units: Vec<T>) -> Vec<T> {
+        let start = units.len() * self.part / self.num_parts;
+        let end = units.len() * (self.part + 1) / self.num_parts;
+        units.into_iter().skip(start).take(end - start).collect()
+    }
+}
+
+/// Implements the functions of `RddBase` shared by the RDDs reading files on every host, which
+/// have `id`, `context`, `hosts` and `partitions_per_host` fields and are split by `HostSplit`.
+macro_rules! impl_host_file_rdd_base_funcs {
+    ($op_name:expr) => {
+        fn get_rdd_id(&self) -> usize {
+            self.id
+        }
+
+        fn get_context(&self) -> Arc<Context> {
+            self.context.clone()
+        }
+
+        fn get_op_name(&self) -> String {
+            $op_name.to_owned()
+        }
+
+        fn get_dependencies(&self) -> Vec<Dependency> {
+            vec![]
+        }
+
+        fn is_pinned(&self) -> bool {
+            true
+        }
+
+        fn preferred_locations(&self, split: Box<dyn Split>) -> Vec<Ipv4Addr> {
+            vec![split.downcast_ref::<HostSplit>().unwrap().host]
+        }
+
+        fn splits(&self) -> Vec<Box<dyn Split>> {
+            HostSplit::for_hosts(&self.hosts, self.partitions_per_host)
+        }
+
+        fn iterator_any(
+            &self,
+            split: Box<dyn Split>,
+        ) -> Result<Box<dyn Iterator<Item = Box<dyn AnyData>>>> {
+            Ok(Box::new(
+                self.iterator(split)?
+                    .map(|x| Box::new(x) as Box<dyn AnyData>),
+            ))
+        }
+    };
+}
@@ -0,0 +1,185 @@
+use std::io::{BufWriter, Write};
+use std::marker::PhantomData;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::context::Context;
+use crate::error::{Error, Result, StdResult};
+use crate::fs;
+use crate::io::{write_partitions, FileListing, FileOutputCommitter, SaveMode, TextFileRdd};
+use crate::rdd::Rdd;
+use crate::serializable_traits::Data;
+use crate::{Fn, SerArc};
+use serde_derive::{Deserialize, Serialize};
+
+const JSON_LINES_EXTENSION: &str = "jsonl";
+
+/// What to do with the lines of a JSON Lines file which can't be parsed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CorruptRecords {
+    /// Read the corrupt lines as errors, which the actions of
+    /// [ResultRdd](../rdd/trait.ResultRdd.html) return.
+    Fail,
+    /// Skip the corrupt lines, which can still be read with `JsonLinesFile::corrupt_records`.
+    Drop,
+}
+
+impl Default for CorruptRecords {
+    fn default() -> Self {
+        CorruptRecords::Fail
+    }
+}
+
+/// Options of the JSON Lines reader.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JsonLinesOptions {
+    corrupt_records: CorruptRecords,
+    partitions_per_host: usize,
+}
+
+impl Default for JsonLinesOptions {
+    fn default() -> Self {
+        JsonLinesOptions {
+            corrupt_records: CorruptRecords::Fail,
+            partitions_per_host: num_cpus::get(),
+        }
+    }
+}
+
+impl JsonLinesOptions {
+    pub fn new() -> Self {
+        JsonLinesOptions::default()
+    }
+
+    /// What to do with the lines which can't be parsed, fail by default.
+    pub fn corrupt_records(mut self, mode: CorruptRecords) -> Self {
+        self.corrupt_records = mode;
+        self
+    }
+
+    /// Number of partitions reading the files of each host.
+    pub fn num_partitions_per_host(mut self, num: usize) -> Self {
+        self.partitions_per_host = num.max(1);
+        self
+    }
+}
+
+/// A line of a JSON Lines file which could not be parsed.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CorruptRecord {
+    pub line: String,
+    pub error: String,
+}
+
+impl From<CorruptRecord> for Error {
+    fn from(record: CorruptRecord) -> Self {
+        Error::MalformedRecord {
+            line: record.line,
+            message: record.error,
+        }
+    }
+}
+
+/// Records read from JSON Lines files, one JSON value per line.
+///
+/// The files are split on line boundaries like text files, so a record can't span several
+/// lines. Blank lines are skipped.
+#[derive(Clone)]
+pub struct JsonLinesFile<T> {
+    context: Arc<Context>,
+    listing: FileListing,
+    options: JsonLinesOptions,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Data> JsonLinesFile<T> {
+    pub(crate) fn try_new(
+        context: Arc<Context>,
+        path: PathBuf,
+        options: JsonLinesOptions,
+    ) -> Result<Self> {
+        let listing = FileListing::new(vec![path]);
+        if listing.list()?.is_empty() {
+            return Err(Error::NoFilesFound);
+        }
+        Ok(JsonLinesFile {
+            context,
+            listing,
+            options,
+            _marker: PhantomData,
+        })
+    }
+
+    fn lines(&self) -> SerArc<dyn Rdd<Item = String>> {
+        TextFileRdd::<String>::new(
+            self.context.clone(),
+            self.listing.clone(),
+            self.options.partitions_per_host,
+        )
+        .map_partitions(Fn!(|lines: Box<dyn Iterator<Item = String>>| {
+            Box::new(lines.filter(|line| !line.trim().is_empty())) as Box<dyn Iterator<Item = _>>
+        }))
+    }
+
+    /// The records parsed from the lines of the files. The corrupt lines are errors, unless
+    /// the options drop them.
+    pub fn records(&self) -> SerArc<dyn Rdd<Item = StdResult<T, CorruptRecord>>> {
+        let mode = self.options.corrupt_records;
+        self.lines()
+            .map_partitions(Fn!(move |lines: Box<dyn Iterator<Item = String>>| {
+                Box::new(
+                    lines.filter_map(move |line| match serde_json::from_str::<T>(&line) {
+                        Ok(record) => Some(Ok(record)),
+                        Err(_) if mode == CorruptRecords::Drop => None,
+                        Err(error) => Some(Err(CorruptRecord {
+                            line,
+                            error: error.to_string(),
+                        })),
+                    }),
+                ) as Box<dyn Iterator<Item = _>>
+            }))
+    }
+
+    /// The lines which can't be parsed, along with the reason. With `CorruptRecords::Drop`
+    /// these are the lines skipped by `records`.
+    pub fn corrupt_records(&self) -> SerArc<dyn Rdd<Item = CorruptRecord>> {
+        self.lines()
+            .map_partitions(Fn!(|lines: Box<dyn Iterator<Item = String>>| {
+                Box::new(
+                    lines.filter_map(|line| match serde_json::from_str::<T>(&line) {
+                        Ok(_) => None,
+                        Err(error) => Some(CorruptRecord {
+                            line,
+                            error: error.to_string(),
+                        }),
+                    }),
+                ) as Box<dyn Iterator<Item = _>>
+            }))
+    }
+}
+
+fn write_json_lines<T: Data>(path: PathBuf, items: Box<dyn Iterator<Item = T>>) -> Result<()> {
+    let file = fs::get_file_system(&path)?
+        .create(&path, true)
+        .map_err(Error::OutputWrite)?;
+    let mut writer = BufWriter::new(file);
+    for item in items {
+        serde_json::to_writer(&mut writer, &item)?;
+        writer.write_all(b"\n").map_err(Error::OutputWrite)?;
+    }
+    writer.flush().map_err(Error::OutputWrite)
+}
+
+/// Write the items of an RDD to a directory, as one JSON Lines file per partition named
+/// `part-NNNNN.jsonl`.
+pub(crate) fn save_as_json_lines<T: Data>(
+    rdd: Arc<dyn Rdd<Item = T>>,
+    path: PathBuf,
+    mode: SaveMode,
+) -> Result<()> {
+    let committer = FileOutputCommitter::new(path, mode);
+    let write =
+        Fn!(|file: PathBuf, items: Box<dyn Iterator<Item = T>>| write_json_lines(file, items));
+    write_partitions(rdd, committer, Some(JSON_LINES_EXTENSION), write)?;
+    Ok(())
+}
@@ -1,8 +1,8 @@
-use std::fs;
+use std::collections::HashMap;
 use std::io::{BufReader, Read};
 use std::marker::PhantomData;
 use std::net::{Ipv4Addr, SocketAddrV4};
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use crate::context::Context;
@@ -10,6 +10,7 @@ use crate::dependency::Dependency;
 use crate::error::{Error, Result};
 use crate::io::*;
 use crate::rdd::{MapPartitionsRdd, MapperRdd, Rdd, RddBase};
+use crate::scheduler::fail_task;
 use crate::serializable_traits::{AnyData, Data, SerFunc};
 use crate::split::Split;
 use crate::Fn;
@@ -20,21 +21,40 @@ use serde_derive::{Deserialize, Serialize};
 pub struct LocalFsReaderConfig {
     filter_ext: Option<std::ffi::OsString>,
     expect_dir: bool,
-    dir_path: PathBuf,
+    paths: Vec<PathBuf>,
+    recursive: bool,
     executor_partitions: Option<u64>,
+    codec: CodecSelection,
 }
 
 impl LocalFsReaderConfig {
-    /// Read all the files from a directory or a path.
+    /// Read all the files from a directory or a path. The path can also be a glob pattern
+    /// such as `logs/2026-*/part-*`.
     pub fn new<T: Into<PathBuf>>(path: T) -> LocalFsReaderConfig {
         LocalFsReaderConfig {
             filter_ext: None,
             expect_dir: true,
-            dir_path: path.into(),
+            paths: vec![path.into()],
+            recursive: false,
             executor_partitions: None,
+            codec: CodecSelection::FromExtension,
         }
     }
 
+    /// Read the files of another directory, path or glob pattern as well.
+    pub fn add_path<T: Into<PathBuf>>(mut self, path: T) -> Self {
+        self.paths.push(path.into());
+        self
+    }
+
+    /// Also read the files of the subdirectories. Subdirectories named `key=value` are
+    /// Hive-style partitions: read the items along with their `PartitionValues` to get the
+    /// values of the partitions holding each file.
+    pub fn recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
     /// Only will read files with a given extension.
     pub fn filter_extension<T: Into<String>>(&mut self, extension: T) {
         self.filter_ext = Some(extension.into().into());
@@ -53,6 +73,27 @@ impl LocalFsReaderConfig {
         self.executor_partitions = Some(num);
         self
     }
+
+    /// How compressed files are detected and decompressed. By default the codec is chosen
+    /// from the file extension: `.gz`, `.zst`, `.bz2` or `.xz`.
+    pub fn codec(mut self, codec: CodecSelection) -> Self {
+        self.codec = codec;
+        self
+    }
+
+    fn listing(&self) -> FileListing {
+        FileListing {
+            roots: self.paths.clone(),
+            recursive: self.recursive,
+            filter_ext: self.filter_ext.clone(),
+            allow_missing: !self.expect_dir,
+        }
+    }
+
+    fn partitions_per_executor(&self) -> usize {
+        self.executor_partitions
+            .unwrap_or_else(|| num_cpus::get() as u64) as usize
+    }
 }
 
 impl ReaderConfiguration<Vec<u8>> for LocalFsReaderConfig {
@@ -99,16 +140,97 @@ impl ReaderConfiguration<PathBuf> for LocalFsReaderConfig {
     }
 }
 
+impl ReaderConfiguration<String> for LocalFsReaderConfig {
+    /// Read the lines of the files, splitting large uncompressed files into byte ranges.
+    fn make_reader<F, U>(self, context: Arc<Context>, decoder: F) -> SerArc<dyn Rdd<Item = U>>
+    where
+        F: SerFunc(String) -> U,
+        U: Data,
+    {
+        let lines =
+            TextFileRdd::<String>::new(context, self.listing(), self.partitions_per_executor())
+                .codec(self.codec);
+        let decoder = MapperRdd::new(Arc::new(lines), decoder).pin();
+        decoder.register_op_name("local_fs_reader<lines>");
+        SerArc::new(decoder)
+    }
+}
+
+impl ReaderConfiguration<(Vec<u8>, PartitionValues)> for LocalFsReaderConfig {
+    /// Read the content of the files along with the partition values of their directories.
+    fn make_reader<F, U>(self, context: Arc<Context>, decoder: F) -> SerArc<dyn Rdd<Item = U>>
+    where
+        F: SerFunc((Vec<u8>, PartitionValues)) -> U,
+        U: Data,
+    {
+        let reader = LocalFsReader::<BytesReader>::new(self, context);
+        let read_files = Fn!(
+            |_part: usize, readers: Box<dyn Iterator<Item = BytesReader>>| {
+                Box::new(
+                    readers.flat_map(|mut reader| std::iter::from_fn(move || reader.read_next())),
+                ) as Box<dyn Iterator<Item = _>>
+            }
+        );
+        let files_per_executor = Arc::new(
+            MapPartitionsRdd::new(Arc::new(reader) as Arc<dyn Rdd<Item = _>>, read_files).pin(),
+        );
+        let decoder = MapperRdd::new(files_per_executor, decoder).pin();
+        decoder.register_op_name("local_fs_reader<bytes>");
+        SerArc::new(decoder)
+    }
+}
+
+impl ReaderConfiguration<(PathBuf, PartitionValues)> for LocalFsReaderConfig {
+    /// Read the paths of the files along with the partition values of their directories.
+    fn make_reader<F, U>(self, context: Arc<Context>, decoder: F) -> SerArc<dyn Rdd<Item = U>>
+    where
+        F: SerFunc((PathBuf, PartitionValues)) -> U,
+        U: Data,
+    {
+        let reader = LocalFsReader::<FileReader>::new(self, context);
+        let read_files = Fn!(
+            |_part: usize, readers: Box<dyn Iterator<Item = FileReader>>| {
+                Box::new(
+                    readers.flat_map(|mut reader| std::iter::from_fn(move || reader.read_next())),
+                ) as Box<dyn Iterator<Item = _>>
+            }
+        );
+        let files_per_executor = Arc::new(
+            MapPartitionsRdd::new(Arc::new(reader) as Arc<dyn Rdd<Item = _>>, read_files).pin(),
+        );
+        let decoder = MapperRdd::new(files_per_executor, decoder).pin();
+        decoder.register_op_name("local_fs_reader<files>");
+        SerArc::new(decoder)
+    }
+}
+
+impl ReaderConfiguration<(String, PartitionValues)> for LocalFsReaderConfig {
+    /// Read the lines of the files along with the partition values of their directories.
+    fn make_reader<F, U>(self, context: Arc<Context>, decoder: F) -> SerArc<dyn Rdd<Item = U>>
+    where
+        F: SerFunc((String, PartitionValues)) -> U,
+        U: Data,
+    {
+        let lines = TextFileRdd::<(String, PartitionValues)>::new(
+            context,
+            self.listing(),
+            self.partitions_per_executor(),
+        )
+        .codec(self.codec);
+        let decoder = MapperRdd::new(Arc::new(lines), decoder).pin();
+        decoder.register_op_name("local_fs_reader<lines>");
+        SerArc::new(decoder)
+    }
+}
+
 /// Reads all files specified in a given directory from the local directory
 /// on all executors on every worker node.
 #[derive(Clone, Serialize, Deserialize)]
 pub struct LocalFsReader<T> {
     id: usize,
-    path: PathBuf,
-    is_single_file: bool,
-    filter_ext: Option<std::ffi::OsString>,
-    expect_dir: bool,
+    listing: FileListing,
     executor_partitions: Option<u64>,
+    codec: CodecSelection,
     #[serde(skip_serializing, skip_deserializing)]
     context: Arc<Context>,
     // explicitly copy the address map as the map under context is not
@@ -119,25 +241,11 @@ pub struct LocalFsReader<T> {
 
 impl<T: Data> LocalFsReader<T> {
     fn new(config: LocalFsReaderConfig, context: Arc<Context>) -> Self {
-        let LocalFsReaderConfig {
-            dir_path,
-            expect_dir,
-            filter_ext,
-            executor_partitions,
-        } = config;
-
-        let is_single_file = {
-            let path: &Path = dir_path.as_ref();
-            path.is_file()
-        };
-
         LocalFsReader {
             id: context.new_rdd_id(),
-            path: dir_path,
-            is_single_file,
-            filter_ext,
-            expect_dir,
-            executor_partitions,
+            listing: config.listing(),
+            executor_partitions: config.executor_partitions,
+            codec: config.codec,
             splits: context.address_map.clone(),
             context,
             _marker_reader_data: PhantomData,
@@ -146,15 +254,16 @@ impl<T: Data> LocalFsReader<T> {
 
     /// This function should be called once per host to come with the paralel workload.
     /// Is safe to recompute on failure though.
-    fn load_local_files(&self) -> Result<Vec<Vec<PathBuf>>> {
+    fn load_local_files(&self) -> Result<Vec<Vec<InputFile>>> {
         let mut total_size = 0_u64;
-        if self.is_single_file {
-            let files = vec![vec![self.path.clone()]];
-            return Ok(files);
+        let input_files = self.listing.list()?;
+        if input_files.len() == 1 {
+            return Ok(vec![input_files]);
         }
 
         let mut num_partitions = self.get_executor_partitions();
         let mut files: Vec<(u64, PathBuf)> = vec![];
+        let mut files_by_path = HashMap::new();
         // We compute std deviation incrementally to estimate a good breakpoint
         // of size per partition.
         let mut total_files = 0_u64;
@@ -162,33 +271,21 @@ impl<T: Data> LocalFsReader<T> {
         let mut ex = 0.0;
         let mut ex2 = 0.0;
 
-        for (i, entry) in fs::read_dir(&self.path)
-            .map_err(Error::InputRead)?
-            .enumerate()
-        {
-            let path = entry.map_err(Error::InputRead)?.path();
-            if path.is_file() {
-                let is_proper_file = {
-                    self.filter_ext.is_none()
-                        || path.extension() == self.filter_ext.as_ref().map(|s| s.as_ref())
-                };
-                if !is_proper_file {
-                    continue;
-                }
-                let size = fs::metadata(&path).map_err(Error::InputRead)?.len();
-                if i == 0 {
-                    // assign first file size as reference sample
-                    k = size;
-                }
-                // compute the necessary statistics
-                let remain = size as f32 - k as f32;
-                ex += remain;
-                ex2 += remain.powf(2.0);
-                total_size += size;
-                total_files += 1;
-
-                files.push((size, path));
+        for (i, file) in input_files.into_iter().enumerate() {
+            let size = file.size;
+            if i == 0 {
+                // assign first file size as reference sample
+                k = size;
             }
+            // compute the necessary statistics
+            let remain = size as f32 - k as f32;
+            ex += remain;
+            ex2 += remain.powf(2.0);
+            total_size += size;
+            total_files += 1;
+
+            files.push((size, file.path.clone()));
+            files_by_path.insert(file.path.clone(), file);
         }
 
         if total_files == 0 {
@@ -205,13 +302,22 @@ impl<T: Data> LocalFsReader<T> {
 
         let avg_partition_size = (total_size / num_partitions) as u64;
 
-        let partitions = self.assign_files_to_partitions(
-            num_partitions,
-            files,
-            file_size_mean,
-            avg_partition_size,
-            std_dev,
-        );
+        let partitions = self
+            .assign_files_to_partitions(
+                num_partitions,
+                files,
+                file_size_mean,
+                avg_partition_size,
+                std_dev,
+            )
+            .into_iter()
+            .map(|partition| {
+                partition
+                    .into_iter()
+                    .filter_map(|path| files_by_path.remove(&path))
+                    .collect()
+            })
+            .collect();
 
         Ok(partitions)
     }
@@ -350,6 +456,7 @@ impl RddBase for LocalFsReader<BytesReader> {
                 idx,
                 host: *host.ip(),
                 files: Vec::new(),
+                codec: self.codec,
             }) as Box<dyn Split>)
         }
         splits
@@ -402,11 +509,15 @@ impl Rdd for LocalFsReader<BytesReader> {
         let files_by_part = self.load_local_files()?;
         let idx = split.idx;
         let host = split.host;
-        Ok(Box::new(
-            files_by_part
-                .into_iter()
-                .map(move |files| BytesReader { files, host, idx }),
-        ) as Box<dyn Iterator<Item = Self::Item>>)
+        let codec = self.codec;
+        Ok(
+            Box::new(files_by_part.into_iter().map(move |files| BytesReader {
+                files,
+                host,
+                idx,
+                codec,
+            })) as Box<dyn Iterator<Item = Self::Item>>,
+        )
     }
 }
 
@@ -430,9 +541,10 @@ impl Rdd for LocalFsReader<FileReader> {
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct BytesReader {
-    files: Vec<PathBuf>,
+    files: Vec<InputFile>,
     idx: usize,
     host: Ipv4Addr,
+    codec: CodecSelection,
 }
 
 impl Split for BytesReader {
@@ -441,24 +553,39 @@ impl Split for BytesReader {
     }
 }
 
+impl BytesReader {
+    /// The content of the next file. A file which can't be read or decompressed fails the
+    /// task and ends the files.
+    fn read_next(&mut self) -> Option<(Vec<u8>, PartitionValues)> {
+        let input = self.files.pop()?;
+        let content = self.codec.open(&input.path).and_then(|file| {
+            let mut content = vec![];
+            BufReader::new(file)
+                .read_to_end(&mut content)
+                .map_err(Error::InputRead)?;
+            Ok(content)
+        });
+        match content {
+            Ok(content) => Some((content, input.partition_values)),
+            Err(error) => {
+                fail_task(error);
+                self.files.clear();
+                None
+            }
+        }
+    }
+}
+
 impl Iterator for BytesReader {
     type Item = Vec<u8>;
     fn next(&mut self) -> Option<Self::Item> {
-        if let Some(path) = self.files.pop() {
-            let file = fs::File::open(path).unwrap();
-            let mut content = vec![];
-            let mut reader = BufReader::new(file);
-            reader.read_to_end(&mut content).unwrap();
-            Some(content)
-        } else {
-            None
-        }
+        self.read_next().map(|(content, _)| content)
     }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct FileReader {
-    files: Vec<PathBuf>,
+    files: Vec<InputFile>,
     idx: usize,
     host: Ipv4Addr,
 }
@@ -469,10 +596,18 @@ impl Split for FileReader {
     }
 }
 
+impl FileReader {
+    fn read_next(&mut self) -> Option<(PathBuf, PartitionValues)> {
+        self.files
+            .pop()
+            .map(|input| (input.path, input.partition_values))
+    }
+}
+
 impl Iterator for FileReader {
     type Item = PathBuf;
     fn next(&mut self) -> Option<Self::Item> {
-        self.files.pop()
+        self.read_next().map(|(path, _)| path)
     }
 }
 
@@ -485,11 +620,9 @@ mod tests {
         let context = Context::new().unwrap();
         let mut loader: LocalFsReader<Vec<u8>> = LocalFsReader {
             id: 0,
-            path: "A".into(),
-            is_single_file: false,
-            filter_ext: None,
-            expect_dir: true,
+            listing: FileListing::new(vec!["A".into()]),
             executor_partitions: Some(4),
+            codec: CodecSelection::FromExtension,
             context,
             splits: Vec::new(),
             _marker_reader_data: PhantomData,
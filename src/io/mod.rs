@@ -5,8 +5,38 @@ use crate::rdd::Rdd;
 use crate::serializable_traits::{Data, SerFunc};
 use crate::SerArc;
 
+mod compression;
+pub use compression::{Codec, CodecSelection};
+mod csv_file;
+pub(crate) use csv_file::save_as_csv;
+pub use csv_file::{BadRow, CsvFile, CsvOptions};
+mod data_source;
+pub use data_source::{DataSource, DataSourceRdd, SourceSplit};
+mod file_listing;
+pub(crate) use file_listing::FileListing;
+pub use file_listing::{InputFile, PartitionValues};
+#[macro_use]
+mod host_split;
+pub(crate) use host_split::HostSplit;
+mod json_lines;
+pub(crate) use json_lines::save_as_json_lines;
+pub use json_lines::{CorruptRecord, CorruptRecords, JsonLinesFile, JsonLinesOptions};
 mod local_file_reader;
 pub use local_file_reader::{LocalFsReader, LocalFsReaderConfig};
+mod object_file;
+pub(crate) use object_file::save_as_object_file;
+pub use object_file::{ObjectFileConfig, ObjectFileRdd};
+mod output_committer;
+pub(crate) use output_committer::write_partitions;
+pub use output_committer::{
+    FileOutputCommitter, OutputCommitter, SaveMode, TaskCommit, SUCCESS_MARKER,
+};
+mod parquet_file;
+pub(crate) use parquet_file::save_as_parquet;
+pub use parquet_file::{ParquetCompression, ParquetRdd, ParquetTable, ParquetWriterConfig};
+mod text_file;
+pub(crate) use text_file::{save_as_text_file, FileRange};
+pub use text_file::{LineReader, TextFileRdd};
 
 pub trait ReaderConfiguration<I: Data> {
     fn make_reader<F, O>(self, context: Arc<Context>, decoder: F) -> SerArc<dyn Rdd<Item = O>>
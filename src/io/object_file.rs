@@ -0,0 +1,326 @@
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::marker::PhantomData;
+use std::net::{Ipv4Addr, SocketAddrV4};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::context::Context;
+use crate::dependency::Dependency;
+use crate::error::{Error, Result};
+use crate::fs;
+use crate::io::{write_partitions, Codec, FileListing, FileOutputCommitter, HostSplit, SaveMode};
+use crate::rdd::{Rdd, RddBase};
+use crate::scheduler::{fail_task, task_failed};
+use crate::serializable_traits::{AnyData, Data};
+use crate::split::Split;
+use crate::Fn;
+use serde_derive::{Deserialize, Serialize};
+
+const MAGIC: &[u8; 4] = b"VOBJ";
+const FORMAT_VERSION: u32 = 1;
+
+/// Header written at the start of every object file, after the magic bytes.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Header {
+    version: u32,
+    /// Name of the type of the items, as given by `std::any::type_name`.
+    type_name: String,
+    codec: Option<Codec>,
+}
+
+impl Header {
+    fn new<T>(codec: Option<Codec>) -> Self {
+        Header {
+            version: FORMAT_VERSION,
+            type_name: std::any::type_name::<T>().to_owned(),
+            codec,
+        }
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_all(MAGIC).map_err(Error::OutputWrite)?;
+        Ok(bincode::serialize_into(writer, self)?)
+    }
+
+    /// Read the header of a file, checking that it holds items of type `T`.
+    fn read<T, R: Read>(path: &Path, reader: &mut R) -> Result<Self> {
+        let invalid = |reason: String| Error::InvalidObjectFile {
+            path: path.to_owned(),
+            reason,
+        };
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic).map_err(Error::InputRead)?;
+        if &magic != MAGIC {
+            return Err(invalid("not an object file".to_owned()));
+        }
+        let header: Header = bincode::deserialize_from(reader)?;
+        if header.version != FORMAT_VERSION {
+            return Err(invalid(format!("unsupported version {}", header.version)));
+        }
+        let expected = std::any::type_name::<T>();
+        if header.type_name != expected {
+            return Err(invalid(format!(
+                "holds items of type {}, not {}",
+                header.type_name, expected
+            )));
+        }
+        Ok(header)
+    }
+}
+
+/// Options of the object files written by `Rdd::save_as_object_file`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ObjectFileConfig {
+    codec: Option<Codec>,
+    block_size: usize,
+    mode: SaveMode,
+}
+
+impl Default for ObjectFileConfig {
+    fn default() -> Self {
+        ObjectFileConfig {
+            codec: None,
+            block_size: 1024,
+            mode: SaveMode::ErrorIfExists,
+        }
+    }
+}
+
+impl ObjectFileConfig {
+    pub fn new() -> Self {
+        ObjectFileConfig::default()
+    }
+
+    /// Codec used to compress each block, none by default.
+    pub fn codec(mut self, codec: Option<Codec>) -> Self {
+        self.codec = codec;
+        self
+    }
+
+    /// Number of items in each block. Blocks are the units of compression and the smallest
+    /// units dealt among the partitions reading the files.
+    pub fn block_size(mut self, num_items: usize) -> Self {
+        self.block_size = num_items.max(1);
+        self
+    }
+
+    /// What to do when the output directory exists, fail by default.
+    pub fn mode(mut self, mode: SaveMode) -> Self {
+        self.mode = mode;
+        self
+    }
+}
+
+fn write_block<T: Data, W: Write>(writer: &mut W, items: &[T], codec: Option<Codec>) -> Result<()> {
+    let mut block = bincode::serialize(items)?;
+    if let Some(codec) = codec {
+        block = codec.compress(&block)?;
+    }
+    writer
+        .write_all(&(block.len() as u64).to_le_bytes())
+        .and_then(|_| writer.write_all(&block))
+        .map_err(Error::OutputWrite)
+}
+
+fn write_object_file<T: Data>(
+    path: &Path,
+    items: Box<dyn Iterator<Item = T>>,
+    config: &ObjectFileConfig,
+) -> Result<()> {
+    let file = fs::get_file_system(path)?
+        .create(path, true)
+        .map_err(Error::OutputWrite)?;
+    let mut writer = BufWriter::new(file);
+    Header::new::<T>(config.codec).write(&mut writer)?;
+    let mut block = Vec::with_capacity(config.block_size);
+    for item in items {
+        block.push(item);
+        if block.len() == config.block_size {
+            write_block(&mut writer, &block, config.codec)?;
+            block.clear();
+        }
+    }
+    if !block.is_empty() {
+        write_block(&mut writer, &block, config.codec)?;
+    }
+    writer.flush().map_err(Error::OutputWrite)
+}
+
+/// Write the items of an RDD to a directory, as one object file per partition named
+/// `part-NNNNN`.
+pub(crate) fn save_as_object_file<T: Data>(
+    rdd: Arc<dyn Rdd<Item = T>>,
+    path: PathBuf,
+    config: ObjectFileConfig,
+) -> Result<()> {
+    let committer = FileOutputCommitter::new(path, config.mode);
+    let write = Fn!(
+        move |file: PathBuf, items: Box<dyn Iterator<Item = T>>| write_object_file(
+            &file, items, &config
+        )
+    );
+    write_partitions(rdd, committer, None, write)?;
+    Ok(())
+}
+
+/// Position of a block in an object file.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Block {
+    path: PathBuf,
+    offset: u64,
+    len: u64,
+    codec: Option<Codec>,
+}
+
+/// Read the header of an object file and the positions of its blocks, seeking over them.
+fn read_blocks<T>(path: &Path) -> Result<Vec<Block>> {
+    let file_system = fs::get_file_system(path)?;
+    let file_len = file_system
+        .get_file_status(path)
+        .map_err(Error::InputRead)?
+        .len;
+    let mut reader = BufReader::new(file_system.open(path).map_err(Error::InputRead)?);
+    let header = Header::read::<T, _>(path, &mut reader)?;
+    let mut offset = reader
+        .seek(SeekFrom::Current(0))
+        .map_err(Error::InputRead)?;
+    let mut blocks = vec![];
+    while offset < file_len {
+        let mut len = [0; 8];
+        reader.read_exact(&mut len).map_err(Error::InputRead)?;
+        let len = u64::from_le_bytes(len);
+        blocks.push(Block {
+            path: path.to_owned(),
+            offset: offset + 8,
+            len,
+            codec: header.codec,
+        });
+        offset = reader
+            .seek(SeekFrom::Current(len as i64))
+            .map_err(Error::InputRead)?;
+    }
+    Ok(blocks)
+}
+
+fn read_block<T: Data>(block: &Block) -> Result<Vec<T>> {
+    let mut file = fs::get_file_system(&block.path)?
+        .open(&block.path)
+        .map_err(Error::InputRead)?;
+    file.seek(SeekFrom::Start(block.offset))
+        .map_err(Error::InputRead)?;
+    let mut bytes = vec![0; block.len as usize];
+    file.read_exact(&mut bytes).map_err(Error::InputRead)?;
+    if let Some(codec) = block.codec {
+        bytes = codec.decompress(&bytes)?;
+    }
+    Ok(bincode::deserialize(&bytes)?)
+}
+
+/// Items of the object files of a path.
+///
+/// The files are indexed once on the driver, which deals a contiguous run of their blocks to
+/// each partition, so that the items are read in the order they were written. The blocks are
+/// read from the file system of the host running the partition.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ObjectFileRdd<T> {
+    id: usize,
+    partitions_per_host: usize,
+    /// Blocks read by each split, by split index.
+    blocks: Vec<Vec<Block>>,
+    #[serde(skip_serializing, skip_deserializing)]
+    context: Arc<Context>,
+    hosts: Vec<SocketAddrV4>,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Data> ObjectFileRdd<T> {
+    /// Index the blocks of the files from the driver, checking that they hold items of type
+    /// `T`.
+    pub(crate) fn try_new(
+        context: Arc<Context>,
+        listing: FileListing,
+        partitions_per_host: usize,
+    ) -> Result<Self> {
+        let files = listing.list()?;
+        if files.is_empty() {
+            return Err(Error::NoFilesFound);
+        }
+        let mut blocks = vec![];
+        for file in files {
+            blocks.extend(read_blocks::<T>(&file.path)?);
+        }
+        let partitions_per_host = partitions_per_host.max(1);
+        let hosts = context.address_map.clone();
+        let num_splits = hosts.len() * partitions_per_host;
+        let blocks = (0..num_splits)
+            .map(|i| {
+                let start = blocks.len() * i / num_splits;
+                let end = blocks.len() * (i + 1) / num_splits;
+                blocks[start..end].to_vec()
+            })
+            .collect();
+        Ok(ObjectFileRdd {
+            id: context.new_rdd_id(),
+            partitions_per_host,
+            blocks,
+            hosts,
+            context,
+            _marker: PhantomData,
+        })
+    }
+}
+
+impl<T: Data> RddBase for ObjectFileRdd<T> {
+    impl_host_file_rdd_base_funcs!("object_file");
+}
+
+impl<T: Data> Rdd for ObjectFileRdd<T> {
+    type Item = T;
+
+    fn get_rdd(&self) -> Arc<dyn Rdd<Item = Self::Item>> {
+        Arc::new(self.clone())
+    }
+
+    fn get_rdd_base(&self) -> Arc<dyn RddBase> {
+        Arc::new(self.clone()) as Arc<dyn RddBase>
+    }
+
+    fn compute(&self, split: Box<dyn Split>) -> Result<Box<dyn Iterator<Item = Self::Item>>> {
+        let blocks = self.blocks[split.get_index()].clone();
+        // A block which can't be read fails the task.
+        let blocks = blocks.into_iter().take_while(|_| !task_failed());
+        Ok(Box::new(blocks.flat_map(|block| {
+            read_block::<T>(&block).unwrap_or_else(|error| {
+                fail_task(error);
+                vec![]
+            })
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocks_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("part-00000");
+        let items = (0..2500).map(|i| (i, i.to_string())).collect::<Vec<_>>();
+        let config = ObjectFileConfig::new().codec(Some(Codec::Zstd));
+        write_object_file(&path, Box::new(items.clone().into_iter()), &config).unwrap();
+
+        let blocks = read_blocks::<(i32, String)>(&path).unwrap();
+        assert_eq!(blocks.len(), 3);
+        let read = blocks
+            .iter()
+            .flat_map(|block| read_block::<(i32, String)>(block).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(read, items);
+
+        match read_blocks::<String>(&path) {
+            Err(Error::InvalidObjectFile { .. }) => {}
+            other => panic!("expected a type mismatch, got {:?}", other),
+        }
+    }
+}
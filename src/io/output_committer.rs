@@ -0,0 +1,339 @@
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::env;
+use crate::error::{Error, Result};
+use crate::fs::{self, FileSystem};
+use crate::rdd::Rdd;
+use crate::scheduler::TaskContext;
+use crate::serializable_traits::{Data, SerFunc};
+use crate::Fn;
+use serde_derive::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Marker file written in an output directory once all of its files are written.
+pub const SUCCESS_MARKER: &str = "_SUCCESS";
+const TEMPORARY_DIR: &str = "_temporary";
+
+/// What to do when the output directory of a job already exists.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SaveMode {
+    /// Fail without writing anything.
+    ErrorIfExists,
+    /// Delete the directory and its content before writing.
+    Overwrite,
+    /// Add new files next to the existing ones.
+    Append,
+}
+
+impl Default for SaveMode {
+    fn default() -> Self {
+        SaveMode::ErrorIfExists
+    }
+}
+
+/// Files committed by a task attempt.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TaskCommit {
+    pub split_id: usize,
+    pub attempt_id: usize,
+    pub files: Vec<PathBuf>,
+}
+
+/// Protocol through which the tasks of a job write their output, so that the output of a
+/// partition becomes visible only once, even when several attempts of its task run.
+///
+/// The driver calls `setup_job` before running the tasks, then `commit_job` with the commits
+/// of every partition or `abort_job` when a task failed. Each task attempt calls `setup_task`,
+/// writes to its work files, then calls `commit_task`, or `abort_task` if writing failed. Only
+/// one attempt per partition may call `commit_task`, as authorized by the output commit
+/// coordinator of the driver.
+pub trait OutputCommitter: Data {
+    fn setup_job(&self) -> Result<()>;
+
+    fn setup_task(&self, context: &TaskContext) -> Result<()>;
+
+    /// File private to the task attempt where it writes its partition.
+    fn work_file(&self, context: &TaskContext, extension: Option<&str>) -> PathBuf;
+
+    fn commit_task(&self, context: &TaskContext) -> Result<TaskCommit>;
+
+    fn abort_task(&self, context: &TaskContext) -> Result<()>;
+
+    fn commit_job(&self, commits: &[TaskCommit]) -> Result<()>;
+
+    fn abort_job(&self) -> Result<()>;
+}
+
+/// Commits the files of a job into a directory, one per partition named `part-NNNNN`.
+///
+/// The directory is on the file system of its scheme. Attempts write in their own directory
+/// under `_temporary`. The attempt authorized to commit a partition moves its files into the
+/// output directory, and the job writes a `_SUCCESS` marker once all the partitions are
+/// committed.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FileOutputCommitter {
+    path: PathBuf,
+    mode: SaveMode,
+    job_id: String,
+}
+
+impl FileOutputCommitter {
+    pub fn new<P: Into<PathBuf>>(path: P, mode: SaveMode) -> Self {
+        FileOutputCommitter {
+            path: path.into(),
+            mode,
+            job_id: Uuid::new_v4().to_string(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Name of the file of a partition. When appending, names include the job id so as not to
+    /// replace the files of previous jobs.
+    pub fn file_name(&self, split_id: usize, extension: Option<&str>) -> String {
+        let mut name = format!("part-{:05}", split_id);
+        if self.mode == SaveMode::Append {
+            name = format!("{}-{}", name, self.job_id);
+        }
+        match extension {
+            Some(extension) => format!("{}.{}", name, extension),
+            None => name,
+        }
+    }
+
+    fn job_dir(&self) -> PathBuf {
+        self.path.join(TEMPORARY_DIR).join(&self.job_id)
+    }
+
+    fn attempt_dir(&self, context: &TaskContext) -> PathBuf {
+        self.job_dir().join(format!(
+            "attempt-{:05}-{}",
+            context.split_id, context.attempt_id
+        ))
+    }
+
+    fn file_system(&self) -> Result<Arc<dyn FileSystem>> {
+        fs::get_file_system(&self.path)
+    }
+
+    fn remove_job_dir(&self) -> Result<()> {
+        let file_system = self.file_system()?;
+        file_system
+            .delete(&self.job_dir(), true)
+            .map_err(Error::OutputWrite)?;
+        // Other jobs may still be appending to the same directory.
+        let _ = file_system.delete(&self.path.join(TEMPORARY_DIR), false);
+        Ok(())
+    }
+}
+
+impl OutputCommitter for FileOutputCommitter {
+    fn setup_job(&self) -> Result<()> {
+        let file_system = self.file_system()?;
+        if file_system.exists(&self.path) {
+            match self.mode {
+                SaveMode::ErrorIfExists => return Err(Error::OutputExists(self.path.clone())),
+                SaveMode::Overwrite => {
+                    file_system
+                        .delete(&self.path, true)
+                        .map_err(Error::OutputWrite)?;
+                }
+                SaveMode::Append => {}
+            }
+        }
+        file_system.mkdirs(&self.path).map_err(Error::OutputWrite)
+    }
+
+    fn setup_task(&self, context: &TaskContext) -> Result<()> {
+        self.file_system()?
+            .mkdirs(&self.attempt_dir(context))
+            .map_err(Error::OutputWrite)
+    }
+
+    fn work_file(&self, context: &TaskContext, extension: Option<&str>) -> PathBuf {
+        self.attempt_dir(context)
+            .join(self.file_name(context.split_id, extension))
+    }
+
+    fn commit_task(&self, context: &TaskContext) -> Result<TaskCommit> {
+        let file_system = self.file_system()?;
+        let attempt_dir = self.attempt_dir(context);
+        let mut files = vec![];
+        for status in file_system
+            .list_status(&attempt_dir)
+            .map_err(Error::OutputWrite)?
+        {
+            let file = match status.path.file_name() {
+                Some(name) => self.path.join(name),
+                None => continue,
+            };
+            file_system
+                .rename(&status.path, &file)
+                .map_err(Error::OutputWrite)?;
+            files.push(file);
+        }
+        files.sort();
+        file_system
+            .delete(&attempt_dir, false)
+            .map_err(Error::OutputWrite)?;
+        Ok(TaskCommit {
+            split_id: context.split_id,
+            attempt_id: context.attempt_id,
+            files,
+        })
+    }
+
+    fn abort_task(&self, context: &TaskContext) -> Result<()> {
+        self.file_system()?
+            .delete(&self.attempt_dir(context), true)
+            .map_err(Error::OutputWrite)?;
+        Ok(())
+    }
+
+    fn commit_job(&self, _commits: &[TaskCommit]) -> Result<()> {
+        self.remove_job_dir()?;
+        self.file_system()?
+            .create(&self.path.join(SUCCESS_MARKER), true)
+            .map_err(Error::OutputWrite)?;
+        Ok(())
+    }
+
+    fn abort_job(&self) -> Result<()> {
+        self.remove_job_dir()
+    }
+}
+
+/// Run the job of a writer: every task writes its partition to the work file given by the
+/// committer with `write`, and the job is committed once every partition is.
+///
+/// Attempts ask the output commit coordinator of the driver before committing, so that only
+/// one attempt per partition commits. An attempt which another attempt committed before
+/// reports no files, and the scheduler keeps the result of the first attempt to finish.
+pub(crate) fn write_partitions<T, C, W>(
+    rdd: Arc<dyn Rdd<Item = T>>,
+    committer: C,
+    extension: Option<&str>,
+    write: W,
+) -> Result<Vec<TaskCommit>>
+where
+    T: Data,
+    C: OutputCommitter,
+    W: SerFunc(PathBuf, Box<dyn Iterator<Item = T>>) -> Result<()>,
+{
+    fn run_task<T, C, W>(
+        committer: &C,
+        extension: Option<&str>,
+        write: &W,
+        context: TaskContext,
+        items: Box<dyn Iterator<Item = T>>,
+    ) -> Result<Option<TaskCommit>>
+    where
+        C: OutputCommitter,
+        W: Fn(PathBuf, Box<dyn Iterator<Item = T>>) -> Result<()>,
+    {
+        let written = committer
+            .setup_task(&context)
+            .and_then(|_| write(committer.work_file(&context, extension), items));
+        if let Err(err) = written {
+            committer.abort_task(&context)?;
+            return Err(err);
+        }
+        let coordinator = &env::Env::get().output_commit_coordinator;
+        match coordinator.authorize_commit(&context) {
+            Ok(true) => {}
+            Ok(false) => {
+                committer.abort_task(&context)?;
+                return Ok(None);
+            }
+            Err(err) => {
+                committer.abort_task(&context)?;
+                return Err(err);
+            }
+        }
+        match committer.commit_task(&context) {
+            Ok(commit) => {
+                coordinator.commit_done(&context)?;
+                Ok(Some(commit))
+            }
+            Err(err) => {
+                // Let another attempt commit the partition.
+                coordinator.commit_aborted(&context)?;
+                committer.abort_task(&context)?;
+                Err(err)
+            }
+        }
+    }
+
+    committer.setup_job()?;
+    let task_committer = committer.clone();
+    let extension = extension.map(str::to_owned);
+    let task = Fn!(move |(context, items)| {
+        run_task(
+            &task_committer,
+            extension.as_deref(),
+            &write,
+            context,
+            items,
+        )
+        .map_err(|err| err.to_string())
+    });
+    let results = rdd.get_context().run_job_with_context(rdd, task);
+    let commits = results.and_then(|results| {
+        results
+            .into_iter()
+            .filter_map(|result| result.transpose())
+            .map(|result| {
+                result.map_err(|message| {
+                    Error::OutputWrite(std::io::Error::new(ErrorKind::Other, message))
+                })
+            })
+            .collect::<Result<Vec<_>>>()
+    });
+    match commits {
+        Ok(commits) => {
+            committer.commit_job(&commits)?;
+            Ok(commits)
+        }
+        Err(err) => {
+            committer.abort_job()?;
+            Err(err)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn commit_authorized_attempt() {
+        let dir = tempfile::tempdir().unwrap();
+        let committer = FileOutputCommitter::new(dir.path().join("out"), SaveMode::Overwrite);
+        committer.setup_job().unwrap();
+
+        let first = TaskContext::new(0, 3, 7);
+        let second = TaskContext::new(0, 3, 8);
+        for (context, text) in &[(&first, "first"), (&second, "second")] {
+            committer.setup_task(context).unwrap();
+            fs::write(committer.work_file(context, Some("txt")), text).unwrap();
+        }
+        let commit = committer.commit_task(&second).unwrap();
+        assert_eq!(commit.files, vec![dir.path().join("out/part-00003.txt")]);
+        committer.abort_task(&first).unwrap();
+        committer.commit_job(&[commit]).unwrap();
+
+        let mut names = fs::read_dir(dir.path().join("out"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, vec![SUCCESS_MARKER, "part-00003.txt"]);
+        let text = fs::read_to_string(dir.path().join("out/part-00003.txt")).unwrap();
+        assert_eq!(text, "second");
+    }
+}
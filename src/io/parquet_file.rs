@@ -0,0 +1,727 @@
+use std::fs::File;
+use std::io::{Seek, SeekFrom, Write};
+use std::net::{Ipv4Addr, SocketAddrV4};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::context::Context;
+use crate::dataframe::{evaluate_binary, DataFrame, DataType, Expr, Field, Operator, PlanError};
+use crate::dataframe::{Row, Schema, TableProvider, Value};
+use crate::dependency::Dependency;
+use crate::error::{Error, Result};
+use crate::fs;
+use crate::io::{write_partitions, FileListing, FileOutputCommitter, HostSplit, SaveMode};
+use crate::rdd::{Rdd, RddBase};
+use crate::serializable_traits::AnyData;
+use crate::split::Split;
+use crate::{Fn, SerArc};
+use parquet::basic::{Compression, LogicalType, Repetition, Type as PhysicalType};
+use parquet::column::writer::ColumnWriter;
+use parquet::data_type::ByteArray;
+use parquet::errors::ParquetError;
+use parquet::file::metadata::RowGroupMetaData;
+use parquet::file::properties::WriterProperties;
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::file::statistics::Statistics;
+use parquet::file::writer::{FileWriter, RowGroupWriter, SerializedFileWriter};
+use parquet::record::Field as ParquetField;
+use parquet::schema::types::{SchemaDescriptor, Type as SchemaType};
+use serde_derive::{Deserialize, Serialize};
+
+const PARQUET_EXTENSION: &str = "parquet";
+
+/// Parquet files of a path: the path itself if it is a file, or the files with the `.parquet`
+/// extension of a directory or glob pattern, sorted so that every task sees them in the same
+/// order.
+fn list_parquet_files(path: &Path) -> Result<Vec<PathBuf>> {
+    let mut listing = FileListing::new(vec![path.to_owned()]);
+    listing.filter_ext = Some(PARQUET_EXTENSION.into());
+    Ok(listing.list()?.into_iter().map(|file| file.path).collect())
+}
+
+/// Parquet reads and writes seekable local files: the files of other file systems are copied
+/// through a temporary file.
+fn open(path: &Path) -> Result<SerializedFileReader<File>> {
+    let file_system = fs::get_file_system(path)?;
+    let file = match file_system.to_local_path(path) {
+        Some(local) => File::open(local).map_err(Error::InputRead)?,
+        None => {
+            let mut reader = file_system.open(path).map_err(Error::InputRead)?;
+            let mut file = tempfile::tempfile().map_err(Error::InputRead)?;
+            std::io::copy(&mut reader, &mut file)
+                .and_then(|_| file.seek(SeekFrom::Start(0)))
+                .map_err(Error::InputRead)?;
+            file
+        }
+    };
+    Ok(SerializedFileReader::new(file)?)
+}
+
+/// Converts the schema of a Parquet file. Only flat schemas are supported; integers are read
+/// as `Int64`, floats as `Float64` and binary columns as `Utf8`. Decimals and unsigned 64 bits
+/// integers, which don't fit in these types, are not supported.
+fn from_parquet_schema(descr: &SchemaDescriptor) -> Result<Schema> {
+    if descr.root_schema().get_fields().len() != descr.num_columns() {
+        return Err(PlanError::UnsupportedType("nested Parquet schema".to_owned()).into());
+    }
+    let fields = (0..descr.num_columns())
+        .map(|i| {
+            let column = descr.column(i);
+            if column.max_rep_level() > 0 {
+                let message = format!("repeated Parquet column {}", column.name());
+                return Err(PlanError::UnsupportedType(message).into());
+            }
+            let data_type = match column.physical_type() {
+                PhysicalType::BOOLEAN => DataType::Boolean,
+                PhysicalType::INT32 | PhysicalType::INT64 => DataType::Int64,
+                PhysicalType::FLOAT | PhysicalType::DOUBLE => DataType::Float64,
+                PhysicalType::BYTE_ARRAY => DataType::Utf8,
+                other => return Err(PlanError::UnsupportedType(format!("{:?}", other)).into()),
+            };
+            match column.logical_type() {
+                LogicalType::DECIMAL | LogicalType::UINT_64 => {
+                    let message = format!(
+                        "{:?} Parquet column {}",
+                        column.logical_type(),
+                        column.name()
+                    );
+                    return Err(PlanError::UnsupportedType(message).into());
+                }
+                _ => {}
+            }
+            Ok(Field::new(
+                column.name(),
+                data_type,
+                column.max_def_level() > 0,
+            ))
+        })
+        .collect::<Result<_>>()?;
+    Ok(Schema::new(fields))
+}
+
+fn to_parquet_schema(schema: &Schema) -> Result<SchemaType> {
+    let mut fields = schema
+        .fields()
+        .iter()
+        .map(|field| {
+            let (physical_type, logical_type) = match field.data_type() {
+                DataType::Null | DataType::Boolean => (PhysicalType::BOOLEAN, LogicalType::NONE),
+                DataType::Int64 => (PhysicalType::INT64, LogicalType::NONE),
+                DataType::Float64 => (PhysicalType::DOUBLE, LogicalType::NONE),
+                DataType::Utf8 => (PhysicalType::BYTE_ARRAY, LogicalType::UTF8),
+            };
+            let repetition = if field.is_nullable() || field.data_type() == DataType::Null {
+                Repetition::OPTIONAL
+            } else {
+                Repetition::REQUIRED
+            };
+            let field = SchemaType::primitive_type_builder(field.name(), physical_type)
+                .with_repetition(repetition)
+                .with_logical_type(logical_type)
+                .build()?;
+            Ok(Rc::new(field))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(SchemaType::group_type_builder("schema")
+        .with_fields(&mut fields)
+        .build()?)
+}
+
+fn to_value(field: &ParquetField) -> Result<Value> {
+    Ok(match field {
+        ParquetField::Null => Value::Null,
+        ParquetField::Bool(v) => Value::Boolean(*v),
+        ParquetField::Byte(v) => Value::Int(*v as i64),
+        ParquetField::Short(v) => Value::Int(*v as i64),
+        ParquetField::Int(v) => Value::Int(*v as i64),
+        ParquetField::Long(v) => Value::Int(*v),
+        ParquetField::UByte(v) => Value::Int(*v as i64),
+        ParquetField::UShort(v) => Value::Int(*v as i64),
+        ParquetField::UInt(v) => Value::Int(*v as i64),
+        ParquetField::Date(v) => Value::Int(*v as i64),
+        ParquetField::TimestampMillis(v) => Value::Int(*v as i64),
+        ParquetField::TimestampMicros(v) => Value::Int(*v as i64),
+        ParquetField::Float(v) => Value::Float(*v as f64),
+        ParquetField::Double(v) => Value::Float(*v),
+        ParquetField::Str(v) => Value::Utf8(v.clone()),
+        ParquetField::Bytes(v) => Value::Utf8(String::from_utf8_lossy(v.data()).into_owned()),
+        // the schema of the table rejects the columns of the other types
+        other => return Err(PlanError::UnsupportedType(format!("{:?}", other)).into()),
+    })
+}
+
+/// Statistics of a column chunk used to skip row groups.
+struct ColumnStats {
+    min_max: Option<(Value, Value)>,
+    null_count: u64,
+    num_rows: u64,
+}
+
+impl ColumnStats {
+    fn new(stats: &Statistics, num_rows: u64) -> Self {
+        let min_max = if !stats.has_min_max_set() {
+            None
+        } else {
+            match stats {
+                Statistics::Boolean(s) => {
+                    Some((Value::Boolean(*s.min()), Value::Boolean(*s.max())))
+                }
+                Statistics::Int32(s) => {
+                    Some((Value::Int(*s.min() as i64), Value::Int(*s.max() as i64)))
+                }
+                Statistics::Int64(s) => Some((Value::Int(*s.min()), Value::Int(*s.max()))),
+                Statistics::Float(s) => {
+                    Some((Value::Float(*s.min() as f64), Value::Float(*s.max() as f64)))
+                }
+                Statistics::Double(s) => Some((Value::Float(*s.min()), Value::Float(*s.max()))),
+                Statistics::ByteArray(s) => {
+                    let utf8 =
+                        |v: &ByteArray| Value::Utf8(String::from_utf8_lossy(v.data()).into_owned());
+                    Some((utf8(s.min()), utf8(s.max())))
+                }
+                _ => None,
+            }
+        };
+        ColumnStats {
+            min_max,
+            null_count: stats.null_count(),
+            num_rows,
+        }
+    }
+}
+
+/// Whether some row of a row group may satisfy the predicate, given the statistics of the
+/// columns it references. Predicates which are not simple comparisons between a column and a
+/// literal never skip anything.
+fn may_match(predicate: &Expr, stats: &[Option<ColumnStats>]) -> bool {
+    let column_stats = |expr: &Expr| match expr {
+        Expr::Column(column) => column.index.and_then(|i| stats.get(i)?.as_ref()),
+        _ => None,
+    };
+    match predicate {
+        Expr::BinaryExpr {
+            left,
+            op: Operator::And,
+            right,
+        } => may_match(left, stats) && may_match(right, stats),
+        Expr::BinaryExpr {
+            left,
+            op: Operator::Or,
+            right,
+        } => may_match(left, stats) || may_match(right, stats),
+        Expr::BinaryExpr { left, op, right } if op.is_comparison() => {
+            let (column, op, value) = match (left.as_ref(), right.as_ref()) {
+                (column, Expr::Literal(value)) => (column, *op, value),
+                (Expr::Literal(value), column) => (column, flip(*op), value),
+                _ => return true,
+            };
+            let (min, max) = match column_stats(column).and_then(|s| s.min_max.as_ref()) {
+                Some((min, max)) => (min, max),
+                None => return true,
+            };
+            if value.is_null() {
+                return false;
+            }
+            let holds = |op: Operator, left: &Value, right: &Value| {
+                evaluate_binary(op, left, right).as_bool() == Some(true)
+            };
+            let fails = |op: Operator, left: &Value, right: &Value| {
+                evaluate_binary(op, left, right).as_bool() == Some(false)
+            };
+            match op {
+                Operator::Eq => {
+                    !(holds(Operator::Lt, value, min) || holds(Operator::Gt, value, max))
+                }
+                Operator::NotEq => {
+                    !(holds(Operator::Eq, min, value) && holds(Operator::Eq, max, value))
+                }
+                Operator::Lt => !fails(Operator::Lt, min, value),
+                Operator::LtEq => !fails(Operator::LtEq, min, value),
+                Operator::Gt => !fails(Operator::Gt, max, value),
+                _ => !fails(Operator::GtEq, max, value),
+            }
+        }
+        Expr::IsNull(expr) => column_stats(expr).map_or(true, |s| s.null_count > 0),
+        Expr::IsNotNull(expr) => column_stats(expr).map_or(true, |s| s.null_count < s.num_rows),
+        _ => true,
+    }
+}
+
+/// Operator of the comparison with swapped operands.
+fn flip(op: Operator) -> Operator {
+    match op {
+        Operator::Lt => Operator::Gt,
+        Operator::LtEq => Operator::GtEq,
+        Operator::Gt => Operator::Lt,
+        Operator::GtEq => Operator::LtEq,
+        op => op,
+    }
+}
+
+/// Reads the rows of the Parquet files of a path from the local file system of every host.
+/// The row groups of the files of each host are dealt in turn to the partitions of the host.
+///
+/// Only the projected columns are decoded, and row groups whose statistics prove that no row
+/// matches the filters are skipped.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ParquetRdd {
+    id: usize,
+    path: PathBuf,
+    /// Indices of the columns of the files to read, all of them if not set.
+    projection: Option<Vec<usize>>,
+    /// Filters over the projected columns, only used to skip row groups.
+    filters: Vec<Expr>,
+    partitions_per_host: usize,
+    #[serde(skip_serializing, skip_deserializing)]
+    context: Arc<Context>,
+    hosts: Vec<SocketAddrV4>,
+}
+
+impl ParquetRdd {
+    fn new(
+        context: Arc<Context>,
+        path: PathBuf,
+        projection: Option<Vec<usize>>,
+        filters: Vec<Expr>,
+        partitions_per_host: usize,
+    ) -> Self {
+        ParquetRdd {
+            id: context.new_rdd_id(),
+            path,
+            projection,
+            filters,
+            partitions_per_host,
+            hosts: context.address_map.clone(),
+            context,
+        }
+    }
+
+    /// Row groups to read in the split, as pairs of row group index and number of rows,
+    /// with the reader of their file. Every file is opened once.
+    fn row_groups(
+        &self,
+        split: &HostSplit,
+    ) -> Result<Vec<(SerializedFileReader<File>, Vec<(usize, usize)>)>> {
+        let mut readers = vec![];
+        let mut row_groups = vec![];
+        for path in list_parquet_files(&self.path)? {
+            let reader = open(&path)?;
+            let metadata = reader.metadata();
+            for i in 0..metadata.num_row_groups() {
+                let row_group = metadata.row_group(i);
+                let num_rows = row_group.num_rows() as usize;
+                row_groups.push((readers.len(), i, num_rows, self.may_match(row_group)));
+            }
+            readers.push((reader, vec![]));
+        }
+        // Skipped row groups are dealt too, so that every split sees the same ones.
+        for (file, i, num_rows, matches) in split.deal(row_groups) {
+            if matches {
+                readers[file].1.push((i, num_rows));
+            }
+        }
+        readers.retain(|(_, row_groups)| !row_groups.is_empty());
+        Ok(readers)
+    }
+
+    fn may_match(&self, row_group: &RowGroupMetaData) -> bool {
+        let num_rows = row_group.num_rows() as u64;
+        let stats = |i: usize| {
+            let column = row_group.column(i);
+            // the statistics of unsigned columns may be ordered as signed integers
+            if column.column_descr().logical_type() == LogicalType::UINT_32 {
+                return None;
+            }
+            column
+                .statistics()
+                .map(|stats| ColumnStats::new(stats, num_rows))
+        };
+        let stats = match &self.projection {
+            Some(projection) => projection.iter().map(|&i| stats(i)).collect::<Vec<_>>(),
+            None => (0..row_group.num_columns()).map(stats).collect(),
+        };
+        self.filters
+            .iter()
+            .all(|predicate| may_match(predicate, &stats))
+    }
+
+    fn read_row_group(
+        &self,
+        reader: &SerializedFileReader<File>,
+        index: usize,
+    ) -> Result<Vec<Row>> {
+        let projection = match &self.projection {
+            Some(projection) => {
+                let descr = reader.metadata().file_metadata().schema_descr();
+                let mut fields = projection
+                    .iter()
+                    .map(|&i| descr.root_schema().get_fields()[i].clone())
+                    .collect::<Vec<_>>();
+                Some(
+                    SchemaType::group_type_builder("schema")
+                        .with_fields(&mut fields)
+                        .build()?,
+                )
+            }
+            None => None,
+        };
+        let row_group = reader.get_row_group(index)?;
+        row_group
+            .get_row_iter(projection)?
+            .map(|row| {
+                row.get_column_iter()
+                    .map(|(_, field)| to_value(field))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl RddBase for ParquetRdd {
+    impl_host_file_rdd_base_funcs!("parquet_reader");
+}
+
+impl Rdd for ParquetRdd {
+    type Item = Row;
+
+    fn get_rdd(&self) -> Arc<dyn Rdd<Item = Self::Item>> {
+        Arc::new(self.clone())
+    }
+
+    fn get_rdd_base(&self) -> Arc<dyn RddBase> {
+        Arc::new(self.clone()) as Arc<dyn RddBase>
+    }
+
+    fn compute(&self, split: Box<dyn Split>) -> Result<Box<dyn Iterator<Item = Self::Item>>> {
+        let split = split.downcast_ref::<HostSplit>().unwrap();
+        let row_groups = self.row_groups(split)?;
+        if self.projection.as_ref().map_or(false, Vec::is_empty) {
+            // Only the number of rows is needed, which is known from the metadata.
+            let num_rows = row_groups
+                .iter()
+                .flat_map(|(_, row_groups)| row_groups.iter().map(|(_, num_rows)| num_rows))
+                .sum();
+            return Ok(Box::new(std::iter::repeat(vec![]).take(num_rows)));
+        }
+        // The row groups are read before iterating, so that a read error fails the task.
+        let mut rows = vec![];
+        for (reader, row_groups) in row_groups {
+            for (index, _) in row_groups {
+                rows.extend(self.read_row_group(&reader, index)?);
+            }
+        }
+        Ok(Box::new(rows.into_iter()))
+    }
+}
+
+/// Parquet files of a path, read by [Context::read_parquet](../struct.Context.html#method.read_parquet).
+pub struct ParquetTable {
+    context: Arc<Context>,
+    path: PathBuf,
+    schema: Schema,
+    partitions_per_host: usize,
+}
+
+impl ParquetTable {
+    /// Open the table, taking the schema from the first file of the path.
+    pub fn try_new<P: Into<PathBuf>>(context: Arc<Context>, path: P) -> Result<Self> {
+        let path = path.into();
+        let first = list_parquet_files(&path)?
+            .into_iter()
+            .next()
+            .ok_or(Error::NoFilesFound)?;
+        let schema = from_parquet_schema(open(&first)?.metadata().file_metadata().schema_descr())?;
+        Ok(ParquetTable {
+            context,
+            path,
+            schema,
+            partitions_per_host: num_cpus::get(),
+        })
+    }
+
+    /// Number of partitions reading the files of each host, the number of CPUs by default.
+    pub fn num_partitions_per_host(mut self, num: usize) -> Self {
+        self.partitions_per_host = num.max(1);
+        self
+    }
+}
+
+impl TableProvider for ParquetTable {
+    fn schema(&self) -> Schema {
+        self.schema.clone()
+    }
+
+    fn scan(
+        &self,
+        projection: Option<&[usize]>,
+        filters: &[Expr],
+    ) -> Result<SerArc<dyn Rdd<Item = Row>>> {
+        let rdd = ParquetRdd::new(
+            self.context.clone(),
+            self.path.clone(),
+            projection.map(<[usize]>::to_vec),
+            filters.to_vec(),
+            self.partitions_per_host,
+        );
+        Ok(SerArc::new(rdd))
+    }
+}
+
+/// Compression codec of the pages of written Parquet files.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ParquetCompression {
+    Uncompressed,
+    Snappy,
+    Gzip,
+    Lzo,
+    Brotli,
+    Lz4,
+    Zstd,
+}
+
+impl ParquetCompression {
+    fn to_codec(self) -> Compression {
+        match self {
+            ParquetCompression::Uncompressed => Compression::UNCOMPRESSED,
+            ParquetCompression::Snappy => Compression::SNAPPY,
+            ParquetCompression::Gzip => Compression::GZIP,
+            ParquetCompression::Lzo => Compression::LZO,
+            ParquetCompression::Brotli => Compression::BROTLI,
+            ParquetCompression::Lz4 => Compression::LZ4,
+            ParquetCompression::Zstd => Compression::ZSTD,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ParquetWriterConfig {
+    compression: ParquetCompression,
+    row_group_size: usize,
+    mode: SaveMode,
+}
+
+impl Default for ParquetWriterConfig {
+    fn default() -> Self {
+        ParquetWriterConfig {
+            compression: ParquetCompression::Snappy,
+            row_group_size: 128 * 1024,
+            mode: SaveMode::ErrorIfExists,
+        }
+    }
+}
+
+impl ParquetWriterConfig {
+    pub fn new() -> Self {
+        ParquetWriterConfig::default()
+    }
+
+    /// Codec used to compress the pages, Snappy by default.
+    pub fn compression(mut self, compression: ParquetCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Maximum number of rows in each row group.
+    pub fn row_group_size(mut self, num_rows: usize) -> Self {
+        self.row_group_size = num_rows.max(1);
+        self
+    }
+
+    /// What to do when the output directory exists, fail by default.
+    pub fn mode(mut self, mode: SaveMode) -> Self {
+        self.mode = mode;
+        self
+    }
+}
+
+fn write_column(
+    column_writer: &mut ColumnWriter,
+    rows: &[Row],
+    index: usize,
+    field: &Field,
+) -> Result<()> {
+    let values = rows
+        .iter()
+        .map(|row| row[index].cast(field.data_type()))
+        .collect::<Vec<_>>();
+    let optional = field.is_nullable() || field.data_type() == DataType::Null;
+    if !optional && values.iter().any(|value| value.is_null()) {
+        let message = format!("null value in required column {}", field.name());
+        return Err(ParquetError::General(message).into());
+    }
+    let def_levels = values
+        .iter()
+        .map(|value| if value.is_null() { 0 } else { 1 })
+        .collect::<Vec<i16>>();
+    let def_levels = if optional {
+        Some(def_levels.as_slice())
+    } else {
+        None
+    };
+    match column_writer {
+        ColumnWriter::BoolColumnWriter(ref mut writer) => {
+            let values = values
+                .iter()
+                .filter_map(|v| v.as_bool())
+                .collect::<Vec<_>>();
+            writer.write_batch(&values, def_levels, None)?;
+        }
+        ColumnWriter::Int64ColumnWriter(ref mut writer) => {
+            let values = values.iter().filter_map(|v| v.as_i64()).collect::<Vec<_>>();
+            writer.write_batch(&values, def_levels, None)?;
+        }
+        ColumnWriter::DoubleColumnWriter(ref mut writer) => {
+            let values = values.iter().filter_map(|v| v.as_f64()).collect::<Vec<_>>();
+            writer.write_batch(&values, def_levels, None)?;
+        }
+        ColumnWriter::ByteArrayColumnWriter(ref mut writer) => {
+            let values = values
+                .iter()
+                .filter_map(|v| v.as_str().map(ByteArray::from))
+                .collect::<Vec<_>>();
+            writer.write_batch(&values, def_levels, None)?;
+        }
+        _ => unreachable!("column type not produced by to_parquet_schema"),
+    }
+    Ok(())
+}
+
+/// Write the rows of a partition to a Parquet file, in row groups of the configured size.
+fn write_partition(
+    path: &Path,
+    rows: Box<dyn Iterator<Item = Row>>,
+    schema: &Schema,
+    config: &ParquetWriterConfig,
+) -> Result<()> {
+    let properties = WriterProperties::builder()
+        .set_compression(config.compression.to_codec())
+        .build();
+    let file_system = fs::get_file_system(path)?;
+    let local = file_system.to_local_path(path);
+    let mut file = match &local {
+        Some(local) => File::create(local),
+        None => tempfile::tempfile(),
+    }
+    .map_err(Error::OutputWrite)?;
+    let mut writer = SerializedFileWriter::new(
+        file.try_clone().map_err(Error::OutputWrite)?,
+        Rc::new(to_parquet_schema(schema)?),
+        Rc::new(properties),
+    )?;
+    let mut rows = rows.peekable();
+    while rows.peek().is_some() {
+        let chunk = rows
+            .by_ref()
+            .take(config.row_group_size)
+            .collect::<Vec<_>>();
+        let mut row_group = writer.next_row_group()?;
+        let mut index = 0;
+        while let Some(mut column_writer) = row_group.next_column()? {
+            write_column(&mut column_writer, &chunk, index, schema.field(index))?;
+            row_group.close_column(column_writer)?;
+            index += 1;
+        }
+        writer.close_row_group(row_group)?;
+    }
+    writer.close()?;
+    if local.is_none() {
+        let mut output = file_system.create(path, true).map_err(Error::OutputWrite)?;
+        file.seek(SeekFrom::Start(0))
+            .and_then(|_| std::io::copy(&mut file, &mut output))
+            .and_then(|_| output.flush())
+            .map_err(Error::OutputWrite)?;
+    }
+    Ok(())
+}
+
+/// Write the rows of the DataFrame to a directory, as one Parquet file per partition named
+/// `part-NNNNN.parquet`.
+pub(crate) fn save_as_parquet(
+    df: &DataFrame,
+    path: PathBuf,
+    config: ParquetWriterConfig,
+) -> Result<()> {
+    let schema = df.schema().clone();
+    let committer = FileOutputCommitter::new(path, config.mode);
+    let write = Fn!(
+        move |file: PathBuf, rows: Box<dyn Iterator<Item = Row>>| write_partition(
+            &file, rows, &schema, &config
+        )
+    );
+    write_partitions(
+        df.to_rdd()?.get_rdd(),
+        committer,
+        Some(PARQUET_EXTENSION),
+        write,
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataframe::{col, lit};
+
+    #[test]
+    fn row_group_pruning() {
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, true),
+        ]);
+        let stats = vec![
+            Some(ColumnStats {
+                min_max: Some((Value::Int(100), Value::Int(199))),
+                null_count: 0,
+                num_rows: 100,
+            }),
+            Some(ColumnStats {
+                min_max: None,
+                null_count: 100,
+                num_rows: 100,
+            }),
+        ];
+        let matches = |expr: Expr| may_match(&expr.resolve(&schema).unwrap(), &stats);
+        assert!(matches(col("id").eq(lit(150))));
+        assert!(!matches(col("id").eq(lit(200))));
+        assert!(!matches(lit(99).gt_eq(col("id"))));
+        assert!(matches(col("id").lt(lit(100.5))));
+        assert!(!matches(col("id").gt(lit(199)).or(col("id").lt(lit(100)))));
+        assert!(!matches(
+            col("id").gt(lit(120)).and(col("name").is_not_null())
+        ));
+        assert!(matches(col("name").eq(lit("x"))));
+        assert!(matches((col("id") % lit(2)).eq(lit(0))));
+    }
+
+    #[test]
+    fn unsigned_and_timestamp_columns() {
+        use parquet::schema::parser::parse_message_type;
+
+        let descr =
+            |message: &str| SchemaDescriptor::new(Rc::new(parse_message_type(message).unwrap()));
+        let schema = from_parquet_schema(&descr(
+            "message schema { REQUIRED INT32 a (UINT_32); OPTIONAL INT64 b (TIMESTAMP_MICROS); }",
+        ))
+        .unwrap();
+        assert_eq!(schema.field(0).data_type(), DataType::Int64);
+        assert_eq!(schema.field(1).data_type(), DataType::Int64);
+        assert!(
+            from_parquet_schema(&descr("message schema { REQUIRED INT64 a (UINT_64); }")).is_err()
+        );
+        assert!(from_parquet_schema(&descr(
+            "message schema { REQUIRED INT32 a (DECIMAL(9, 2)); }"
+        ))
+        .is_err());
+
+        assert_eq!(
+            to_value(&ParquetField::UInt(u32::MAX)).unwrap(),
+            Value::Int(u32::MAX as i64)
+        );
+        assert_eq!(
+            to_value(&ParquetField::TimestampMicros(1_500_000)).unwrap(),
+            Value::Int(1_500_000)
+        );
+        assert!(to_value(&ParquetField::ULong(u64::MAX)).is_err());
+    }
+}
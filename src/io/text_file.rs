@@ -0,0 +1,351 @@
+use std::io::{BufRead, BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::marker::PhantomData;
+use std::net::{Ipv4Addr, SocketAddrV4};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::context::Context;
+use crate::dependency::Dependency;
+use crate::error::{Error, Result};
+use crate::fs;
+use crate::io::{write_partitions, CodecSelection, FileListing, FileOutputCommitter, InputFile};
+use crate::io::{HostSplit, PartitionValues, SaveMode};
+use crate::rdd::{Rdd, RddBase};
+use crate::scheduler::{fail_task, task_failed};
+use crate::serializable_traits::{AnyData, Data, SerFunc};
+use crate::split::Split;
+use crate::Fn;
+use serde_derive::{Deserialize, Serialize};
+
+/// Byte range of a file read by a split.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct FileRange {
+    pub path: PathBuf,
+    pub start: u64,
+    pub end: u64,
+    pub partition_values: PartitionValues,
+}
+
+/// Cut the files into ranges of about `total size / num_parts` bytes. Compressed files can't
+/// be split and are read whole, in a range which ends past any position.
+pub(crate) fn split_ranges(
+    files: Vec<InputFile>,
+    num_parts: usize,
+    codec: CodecSelection,
+) -> Vec<FileRange> {
+    let total_size: u64 = files.iter().map(|file| file.size).sum();
+    let range_size = ((total_size + num_parts as u64 - 1) / num_parts as u64).max(1);
+    let mut ranges = vec![];
+    for file in files {
+        if codec.codec_for(&file.path).is_some() {
+            ranges.push(FileRange {
+                path: file.path,
+                start: 0,
+                end: u64::MAX,
+                partition_values: file.partition_values,
+            });
+            continue;
+        }
+        let mut start = 0;
+        while start < file.size {
+            let end = (start + range_size).min(file.size);
+            ranges.push(FileRange {
+                path: file.path.clone(),
+                start,
+                end,
+                partition_values: file.partition_values.clone(),
+            });
+            start = end;
+        }
+    }
+    ranges
+}
+
+/// Lines starting within a byte range of a file.
+///
+/// A line belongs to the range holding its first byte, so unless the range starts the file
+/// the partial line at its beginning is skipped, and the last line is read past the end of
+/// the range. An error reading the file fails the task and ends the lines.
+pub struct LineReader<R> {
+    reader: R,
+    pos: u64,
+    end: u64,
+    buf: Vec<u8>,
+}
+
+impl LineReader<Box<dyn BufRead>> {
+    pub(crate) fn open(range: &FileRange, codec: CodecSelection) -> Result<Self> {
+        let file = fs::get_file_system(&range.path)?
+            .open(&range.path)
+            .map_err(Error::InputRead)?;
+        if let Some(codec) = codec.codec_for(&range.path) {
+            let reader: Box<dyn BufRead> = Box::new(BufReader::new(codec.decoder(file)?));
+            return Ok(LineReader::new(reader, 0, range.end));
+        }
+        let mut reader = BufReader::new(file);
+        let mut pos = range.start;
+        if range.start > 0 {
+            // Skip up to the first line starting in the range; when the previous byte is a
+            // newline that line starts right at the beginning of the range.
+            reader
+                .seek(SeekFrom::Start(range.start - 1))
+                .map_err(Error::InputRead)?;
+            let mut skipped = vec![];
+            pos = range.start - 1
+                + reader
+                    .read_until(b'\n', &mut skipped)
+                    .map_err(Error::InputRead)? as u64;
+        }
+        let reader: Box<dyn BufRead> = Box::new(reader);
+        Ok(LineReader::new(reader, pos, range.end))
+    }
+}
+
+impl<R: BufRead> LineReader<R> {
+    /// Read the lines of a reader positioned at `pos`, until a line starts at or after `end`.
+    pub(crate) fn new(reader: R, pos: u64, end: u64) -> Self {
+        LineReader {
+            reader,
+            pos,
+            end,
+            buf: vec![],
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for LineReader<R> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        if self.pos >= self.end {
+            return None;
+        }
+        self.buf.clear();
+        let read = match self.reader.read_until(b'\n', &mut self.buf) {
+            Ok(read) => read,
+            Err(error) => {
+                fail_task(Error::InputRead(error));
+                self.end = self.pos;
+                return None;
+            }
+        };
+        if read == 0 {
+            return None;
+        }
+        self.pos += read as u64;
+        if self.buf.last() == Some(&b'\n') {
+            self.buf.pop();
+            if self.buf.last() == Some(&b'\r') {
+                self.buf.pop();
+            }
+        }
+        Some(String::from_utf8_lossy(&self.buf).into_owned())
+    }
+}
+
+/// Items of a text file RDD built from each line and the partition values of its file.
+pub trait TextLine: Data {
+    fn from_line(line: String, partition_values: &PartitionValues) -> Self;
+}
+
+impl TextLine for String {
+    fn from_line(line: String, _partition_values: &PartitionValues) -> Self {
+        line
+    }
+}
+
+impl TextLine for (String, PartitionValues) {
+    fn from_line(line: String, partition_values: &PartitionValues) -> Self {
+        (line, partition_values.clone())
+    }
+}
+
+/// Lines of text files, read from the local file system of every host.
+///
+/// The files of each host are cut into byte ranges which are dealt among the partitions of
+/// the host, so that large files are read in parallel and lazily, one line at a time. Lines
+/// are read either alone or along with the partition values of their file.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TextFileRdd<T = String> {
+    id: usize,
+    listing: FileListing,
+    partitions_per_host: usize,
+    codec: CodecSelection,
+    #[serde(skip_serializing, skip_deserializing)]
+    context: Arc<Context>,
+    hosts: Vec<SocketAddrV4>,
+    _marker: PhantomData<T>,
+}
+
+impl<T: TextLine> TextFileRdd<T> {
+    pub(crate) fn new(
+        context: Arc<Context>,
+        listing: FileListing,
+        partitions_per_host: usize,
+    ) -> Self {
+        TextFileRdd {
+            id: context.new_rdd_id(),
+            listing,
+            partitions_per_host: partitions_per_host.max(1),
+            codec: CodecSelection::FromExtension,
+            hosts: context.address_map.clone(),
+            context,
+            _marker: PhantomData,
+        }
+    }
+
+    pub(crate) fn codec(mut self, codec: CodecSelection) -> Self {
+        self.codec = codec;
+        self
+    }
+}
+
+impl<T: TextLine> RddBase for TextFileRdd<T> {
+    impl_host_file_rdd_base_funcs!("text_file");
+}
+
+impl<T: TextLine> Rdd for TextFileRdd<T> {
+    type Item = T;
+
+    fn get_rdd(&self) -> Arc<dyn Rdd<Item = Self::Item>> {
+        Arc::new(self.clone())
+    }
+
+    fn get_rdd_base(&self) -> Arc<dyn RddBase> {
+        Arc::new(self.clone()) as Arc<dyn RddBase>
+    }
+
+    fn compute(&self, split: Box<dyn Split>) -> Result<Box<dyn Iterator<Item = Self::Item>>> {
+        let split = split.downcast_ref::<HostSplit>().unwrap();
+        let files = self.listing.list()?;
+        let ranges = split.deal(split_ranges(files, split.num_parts, self.codec));
+        let codec = self.codec;
+        // The files are opened as they are read; one which can't be fails the task.
+        let ranges = ranges.into_iter().take_while(|_| !task_failed());
+        Ok(Box::new(ranges.flat_map(
+            move |range| -> Box<dyn Iterator<Item = T>> {
+                match LineReader::open(&range, codec) {
+                    Ok(lines) => {
+                        Box::new(lines.map(move |line| T::from_line(line, &range.partition_values)))
+                    }
+                    Err(error) => {
+                        fail_task(error);
+                        Box::new(std::iter::empty())
+                    }
+                }
+            },
+        )))
+    }
+}
+
+/// Write the items of an RDD to a directory, as one text file per partition named
+/// `part-NNNNN` with one formatted item per line.
+pub(crate) fn save_as_text_file<T: Data, F>(
+    rdd: Arc<dyn Rdd<Item = T>>,
+    path: PathBuf,
+    mode: SaveMode,
+    format: F,
+) -> Result<()>
+where
+    F: SerFunc(T) -> String,
+{
+    fn write_lines<T, F: Fn(T) -> String>(
+        file: PathBuf,
+        items: Box<dyn Iterator<Item = T>>,
+        format: &F,
+    ) -> Result<()> {
+        let file = fs::get_file_system(&file)?
+            .create(&file, true)
+            .map_err(Error::OutputWrite)?;
+        let mut writer = BufWriter::new(file);
+        for item in items {
+            writer
+                .write_all(format(item).as_bytes())
+                .and_then(|_| writer.write_all(b"\n"))
+                .map_err(Error::OutputWrite)?;
+        }
+        writer.flush().map_err(Error::OutputWrite)
+    }
+
+    let write = Fn!(
+        move |file: PathBuf, items: Box<dyn Iterator<Item = T>>| write_lines(file, items, &format)
+    );
+    write_partitions(rdd, FileOutputCommitter::new(path, mode), None, write)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines_of(path: &std::path::Path, start: u64, end: u64) -> Vec<String> {
+        let range = FileRange {
+            path: path.to_owned(),
+            start,
+            end,
+            partition_values: vec![],
+        };
+        LineReader::open(&range, CodecSelection::FromExtension)
+            .unwrap()
+            .collect()
+    }
+
+    #[test]
+    fn lines_across_ranges() {
+        let text = "first line\r\nsecond\n\nfourth line is long\nlast";
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lines.txt");
+        std::fs::write(&path, text).unwrap();
+        let size = text.len() as u64;
+        let all = lines_of(&path, 0, size);
+        assert_eq!(
+            all,
+            vec!["first line", "second", "", "fourth line is long", "last"]
+        );
+        for range_size in 1..size {
+            let mut lines = vec![];
+            let mut start = 0;
+            while start < size {
+                let end = (start + range_size).min(size);
+                lines.extend(lines_of(&path, start, end));
+                start = end;
+            }
+            assert_eq!(lines, all, "range size {}", range_size);
+        }
+    }
+
+    fn input_files(sizes: &[(&str, u64)]) -> Vec<InputFile> {
+        sizes
+            .iter()
+            .map(|&(path, size)| InputFile {
+                path: path.into(),
+                size,
+                partition_values: vec![],
+            })
+            .collect()
+    }
+
+    #[test]
+    fn ranges_cover_files() {
+        let files = input_files(&[("a", 10), ("b", 0), ("c", 25)]);
+        let ranges = split_ranges(files, 4, CodecSelection::FromExtension);
+        assert_eq!(ranges.len(), 5);
+        assert_eq!(ranges.iter().map(|r| r.end - r.start).sum::<u64>(), 35);
+        assert!(ranges.iter().all(|r| r.end - r.start <= 9));
+
+        // Compressed files are never split.
+        let ranges = split_ranges(
+            input_files(&[("a.gz", 100), ("b.txt", 100)]),
+            4,
+            CodecSelection::FromExtension,
+        );
+        assert_eq!(ranges.len(), 3);
+        assert_eq!((ranges[0].start, ranges[0].end), (0, u64::MAX));
+        let ranges = split_ranges(
+            input_files(&[("a.gz", 100)]),
+            4,
+            CodecSelection::Uncompressed,
+        );
+        assert_eq!(ranges.len(), 4);
+    }
+}
@@ -20,9 +20,11 @@ mod aggregator;
 mod cache;
 mod cache_tracker;
 mod context;
+pub mod dataframe;
 mod dependency;
 mod env;
 mod executor;
+mod future_action;
 pub mod io;
 mod map_output_tracker;
 mod partial;
@@ -46,6 +48,11 @@ pub use serde_traitobject::{Arc as SerArc, Box as SerBox};
 // Re-exports:
 pub use context::Context;
 pub use error::*;
+pub use future_action::FutureAction;
 pub use io::LocalFsReaderConfig;
-pub use partial::BoundedDouble;
-pub use rdd::{PairRdd, Rdd};
+pub use partial::{BoundedDouble, PartialResult};
+pub use rdd::{NumericRdd, PairRdd, Rdd, ResultRdd};
+pub use scheduler::TaskContext;
+pub use utils::hyper_log_log::HyperLogLogPlus;
+pub use utils::quantile_summaries::QuantileSummaries;
+pub use utils::stat_counter::StatCounter;
@@ -7,7 +7,7 @@ use crate::partial::*;
 use crate::scheduler::JobListener;
 use crate::serializable_traits::AnyData;
 use crate::{Error, Result};
-use tokio::sync::Mutex;
+use tokio::sync::{watch, Mutex};
 
 /// A JobListener for an approximate single-result action, such as count() or non-parallel reduce().
 /// This listener waits up to timeout milliseconds and will return a partial answer even if the
@@ -29,6 +29,8 @@ where
     finished_tasks: AtomicUsize,
     /// Set if we've already returned a PartialResult
     result_object: Mutex<Option<PartialResult<R>>>,
+    /// Notified each time a task finishes or the job fails.
+    progress: (watch::Sender<()>, watch::Receiver<()>),
     _marker_r: PhantomData<R>,
     _marker_u: PhantomData<U>,
 }
@@ -48,6 +50,7 @@ where
             total_tasks: num_partitions,
             finished_tasks: AtomicUsize::new(0),
             result_object: Mutex::new(None),
+            progress: watch::channel(()),
             _marker_r: PhantomData,
             _marker_u: PhantomData,
         }
@@ -57,7 +60,8 @@ where
     /// PartialResult with the result so far. This may be complete if the whole job is done.
     pub async fn get_result(&self) -> Result<PartialResult<R>> {
         let finish_time = self.start_time + self.timeout;
-        while Instant::now() < finish_time {
+        let mut progress = self.progress.1.clone();
+        loop {
             {
                 let mut failure = self.failure.lock().await;
                 if failure.is_some() {
@@ -70,12 +74,28 @@ where
                     true,
                 ));
             }
-            tokio::time::delay_for(self.timeout / 20).await;
+            let now = Instant::now();
+            if now >= finish_time {
+                break;
+            }
+            // Wait until a task finishes or the job fails, but not longer than the timeout.
+            if tokio::time::timeout(finish_time - now, progress.recv())
+                .await
+                .is_err()
+            {
+                break;
+            }
         }
-        // Ran out of time before full completion, return partial job
+        // Ran out of time before full completion, return partial job. Holding the lock on
+        // result_object makes sure the last task either sees the result or is seen by us.
+        let mut result_object = self.result_object.lock().await;
         let result = PartialResult::new(self.evaluator.lock().await.current_result(), false);
-        let mut current_result = self.result_object.lock().await;
-        *current_result = Some(result.clone());
+        if let Some(err) = self.failure.lock().await.take() {
+            result.set_failure(err)?;
+        } else if self.finished_tasks.load(Ordering::SeqCst) == self.total_tasks {
+            result.set_final_value(self.evaluator.lock().await.current_result())?;
+        }
+        *result_object = Some(result.clone());
         Ok(result)
     }
 }
@@ -94,18 +114,29 @@ where
             )
         })?;
         self.evaluator.lock().await.merge(index, result);
+        let result_object = self.result_object.lock().await;
         let current_finished = self.finished_tasks.fetch_add(1, Ordering::SeqCst) + 1;
         if current_finished == self.total_tasks {
             // If we had already returned a PartialResult, set its final value
-            if let Some(ref mut value) = *self.result_object.lock().await {
+            if let Some(ref value) = *result_object {
                 value.set_final_value(self.evaluator.lock().await.current_result())?;
             }
         }
+        let _ = self.progress.0.broadcast(());
         Ok(())
     }
 
     async fn job_failed(&self, err: Error) {
-        let mut failure = self.failure.lock().await;
-        *failure = Some(err);
+        let result_object = self.result_object.lock().await;
+        match *result_object {
+            // If we had already returned a PartialResult, fail it
+            Some(ref value) => {
+                if let Err(err) = value.set_failure(err) {
+                    log::error!("failed to set the failure of a partial result: {}", err);
+                }
+            }
+            None => *self.failure.lock().await = Some(err),
+        }
+        let _ = self.progress.0.broadcast(());
     }
 }
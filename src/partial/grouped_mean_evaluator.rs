@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::partial::{
+    approximate_evaluator::ApproximateEvaluator, bounded_double::BoundedDouble,
+    mean_evaluator::mean_bound,
+};
+use crate::serializable_traits::Data;
+use crate::utils::stat_counter::StatCounter;
+
+/// An ApproximateEvaluator for means by key. Returns a map of key to confidence interval.
+pub(crate) struct GroupedMeanEvaluator<T>
+where
+    T: Eq + Hash,
+{
+    total_outputs: usize,
+    confidence: f64,
+    outputs_merged: usize,
+    sums: HashMap<T, StatCounter>,
+}
+
+impl<T: Eq + Hash> GroupedMeanEvaluator<T> {
+    pub fn new(total_outputs: usize, confidence: f64) -> Self {
+        GroupedMeanEvaluator {
+            total_outputs,
+            confidence,
+            outputs_merged: 0,
+            sums: HashMap::new(),
+        }
+    }
+}
+
+impl<T: Data + Eq + Hash> ApproximateEvaluator<HashMap<T, StatCounter>, HashMap<T, BoundedDouble>>
+    for GroupedMeanEvaluator<T>
+{
+    fn merge(&mut self, _output_id: usize, task_result: &HashMap<T, StatCounter>) {
+        self.outputs_merged += 1;
+        task_result.iter().for_each(|(k, v)| {
+            self.sums
+                .entry(k.clone())
+                .or_insert_with(StatCounter::new)
+                .merge(v);
+        });
+    }
+
+    fn current_result(&self) -> HashMap<T, BoundedDouble> {
+        if self.outputs_merged == 0 {
+            HashMap::new()
+        } else if self.outputs_merged == self.total_outputs {
+            self.sums
+                .iter()
+                .map(|(k, counter)| {
+                    let mean = counter.mean();
+                    (k.clone(), BoundedDouble::from((mean, 1.0, mean, mean)))
+                })
+                .collect()
+        } else {
+            self.sums
+                .iter()
+                .map(|(k, counter)| (k.clone(), mean_bound(self.confidence, counter)))
+                .collect()
+        }
+    }
+}
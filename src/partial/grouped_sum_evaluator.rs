@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::partial::{
+    approximate_evaluator::ApproximateEvaluator, bounded_double::BoundedDouble,
+    sum_evaluator::sum_bound,
+};
+use crate::serializable_traits::Data;
+use crate::utils::stat_counter::StatCounter;
+
+/// An ApproximateEvaluator for sums by key. Returns a map of key to confidence interval.
+pub(crate) struct GroupedSumEvaluator<T>
+where
+    T: Eq + Hash,
+{
+    total_outputs: usize,
+    confidence: f64,
+    outputs_merged: usize,
+    sums: HashMap<T, StatCounter>,
+}
+
+impl<T: Eq + Hash> GroupedSumEvaluator<T> {
+    pub fn new(total_outputs: usize, confidence: f64) -> Self {
+        GroupedSumEvaluator {
+            total_outputs,
+            confidence,
+            outputs_merged: 0,
+            sums: HashMap::new(),
+        }
+    }
+}
+
+impl<T: Data + Eq + Hash> ApproximateEvaluator<HashMap<T, StatCounter>, HashMap<T, BoundedDouble>>
+    for GroupedSumEvaluator<T>
+{
+    fn merge(&mut self, _output_id: usize, task_result: &HashMap<T, StatCounter>) {
+        self.outputs_merged += 1;
+        task_result.iter().for_each(|(k, v)| {
+            self.sums
+                .entry(k.clone())
+                .or_insert_with(StatCounter::new)
+                .merge(v);
+        });
+    }
+
+    fn current_result(&self) -> HashMap<T, BoundedDouble> {
+        if self.outputs_merged == 0 {
+            HashMap::new()
+        } else if self.outputs_merged == self.total_outputs {
+            self.sums
+                .iter()
+                .map(|(k, counter)| {
+                    let sum = counter.sum();
+                    (k.clone(), BoundedDouble::from((sum, 1.0, sum, sum)))
+                })
+                .collect()
+        } else {
+            let p = self.outputs_merged as f64 / self.total_outputs as f64;
+            self.sums
+                .iter()
+                .map(|(k, counter)| (k.clone(), sum_bound(self.confidence, counter, p)))
+                .collect()
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use crate::partial::{approximate_evaluator::ApproximateEvaluator, bounded_double::BoundedDouble};
+use crate::utils::stat_counter::StatCounter;
+use statrs::distribution::{Normal, StudentsT, Univariate};
+
+/// An ApproximateEvaluator for means.
+pub(crate) struct MeanEvaluator {
+    total_outputs: usize,
+    confidence: f64,
+    outputs_merged: usize,
+    counter: StatCounter,
+}
+
+impl MeanEvaluator {
+    pub fn new(total_outputs: usize, confidence: f64) -> Self {
+        MeanEvaluator {
+            total_outputs,
+            confidence,
+            outputs_merged: 0,
+            counter: StatCounter::new(),
+        }
+    }
+}
+
+impl ApproximateEvaluator<StatCounter, BoundedDouble> for MeanEvaluator {
+    fn merge(&mut self, _output_id: usize, task_result: &StatCounter) {
+        self.outputs_merged += 1;
+        self.counter.merge(task_result);
+    }
+
+    fn current_result(&self) -> BoundedDouble {
+        if self.outputs_merged == self.total_outputs {
+            let mean = self.counter.mean();
+            BoundedDouble::from((mean, 1.0, mean, mean))
+        } else if self.outputs_merged == 0 {
+            BoundedDouble::from((0.0, 0.0, f64::NEG_INFINITY, f64::INFINITY))
+        } else {
+            mean_bound(self.confidence, &self.counter)
+        }
+    }
+}
+
+pub(super) fn mean_bound(confidence: f64, counter: &StatCounter) -> BoundedDouble {
+    let mean = counter.mean();
+    if counter.count() <= 1 {
+        // not enough values observed to estimate the variance
+        BoundedDouble::from((mean, confidence, f64::NEG_INFINITY, f64::INFINITY))
+    } else {
+        let stdev = (counter.sample_variance() / counter.count() as f64).sqrt();
+        let conf_factor = confidence_factor(confidence, counter.count());
+        let low = mean - conf_factor * stdev;
+        let high = mean + conf_factor * stdev;
+        BoundedDouble::from((mean, confidence, low, high))
+    }
+}
+
+/// Returns the factor by which the standard deviation of an estimate has to be scaled to get
+/// a two-sided interval with the given confidence. Uses the normal distribution when enough
+/// samples have been seen and the Student's t distribution otherwise.
+pub(super) fn confidence_factor(confidence: f64, count: u64) -> f64 {
+    let prob = 1.0 - (1.0 - confidence) / 2.0;
+    if count > 100 {
+        inverse_cdf(&Normal::new(0.0, 1.0).unwrap(), prob)
+    } else {
+        let degrees_of_freedom = (count.max(2) - 1) as f64;
+        inverse_cdf(&StudentsT::new(0.0, 1.0, degrees_of_freedom).unwrap(), prob)
+    }
+}
+
+/// Inverse of the cdf of a distribution symmetric around zero, found by bisection.
+fn inverse_cdf<D: Univariate<f64, f64>>(dist: &D, prob: f64) -> f64 {
+    if prob >= 1.0 {
+        return f64::INFINITY;
+    } else if prob <= 0.5 {
+        return 0.0;
+    }
+    let mut low = 0.0;
+    let mut high = 1.0;
+    while dist.cdf(high) < prob {
+        low = high;
+        high *= 2.0;
+    }
+    for _ in 0..100 {
+        let mid = (low + high) / 2.0;
+        if dist.cdf(mid) < prob {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    (low + high) / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normal_confidence_factor() {
+        assert!((confidence_factor(0.95, 1000) - 1.959964).abs() < 1e-5);
+    }
+
+    #[test]
+    fn students_t_confidence_factor() {
+        // two-sided 95% critical value with 9 degrees of freedom
+        assert!((confidence_factor(0.95, 10) - 2.262157).abs() < 1e-5);
+    }
+}
@@ -7,29 +7,31 @@ pub(self) mod approximate_evaluator;
 mod bounded_double;
 mod count_evaluator;
 mod grouped_count_evaluator;
+mod grouped_mean_evaluator;
+mod grouped_sum_evaluator;
+mod mean_evaluator;
 mod partial_result;
+mod sum_evaluator;
 
 pub(crate) use approximate_action_listener::ApproximateActionListener;
 pub(crate) use approximate_evaluator::ApproximateEvaluator;
 pub use bounded_double::BoundedDouble;
 pub(crate) use count_evaluator::CountEvaluator;
 pub(crate) use grouped_count_evaluator::GroupedCountEvaluator;
-pub use partial_result::PartialResult;
+pub(crate) use grouped_mean_evaluator::GroupedMeanEvaluator;
+pub(crate) use grouped_sum_evaluator::GroupedSumEvaluator;
+pub(crate) use mean_evaluator::MeanEvaluator;
+pub use partial_result::{FinalValue, PartialResult};
+pub(crate) use sum_evaluator::SumEvaluator;
 
 #[derive(Debug, Error)]
 pub enum PartialJobError {
-    #[error("on_fail cannot be called twice")]
-    SetOnFailTwice,
-
     #[error("set_failure called twice on a PartialResult")]
     SetFailureValTwice,
 
     #[error("set_final_value called twice on a PartialResult")]
     SetFinalValTwice,
 
-    #[error("on_complete cannot be called twice")]
-    SetOnCompleteTwice,
-
-    #[error("unreachable")]
-    None,
+    #[error("the approximate job failed: {0}")]
+    JobFailed(String),
 }
@@ -1,103 +1,62 @@
 use std::fmt::Debug;
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
 
 use crate::partial::PartialJobError;
 use crate::{Error, Result};
-use parking_lot::Mutex;
+use parking_lot::{Condvar, Mutex};
 
-type OnComplete<R> = Arc<
-    dyn Fn(Arc<PartialResult<R>>, Box<dyn Fn(R) + Send + Sync>) -> Result<Arc<PartialResult<R>>>
-        + Send
-        + Sync,
->;
-type OnFail<R> = Arc<
-    dyn Fn(Arc<PartialResult<R>>, Box<dyn Fn(&Error) + Send + Sync>) -> Result<()> + Send + Sync,
->;
+type CompletionHandler<R> = Box<dyn FnOnce(R) + Send>;
+type FailureHandler = Box<dyn FnOnce(&Error) + Send>;
 
+/// The result of an approximate job. It holds the value available when the job returned, which
+/// may be or not the final value, and will eventually hold the final value (or the failure) once
+/// the job completes.
+///
+/// Clones of a PartialResult share the same final value; waiting for it never polls, the waiting
+/// threads and tasks are notified as soon as the job completes.
 #[derive(Clone)]
 pub struct PartialResult<R>
 where
     R: Clone + Debug,
 {
-    final_value: Arc<Mutex<Option<R>>>,
-    failure: Arc<Mutex<Option<Error>>>,
-    completion_handler: Arc<Mutex<Option<Box<dyn Fn(R) + Send + Sync>>>>,
-    failure_handler: Arc<Mutex<Option<Box<dyn Fn(&Error) + Send + Sync>>>>,
+    shared: Arc<Shared<R>>,
     /// The partial result initial value, which may be or not the final value.
     pub initial_value: R,
     /// Whether this is the final value or not.
     pub is_final: bool,
-    // funcs:
-    _get_final_value: Arc<dyn Fn(Arc<PartialResult<R>>) -> Result<R> + Send + Sync>,
-    /// Set a handler to be called when this PartialResult completes. Only one completion handler
-    /// is supported per PartialResult.
-    on_complete: OnComplete<R>,
-    /// Set a handler to be called if this PartialResult's job fails. Only one failure handler
-    /// is supported per PartialResult
-    on_fail: OnFail<R>,
 }
 
-#[inline(always)]
-fn _internal_get_final_value<R>(this: Arc<PartialResult<R>>) -> Result<R>
-where
-    R: Clone + Debug + Send + Sync + 'static,
-{
-    while this.final_value.lock().is_none() && this.failure.lock().is_none() {
-        // TODO: improve this with channels for notification
-        std::thread::sleep_ms(5);
-    }
+struct Shared<R> {
+    state: Mutex<State<R>>,
+    completed: Condvar,
+}
 
-    let final_value = &mut *this.final_value.lock();
-    let failure = &mut *this.failure.lock();
-    if final_value.is_some() {
-        Ok(final_value.take().ok_or_else(|| PartialJobError::None)?)
-    } else {
-        Err(failure.take().ok_or_else(|| PartialJobError::None)?)
-    }
+struct State<R> {
+    final_value: Option<R>,
+    failure: Option<Error>,
+    completion_handlers: Vec<CompletionHandler<R>>,
+    failure_handlers: Vec<FailureHandler>,
+    wakers: Vec<Waker>,
 }
 
-#[inline(always)]
-fn _internal_on_complete<R>(
-    this: Arc<PartialResult<R>>,
-    handler: Box<dyn Fn(R) + Send + Sync>,
-) -> Result<Arc<PartialResult<R>>>
-where
-    R: Clone + Debug + Send + Sync + 'static,
-{
-    {
-        let mut completion_handler = this.completion_handler.lock();
-        if completion_handler.is_some() {
-            return Err(PartialJobError::SetOnCompleteTwice.into());
-        }
-        if let Some(final_value) = this.final_value.lock().take() {
-            // We already have a final value, so let's call the handler
-            handler(final_value);
-        }
-        completion_handler.replace(handler);
+impl<R: Clone> State<R> {
+    fn is_completed(&self) -> bool {
+        self.final_value.is_some() || self.failure.is_some()
     }
-    Ok(this)
-}
 
-#[inline(always)]
-fn _internal_on_fail<R>(
-    this: Arc<PartialResult<R>>,
-    handler: Box<dyn Fn(&Error) + Send + Sync>,
-) -> Result<()>
-where
-    R: Clone + Debug + Send + Sync + 'static,
-{
-    {
-        let mut failure_handler = this.failure_handler.lock();
-        if failure_handler.is_some() {
-            return Err(PartialJobError::SetOnFailTwice.into());
-        }
-        if let Some(ref failure) = *this.failure.lock() {
-            // We already have a failure, so let's call the handler
-            handler(failure);
+    /// Returns the outcome of the job, if it already completed. The original failure is handed
+    /// to the first caller.
+    fn outcome(&mut self) -> Option<Result<R>> {
+        if let Some(ref value) = self.final_value {
+            return Some(Ok(value.clone()));
         }
-        failure_handler.replace(handler);
+        let failure = self.failure.take()?;
+        self.failure = Some(failure_of(&failure));
+        Some(Err(failure))
     }
-    Ok(())
 }
 
 impl<R> PartialResult<R>
@@ -105,123 +64,171 @@ where
     R: Clone + Debug + Send + Sync + 'static,
 {
     pub(crate) fn new(initial_value: R, is_final: bool) -> PartialResult<R> {
-        let _get_final_value = Arc::new(|this: Arc<PartialResult<R>>| -> Result<R> {
-            _internal_get_final_value(this)
-        });
-
-        let on_complete = Arc::new(
-            |this: Arc<PartialResult<R>>,
-             handler: Box<dyn Fn(R) + Send + Sync>|
-             -> Result<Arc<PartialResult<R>>> { _internal_on_complete(this, handler) },
-        );
-
-        let on_fail = Arc::new(
-            |this: Arc<PartialResult<R>>,
-             handler: Box<dyn Fn(&Error) + Send + Sync>|
-             -> Result<()> { _internal_on_fail(this, handler) },
-        );
-
         let final_value = if is_final {
-            Arc::new(Mutex::new(Some(initial_value.clone())))
+            Some(initial_value.clone())
         } else {
-            Arc::new(Mutex::new(None))
+            None
         };
-
-        PartialResult {
+        let state = State {
             final_value,
-            failure: Arc::new(Mutex::new(None)),
-            completion_handler: Arc::new(Mutex::new(None)),
-            failure_handler: Arc::new(Mutex::new(None)),
+            failure: None,
+            completion_handlers: Vec::new(),
+            failure_handlers: Vec::new(),
+            wakers: Vec::new(),
+        };
+        PartialResult {
+            shared: Arc::new(Shared {
+                state: Mutex::new(state),
+                completed: Condvar::new(),
+            }),
             initial_value,
             is_final,
-            _get_final_value,
-            on_complete,
-            on_fail,
         }
     }
 
     /// Blocking method to wait for and return the final value.
-    pub fn get_final_value(self: Self) -> Result<R> {
-        let selfc = Arc::new(self);
-        (selfc._get_final_value)(selfc.clone())
+    pub fn get_final_value(&self) -> Result<R> {
+        let mut state = self.shared.state.lock();
+        loop {
+            if let Some(outcome) = state.outcome() {
+                return outcome;
+            }
+            self.shared.completed.wait(&mut state);
+        }
     }
 
-    pub(crate) fn set_final_value(&mut self, value: R) -> Result<()> {
-        let final_value = &mut *self.final_value.lock();
-        if final_value.is_some() {
-            return Err(PartialJobError::SetFinalValTwice.into());
-        }
-        // Call the completion handler if it was set
-        if let Some(ref handler) = *self.completion_handler.lock() {
-            handler(value.clone());
+    /// Returns a future which resolves to the final value once the job completes.
+    pub fn final_value(&self) -> FinalValue<R> {
+        FinalValue {
+            shared: self.shared.clone(),
         }
-        *final_value = Some(value);
-        Ok(())
     }
 
-    /// Transform this PartialResult into a PartialResult of type T.
-    pub(crate) fn map<T>(self: Arc<Self>) -> Result<PartialResult<T>>
+    /// Set a handler to be called when this PartialResult completes. If the final value is
+    /// already available the handler is called immediately.
+    pub fn on_complete<F>(&self, handler: F) -> &Self
     where
-        T: From<R> + Debug + Send + Sync + Clone + 'static,
+        F: FnOnce(R) + Send + 'static,
     {
-        let initial_value = self.initial_value.clone().into();
-        let mut new_partial_res: PartialResult<T> =
-            PartialResult::new(initial_value, self.is_final);
-
-        // override the current closures
-        let sc = self.clone();
-        new_partial_res._get_final_value =
-            Arc::new(move |_this: Arc<PartialResult<T>>| -> Result<T> {
-                let ori_val = (sc._get_final_value)(sc.clone())?;
-                let transformed = ori_val.into();
-                Ok(transformed)
-            });
+        let final_value = {
+            let mut state = self.shared.state.lock();
+            match state.final_value {
+                Some(ref value) => value.clone(),
+                None => {
+                    if state.failure.is_none() {
+                        state.completion_handlers.push(Box::new(handler));
+                    }
+                    return self;
+                }
+            }
+        };
+        handler(final_value);
+        self
+    }
 
-        let sc = self.clone();
-        new_partial_res.on_fail = Arc::new(
-            move |_this: Arc<PartialResult<T>>,
-                  handler: Box<dyn Fn(&Error) + Send + Sync>|
-                  -> Result<()> {
-                (sc.on_fail)(sc.clone(), handler)?;
-                Ok(())
-            },
-        );
+    /// Set a handler to be called if this PartialResult's job fails. If the job already failed
+    /// the handler is called immediately. The handler receives an error carrying the message of
+    /// the failure, the original error is returned when waiting for the final value.
+    pub fn on_fail<F>(&self, handler: F) -> &Self
+    where
+        F: FnOnce(&Error) + Send + 'static,
+    {
+        let failure = {
+            let mut state = self.shared.state.lock();
+            match state.failure {
+                Some(ref failure) => failure_of(failure),
+                None => {
+                    if state.final_value.is_none() {
+                        state.failure_handlers.push(Box::new(handler));
+                    }
+                    return self;
+                }
+            }
+        };
+        handler(&failure);
+        self
+    }
 
-        new_partial_res.on_complete = Arc::new(
-            move |_this: Arc<PartialResult<T>>,
-                  handler: Box<dyn Fn(T) + Send + Sync>|
-                  -> Result<Arc<PartialResult<T>>> {
-                let transformed_handler = Box::new(move |res: R| handler(res.into()));
-                let res: Arc<PartialResult<R>> =
-                    (self.on_complete)(self.clone(), transformed_handler)?;
-                Ok(Arc::new(res.map::<T>()?))
-            },
-        );
+    /// Transform this PartialResult into a PartialResult of type T. The function is applied to
+    /// the initial value right away and to the final value once it is available.
+    pub fn map<T, F>(&self, func: F) -> PartialResult<T>
+    where
+        T: Clone + Debug + Send + Sync + 'static,
+        F: FnOnce(R) -> T + Clone + Send + 'static,
+    {
+        let mapped = PartialResult::new(func.clone()(self.initial_value.clone()), self.is_final);
+        if !self.is_final {
+            let on_complete = mapped.clone();
+            self.on_complete(move |value| {
+                let _ = on_complete.set_final_value(func(value));
+            });
+            let on_fail = mapped.clone();
+            self.on_fail(move |err| {
+                let _ = on_fail.set_failure(failure_of(err));
+            });
+        }
+        mapped
+    }
 
-        Ok(new_partial_res)
+    pub(crate) fn set_final_value(&self, value: R) -> Result<()> {
+        let (handlers, wakers) = {
+            let mut state = self.shared.state.lock();
+            if state.is_completed() {
+                return Err(PartialJobError::SetFinalValTwice.into());
+            }
+            state.final_value = Some(value.clone());
+            state.failure_handlers.clear();
+            (
+                std::mem::replace(&mut state.completion_handlers, Vec::new()),
+                std::mem::replace(&mut state.wakers, Vec::new()),
+            )
+        };
+        self.shared.completed.notify_all();
+        wakers.into_iter().for_each(Waker::wake);
+        // Handlers are called without holding the lock, so they can use this PartialResult.
+        handlers
+            .into_iter()
+            .for_each(|handler| handler(value.clone()));
+        Ok(())
     }
 
-    pub(crate) fn set_failure(&mut self, err: Error) -> Result<()> {
-        let mut failure = self.failure.lock();
-        if failure.is_some() {
-            // We already have a failure, so let's call the handler
-            return Err(PartialJobError::SetFailureValTwice.into());
-        }
-        // // Call the failure handler if it was set
-        if let Some(ref handler) = *self.failure_handler.lock() {
-            handler(&err);
-        }
-        failure.replace(err);
+    pub(crate) fn set_failure(&self, err: Error) -> Result<()> {
+        let failure = failure_of(&err);
+        let (handlers, wakers) = {
+            let mut state = self.shared.state.lock();
+            if state.is_completed() {
+                return Err(PartialJobError::SetFailureValTwice.into());
+            }
+            state.failure = Some(err);
+            state.completion_handlers.clear();
+            (
+                std::mem::replace(&mut state.failure_handlers, Vec::new()),
+                std::mem::replace(&mut state.wakers, Vec::new()),
+            )
+        };
+        self.shared.completed.notify_all();
+        wakers.into_iter().for_each(Waker::wake);
+        handlers.into_iter().for_each(|handler| handler(&failure));
         Ok(())
     }
 }
 
+/// Errors can't be cloned, so everything but the first consumer of a failure gets an error
+/// carrying the same message.
+fn failure_of(err: &Error) -> Error {
+    let message = match err {
+        Error::PartialJobError(PartialJobError::JobFailed(message)) => message.clone(),
+        err => err.to_string(),
+    };
+    PartialJobError::JobFailed(message).into()
+}
+
 impl<R> Debug for PartialResult<R>
 where
     R: Clone + Debug,
 {
     fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
-        match *self.final_value.lock() {
+        match self.shared.state.lock().final_value {
             Some(ref value) => write!(fmt, "PartialResult {{ final: {:?} }})", value),
             None => write!(
                 fmt,
@@ -231,3 +238,78 @@ where
         }
     }
 }
+
+/// Future for the final value of a [PartialResult](struct.PartialResult.html), returned by
+/// [final_value](struct.PartialResult.html#method.final_value).
+pub struct FinalValue<R> {
+    shared: Arc<Shared<R>>,
+}
+
+impl<R: Clone> Future for FinalValue<R> {
+    type Output = Result<R>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.shared.state.lock();
+        match state.outcome() {
+            Some(outcome) => Poll::Ready(outcome),
+            None => {
+                if !state.wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+                    state.wakers.push(cx.waker().clone());
+                }
+                Poll::Pending
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn wait_for_final_value() {
+        let result = PartialResult::new(1, false);
+        let completed = Arc::new(AtomicUsize::new(0));
+        for _ in 0..2 {
+            let completed = completed.clone();
+            result.on_complete(move |value| {
+                completed.fetch_add(value, Ordering::SeqCst);
+            });
+        }
+        let mapped = result.map(|value| value.to_string());
+        assert_eq!(mapped.initial_value, "1");
+
+        let setter = result.clone();
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            setter.set_final_value(10).unwrap();
+        });
+        assert_eq!(result.get_final_value().unwrap(), 10);
+        assert_eq!(
+            futures::executor::block_on(mapped.final_value()).unwrap(),
+            "10"
+        );
+        handle.join().unwrap();
+        assert_eq!(completed.load(Ordering::SeqCst), 20);
+        assert!(result.set_final_value(11).is_err());
+    }
+
+    #[test]
+    fn propagate_failure() {
+        let result = PartialResult::new(1, false);
+        let mapped = result.map(|value| value * 2);
+        let failed = Arc::new(AtomicUsize::new(0));
+        let on_fail = failed.clone();
+        mapped.on_fail(move |_| {
+            on_fail.fetch_add(1, Ordering::SeqCst);
+        });
+        result.set_failure(Error::Other).unwrap();
+        assert!(matches!(result.get_final_value(), Err(Error::Other)));
+        assert!(result.get_final_value().is_err());
+        assert!(futures::executor::block_on(mapped.final_value()).is_err());
+        assert_eq!(failed.load(Ordering::SeqCst), 1);
+    }
+}
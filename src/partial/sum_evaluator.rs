@@ -0,0 +1,70 @@
+use crate::partial::{
+    approximate_evaluator::ApproximateEvaluator, bounded_double::BoundedDouble,
+    mean_evaluator::confidence_factor,
+};
+use crate::utils::stat_counter::StatCounter;
+
+/// An ApproximateEvaluator for sums. It estimates the mean and the count and multiplies them
+/// together, then uses the formula for the variance of two independent random variables to get
+/// a variance for the result and compute a confidence interval.
+pub(crate) struct SumEvaluator {
+    total_outputs: usize,
+    confidence: f64,
+    outputs_merged: usize,
+    counter: StatCounter,
+}
+
+impl SumEvaluator {
+    pub fn new(total_outputs: usize, confidence: f64) -> Self {
+        SumEvaluator {
+            total_outputs,
+            confidence,
+            outputs_merged: 0,
+            counter: StatCounter::new(),
+        }
+    }
+}
+
+impl ApproximateEvaluator<StatCounter, BoundedDouble> for SumEvaluator {
+    fn merge(&mut self, _output_id: usize, task_result: &StatCounter) {
+        self.outputs_merged += 1;
+        self.counter.merge(task_result);
+    }
+
+    fn current_result(&self) -> BoundedDouble {
+        if self.outputs_merged == self.total_outputs {
+            let sum = self.counter.sum();
+            BoundedDouble::from((sum, 1.0, sum, sum))
+        } else if self.outputs_merged == 0 {
+            BoundedDouble::from((0.0, 0.0, f64::NEG_INFINITY, f64::INFINITY))
+        } else {
+            let p = self.outputs_merged as f64 / self.total_outputs as f64;
+            sum_bound(self.confidence, &self.counter, p)
+        }
+    }
+}
+
+pub(super) fn sum_bound(confidence: f64, counter: &StatCounter, p: f64) -> BoundedDouble {
+    // Expected value for the count of the whole data set after having scanned a fraction p of it
+    let count = counter.count() as f64;
+    let count_estimate = (count + 1.0 - p) / p;
+    let mean_estimate = counter.mean();
+    let sum_estimate = mean_estimate * count_estimate;
+
+    let mean_var = counter.sample_variance() / count;
+    if mean_var.is_nan() || counter.count() == 0 {
+        // not enough values observed to estimate the variance
+        return BoundedDouble::from((sum_estimate, confidence, f64::NEG_INFINITY, f64::INFINITY));
+    }
+
+    // Variance of the product of the (independent) mean and count estimates
+    let count_var = (count + 1.0) * (1.0 - p) / (p * p);
+    let sum_var = mean_estimate * mean_estimate * count_var
+        + count_estimate * count_estimate * mean_var
+        + mean_var * count_var;
+    let sum_stdev = sum_var.sqrt();
+    let conf_factor = confidence_factor(confidence, counter.count());
+    let low = sum_estimate - conf_factor * sum_stdev;
+    let high = sum_estimate + conf_factor * sum_stdev;
+    BoundedDouble::from((sum_estimate, confidence, low, high))
+}
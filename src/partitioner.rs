@@ -57,6 +57,51 @@ impl<K: Data + Hash + Eq> Partitioner for HashPartitioner<K> {
     }
 }
 
+/// Partitioner which assigns sorted ranges of keys to the partitions, so that all the keys of a
+/// partition are smaller than the keys of the next one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RangePartitioner<K: Data + Ord> {
+    /// Upper bound (inclusive) of the keys of each partition but the last one.
+    bounds: Vec<K>,
+}
+
+impl<K: Data + Ord> RangePartitioner<K> {
+    pub fn new(mut bounds: Vec<K>) -> Self {
+        bounds.sort();
+        bounds.dedup();
+        RangePartitioner { bounds }
+    }
+
+    /// Compute the bounds of at most `partitions` ranges holding about the same number of
+    /// keys, from a sample of the keys.
+    pub fn from_sample(partitions: usize, mut sample: Vec<K>) -> Self {
+        sample.sort();
+        let bounds = (1..partitions)
+            .filter_map(|i| sample.get(i * sample.len() / partitions).cloned())
+            .collect();
+        RangePartitioner::new(bounds)
+    }
+}
+
+impl<K: Data + Ord> Partitioner for RangePartitioner<K> {
+    fn equals(&self, other: &dyn Any) -> bool {
+        if let Some(rp) = other.downcast_ref::<RangePartitioner<K>>() {
+            self.bounds == rp.bounds
+        } else {
+            false
+        }
+    }
+    fn get_num_of_partitions(&self) -> usize {
+        self.bounds.len() + 1
+    }
+    fn get_partition(&self, key: &dyn Any) -> usize {
+        let key = key.downcast_ref::<K>().unwrap();
+        match self.bounds.binary_search(key) {
+            Ok(index) | Err(index) => index,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -117,4 +162,16 @@ mod tests {
         let p2_2 = Box::new(p2_2) as Box<dyn Partitioner>;
         assert!(p2_1.equals((&*p2_2).as_any()))
     }
+
+    #[test]
+    fn range_partition() {
+        let range_partitioner = RangePartitioner::from_sample(3, (0..30).rev().collect());
+        assert_eq!(range_partitioner.get_num_of_partitions(), 3);
+        let partitions = [0, 9, 10, 19, 20, 29, 100]
+            .iter()
+            .map(|i| range_partitioner.get_partition(i))
+            .collect::<Vec<_>>();
+        assert_eq!(partitions, vec![0, 0, 0, 1, 1, 2, 2]);
+        assert!(range_partitioner.equals(&RangePartitioner::new(vec![20, 10])));
+    }
 }
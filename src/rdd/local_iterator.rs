@@ -0,0 +1,117 @@
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use crate::context::Context;
+use crate::error::Result;
+use crate::rdd::Rdd;
+use crate::serializable_traits::Data;
+use crate::Fn;
+
+/// Iterator over the elements of an RDD which only fetches a window of partitions to the driver
+/// at a time. Returned by [to_local_iterator](trait.Rdd.html#method.to_local_iterator).
+///
+/// If one of the jobs fails the error is returned and the iteration stops.
+pub struct LocalIterator<T: Data> {
+    context: Arc<Context>,
+    rdd: Arc<dyn Rdd<Item = T>>,
+    num_partitions: usize,
+    /// First partition which has not been requested yet.
+    next_partition: usize,
+    partitions_per_job: usize,
+    prefetch: bool,
+    current: std::vec::IntoIter<T>,
+    pending: Option<JoinHandle<Result<Vec<Vec<T>>>>>,
+    failed: bool,
+}
+
+impl<T: Data> LocalIterator<T> {
+    pub(crate) fn new(
+        rdd: Arc<dyn Rdd<Item = T>>,
+        partitions_per_job: usize,
+        prefetch: bool,
+    ) -> Self {
+        assert!(
+            partitions_per_job >= 1,
+            "at least one partition must be fetched per job"
+        );
+        LocalIterator {
+            context: rdd.get_context(),
+            num_partitions: rdd.number_of_splits(),
+            rdd,
+            next_partition: 0,
+            partitions_per_job,
+            prefetch,
+            current: Vec::new().into_iter(),
+            pending: None,
+            failed: false,
+        }
+    }
+
+    /// Returns the range of the next window of partitions, if any partition is left.
+    fn next_window(&mut self) -> Option<std::ops::Range<usize>> {
+        if self.next_partition >= self.num_partitions {
+            return None;
+        }
+        let start = self.next_partition;
+        self.next_partition = (start + self.partitions_per_job).min(self.num_partitions);
+        Some(start..self.next_partition)
+    }
+
+    fn fetch(
+        context: Arc<Context>,
+        rdd: Arc<dyn Rdd<Item = T>>,
+        partitions: std::ops::Range<usize>,
+    ) -> Result<Vec<Vec<T>>> {
+        let collect_partition = Fn!(|iter: Box<dyn Iterator<Item = T>>| iter.collect::<Vec<T>>());
+        context.run_job_with_partitions(rdd, collect_partition, partitions)
+    }
+
+    fn start_prefetch(&mut self) {
+        if let Some(window) = self.next_window() {
+            let context = self.context.clone();
+            let rdd = self.rdd.clone();
+            self.pending = Some(thread::spawn(move || Self::fetch(context, rdd, window)));
+        }
+    }
+}
+
+impl<T: Data> Iterator for LocalIterator<T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.current.next() {
+                return Some(Ok(item));
+            }
+            if self.failed {
+                return None;
+            }
+            let fetched = if let Some(pending) = self.pending.take() {
+                match pending.join() {
+                    Ok(fetched) => fetched,
+                    Err(panic) => std::panic::resume_unwind(panic),
+                }
+            } else if let Some(window) = self.next_window() {
+                Self::fetch(self.context.clone(), self.rdd.clone(), window)
+            } else {
+                return None;
+            };
+            match fetched {
+                Ok(partitions) => {
+                    self.current = partitions
+                        .into_iter()
+                        .flatten()
+                        .collect::<Vec<_>>()
+                        .into_iter();
+                }
+                Err(err) => {
+                    self.failed = true;
+                    return Some(Err(err));
+                }
+            }
+            if self.prefetch {
+                self.start_prefetch();
+            }
+        }
+    }
+}
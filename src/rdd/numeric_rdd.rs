@@ -0,0 +1,329 @@
+use std::cmp::Ordering;
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+use crate::partial::{BoundedDouble, MeanEvaluator, PartialResult, SumEvaluator};
+use crate::rdd::Rdd;
+use crate::scheduler::TaskContext;
+use crate::serializable_traits::{Data, SerFunc};
+use crate::utils::quantile_summaries::QuantileSummaries;
+use crate::utils::stat_counter::StatCounter;
+use crate::{Fn, SerArc};
+
+/// Relative tolerance used when checking whether a set of buckets is evenly spaced.
+const EVEN_BUCKETS_EPSILON: f64 = 1e-9;
+
+// Trait containing numeric rdd methods. No need of implicit conversion like in Spark version.
+pub trait NumericRdd<T: Data + Into<f64>>: Rdd<Item = T> + Send + Sync {
+    /// Return a StatCounter object that captures the mean, variance and count of the
+    /// RDD's elements in one operation.
+    fn stats(&self) -> Result<StatCounter>
+    where
+        Self: Sized,
+    {
+        let merge_value = Fn!(|mut counter: StatCounter, item: T| {
+            counter.merge_value(item.into());
+            counter
+        });
+        let merge_counters = Fn!(|mut counter: StatCounter, other: StatCounter| {
+            counter.merge(&other);
+            counter
+        });
+        self.aggregate(StatCounter::new(), merge_value, merge_counters)
+    }
+
+    /// Add up the elements in this RDD.
+    fn sum(&self) -> Result<f64>
+    where
+        Self: Sized,
+    {
+        let add_value = Fn!(|acc: f64, item: T| acc + item.into());
+        let add_partials = Fn!(|a: f64, b: f64| a + b);
+        self.aggregate(0.0, add_value, add_partials)
+    }
+
+    /// Compute the mean of this RDD's elements.
+    fn mean(&self) -> Result<f64>
+    where
+        Self: Sized,
+    {
+        Ok(self.stats()?.mean())
+    }
+
+    /// Compute the population variance of this RDD's elements.
+    fn variance(&self) -> Result<f64>
+    where
+        Self: Sized,
+    {
+        Ok(self.stats()?.variance())
+    }
+
+    /// Compute the population standard deviation of this RDD's elements.
+    fn stdev(&self) -> Result<f64>
+    where
+        Self: Sized,
+    {
+        Ok(self.stats()?.stdev())
+    }
+
+    /// Compute the sample variance of this RDD's elements (which corrects for bias in
+    /// estimating the variance by dividing by N-1 instead of N).
+    fn sample_variance(&self) -> Result<f64>
+    where
+        Self: Sized,
+    {
+        Ok(self.stats()?.sample_variance())
+    }
+
+    /// Compute the sample standard deviation of this RDD's elements (which corrects for bias in
+    /// estimating the standard deviation by dividing by N-1 instead of N).
+    fn sample_stdev(&self) -> Result<f64>
+    where
+        Self: Sized,
+    {
+        Ok(self.stats()?.sample_stdev())
+    }
+
+    /// Approximate operation to return the sum within a timeout.
+    ///
+    /// # Arguments
+    /// * `timeout` - maximum time to wait for the job, in milliseconds
+    /// * `confidence` - the desired statistical confidence in the result
+    fn sum_approx(
+        &self,
+        timeout: Duration,
+        confidence: Option<f64>,
+    ) -> Result<PartialResult<BoundedDouble>>
+    where
+        Self: Sized,
+    {
+        let confidence = confidence.unwrap_or(0.95);
+        assert!(0.0 <= confidence && confidence <= 1.0);
+
+        let evaluator = SumEvaluator::new(self.number_of_splits(), confidence);
+        let rdd = self.get_rdd();
+        rdd.register_op_name("sum_approx");
+        self.get_context()
+            .run_approximate_job(stats_partition::<T>(), rdd, evaluator, timeout)
+    }
+
+    /// Approximate operation to return the mean within a timeout.
+    ///
+    /// # Arguments
+    /// * `timeout` - maximum time to wait for the job, in milliseconds
+    /// * `confidence` - the desired statistical confidence in the result
+    fn mean_approx(
+        &self,
+        timeout: Duration,
+        confidence: Option<f64>,
+    ) -> Result<PartialResult<BoundedDouble>>
+    where
+        Self: Sized,
+    {
+        let confidence = confidence.unwrap_or(0.95);
+        assert!(0.0 <= confidence && confidence <= 1.0);
+
+        let evaluator = MeanEvaluator::new(self.number_of_splits(), confidence);
+        let rdd = self.get_rdd();
+        rdd.register_op_name("mean_approx");
+        self.get_context()
+            .run_approximate_job(stats_partition::<T>(), rdd, evaluator, timeout)
+    }
+
+    /// Calculates the approximate quantiles of the RDD's elements.
+    ///
+    /// This is a variant of the Greenwald-Khanna algorithm: if the RDD has N elements and the
+    /// quantile at probability `p` is requested up to error `err`, then a sample `x` from the RDD
+    /// is returned whose *exact* rank is close to (p * N), roughly
+    /// floor((p - err) * N) <= rank(x) <= ceil((p + err) * N).
+    ///
+    /// NaN values are ignored. Returns an empty vector if the RDD contains no values.
+    ///
+    /// # Arguments
+    /// * `probabilities` - a list of quantile probabilities, each of them must belong to [0, 1]
+    /// * `relative_error` - the relative target precision to achieve (greater than 0)
+    fn approx_quantiles(&self, probabilities: &[f64], relative_error: f64) -> Result<Vec<f64>>
+    where
+        Self: Sized,
+    {
+        assert!(
+            probabilities.iter().all(|p| *p >= 0.0 && *p <= 1.0),
+            "quantile probabilities must be in the range [0, 1]"
+        );
+        let insert = Fn!(|mut summary: QuantileSummaries, item: T| {
+            summary.insert(item.into());
+            summary
+        });
+        let merge = Fn!(|mut summary: QuantileSummaries, other: QuantileSummaries| {
+            summary.merge(other);
+            summary
+        });
+        let mut summary = self.aggregate(QuantileSummaries::new(relative_error), insert, merge)?;
+        summary.compress();
+        Ok(probabilities
+            .iter()
+            .filter_map(|p| summary.query(*p))
+            .collect())
+    }
+
+    /// Compute a histogram of the data using `bucket_count` number of buckets evenly
+    /// spaced between the minimum and maximum of the RDD. For example if the min value is 0
+    /// and the max is 100 and there are two buckets the resulting buckets will be [0, 50) [50, 100].
+    /// `bucket_count` must be at least 1.
+    ///
+    /// Returns a tuple of buckets and histogram.
+    ///
+    /// # Notes
+    ///
+    /// If the RDD contains infinity or NaN, or it is empty, an error is returned.
+    fn histogram(&self, bucket_count: usize) -> Result<(Vec<f64>, Vec<u64>)>
+    where
+        Self: Sized,
+    {
+        assert!(
+            bucket_count >= 1,
+            format!("Bucket count must be at least 1 but got {}", bucket_count)
+        );
+        // Compute the minimum and the maximum
+        let stats = self.stats()?;
+        let (min, max) = (stats.min(), stats.max());
+        // The minimum and maximum ignore NaN, while a NaN value makes the mean NaN.
+        let has_nan = stats.mean().is_nan();
+        if stats.count() == 0 || has_nan || !min.is_finite() || !max.is_finite() {
+            return Err(Error::UnsupportedOperation(
+                "histogram on either an empty RDD or RDD containing +/-infinity or NaN",
+            ));
+        }
+        let buckets = if min != max {
+            // Compute the buckets from the range, the last bucket is always the maximum value
+            // in order to avoid rounding errors leaving it out.
+            let span = max - min;
+            let mut buckets: Vec<f64> = (0..bucket_count)
+                .map(|i| min + i as f64 * span / bucket_count as f64)
+                .collect();
+            buckets.push(max);
+            buckets
+        } else {
+            vec![min, min]
+        };
+        let histogram = compute_histogram(self, buckets.clone(), true)?;
+        Ok((buckets, histogram))
+    }
+
+    /// Compute a histogram using the provided buckets. The buckets are all open
+    /// to the right except for the last which is closed.
+    /// e.g. for the array [1, 10, 20, 50] the buckets are [1, 10) [10, 20) [20, 50]
+    /// e.g. `<=x<10, 10<=x<20, 20<=x<=50`.
+    /// And on the input of 1 and 50 we would have a histogram of 1, 0, 1.
+    ///
+    /// # Notes
+    ///
+    /// If the buckets are evenly spaced (e.g. [0, 10, 20, 30]), this is switched from an
+    /// O(log n) insertion to O(1) per element.
+    ///
+    /// Buckets must be sorted, not contain any duplicates, and have at least two elements.
+    fn histogram_with_buckets(&self, buckets: &[f64]) -> Result<Vec<u64>>
+    where
+        Self: Sized,
+    {
+        assert!(
+            buckets.len() >= 2,
+            "buckets array must have at least two elements"
+        );
+        assert!(
+            buckets
+                .windows(2)
+                .all(|w| w[0].partial_cmp(&w[1]) == Some(Ordering::Less)),
+            "buckets array must be sorted and not contain duplicates"
+        );
+        let even_buckets = is_evenly_spaced(buckets);
+        compute_histogram(self, buckets.to_vec(), even_buckets)
+    }
+}
+
+// Implementing the NumericRdd trait for all types which implements Rdd
+impl<T: Data + Into<f64>, R> NumericRdd<T> for R where R: Rdd<Item = T> {}
+impl<T: Data + Into<f64>, R> NumericRdd<T> for SerArc<R> where R: Rdd<Item = T> {}
+
+/// Per partition function which summarizes the partition in a StatCounter.
+fn stats_partition<T: Data + Into<f64>>(
+) -> impl SerFunc((TaskContext, Box<dyn Iterator<Item = T>>)) -> StatCounter {
+    Fn!(
+        |(_ctx, iter): (TaskContext, Box<dyn Iterator<Item = T>>)| -> StatCounter {
+            StatCounter::from_values(iter.map(|x| x.into()))
+        }
+    )
+}
+
+fn compute_histogram<T, R>(rdd: &R, buckets: Vec<f64>, even_buckets: bool) -> Result<Vec<u64>>
+where
+    T: Data + Into<f64>,
+    R: Rdd<Item = T> + Sized,
+{
+    let num_buckets = buckets.len() - 1;
+    let count_item = Fn!(move |mut counts: Vec<u64>, item: T| {
+        let value: f64 = item.into();
+        let bucket = if even_buckets {
+            fast_bucket_function(buckets[0], buckets[num_buckets], num_buckets, value)
+        } else {
+            basic_bucket_function(&buckets, value)
+        };
+        if let Some(idx) = bucket {
+            counts[idx] += 1;
+        }
+        counts
+    });
+    let merge_counts = Fn!(|mut counts: Vec<u64>, other: Vec<u64>| {
+        counts
+            .iter_mut()
+            .zip(other.into_iter())
+            .for_each(|(c, o)| *c += o);
+        counts
+    });
+    rdd.aggregate(vec![0u64; num_buckets], count_item, merge_counts)
+}
+
+/// Determine the bucket of a value with a binary search. Returns `None` if the value
+/// falls out of the range of the buckets.
+fn basic_bucket_function(buckets: &[f64], value: f64) -> Option<usize> {
+    match buckets.binary_search_by(|b| b.partial_cmp(&value).unwrap_or(Ordering::Greater)) {
+        Ok(location) => {
+            // Exact match, the last element is included in the last bucket
+            if location < buckets.len() - 1 {
+                Some(location)
+            } else {
+                Some(location - 1)
+            }
+        }
+        Err(insertion_point) => {
+            // If the insertion point is before the first element or after the last
+            // element the value is out of range.
+            if insertion_point == 0 || insertion_point == buckets.len() {
+                None
+            } else {
+                Some(insertion_point - 1)
+            }
+        }
+    }
+}
+
+/// Determine the bucket of a value in constant time when the buckets are evenly spaced.
+fn fast_bucket_function(min: f64, max: f64, count: usize, value: f64) -> Option<usize> {
+    if value.is_nan() || value < min || value > max {
+        None
+    } else if (max - min).abs() < std::f64::EPSILON {
+        // all the values fall in a single bucket
+        Some(0)
+    } else {
+        let bucket = ((value - min) / (max - min) * count as f64) as usize;
+        Some(bucket.min(count - 1))
+    }
+}
+
+fn is_evenly_spaced(buckets: &[f64]) -> bool {
+    let step = (buckets[buckets.len() - 1] - buckets[0]) / (buckets.len() - 1) as f64;
+    buckets
+        .iter()
+        .enumerate()
+        .all(|(i, b)| (b - (buckets[0] + i as f64 * step)).abs() <= EVEN_BUCKETS_EPSILON * step)
+}
@@ -1,17 +1,25 @@
+use std::collections::HashMap;
 use std::hash::Hash;
 use std::marker::PhantomData;
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::aggregator::Aggregator;
 use crate::context::Context;
 use crate::dependency::{Dependency, OneToOneDependency};
 use crate::error::Result;
+use crate::partial::{BoundedDouble, GroupedMeanEvaluator, GroupedSumEvaluator, PartialResult};
 use crate::partitioner::{HashPartitioner, Partitioner};
 use crate::rdd::co_grouped_rdd::CoGroupedRdd;
 use crate::rdd::shuffled_rdd::ShuffledRdd;
 use crate::rdd::*;
+use crate::scheduler::TaskContext;
 use crate::serializable_traits::{AnyData, Data, Func, SerFunc};
 use crate::split::Split;
+use crate::utils::hyper_log_log::HyperLogLogPlus;
+use crate::utils::quantile_summaries::QuantileSummaries;
+use crate::utils::stat_counter::StatCounter;
+use crate::utils::stratified_sampling::{self, AcceptanceResult};
 use serde_derive::{Deserialize, Serialize};
 use serde_traitobject::{Deserialize, Serialize};
 
@@ -169,6 +177,344 @@ pub trait PairRdd<K: Data + Eq + Hash, V: Data>: Rdd<Item = (K, V)> + Send + Syn
         });
         shuffle_steep.flat_map(flattener)
     }
+
+    /// Return approximate number of distinct values for each key in this RDD.
+    ///
+    /// The algorithm used is HyperLogLog++ ("HyperLogLog in Practice: Algorithmic Engineering
+    /// of a State of The Art Cardinality Estimation Algorithm") with 64-bit hashes, estimating
+    /// the cardinality with Otmar Ertl's improved estimator instead of bias correction.
+    /// The sketch of each key starts sparse, so keys with few values take little memory.
+    ///
+    /// # Arguments
+    /// * `relative_sd` - Relative accuracy. Smaller values create counters that require more space.
+    /// It must be greater than 0.0021 and smaller than 0.37.
+    /// * `num_splits` - number of partitions of the resulting RDD
+    fn count_approx_distinct_by_key(
+        &self,
+        relative_sd: f64,
+        num_splits: usize,
+    ) -> SerArc<dyn Rdd<Item = (K, u64)>>
+    where
+        Self: Sized + Serialize + Deserialize + 'static,
+        V: Hash,
+    {
+        let precision = HyperLogLogPlus::precision_for(relative_sd);
+        let create_combiner = Box::new(Fn!(move |v: V| {
+            let mut hll = HyperLogLogPlus::new(precision);
+            hll.offer(&v);
+            hll
+        }));
+        let merge_value = Box::new(Fn!(|(mut hll, v): (HyperLogLogPlus, V)| {
+            hll.offer(&v);
+            hll
+        }));
+        let merge_combiners = Box::new(Fn!(|(mut hll, other): (
+            HyperLogLogPlus,
+            HyperLogLogPlus
+        )| {
+            hll.merge(&other);
+            hll
+        }));
+        let aggregator = Aggregator::new(create_combiner, merge_value, merge_combiners);
+        self.combine_by_key(
+            aggregator,
+            Box::new(HashPartitioner::<K>::new(num_splits)) as Box<dyn Partitioner>,
+        )
+        .map_values(Fn!(|hll: HyperLogLogPlus| hll.cardinality()))
+    }
+
+    /// Calculates the approximate quantiles of the values for each key in this RDD.
+    /// See `NumericRdd::approx_quantiles` for the error guarantees.
+    ///
+    /// NaN values are ignored; keys whose values are all NaN map to an empty vector.
+    ///
+    /// # Arguments
+    /// * `probabilities` - a list of quantile probabilities, each of them must belong to [0, 1]
+    /// * `relative_error` - the relative target precision to achieve (greater than 0)
+    /// * `num_splits` - number of partitions of the resulting RDD
+    fn approx_quantiles_by_key(
+        &self,
+        probabilities: &[f64],
+        relative_error: f64,
+        num_splits: usize,
+    ) -> SerArc<dyn Rdd<Item = (K, Vec<f64>)>>
+    where
+        Self: Sized + Serialize + Deserialize + 'static,
+        V: Into<f64>,
+    {
+        assert!(
+            probabilities.iter().all(|p| *p >= 0.0 && *p <= 1.0),
+            "quantile probabilities must be in the range [0, 1]"
+        );
+        // validate the relative error eagerly instead of failing inside the tasks
+        QuantileSummaries::new(relative_error);
+        let probabilities = probabilities.to_vec();
+        let create_combiner = Box::new(Fn!(move |v: V| {
+            let mut summary = QuantileSummaries::new(relative_error);
+            summary.insert(v.into());
+            summary
+        }));
+        let merge_value = Box::new(Fn!(|(mut summary, v): (QuantileSummaries, V)| {
+            summary.insert(v.into());
+            summary
+        }));
+        let merge_combiners = Box::new(Fn!(|(mut summary, other): (
+            QuantileSummaries,
+            QuantileSummaries
+        )| {
+            summary.merge(other);
+            summary
+        }));
+        let aggregator = Aggregator::new(create_combiner, merge_value, merge_combiners);
+        self.combine_by_key(
+            aggregator,
+            Box::new(HashPartitioner::<K>::new(num_splits)) as Box<dyn Partitioner>,
+        )
+        .map_values(Fn!(move |mut summary: QuantileSummaries| {
+            summary.compress();
+            probabilities
+                .iter()
+                .filter_map(|p| summary.query(*p))
+                .collect::<Vec<_>>()
+        }))
+    }
+
+    /// Return a subset of this RDD sampled by key (via stratified sampling).
+    ///
+    /// Create a sample of this RDD using variable sampling rates for different keys as specified
+    /// by `fractions`, a key to sampling rate map, via simple random sampling with one pass over
+    /// the RDD, to produce a sample of size that's approximately equal to the sum of
+    /// math.ceil(num_items * sampling_rate) over all key values.
+    ///
+    /// Keys missing from `fractions` are not sampled.
+    ///
+    /// # Arguments
+    /// * `with_replacement` - whether to sample with or without replacement
+    /// * `fractions` - map of specific keys to sampling rates
+    /// * `seed` - seed for the random number generator
+    fn sample_by_key(
+        &self,
+        with_replacement: bool,
+        fractions: HashMap<K, f64>,
+        seed: Option<u64>,
+    ) -> SerArc<dyn Rdd<Item = (K, V)>>
+    where
+        Self: Sized,
+    {
+        check_sampling_fractions(with_replacement, &fractions);
+        let seed = seed.unwrap_or_else(rand::random);
+        let sample_partition = Fn!(move |index: usize,
+                                         iter: Box<dyn Iterator<Item = (K, V)>>|
+              -> Box<dyn Iterator<Item = (K, V)>> {
+            let fractions = fractions.clone();
+            let mut rng = stratified_sampling::partition_rng(seed, index);
+            Box::new(iter.flat_map(move |(k, v)| {
+                let fraction = fractions.get(&k).copied().unwrap_or(0.0);
+                let count = stratified_sampling::sample_count(with_replacement, fraction, &mut rng);
+                std::iter::repeat((k, v)).take(count as usize)
+            }))
+        });
+        self.map_partitions_with_index(sample_partition)
+    }
+
+    /// Return a subset of this RDD sampled by key (via stratified sampling) containing exactly
+    /// math.ceil(num_items * sampling_rate) for each stratum (group of pairs with the same key).
+    ///
+    /// This method differs from `sample_by_key` in that additional passes over the RDD are made
+    /// to create a sample size that's exactly equal to the sum of math.ceil(num_items * sampling_rate)
+    /// over all key values with a 99.99% confidence. When sampling without replacement, one
+    /// additional pass over the RDD is needed to guarantee sample size; when sampling with
+    /// replacement, two additional passes are needed.
+    ///
+    /// Keys missing from `fractions` are not sampled.
+    ///
+    /// # Arguments
+    /// * `with_replacement` - whether to sample with or without replacement
+    /// * `fractions` - map of specific keys to sampling rates
+    /// * `seed` - seed for the random number generator
+    fn sample_by_key_exact(
+        &self,
+        with_replacement: bool,
+        fractions: HashMap<K, f64>,
+        seed: Option<u64>,
+    ) -> Result<SerArc<dyn Rdd<Item = (K, V)>>>
+    where
+        Self: Sized,
+    {
+        check_sampling_fractions(with_replacement, &fractions);
+        // the same seed must be used in all the passes
+        let seed = seed.unwrap_or_else(rand::random);
+
+        // sampling with replacement requires the size of each stratum beforehand
+        let counts: HashMap<K, u64> = if with_replacement {
+            self.map(Fn!(|(k, _v): (K, V)| k))
+                .count_by_value()
+                .collect()?
+                .into_iter()
+                .collect()
+        } else {
+            HashMap::new()
+        };
+
+        // first pass, compute the acceptance thresholds of each stratum
+        let thresholds_fractions = fractions.clone();
+        let acceptance_results =
+            Fn!(move |index: usize,
+                      iter: Box<dyn Iterator<Item = (K, V)>>|
+                  -> Box<dyn Iterator<Item = HashMap<K, AcceptanceResult>>> {
+                let mut rng = stratified_sampling::partition_rng(seed, index);
+                let mut results: HashMap<K, AcceptanceResult> = HashMap::new();
+                for (k, _v) in iter {
+                    let fraction = thresholds_fractions.get(&k).copied().unwrap_or(0.0);
+                    let stratum_count = counts.get(&k).copied().unwrap_or(0);
+                    let result = results.entry(k).or_default();
+                    stratified_sampling::update_acceptance_result(
+                        result,
+                        with_replacement,
+                        fraction,
+                        stratum_count,
+                        &mut rng,
+                    );
+                }
+                Box::new(std::iter::once(results))
+            });
+        let results = self
+            .map_partitions_with_index(acceptance_results)
+            .reduce(Fn!(
+                |a: HashMap<K, AcceptanceResult>, b: HashMap<K, AcceptanceResult>| {
+                    stratified_sampling::merge_acceptance_results(a, b)
+                }
+            ))?
+            .unwrap_or_default();
+        let thresholds = stratified_sampling::compute_threshold_by_key(results, &fractions);
+
+        // second pass, draw the same random values and keep the items under the threshold
+        let sample_partition = Fn!(move |index: usize,
+                                         iter: Box<dyn Iterator<Item = (K, V)>>|
+              -> Box<dyn Iterator<Item = (K, V)>> {
+            let fractions = fractions.clone();
+            let thresholds = thresholds.clone();
+            let mut rng = stratified_sampling::partition_rng(seed, index);
+            Box::new(iter.flat_map(move |(k, v)| {
+                let fraction = fractions.get(&k).copied().unwrap_or(0.0);
+                let count = match thresholds.get(&k) {
+                    Some(threshold) => stratified_sampling::exact_sample_count(
+                        with_replacement,
+                        fraction,
+                        threshold,
+                        &mut rng,
+                    ),
+                    None => 0,
+                };
+                std::iter::repeat((k, v)).take(count as usize)
+            }))
+        });
+        Ok(self.map_partitions_with_index(sample_partition))
+    }
+
+    /// Approximate version of count_by_key that can return a partial result if it does
+    /// not finish within a timeout.
+    ///
+    /// The confidence is the probability that the error bounds of the result will
+    /// contain the true value. That is, if count_by_key_approx were called repeatedly
+    /// with confidence 0.9, we would expect 90% of the results to contain the
+    /// true count. The confidence must be in the range [0,1] or an exception will
+    /// be thrown.
+    ///
+    /// # Arguments
+    /// * `timeout` - maximum time to wait for the job, in milliseconds
+    /// * `confidence` - the desired statistical confidence in the result
+    fn count_by_key_approx(
+        &self,
+        timeout: Duration,
+        confidence: Option<f64>,
+    ) -> Result<PartialResult<HashMap<K, BoundedDouble>>>
+    where
+        Self: Sized,
+    {
+        self.map(Fn!(|(k, _v): (K, V)| k))
+            .count_by_value_aprox(timeout, confidence)
+    }
+
+    /// Approximate version of summing the values for each key that can return a partial
+    /// result if it does not finish within a timeout.
+    ///
+    /// # Arguments
+    /// * `timeout` - maximum time to wait for the job, in milliseconds
+    /// * `confidence` - the desired statistical confidence in the result
+    fn sum_by_key_approx(
+        &self,
+        timeout: Duration,
+        confidence: Option<f64>,
+    ) -> Result<PartialResult<HashMap<K, BoundedDouble>>>
+    where
+        Self: Sized,
+        V: Into<f64>,
+    {
+        let confidence = confidence.unwrap_or(0.95);
+        assert!(0.0 <= confidence && confidence <= 1.0);
+
+        let evaluator = GroupedSumEvaluator::new(self.number_of_splits(), confidence);
+        let rdd = self.get_rdd();
+        rdd.register_op_name("sum_by_key_approx");
+        self.get_context()
+            .run_approximate_job(stats_by_key::<K, V>(), rdd, evaluator, timeout)
+    }
+
+    /// Approximate version of averaging the values for each key that can return a partial
+    /// result if it does not finish within a timeout.
+    ///
+    /// # Arguments
+    /// * `timeout` - maximum time to wait for the job, in milliseconds
+    /// * `confidence` - the desired statistical confidence in the result
+    fn mean_by_key_approx(
+        &self,
+        timeout: Duration,
+        confidence: Option<f64>,
+    ) -> Result<PartialResult<HashMap<K, BoundedDouble>>>
+    where
+        Self: Sized,
+        V: Into<f64>,
+    {
+        let confidence = confidence.unwrap_or(0.95);
+        assert!(0.0 <= confidence && confidence <= 1.0);
+
+        let evaluator = GroupedMeanEvaluator::new(self.number_of_splits(), confidence);
+        let rdd = self.get_rdd();
+        rdd.register_op_name("mean_by_key_approx");
+        self.get_context()
+            .run_approximate_job(stats_by_key::<K, V>(), rdd, evaluator, timeout)
+    }
+}
+
+fn check_sampling_fractions<K>(with_replacement: bool, fractions: &HashMap<K, f64>) {
+    if with_replacement {
+        assert!(
+            fractions.values().all(|f| *f >= 0.0),
+            "sampling rates must be >= 0 when sampling with replacement"
+        );
+    } else {
+        assert!(
+            fractions.values().all(|f| *f >= 0.0 && *f <= 1.0),
+            "sampling rates must be in [0, 1] when sampling without replacement"
+        );
+    }
+}
+
+/// Per partition function which summarizes the values of each key in a StatCounter.
+fn stats_by_key<K: Data + Eq + Hash, V: Data + Into<f64>>(
+) -> impl SerFunc((TaskContext, Box<dyn Iterator<Item = (K, V)>>)) -> HashMap<K, StatCounter> {
+    Fn!(
+        |(_ctx, iter): (TaskContext, Box<dyn Iterator<Item = (K, V)>>)| -> HashMap<K, StatCounter> {
+            let mut map = HashMap::new();
+            iter.for_each(|(k, v)| {
+                map.entry(k)
+                    .or_insert_with(StatCounter::new)
+                    .merge_value(v.into());
+            });
+            map
+        }
+    )
 }
 
 // Implementing the PairRdd trait for all types which implements Rdd
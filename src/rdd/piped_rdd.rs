@@ -0,0 +1,301 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, BufWriter, ErrorKind, Write};
+use std::marker::PhantomData;
+use std::net::Ipv4Addr;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+
+use crate::context::Context;
+use crate::dependency::{Dependency, OneToOneDependency};
+use crate::error::{Error, Result};
+use crate::rdd::{Rdd, RddBase, RddVals};
+use crate::scheduler::fail_task;
+use crate::serializable_traits::{AnyData, Data, Func, SerFunc};
+use crate::split::Split;
+use parking_lot::Mutex;
+use serde_derive::{Deserialize, Serialize};
+
+/// An RDD that pipes the contents of each parent partition through an external command
+/// (printing them one per line) and returns the output as a collection of strings.
+#[derive(Serialize, Deserialize)]
+pub struct PipedRdd<T: Data, F>
+where
+    F: Func(&T) -> String + Clone,
+{
+    #[serde(skip_serializing, skip_deserializing)]
+    name: Mutex<String>,
+    #[serde(with = "serde_traitobject")]
+    prev: Arc<dyn Rdd<Item = T>>,
+    vals: Arc<RddVals>,
+    command: Vec<String>,
+    env: HashMap<String, String>,
+    formatter: F,
+    _marker_t: PhantomData<T>,
+}
+
+impl<T: Data, F> Clone for PipedRdd<T, F>
+where
+    F: Func(&T) -> String + Clone,
+{
+    fn clone(&self) -> Self {
+        PipedRdd {
+            name: Mutex::new(self.name.lock().clone()),
+            prev: self.prev.clone(),
+            vals: self.vals.clone(),
+            command: self.command.clone(),
+            env: self.env.clone(),
+            formatter: self.formatter.clone(),
+            _marker_t: PhantomData,
+        }
+    }
+}
+
+impl<T: Data, F> PipedRdd<T, F>
+where
+    F: SerFunc(&T) -> String,
+{
+    pub(crate) fn new(
+        prev: Arc<dyn Rdd<Item = T>>,
+        command: Vec<String>,
+        env: HashMap<String, String>,
+        formatter: F,
+    ) -> Self {
+        assert!(!command.is_empty(), "the piped command cannot be empty");
+        let mut vals = RddVals::new(prev.get_context());
+        vals.dependencies
+            .push(Dependency::NarrowDependency(Arc::new(
+                OneToOneDependency::new(prev.get_rdd_base()),
+            )));
+        let vals = Arc::new(vals);
+        PipedRdd {
+            name: Mutex::new("pipe".to_owned()),
+            prev,
+            vals,
+            command,
+            env,
+            formatter,
+            _marker_t: PhantomData,
+        }
+    }
+
+    fn command_line(&self) -> String {
+        self.command.join(" ")
+    }
+}
+
+impl<T: Data, F> RddBase for PipedRdd<T, F>
+where
+    F: SerFunc(&T) -> String,
+{
+    fn get_rdd_id(&self) -> usize {
+        self.vals.id
+    }
+
+    fn get_context(&self) -> Arc<Context> {
+        self.vals.context.upgrade().unwrap()
+    }
+
+    fn get_op_name(&self) -> String {
+        self.name.lock().to_owned()
+    }
+
+    fn register_op_name(&self, name: &str) {
+        let own_name = &mut *self.name.lock();
+        *own_name = name.to_owned();
+    }
+
+    fn get_dependencies(&self) -> Vec<Dependency> {
+        self.vals.dependencies.clone()
+    }
+
+    fn preferred_locations(&self, split: Box<dyn Split>) -> Vec<Ipv4Addr> {
+        self.prev.preferred_locations(split)
+    }
+
+    fn splits(&self) -> Vec<Box<dyn Split>> {
+        self.prev.splits()
+    }
+
+    fn number_of_splits(&self) -> usize {
+        self.prev.number_of_splits()
+    }
+
+    fn cogroup_iterator_any(
+        &self,
+        split: Box<dyn Split>,
+    ) -> Result<Box<dyn Iterator<Item = Box<dyn AnyData>>>> {
+        self.iterator_any(split)
+    }
+
+    fn iterator_any(
+        &self,
+        split: Box<dyn Split>,
+    ) -> Result<Box<dyn Iterator<Item = Box<dyn AnyData>>>> {
+        log::debug!("inside iterator_any piped_rdd",);
+        Ok(Box::new(
+            self.iterator(split)?
+                .map(|x| Box::new(x) as Box<dyn AnyData>),
+        ))
+    }
+}
+
+impl<T: Data, F: 'static> Rdd for PipedRdd<T, F>
+where
+    F: SerFunc(&T) -> String,
+{
+    type Item = String;
+    fn get_rdd_base(&self) -> Arc<dyn RddBase> {
+        Arc::new(self.clone()) as Arc<dyn RddBase>
+    }
+
+    fn get_rdd(&self) -> Arc<dyn Rdd<Item = Self::Item>> {
+        Arc::new(self.clone())
+    }
+
+    fn compute(&self, split: Box<dyn Split>) -> Result<Box<dyn Iterator<Item = Self::Item>>> {
+        let items = self.prev.iterator(split)?;
+        let mut child = Command::new(&self.command[0])
+            .args(&self.command[1..])
+            .envs(&self.env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|source| Error::CommandOutput {
+                source,
+                command: self.command_line(),
+            })?;
+
+        // Read the output in a separate thread so the child never blocks on a full stdout
+        // pipe while the elements are being written to its stdin.
+        let stdout = child.stdout.take().unwrap();
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                // the receiver is gone once the output is no longer read
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let stdin = BufWriter::new(child.stdin.take().unwrap());
+        Ok(Box::new(PipedOutput {
+            command: self.command_line(),
+            input: Some((items, stdin)),
+            formatter: self.formatter.clone(),
+            lines,
+            child: Some(child),
+        }))
+    }
+}
+
+/// Lines printed by the command piping a partition, returned as they arrive. The elements of
+/// the partition are written to the command as its output is consumed.
+struct PipedOutput<T, F> {
+    command: String,
+    input: Option<(Box<dyn Iterator<Item = T>>, BufWriter<ChildStdin>)>,
+    formatter: F,
+    lines: Receiver<io::Result<String>>,
+    child: Option<Child>,
+}
+
+impl<T, F> PipedOutput<T, F>
+where
+    F: Fn(&T) -> String,
+{
+    /// Write the next element to the command, or close its stdin once all are written.
+    fn write_next(&mut self) -> io::Result<()> {
+        if let Some((items, stdin)) = &mut self.input {
+            match items.next() {
+                Some(item) => return writeln!(stdin, "{}", (self.formatter)(&item)),
+                None => stdin.flush()?,
+            }
+        }
+        self.input = None;
+        Ok(())
+    }
+
+    /// Wait for the command to exit once its output is read. The task fails if it didn't
+    /// succeed.
+    fn finish(&mut self) {
+        self.input = None;
+        if let Some(mut child) = self.child.take() {
+            match child.wait() {
+                Ok(status) if status.success() => {}
+                Ok(status) => fail_task(Error::CommandExit {
+                    command: self.command.clone(),
+                    status,
+                }),
+                Err(source) => self.fail(source),
+            }
+        }
+    }
+
+    /// Fail the task with an error talking to the command, and stop the command.
+    fn fail(&mut self, source: io::Error) {
+        fail_task(Error::CommandOutput {
+            source,
+            command: self.command.clone(),
+        });
+        self.input = None;
+        if let Some(mut child) = self.child.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+impl<T, F> Iterator for PipedOutput<T, F>
+where
+    F: Fn(&T) -> String,
+{
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        loop {
+            let line = if self.input.is_some() {
+                match self.lines.try_recv() {
+                    Ok(line) => Some(line),
+                    Err(TryRecvError::Empty) => {
+                        if let Err(err) = self.write_next() {
+                            // Close the child's stdin, the command is allowed to stop reading
+                            // its input early.
+                            self.input = None;
+                            if err.kind() != ErrorKind::BrokenPipe {
+                                self.fail(err);
+                                return None;
+                            }
+                        }
+                        continue;
+                    }
+                    Err(TryRecvError::Disconnected) => None,
+                }
+            } else {
+                self.lines.recv().ok()
+            };
+            return match line {
+                Some(Ok(line)) => Some(line),
+                Some(Err(err)) => {
+                    self.fail(err);
+                    None
+                }
+                None => {
+                    self.finish();
+                    None
+                }
+            };
+        }
+    }
+}
+
+impl<T, F> Drop for PipedOutput<T, F> {
+    fn drop(&mut self) {
+        // The output was not read to the end: stop the command.
+        if let Some(mut child) = self.child.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
@@ -1,23 +1,26 @@
 use std::cmp::Ordering;
 use std::cmp::Reverse;
 use std::collections::HashMap;
-use std::fs;
+use std::fmt::Display;
 use std::hash::Hash;
-use std::io::{BufWriter, Write};
 use std::net::Ipv4Addr;
-use std::path::Path;
+use std::ops::Range;
+use std::path::PathBuf;
 use std::sync::{Arc, Weak};
 use std::time::Duration;
 
 use crate::context::Context;
 use crate::dependency::Dependency;
 use crate::error::{Error, Result};
+use crate::future_action::FutureAction;
+use crate::io::{self, SaveMode};
 use crate::partial::{BoundedDouble, CountEvaluator, GroupedCountEvaluator, PartialResult};
 use crate::partitioner::{HashPartitioner, Partitioner};
 use crate::scheduler::TaskContext;
 use crate::serializable_traits::{AnyData, Data, Func, SerFunc};
 use crate::split::Split;
 use crate::utils::bounded_priority_queue::BoundedPriorityQueue;
+use crate::utils::hyper_log_log::HyperLogLogPlus;
 use crate::utils::random::{BernoulliCellSampler, BernoulliSampler, PoissonSampler, RandomSampler};
 use crate::{utils, Fn, SerArc, SerBox};
 use fasthash::MetroHasher;
@@ -37,12 +40,20 @@ mod flatmapper_rdd;
 mod mapper_rdd;
 pub use flatmapper_rdd::*;
 pub use mapper_rdd::*;
+mod numeric_rdd;
+pub use numeric_rdd::*;
 mod pair_rdd;
 pub use pair_rdd::*;
+mod result_rdd;
+pub use result_rdd::*;
+mod piped_rdd;
+pub use piped_rdd::*;
 mod partitionwise_sampled_rdd;
 pub use partitionwise_sampled_rdd::*;
 mod shuffled_rdd;
 pub use shuffled_rdd::*;
+mod local_iterator;
+pub use local_iterator::*;
 mod map_partitions_rdd;
 pub use map_partitions_rdd::*;
 mod zip_rdd;
@@ -251,24 +262,70 @@ pub trait Rdd: RddBase + 'static {
         SerArc::new(rdd)
     }
 
-    fn save_as_text_file(&self, path: String) -> Result<Vec<()>>
+    /// Save the items as text files in a directory, one per partition named `part-NNNNN`, with
+    /// one item per line formatted with `Display`.
+    ///
+    /// Every task writes to a temporary file which is moved into the directory only when the
+    /// task succeeds, and a `_SUCCESS` file is written once all the partitions are saved.
+    fn save_as_text_file<P: Into<PathBuf>>(&self, path: P, mode: SaveMode) -> Result<()>
     where
         Self: Sized,
+        Self::Item: Display,
     {
-        fn save<R: Data>(ctx: TaskContext, iter: Box<dyn Iterator<Item = R>>, path: String) {
-            fs::create_dir_all(&path).unwrap();
-            let id = ctx.split_id;
-            let file_path = Path::new(&path).join(format!("part-{}", id));
-            let f = fs::File::create(file_path).expect("unable to create file");
-            let mut f = BufWriter::new(f);
-            for item in iter {
-                let line = format!("{:?}", item);
-                f.write_all(line.as_bytes())
-                    .expect("error while writing to file");
-            }
-        }
-        let cl = Fn!(move |(ctx, iter)| save::<Self::Item>(ctx, iter, path.to_string()));
-        self.get_context().run_job_with_context(self.get_rdd(), cl)
+        let format = Fn!(|item: Self::Item| item.to_string());
+        io::save_as_text_file(self.get_rdd(), path.into(), mode, format)
+    }
+
+    /// Save the items as text files like `save_as_text_file`, formatting each item into a line
+    /// with the given function.
+    fn save_as_text_file_with<P, F>(&self, path: P, mode: SaveMode, format: F) -> Result<()>
+    where
+        Self: Sized,
+        P: Into<PathBuf>,
+        F: SerFunc(Self::Item) -> String,
+    {
+        io::save_as_text_file(self.get_rdd(), path.into(), mode, format)
+    }
+
+    /// Save the items in a directory as object files, one per partition named `part-NNNNN`,
+    /// which `Context::object_file` reads back.
+    ///
+    /// Items are serialized with bincode in blocks, optionally compressed, after a header
+    /// recording the format version and the name of the item type.
+    fn save_as_object_file<P: Into<PathBuf>>(
+        &self,
+        path: P,
+        config: io::ObjectFileConfig,
+    ) -> Result<()>
+    where
+        Self: Sized,
+    {
+        io::save_as_object_file(self.get_rdd(), path.into(), config)
+    }
+
+    /// Save the items in a directory as CSV files, one per partition named `part-NNNNN.csv`.
+    ///
+    /// Items are serialized with serde: structs are written as one row each, with a header
+    /// holding the names of their fields when the options have one.
+    fn save_as_csv<P: Into<PathBuf>>(
+        &self,
+        path: P,
+        options: io::CsvOptions,
+        mode: SaveMode,
+    ) -> Result<()>
+    where
+        Self: Sized,
+    {
+        io::save_as_csv(self.get_rdd(), path.into(), options, mode)
+    }
+
+    /// Save the items in a directory as JSON Lines files, one per partition named
+    /// `part-NNNNN.jsonl`, with one item serialized as JSON per line.
+    fn save_as_json_lines<P: Into<PathBuf>>(&self, path: P, mode: SaveMode) -> Result<()>
+    where
+        Self: Sized,
+    {
+        io::save_as_json_lines(self.get_rdd(), path.into(), mode)
     }
 
     fn reduce<F>(&self, f: F) -> Result<Option<Self::Item>>
@@ -349,6 +406,108 @@ pub trait Rdd: RddBase + 'static {
         Ok(results?.into_iter().fold(init, comb_fn))
     }
 
+    /// Reduces the elements of this RDD in a multi-level tree pattern.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - an associative function used to combine the elements of the RDD
+    /// * `depth` - suggested depth of the tree (should be greater than or equal to 1)
+    ///
+    /// See [tree_aggregate](#tree_aggregate) for details.
+    fn tree_reduce<F>(&self, f: F, depth: usize) -> Result<Option<Self::Item>>
+    where
+        Self: Sized,
+        F: SerFunc(Self::Item, Self::Item) -> Self::Item,
+    {
+        let cf = f.clone();
+        let reduce_partition = Fn!(move |acc: Option<Self::Item>,
+                                         item: Self::Item|
+              -> Option<Self::Item> {
+            match acc {
+                Some(acc) => Some((cf)(acc, item)),
+                None => Some(item),
+            }
+        });
+        let merge_results = Fn!(move |x: Option<Self::Item>,
+                                      y: Option<Self::Item>|
+              -> Option<Self::Item> {
+            match (x, y) {
+                (Some(x), Some(y)) => Some((f)(x, y)),
+                (x, None) => x,
+                (None, y) => y,
+            }
+        });
+        self.tree_aggregate(None, reduce_partition, merge_results, depth)
+    }
+
+    /// Aggregates the elements of this RDD in a multi-level tree pattern. This method is
+    /// semantically identical to [aggregate](#aggregate), but instead of sending every partition
+    /// result to the driver, partial results are first combined in intermediate stages.
+    ///
+    /// Every intermediate level groups the partial results into buckets, keyed by the index of
+    /// the partition which produced them, and merges them through `reduce_by_key`. Levels are
+    /// added until doing so would not reduce the amount of results merged in the driver.
+    ///
+    /// # Arguments
+    ///
+    /// * `init` - the initial value for the accumulated result of each partition for the `seq_fn`
+    ///                  function, and also the initial value for the combine results from
+    ///                  different partitions for the `comb_fn` function
+    /// * `seq_fn` - a function used to accumulate results within a partition
+    /// * `comb_fn` - an associative function used to combine results from different partitions
+    /// * `depth` - suggested depth of the tree (should be greater than or equal to 1)
+    fn tree_aggregate<U: Data, SF, CF>(
+        &self,
+        init: U,
+        seq_fn: SF,
+        comb_fn: CF,
+        depth: usize,
+    ) -> Result<U>
+    where
+        Self: Sized,
+        SF: SerFunc(U, Self::Item) -> U,
+        CF: SerFunc(U, U) -> U,
+    {
+        assert!(
+            depth >= 1,
+            format!("Depth must be greater than or equal to 1 but got {}", depth)
+        );
+        if self.number_of_splits() == 0 {
+            return Ok(init);
+        }
+
+        let zero = init.clone();
+        let aggregate_partition = Fn!(
+            move |iter: Box<dyn Iterator<Item = Self::Item>>| -> Box<dyn Iterator<Item = U>> {
+                Box::new(std::iter::once(iter.fold(zero.clone(), &seq_fn)))
+            }
+        );
+        let mut partially_aggregated = self.map_partitions(aggregate_partition);
+        let mut num_partitions = partially_aggregated.number_of_splits();
+        let scale = ((num_partitions as f64).powf(1.0 / depth as f64).ceil() as usize).max(2);
+        // If creating an extra level doesn't help reduce the wall-clock time, stop the tree
+        // aggregation and merge the remaining results in the driver.
+        while num_partitions > scale + (num_partitions as f64 / scale as f64).ceil() as usize {
+            num_partitions /= scale;
+            let cur_num_partitions = num_partitions;
+            let bucket_by_partition =
+                Fn!(move |index: usize,
+                          iter: Box<dyn Iterator<Item = U>>|
+                      -> Box<dyn Iterator<Item = (usize, U)>> {
+                    Box::new(iter.map(move |u| (index % cur_num_partitions, u)))
+                });
+            let cf = comb_fn.clone();
+            partially_aggregated = partially_aggregated
+                .map_partitions_with_index(bucket_by_partition)
+                .reduce_by_key(
+                    Fn!(move |(x, y): (U, U)| -> U { (cf)(x, y) }),
+                    cur_num_partitions,
+                )
+                .map(Fn!(|(_bucket, u): (usize, U)| u));
+        }
+        partially_aggregated.fold(init, comb_fn)
+    }
+
     /// Return the Cartesian product of this RDD and another one, that is, the RDD of all pairs of
     /// elements (a, b) where a is in `this` and b is in `other`.
     fn cartesian<U: Data>(
@@ -433,6 +592,142 @@ pub trait Rdd: RddBase + 'static {
             }))
     }
 
+    /// Asynchronous version of [collect](#method.collect), returning a future for the
+    /// elements of this RDD.
+    fn collect_async(&self) -> FutureAction<Vec<Self::Item>>
+    where
+        Self: Sized,
+    {
+        let rdd = self.get_rdd();
+        let cl =
+            Fn!(|iter: Box<dyn Iterator<Item = Self::Item>>| iter.collect::<Vec<Self::Item>>());
+        FutureAction::spawn(move |cancellation| {
+            let partitions = (0..rdd.number_of_splits()).collect();
+            let results = rdd
+                .get_context()
+                .run_job_async(rdd, cl, partitions, cancellation);
+            async move { Ok(results.await?.into_iter().flatten().collect()) }
+        })
+    }
+
+    /// Asynchronous version of [count](#method.count), returning a future for the number of
+    /// elements in this RDD.
+    fn count_async(&self) -> FutureAction<u64>
+    where
+        Self: Sized,
+    {
+        let rdd = self.get_rdd();
+        let counting_func =
+            Fn!(|iter: Box<dyn Iterator<Item = Self::Item>>| { iter.count() as u64 });
+        FutureAction::spawn(move |cancellation| {
+            let partitions = (0..rdd.number_of_splits()).collect();
+            let counts =
+                rdd.get_context()
+                    .run_job_async(rdd, counting_func, partitions, cancellation);
+            async move { Ok(counts.await?.into_iter().sum()) }
+        })
+    }
+
+    /// Asynchronous version of [take](#method.take), returning a future for the first num
+    /// elements of this RDD. When cancelled no further partitions are scanned.
+    fn take_async(&self, num: usize) -> FutureAction<Vec<Self::Item>>
+    where
+        Self: Sized,
+    {
+        let rdd = self.get_rdd();
+        FutureAction::spawn(move |cancellation| async move {
+            let mut buf = vec![];
+            let total_parts = rdd.number_of_splits();
+            let mut parts_scanned = 0;
+            while buf.len() < num && parts_scanned < total_parts {
+                let partitions = partitions_to_take(num, buf.len(), parts_scanned, total_parts);
+                let num_partitions = partitions.len();
+                let left = num - buf.len();
+                let take_from_partion = Fn!(move |iter: Box<dyn Iterator<Item = Self::Item>>| {
+                    iter.take(left).collect::<Vec<Self::Item>>()
+                });
+                let res = rdd
+                    .get_context()
+                    .run_job_async(
+                        rdd.clone(),
+                        take_from_partion,
+                        partitions.collect(),
+                        cancellation.clone(),
+                    )
+                    .await?;
+                res.into_iter().for_each(|r| {
+                    let take = num - buf.len();
+                    buf.extend(r.into_iter().take(take));
+                });
+                parts_scanned += num_partitions;
+            }
+            Ok(buf)
+        })
+    }
+
+    /// Asynchronous version of [for_each](#method.for_each), applying a function to all
+    /// elements of this RDD in the background.
+    fn for_each_async<F>(&self, func: F) -> FutureAction<()>
+    where
+        F: SerFunc(Self::Item),
+        Self: Sized,
+    {
+        let rdd = self.get_rdd();
+        let func = Fn!(move |iter: Box<dyn Iterator<Item = Self::Item>>| iter.for_each(&func));
+        FutureAction::spawn(move |cancellation| {
+            let partitions = (0..rdd.number_of_splits()).collect();
+            let results = rdd
+                .get_context()
+                .run_job_async(rdd, func, partitions, cancellation);
+            async move { results.await.map(|_| ()) }
+        })
+    }
+
+    /// Asynchronous version of [for_each_partition](#method.for_each_partition), applying a
+    /// function to each partition of this RDD in the background.
+    fn for_each_partition_async<F>(&self, func: F) -> FutureAction<()>
+    where
+        F: SerFunc(Box<dyn Iterator<Item = Self::Item>>),
+        Self: Sized,
+    {
+        let rdd = self.get_rdd();
+        let func = Fn!(move |iter: Box<dyn Iterator<Item = Self::Item>>| (&func)(iter));
+        FutureAction::spawn(move |cancellation| {
+            let partitions = (0..rdd.number_of_splits()).collect();
+            let results = rdd
+                .get_context()
+                .run_job_async(rdd, func, partitions, cancellation);
+            async move { results.await.map(|_| ()) }
+        })
+    }
+
+    /// Return an iterator that contains all of the elements in this RDD.
+    ///
+    /// One job is run per partition when the iterator advances to it, so the driver only
+    /// needs memory for the largest partition of this RDD.
+    fn to_local_iterator(&self) -> LocalIterator<Self::Item>
+    where
+        Self: Sized,
+    {
+        LocalIterator::new(self.get_rdd(), 1, false)
+    }
+
+    /// Return an iterator that contains all of the elements in this RDD, fetching
+    /// `partitions_per_job` partitions to the driver with each job.
+    ///
+    /// With `prefetch` the job for the next window of partitions runs in the background while
+    /// the current one is consumed, at the cost of holding up to two windows in memory.
+    fn to_local_iterator_with_window(
+        &self,
+        partitions_per_job: usize,
+        prefetch: bool,
+    ) -> LocalIterator<Self::Item>
+    where
+        Self: Sized,
+    {
+        LocalIterator::new(self.get_rdd(), partitions_per_job, prefetch)
+    }
+
     fn count(&self) -> Result<u64>
     where
         Self: Sized,
@@ -498,6 +793,71 @@ pub trait Rdd: RddBase + 'static {
             .run_approximate_job(count_partition, rdd, evaluator, timeout)
     }
 
+    /// Return approximate number of distinct elements in the RDD.
+    ///
+    /// The algorithm used is HyperLogLog++ ("HyperLogLog in Practice: Algorithmic Engineering
+    /// of a State of The Art Cardinality Estimation Algorithm") with 64-bit hashes, estimating
+    /// the cardinality with Otmar Ertl's improved estimator instead of bias correction.
+    ///
+    /// # Arguments
+    /// * `relative_sd` - Relative accuracy. Smaller values create counters that require more space.
+    /// It must be greater than 0.0021 and smaller than 0.37.
+    fn count_approx_distinct(&self, relative_sd: f64) -> Result<u64>
+    where
+        Self: Sized,
+        Self::Item: Hash,
+    {
+        let zero = HyperLogLogPlus::with_relative_sd(relative_sd);
+        let offer = Fn!(|mut hll: HyperLogLogPlus, item: Self::Item| {
+            hll.offer(&item);
+            hll
+        });
+        let merge = Fn!(|mut hll: HyperLogLogPlus, other: HyperLogLogPlus| {
+            hll.merge(&other);
+            hll
+        });
+        Ok(self.aggregate(zero, offer, merge)?.cardinality())
+    }
+
+    /// Return an RDD created by piping elements to a forked external process.
+    ///
+    /// The command is given as the program followed by its arguments, and started once per
+    /// partition with the given environment variables added. The elements of the partition
+    /// are written to the process's stdin one per line, and the lines it prints to stdout make up
+    /// the resulting partition, streamed as they are printed. A command which can't be run or
+    /// exits with a non-zero status fails its task, and the action returns the error.
+    fn pipe<I, S>(&self, command: I, env: HashMap<String, String>) -> SerArc<dyn Rdd<Item = String>>
+    where
+        Self: Sized,
+        Self::Item: std::fmt::Display,
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let formatter = Fn!(|item: &Self::Item| -> String { item.to_string() });
+        self.pipe_with_formatter(command, env, formatter)
+    }
+
+    /// Return an RDD created by piping elements to a forked external process, printing each
+    /// element with the given formatter. See [pipe](#pipe) for details.
+    fn pipe_with_formatter<I, S, F>(
+        &self,
+        command: I,
+        env: HashMap<String, String>,
+        formatter: F,
+    ) -> SerArc<dyn Rdd<Item = String>>
+    where
+        Self: Sized,
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+        F: SerFunc(&Self::Item) -> String,
+    {
+        let command = command
+            .into_iter()
+            .map(|arg| arg.as_ref().to_owned())
+            .collect();
+        SerArc::new(PipedRdd::new(self.get_rdd(), command, env, formatter))
+    }
+
     /// Return a new RDD containing the distinct elements in this RDD.
     fn distinct_with_num_partitions(
         &self,
@@ -566,38 +926,13 @@ pub trait Rdd: RddBase + 'static {
     where
         Self: Sized,
     {
-        // TODO: in original spark this is configurable; see rdd/RDD.scala:1397
-        // Math.max(conf.get(RDD_LIMIT_SCALE_UP_FACTOR), 2)
-        const SCALE_UP_FACTOR: f64 = 2.0;
-        if num == 0 {
-            return Ok(vec![]);
-        }
         let mut buf = vec![];
-        let total_parts = self.number_of_splits() as u32;
-        let mut parts_scanned = 0_u32;
+        let total_parts = self.number_of_splits();
+        let mut parts_scanned = 0;
         while buf.len() < num && parts_scanned < total_parts {
-            // The number of partitions to try in this iteration. It is ok for this number to be
-            // greater than total_parts because we actually cap it at total_parts in run_job.
-            let mut num_parts_to_try = 1u32;
+            let partitions = partitions_to_take(num, buf.len(), parts_scanned, total_parts);
+            let num_partitions = partitions.len();
             let left = num - buf.len();
-            if parts_scanned > 0 {
-                // If we didn't find any rows after the previous iteration, quadruple and retry.
-                // Otherwise, interpolate the number of partitions we need to try, but overestimate
-                // it by 50%. We also cap the estimation in the end.
-                let parts_scanned = f64::from(parts_scanned);
-                num_parts_to_try = if buf.is_empty() {
-                    (parts_scanned * SCALE_UP_FACTOR).ceil() as u32
-                } else {
-                    let num_parts_to_try =
-                        (1.5 * left as f64 * parts_scanned / (buf.len() as f64)).ceil();
-                    num_parts_to_try.min(parts_scanned * SCALE_UP_FACTOR) as u32
-                };
-            }
-
-            let partitions: Vec<_> = (parts_scanned as usize
-                ..total_parts.min(parts_scanned + num_parts_to_try) as usize)
-                .collect();
-            let num_partitions = partitions.len() as u32;
             let take_from_partion = Fn!(move |iter: Box<dyn Iterator<Item = Self::Item>>| {
                 iter.take(left).collect::<Vec<Self::Item>>()
             });
@@ -828,6 +1163,94 @@ pub trait Rdd: RddBase + 'static {
         ))
     }
 
+    /// Zip this RDD's partitions with the partitions of another RDD and return a new RDD by
+    /// applying a function to the zipped partitions. Both RDDs must have the same number of
+    /// partitions, but not necessarily the same number of elements in each partition.
+    fn zip_partitions<S: Data, U: Data, F>(
+        &self,
+        other: Arc<dyn Rdd<Item = S>>,
+        func: F,
+    ) -> Result<SerArc<dyn Rdd<Item = U>>>
+    where
+        Self: Sized,
+        F: SerFunc(
+            Box<dyn Iterator<Item = Self::Item>>,
+            Box<dyn Iterator<Item = S>>,
+        ) -> Box<dyn Iterator<Item = U>>,
+    {
+        let (first, second) = (self.number_of_splits(), other.number_of_splits());
+        if first != second {
+            return Err(Error::UnequalPartitionCounts { first, second });
+        }
+        Ok(SerArc::new(ZippedPartitionsWithFuncRdd::new(
+            self.get_rdd(),
+            other,
+            func,
+        )))
+    }
+
+    /// Zips this RDD with its element indices. The ordering is first based on the partition index
+    /// and then the ordering of items within each partition. So the first item in the first
+    /// partition gets index 0, and the last item in the last partition receives the largest index.
+    ///
+    /// # Notes
+    ///
+    /// This method needs to trigger a job to compute the size of each partition when this RDD
+    /// contains more than one partition.
+    fn zip_with_index(&self) -> Result<SerArc<dyn Rdd<Item = (Self::Item, u64)>>>
+    where
+        Self: Sized,
+    {
+        let num_splits = self.number_of_splits();
+        let mut start_indices = vec![0u64];
+        if num_splits > 1 {
+            // the size of the last partition is not required to compute the start indices
+            let counting_func =
+                Fn!(|iter: Box<dyn Iterator<Item = Self::Item>>| { iter.count() as u64 });
+            let sizes = self.get_context().run_job_with_partitions(
+                self.get_rdd(),
+                counting_func,
+                0..num_splits - 1,
+            )?;
+            start_indices.extend(sizes.into_iter().scan(0u64, |acc, size| {
+                *acc += size;
+                Some(*acc)
+            }));
+        }
+
+        let zip_func = Fn!(move |index: usize,
+                                 items: Box<dyn Iterator<Item = Self::Item>>|
+              -> Box<dyn Iterator<Item = (Self::Item, u64)>> {
+            Box::new(items.zip(start_indices[index]..))
+        });
+        let rdd = MapPartitionsRdd::new(self.get_rdd(), zip_func);
+        rdd.register_op_name("zip_with_index");
+        Ok(SerArc::new(rdd))
+    }
+
+    /// Zips this RDD with generated unique ids. Items in the kth partition will get ids k, n+k,
+    /// 2*n+k, ..., where n is the number of partitions. So there may exist gaps, but this
+    /// method won't trigger a job, which is different from [zip_with_index](#zip_with_index).
+    fn zip_with_unique_id(&self) -> SerArc<dyn Rdd<Item = (Self::Item, u64)>>
+    where
+        Self: Sized,
+    {
+        let num_splits = self.number_of_splits() as u64;
+        let zip_func = Fn!(move |index: usize,
+                                 items: Box<dyn Iterator<Item = Self::Item>>|
+              -> Box<dyn Iterator<Item = (Self::Item, u64)>> {
+            let index = index as u64;
+            Box::new(
+                items
+                    .enumerate()
+                    .map(move |(i, item)| (item, i as u64 * num_splits + index)),
+            )
+        });
+        let rdd = MapPartitionsRdd::new(self.get_rdd(), zip_func);
+        rdd.register_op_name("zip_with_unique_id");
+        SerArc::new(rdd)
+    }
+
     fn intersection<T>(&self, other: Arc<T>) -> SerArc<dyn Rdd<Item = Self::Item>>
     where
         Self: Clone,
@@ -1159,3 +1582,33 @@ pub trait Reduce<T> {
         Self: Sized,
         F: FnMut(T, T) -> T;
 }
+
+/// The partitions scanned by the next job of `take`, given the number of elements taken from
+/// the partitions scanned so far: more partitions are scanned with each job.
+fn partitions_to_take(
+    num: usize,
+    taken: usize,
+    parts_scanned: usize,
+    total_parts: usize,
+) -> Range<usize> {
+    // TODO: in original spark this is configurable; see rdd/RDD.scala:1397
+    // Math.max(conf.get(RDD_LIMIT_SCALE_UP_FACTOR), 2)
+    const SCALE_UP_FACTOR: f64 = 2.0;
+    // The number of partitions to try in this iteration. It is ok for this number to be
+    // greater than total_parts because we actually cap it at total_parts in run_job.
+    let mut num_parts_to_try = 1;
+    if parts_scanned > 0 {
+        // If we didn't find any rows after the previous iteration, quadruple and retry.
+        // Otherwise, interpolate the number of partitions we need to try, but overestimate
+        // it by 50%. We also cap the estimation in the end.
+        let parts_scanned = parts_scanned as f64;
+        num_parts_to_try = if taken == 0 {
+            (parts_scanned * SCALE_UP_FACTOR).ceil() as usize
+        } else {
+            let left = (num - taken) as f64;
+            let num_parts_to_try = (1.5 * left * parts_scanned / (taken as f64)).ceil();
+            num_parts_to_try.min(parts_scanned * SCALE_UP_FACTOR) as usize
+        };
+    }
+    parts_scanned..total_parts.min(parts_scanned + num_parts_to_try)
+}
@@ -0,0 +1,54 @@
+use crate::error::{Error, Result, StdResult};
+use crate::rdd::Rdd;
+use crate::serializable_traits::Data;
+use crate::{Fn, SerArc};
+
+/// Actions for RDDs whose items are results, like the rows of a file which may be malformed.
+///
+/// The actions return the first error of the RDD, in the order of the partitions, instead of
+/// the items.
+pub trait ResultRdd<T: Data, E: Data + Into<Error>>:
+    Rdd<Item = StdResult<T, E>> + Send + Sync
+{
+    /// Return the successful items of this RDD, or the first error.
+    fn try_collect(&self) -> Result<Vec<T>>
+    where
+        Self: Sized,
+    {
+        let cl = Fn!(|iter: Box<dyn Iterator<Item = StdResult<T, E>>>| iter
+            .collect::<StdResult<Vec<T>, E>>());
+        let mut items = vec![];
+        for partition in self.get_context().run_job(self.get_rdd(), cl)? {
+            items.extend(partition.map_err(Into::into)?);
+        }
+        Ok(items)
+    }
+
+    /// Return the number of items of this RDD, or the first error.
+    fn try_count(&self) -> Result<u64>
+    where
+        Self: Sized,
+    {
+        let cl = Fn!(
+            |iter: Box<dyn Iterator<Item = StdResult<T, E>>>| -> StdResult<u64, E> {
+                let mut count = 0;
+                for item in iter {
+                    item?;
+                    count += 1;
+                }
+                Ok(count)
+            }
+        );
+        let mut count = 0;
+        for partition in self.get_context().run_job(self.get_rdd(), cl)? {
+            count += partition.map_err(Into::into)?;
+        }
+        Ok(count)
+    }
+}
+
+impl<T: Data, E: Data + Into<Error>, R> ResultRdd<T, E> for R where R: Rdd<Item = StdResult<T, E>> {}
+impl<T: Data, E: Data + Into<Error>, R> ResultRdd<T, E> for SerArc<R> where
+    R: Rdd<Item = StdResult<T, E>>
+{
+}
@@ -6,7 +6,7 @@ use crate::context::Context;
 use crate::dependency::{Dependency, OneToOneDependency};
 use crate::error::{Error, Result};
 use crate::rdd::{Rdd, RddBase, RddVals};
-use crate::serializable_traits::{AnyData, Data};
+use crate::serializable_traits::{AnyData, Data, SerFunc};
 use crate::split::Split;
 use serde_derive::{Deserialize, Serialize};
 
@@ -148,3 +148,166 @@ impl<F: Data, S: Data> ZippedPartitionsRdd<F, S> {
         }
     }
 }
+
+/// An RDD that passes the iterators of the partitions with the same index of two parent RDDs
+/// to a user provided function and yields whatever that function returns.
+#[derive(Serialize, Deserialize)]
+pub struct ZippedPartitionsWithFuncRdd<F: Data, S: Data, U: Data, Func>
+where
+    Func: SerFunc(
+        Box<dyn Iterator<Item = F>>,
+        Box<dyn Iterator<Item = S>>,
+    ) -> Box<dyn Iterator<Item = U>>,
+{
+    #[serde(with = "serde_traitobject")]
+    first: Arc<dyn Rdd<Item = F>>,
+    #[serde(with = "serde_traitobject")]
+    second: Arc<dyn Rdd<Item = S>>,
+    vals: Arc<RddVals>,
+    func: Func,
+    _marker_t: PhantomData<(F, S, U)>,
+}
+
+impl<F: Data, S: Data, U: Data, Func> Clone for ZippedPartitionsWithFuncRdd<F, S, U, Func>
+where
+    Func: SerFunc(
+        Box<dyn Iterator<Item = F>>,
+        Box<dyn Iterator<Item = S>>,
+    ) -> Box<dyn Iterator<Item = U>>,
+{
+    fn clone(&self) -> Self {
+        ZippedPartitionsWithFuncRdd {
+            first: self.first.clone(),
+            second: self.second.clone(),
+            vals: self.vals.clone(),
+            func: self.func.clone(),
+            _marker_t: PhantomData,
+        }
+    }
+}
+
+impl<F: Data, S: Data, U: Data, Func> RddBase for ZippedPartitionsWithFuncRdd<F, S, U, Func>
+where
+    Func: SerFunc(
+        Box<dyn Iterator<Item = F>>,
+        Box<dyn Iterator<Item = S>>,
+    ) -> Box<dyn Iterator<Item = U>>,
+{
+    fn get_rdd_id(&self) -> usize {
+        self.vals.id
+    }
+
+    fn get_context(&self) -> Arc<Context> {
+        self.vals.context.upgrade().unwrap()
+    }
+
+    fn get_dependencies(&self) -> Vec<Dependency> {
+        self.vals.dependencies.clone()
+    }
+
+    fn splits(&self) -> Vec<Box<dyn Split>> {
+        let mut arr = Vec::with_capacity(min(
+            self.first.number_of_splits(),
+            self.second.number_of_splits(),
+        ));
+
+        for (fst, sec) in self.first.splits().iter().zip(self.second.splits().iter()) {
+            let fst_idx = fst.get_index();
+            let sec_idx = sec.get_index();
+
+            arr.push(Box::new(ZippedPartitionsSplit {
+                fst_idx,
+                sec_idx,
+                idx: fst_idx,
+                fst_split: fst.clone(),
+                sec_split: sec.clone(),
+            }) as Box<dyn Split>)
+        }
+        arr
+    }
+
+    fn number_of_splits(&self) -> usize {
+        min(
+            self.first.number_of_splits(),
+            self.second.number_of_splits(),
+        )
+    }
+
+    fn iterator_any(
+        &self,
+        split: Box<dyn Split>,
+    ) -> Result<Box<dyn Iterator<Item = Box<dyn AnyData>>>> {
+        Ok(Box::new(
+            self.iterator(split)?
+                .map(|x| Box::new(x) as Box<dyn AnyData>),
+        ))
+    }
+
+    fn cogroup_iterator_any(
+        &self,
+        split: Box<dyn Split>,
+    ) -> Result<Box<dyn Iterator<Item = Box<dyn AnyData>>>> {
+        self.iterator_any(split)
+    }
+}
+
+impl<F: Data, S: Data, U: Data, Func> Rdd for ZippedPartitionsWithFuncRdd<F, S, U, Func>
+where
+    Func: SerFunc(
+        Box<dyn Iterator<Item = F>>,
+        Box<dyn Iterator<Item = S>>,
+    ) -> Box<dyn Iterator<Item = U>>,
+{
+    type Item = U;
+
+    fn get_rdd(&self) -> Arc<dyn Rdd<Item = Self::Item>> {
+        Arc::new(self.clone())
+    }
+
+    fn get_rdd_base(&self) -> Arc<dyn RddBase> {
+        Arc::new(self.clone()) as Arc<dyn RddBase>
+    }
+
+    fn compute(&self, split: Box<dyn Split>) -> Result<Box<dyn Iterator<Item = Self::Item>>> {
+        let current_split = split
+            .downcast::<ZippedPartitionsSplit>()
+            .or(Err(Error::DowncastFailure("ZippedPartitionsSplit")))?;
+
+        let fst_iter = self.first.iterator(current_split.fst_split.clone())?;
+        let sec_iter = self.second.iterator(current_split.sec_split.clone())?;
+        Ok((self.func)(fst_iter, sec_iter))
+    }
+}
+
+impl<F: Data, S: Data, U: Data, Func> ZippedPartitionsWithFuncRdd<F, S, U, Func>
+where
+    Func: SerFunc(
+        Box<dyn Iterator<Item = F>>,
+        Box<dyn Iterator<Item = S>>,
+    ) -> Box<dyn Iterator<Item = U>>,
+{
+    pub(crate) fn new(
+        first: Arc<dyn Rdd<Item = F>>,
+        second: Arc<dyn Rdd<Item = S>>,
+        func: Func,
+    ) -> Self {
+        let mut vals = RddVals::new(first.get_context());
+        vals.dependencies
+            .push(Dependency::NarrowDependency(Arc::new(
+                OneToOneDependency::new(first.get_rdd_base()),
+            )));
+        vals.dependencies
+            .push(Dependency::NarrowDependency(Arc::new(
+                OneToOneDependency::new(second.get_rdd_base()),
+            )));
+        let vals = Arc::new(vals);
+
+        ZippedPartitionsWithFuncRdd {
+            first,
+            second,
+            vals,
+            func,
+            _marker_t: PhantomData,
+        }
+    }
+}
@@ -1,22 +1,43 @@
 use std::collections::{BTreeSet, VecDeque};
 use std::net::{Ipv4Addr, SocketAddrV4};
 use std::sync::Arc;
-use std::thread;
-use std::time::{Duration, Instant};
 
 use crate::dependency::{Dependency, ShuffleDependencyTrait};
 use crate::env;
 use crate::error::{Error, Result};
 use crate::rdd::RddBase;
 use crate::scheduler::{
-    CompletionEvent, FetchFailedVals, JobListener, JobTracker, ResultTask, Stage, TaskBase,
-    TaskContext, TaskOption,
+    catch_task_failure, CompletionEvent, FetchFailedVals, JobCancellation, JobListener, JobTracker,
+    ResultTask, Stage, TaskBase, TaskContext, TaskOption,
 };
 use crate::serializable_traits::{Data, SerFunc};
 use crate::shuffle::ShuffleMapTask;
 use dashmap::DashMap;
+use futures::future::{self, Either};
+use tokio::sync::Notify;
 
-pub(crate) type EventQueue = Arc<DashMap<usize, VecDeque<CompletionEvent>>>;
+/// Completion events of the tasks of a job, waking up the event loop of the job when pushed.
+pub(crate) struct JobEvents {
+    events: VecDeque<CompletionEvent>,
+    notify: Arc<Notify>,
+}
+
+impl JobEvents {
+    pub fn new() -> Self {
+        JobEvents {
+            events: VecDeque::new(),
+            notify: Arc::new(Notify::new()),
+        }
+    }
+
+    pub fn push(&mut self, event: CompletionEvent) {
+        self.events.push_back(event);
+        self.notify.notify();
+    }
+}
+
+/// The key is the run id of the job.
+pub(crate) type EventQueue = Arc<DashMap<usize, JobEvents>>;
 
 /// Functionality of the library built-in schedulers
 #[async_trait::async_trait]
@@ -32,10 +53,10 @@ pub(crate) trait NativeScheduler: Send + Sync {
         if jt.final_stage.parents.is_empty() && (jt.num_output_parts == 1) {
             let split = (jt.final_rdd.splits()[jt.output_parts[0]]).clone();
             let task_context = TaskContext::new(jt.final_stage.id, jt.output_parts[0], 0);
-            Ok(Some(vec![(&jt.func)((
-                task_context,
-                jt.final_rdd.iterator(split)?,
-            ))]))
+            let iter = jt.final_rdd.iterator(split)?;
+            let result = catch_task_failure(|| (&jt.func)((task_context, iter)))
+                .map_err(Error::TaskFailed)?;
+            Ok(Some(vec![result]))
         } else {
             Ok(None)
         }
@@ -219,6 +240,12 @@ pub(crate) trait NativeScheduler: Send + Sync {
             .is_some();
         if result_type {
             if let Ok(rt) = completed_event.task.downcast::<ResultTask<T, U, F>>() {
+                if jt.finished.lock().await[rt.output_id] {
+                    // Another attempt of the task already finished: keep the first result so
+                    // that the partition is only counted, and its output only committed, once.
+                    log::debug!("ignoring duplicate result for output #{}", rt.output_id);
+                    return Ok(());
+                }
                 let any_result = completed_event.result.take().ok_or_else(|| Error::Other)?;
                 jt.listener
                     .task_succeeded(rt.output_id, &*any_result)
@@ -454,17 +481,70 @@ pub(crate) trait NativeScheduler: Send + Sync {
         Ok(())
     }
 
-    fn wait_for_event(&self, run_id: usize, timeout: u64) -> Option<CompletionEvent> {
-        // TODO: make use of async to wait for events
-        let end = Instant::now() + Duration::from_millis(timeout);
-        while self.get_event_queue().get(&run_id)?.is_empty() {
-            if Instant::now() > end {
-                return None;
-            } else {
-                thread::sleep(end - Instant::now());
+    /// Wait for the next completion event of a job, or until the job is cancelled.
+    async fn wait_for_event(
+        &self,
+        run_id: usize,
+        cancellation: Option<&JobCancellation>,
+    ) -> Result<CompletionEvent> {
+        loop {
+            if cancellation.map_or(false, JobCancellation::is_cancelled) {
+                return Err(Error::JobCancelled);
+            }
+            let notify = {
+                let mut job_events = self
+                    .get_event_queue()
+                    .get_mut(&run_id)
+                    .ok_or_else(|| Error::Other)?;
+                if let Some(event) = job_events.events.pop_front() {
+                    return Ok(event);
+                }
+                job_events.notify.clone()
+            };
+            match cancellation {
+                Some(cancellation) => {
+                    let event = Box::pin(notify.notified());
+                    let cancelled = Box::pin(cancellation.cancelled());
+                    if let Either::Right(_) = future::select(event, cancelled).await {
+                        return Err(Error::JobCancelled);
+                    }
+                }
+                None => notify.notified().await,
             }
         }
-        self.get_event_queue().get_mut(&run_id)?.pop_front()
+    }
+
+    /// Stop a cancelled job. Its tasks which did not start yet are skipped, and the results of
+    /// the running ones are ignored.
+    async fn kill_job<T: Data, U: Data, F, L>(&self, jt: Arc<JobTracker<F, U, T, L>>)
+    where
+        F: SerFunc((TaskContext, Box<dyn Iterator<Item = T>>)) -> U,
+        L: JobListener,
+    {
+        log::info!("cancelling job #{}", jt.run_id);
+        self.get_event_queue().remove(&jt.run_id);
+        jt.waiting.lock().await.clear();
+        jt.running.lock().await.clear();
+        jt.failed.lock().await.clear();
+        jt.pending_tasks.lock().await.clear();
+    }
+
+    /// Stop a job after one of its tasks failed, returning the failure.
+    async fn fail_job<T: Data, U: Data, F, L>(
+        &self,
+        jt: Arc<JobTracker<F, U, T, L>>,
+        message: String,
+    ) -> Result<Vec<U>>
+    where
+        F: SerFunc((TaskContext, Box<dyn Iterator<Item = T>>)) -> U,
+        L: JobListener,
+    {
+        log::error!("job #{} failed: {}", jt.run_id, message);
+        self.kill_job(jt.clone()).await;
+        jt.listener
+            .job_failed(Error::TaskFailed(message.clone()))
+            .await;
+        Err(Error::TaskFailed(message))
     }
 
     fn submit_task<T: Data, U: Data, F>(
@@ -489,7 +569,7 @@ pub(crate) trait NativeScheduler: Send + Sync {
     fn fetch_from_stage_cache(&self, id: usize) -> Stage;
     fn fetch_from_shuffle_to_cache(&self, id: usize) -> Stage;
     fn get_cache_locs(&self, rdd: Arc<dyn RddBase>) -> Option<Vec<Vec<Ipv4Addr>>>;
-    fn get_event_queue(&self) -> &Arc<DashMap<usize, VecDeque<CompletionEvent>>>;
+    fn get_event_queue(&self) -> &EventQueue;
     async fn get_missing_parent_stages<'a>(&'a self, stage: Stage) -> Result<Vec<Stage>>;
     fn get_next_job_id(&self) -> usize;
     fn get_next_stage_id(&self) -> usize;
@@ -588,7 +668,7 @@ macro_rules! impl_common_scheduler_funcs {
         }
 
         #[inline]
-        fn get_event_queue(&self) -> &Arc<DashMap<usize, VecDeque<CompletionEvent>>> {
+        fn get_event_queue(&self) -> &EventQueue {
             &self.event_queues
         }
 
@@ -10,14 +10,15 @@ use std::time::{Duration, Instant};
 
 use crate::dependency::ShuffleDependencyTrait;
 use crate::env;
-use crate::error::{Error, NetworkError, Result};
+use crate::error::{Error, NetworkError, Result, StdResult};
 use crate::map_output_tracker::MapOutputTracker;
 use crate::partial::{ApproximateActionListener, ApproximateEvaluator, PartialResult};
 use crate::rdd::{Rdd, RddBase};
 use crate::scheduler::{
     listener::{JobEndListener, JobStartListener},
-    CompletionEvent, EventQueue, Job, JobListener, JobTracker, LiveListenerBus, NativeScheduler,
-    NoOpListener, ResultTask, Stage, TaskBase, TaskContext, TaskOption, TaskResult, TastEndReason,
+    CompletionEvent, EventQueue, Job, JobCancellation, JobEvents, JobListener, JobTracker,
+    LiveListenerBus, NativeScheduler, NoOpListener, ResultTask, Stage, TaskBase, TaskContext,
+    TaskOption, TaskResult, TastEndReason,
 };
 use crate::serializable_traits::{AnyData, Data, SerFunc};
 use crate::serialized_data_capnp::serialized_data;
@@ -41,7 +42,6 @@ pub(crate) struct DistributedScheduler {
     max_failures: usize,
     attempt_id: Arc<AtomicUsize>,
     resubmit_timeout: u128,
-    poll_timeout: u64,
     event_queues: EventQueue,
     next_job_id: Arc<AtomicUsize>,
     next_run_id: Arc<AtomicUsize>,
@@ -63,7 +63,7 @@ pub(crate) struct DistributedScheduler {
     port: u16,
     map_output_tracker: MapOutputTracker,
     // TODO: fix proper locking mechanism
-    scheduler_lock: Arc<Mutex<bool>>,
+    scheduler_lock: Arc<tokio::sync::Mutex<()>>,
     live_listener_bus: LiveListenerBus,
 }
 
@@ -85,7 +85,6 @@ impl DistributedScheduler {
             max_failures,
             attempt_id: Arc::new(AtomicUsize::new(0)),
             resubmit_timeout: 2000,
-            poll_timeout: 50,
             event_queues: Arc::new(DashMap::new()),
             next_job_id: Arc::new(AtomicUsize::new(0)),
             next_run_id: Arc::new(AtomicUsize::new(0)),
@@ -110,7 +109,7 @@ impl DistributedScheduler {
             },
             port,
             map_output_tracker: env::Env::get().map_output_tracker.clone(),
-            scheduler_lock: Arc::new(Mutex::new(true)),
+            scheduler_lock: Arc::new(tokio::sync::Mutex::new(())),
             live_listener_bus,
         }
     }
@@ -132,10 +131,10 @@ impl DistributedScheduler {
         // acquiring lock so that only one job can run at same time this lock is just
         // a temporary patch for preventing multiple jobs to update cache locks which affects
         // construction of dag task graph. dag task graph construction needs to be altered
-        let selfc = self.clone();
-        let _lock = selfc.scheduler_lock.lock();
         env::Env::run_in_async_rt(|| -> Result<PartialResult<R>> {
             futures::executor::block_on(async move {
+                let scheduler_lock = self.scheduler_lock.clone();
+                let _lock = scheduler_lock.lock().await;
                 let partitions: Vec<_> = (0..final_rdd.number_of_splits()).collect();
                 let listener = ApproximateActionListener::new(evaluator, timeout, partitions.len());
                 let jt = JobTracker::from_scheduler(
@@ -144,6 +143,7 @@ impl DistributedScheduler {
                     final_rdd.clone(),
                     partitions,
                     listener,
+                    None,
                 )
                 .await?;
                 if final_rdd.number_of_splits() == 0 {
@@ -177,27 +177,51 @@ impl DistributedScheduler {
         partitions: Vec<usize>,
         allow_local: bool,
     ) -> Result<Vec<U>>
+    where
+        F: SerFunc((TaskContext, Box<dyn Iterator<Item = T>>)) -> U,
+    {
+        env::Env::run_in_async_rt(|| -> Result<Vec<U>> {
+            futures::executor::block_on(self.run_job_async(
+                func,
+                final_rdd,
+                partitions,
+                allow_local,
+                None,
+            ))
+        })
+    }
+
+    /// Run a job from the async runtime, without blocking a thread while waiting for its
+    /// tasks. The job is stopped as soon as `cancellation` is cancelled.
+    pub async fn run_job_async<T: Data, U: Data, F>(
+        self: Arc<Self>,
+        func: Arc<F>,
+        final_rdd: Arc<dyn Rdd<Item = T>>,
+        partitions: Vec<usize>,
+        allow_local: bool,
+        cancellation: Option<Arc<JobCancellation>>,
+    ) -> Result<Vec<U>>
     where
         F: SerFunc((TaskContext, Box<dyn Iterator<Item = T>>)) -> U,
     {
         // acquiring lock so that only one job can run at same time this lock is just
         // a temporary patch for preventing multiple jobs to update cache locks which affects
         // construction of dag task graph. dag task graph construction needs to be altered
-        let selfc = self.clone();
-        let _lock = selfc.scheduler_lock.lock();
-        env::Env::run_in_async_rt(|| -> Result<Vec<U>> {
-            futures::executor::block_on(async move {
-                let jt = JobTracker::from_scheduler(
-                    &*self,
-                    func,
-                    final_rdd.clone(),
-                    partitions,
-                    NoOpListener,
-                )
-                .await?;
-                self.event_process_loop(allow_local, jt).await
-            })
-        })
+        let scheduler_lock = self.scheduler_lock.clone();
+        let _lock = scheduler_lock.lock().await;
+        if cancellation.as_ref().map_or(false, |c| c.is_cancelled()) {
+            return Err(Error::JobCancelled);
+        }
+        let jt = JobTracker::from_scheduler(
+            &*self,
+            func,
+            final_rdd.clone(),
+            partitions,
+            NoOpListener,
+            cancellation,
+        )
+        .await?;
+        self.event_process_loop(allow_local, jt).await
     }
 
     /// Start the event processing loop for a given job.
@@ -218,7 +242,7 @@ impl DistributedScheduler {
             }
         }
 
-        self.event_queues.insert(jt.run_id, VecDeque::new());
+        self.event_queues.insert(jt.run_id, JobEvents::new());
 
         let mut results: Vec<Option<U>> = (0..jt.num_output_parts).map(|_| None).collect();
         let mut fetch_failure_duration = Duration::new(0, 0);
@@ -237,41 +261,53 @@ impl DistributedScheduler {
 
         let mut num_finished = 0;
         while num_finished != jt.num_output_parts {
-            let event_option = self.wait_for_event(jt.run_id, self.poll_timeout);
+            let evt = match self
+                .wait_for_event(jt.run_id, jt.cancellation.as_deref())
+                .await
+            {
+                Ok(evt) => evt,
+                Err(err) => {
+                    self.kill_job(jt.clone()).await;
+                    return Err(err);
+                }
+            };
             let start = Instant::now();
-
-            if let Some(evt) = event_option {
-                log::debug!("event starting");
-                let stage = self
-                    .stage_cache
-                    .get(&evt.task.get_stage_id())
-                    .unwrap()
-                    .clone();
-                log::debug!(
-                    "removing stage #{} task from pending task #{}",
-                    stage.id,
-                    evt.task.get_task_id()
-                );
-                jt.pending_tasks
-                    .lock()
-                    .await
-                    .get_mut(&stage)
-                    .unwrap()
-                    .remove(&evt.task);
-                use super::dag_scheduler::TastEndReason::*;
-                match evt.reason {
-                    Success => {
-                        self.on_event_success(evt, &mut results, &mut num_finished, jt.clone())
-                            .await?;
-                    }
-                    FetchFailed(failed_vals) => {
-                        self.on_event_failure(jt.clone(), failed_vals, evt.task.get_stage_id())
-                            .await;
-                        fetch_failure_duration = start.elapsed();
-                    }
-                    Error(error) => panic!("{}", error),
-                    OtherFailure(msg) => panic!("{}", msg),
+            log::debug!("event starting");
+            let stage = self
+                .stage_cache
+                .get(&evt.task.get_stage_id())
+                .unwrap()
+                .clone();
+            log::debug!(
+                "removing stage #{} task from pending task #{}",
+                stage.id,
+                evt.task.get_task_id()
+            );
+            jt.pending_tasks
+                .lock()
+                .await
+                .get_mut(&stage)
+                .unwrap()
+                .remove(&evt.task);
+            if !matches!(evt.reason, TastEndReason::Success) {
+                // The attempt gives up committing its output, if it was authorized to.
+                env::Env::get()
+                    .output_commit_coordinator
+                    .task_failed(evt.task.get_stage_id(), evt.task.get_task_id());
+            }
+            use super::dag_scheduler::TastEndReason::*;
+            match evt.reason {
+                Success => {
+                    self.on_event_success(evt, &mut results, &mut num_finished, jt.clone())
+                        .await?;
+                }
+                FetchFailed(failed_vals) => {
+                    self.on_event_failure(jt.clone(), failed_vals, evt.task.get_stage_id())
+                        .await;
+                    fetch_failure_duration = start.elapsed();
                 }
+                Error(error) => return self.fail_job(jt, error.to_string()).await,
+                OtherFailure(msg) => return self.fail_job(jt, msg).await,
             }
         }
 
@@ -296,7 +332,7 @@ impl DistributedScheduler {
     }
 
     async fn receive_results<T: Data, U: Data, F, R>(
-        event_queues: Arc<DashMap<usize, VecDeque<CompletionEvent>>>,
+        event_queues: EventQueue,
         receiver: R,
         task: TaskOption,
         target_port: u16,
@@ -323,49 +359,42 @@ impl DistributedScheduler {
         match task {
             TaskOption::ResultTask(tsk) => {
                 let result = match result {
-                    TaskResult::ResultTask(r) => r,
+                    TaskResult::ResultTask(r) => Ok(r.into_box()),
+                    TaskResult::Failed(message) => Err(message),
                     _ => panic!("wrong result type"),
                 };
                 if let Ok(task_final) = tsk.downcast::<ResultTask<T, U, F>>() {
                     let task_final = task_final as Box<dyn TaskBase>;
-                    DistributedScheduler::task_ended(
-                        event_queues,
-                        task_final,
-                        TastEndReason::Success,
-                        // Can break in future. But actually not needed for distributed scheduler since task runs on different processes.
-                        // Currently using this because local scheduler needs it. It can be solved by refactoring tasks differently for local and distributed scheduler
-                        result.into_box(),
-                    );
+                    DistributedScheduler::task_ended(event_queues, task_final, result);
                 }
             }
             TaskOption::ShuffleMapTask(tsk) => {
                 let result = match result {
-                    TaskResult::ShuffleTask(r) => r,
+                    TaskResult::ShuffleTask(r) => Ok(r.into_box()),
+                    TaskResult::Failed(message) => Err(message),
                     _ => panic!("wrong result type"),
                 };
                 if let Ok(task_final) = tsk.downcast::<ShuffleMapTask>() {
                     let task_final = task_final as Box<dyn TaskBase>;
-                    DistributedScheduler::task_ended(
-                        event_queues,
-                        task_final,
-                        TastEndReason::Success,
-                        result.into_box(),
-                    );
+                    DistributedScheduler::task_ended(event_queues, task_final, result);
                 }
             }
         };
     }
 
+    /// Report the end of a task, with its result or the message of its failure.
     fn task_ended(
-        event_queues: Arc<DashMap<usize, VecDeque<CompletionEvent>>>,
+        event_queues: EventQueue,
         task: Box<dyn TaskBase>,
-        reason: TastEndReason,
-        result: Box<dyn AnyData>,
+        result: StdResult<Box<dyn AnyData>, String>,
         // TODO: accumvalues needs to be done
     ) {
-        let result = Some(result);
+        let (reason, result) = match result {
+            Ok(result) => (TastEndReason::Success, Some(result)),
+            Err(message) => (TastEndReason::OtherFailure(message), None),
+        };
         if let Some(mut queue) = event_queues.get_mut(&(task.get_run_id())) {
-            queue.push_back(CompletionEvent {
+            queue.push(CompletionEvent {
                 task,
                 reason,
                 result,
@@ -393,6 +422,10 @@ impl NativeScheduler for DistributedScheduler {
         log::debug!("inside submit task");
         let event_queues_clone = self.event_queues.clone();
         tokio::spawn(async move {
+            if !event_queues_clone.contains_key(&task.get_run_id()) {
+                log::debug!("skipping task #{} of a cancelled job", task.get_task_id());
+                return;
+            }
             let mut num_retries = 0;
             loop {
                 match TcpStream::connect(&target_executor).await {
@@ -446,19 +479,22 @@ impl NativeScheduler for DistributedScheduler {
 
     fn next_executor_server(&self, task: &dyn TaskBase) -> SocketAddrV4 {
         if !task.is_pinned() {
-            // pick the first available server
-            let socket_addrs = self.server_uris.lock().pop_back().unwrap();
-            self.server_uris.lock().push_front(socket_addrs);
+            // Pick a preferred host when one is available, otherwise any server. Used servers
+            // move to the front, so searching from the back rotates among the candidates.
+            let servers = &mut *self.server_uris.lock();
+            let preferred = task.preferred_locations();
+            let pos = servers
+                .iter()
+                .rposition(|e| preferred.contains(e.ip()))
+                .unwrap_or(servers.len() - 1);
+            let socket_addrs = servers.remove(pos).unwrap();
+            servers.push_front(socket_addrs);
             socket_addrs
         } else {
             // seek and pick the selected host
             let servers = &mut *self.server_uris.lock();
             let location: Ipv4Addr = task.preferred_locations()[0];
-            if let Some((pos, _)) = servers
-                .iter()
-                .enumerate()
-                .find(|(_, e)| *e.ip() == location)
-            {
+            if let Some(pos) = servers.iter().rposition(|e| *e.ip() == location) {
                 let target_host = servers.remove(pos).unwrap();
                 servers.push_front(target_host);
                 target_host
@@ -3,12 +3,14 @@ use std::cmp::Ordering;
 use std::collections::{BTreeMap, BTreeSet};
 use std::marker::PhantomData;
 use std::option::Option;
+use std::sync::atomic::{self, AtomicBool};
 use std::sync::Arc;
 
+use crate::env;
 use crate::scheduler::{JobListener, NativeScheduler, Stage, TaskBase, TaskContext};
 use crate::serializable_traits::{Data, SerFunc};
 use crate::{Rdd, Result};
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, Notify};
 
 #[derive(Clone, Debug)]
 pub(crate) struct Job {
@@ -43,6 +45,39 @@ impl Ord for Job {
     }
 }
 
+/// Cancellation of the jobs of an action. The event loop of a job running with a cancelled
+/// cancellation stops the job and returns `Error::JobCancelled`.
+pub(crate) struct JobCancellation {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl JobCancellation {
+    pub fn new() -> Self {
+        JobCancellation {
+            cancelled: AtomicBool::new(false),
+            notify: Notify::new(),
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, atomic::Ordering::SeqCst);
+        // wakes up the event loop of the running job, if any
+        self.notify.notify();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(atomic::Ordering::SeqCst)
+    }
+
+    /// Resolves once cancelled.
+    pub async fn cancelled(&self) {
+        while !self.is_cancelled() {
+            self.notify.notified().await;
+        }
+    }
+}
+
 type PendingTasks = BTreeMap<Stage, BTreeSet<Box<dyn TaskBase>>>;
 
 /// Contains all the necessary types to run and track a job progress
@@ -63,6 +98,7 @@ where
     pub finished: Mutex<Vec<bool>>,
     pub pending_tasks: Mutex<PendingTasks>,
     pub listener: L,
+    pub cancellation: Option<Arc<JobCancellation>>,
     _marker_t: PhantomData<T>,
     _marker_u: PhantomData<U>,
 }
@@ -78,6 +114,7 @@ where
         final_rdd: Arc<dyn Rdd<Item = T>>,
         output_parts: Vec<usize>,
         listener: L,
+        cancellation: Option<Arc<JobCancellation>>,
     ) -> Result<Arc<JobTracker<F, U, T, L>>>
     where
         S: NativeScheduler,
@@ -93,6 +130,7 @@ where
             final_rdd,
             output_parts,
             listener,
+            cancellation,
         ))
     }
 
@@ -103,9 +141,13 @@ where
         final_rdd: Arc<dyn Rdd<Item = T>>,
         output_parts: Vec<usize>,
         listener: L,
+        cancellation: Option<Arc<JobCancellation>>,
     ) -> Arc<JobTracker<F, U, T, L>> {
         let finished: Vec<bool> = (0..output_parts.len()).map(|_| false).collect();
         let pending_tasks: BTreeMap<Stage, BTreeSet<Box<dyn TaskBase>>> = BTreeMap::new();
+        env::Env::get()
+            .output_commit_coordinator
+            .stage_start(final_stage.id);
         Arc::new(JobTracker {
             num_output_parts: output_parts.len(),
             output_parts,
@@ -119,12 +161,26 @@ where
             finished: Mutex::new(finished),
             pending_tasks: Mutex::new(pending_tasks),
             listener: listener,
+            cancellation,
             _marker_t: PhantomData,
             _marker_u: PhantomData,
         })
     }
 }
 
+// The attempts of the final stage may only commit their output while the job runs.
+impl<F, U: Data, T: Data, L> Drop for JobTracker<F, U, T, L>
+where
+    F: SerFunc((TaskContext, Box<dyn Iterator<Item = T>>)) -> U,
+    L: JobListener,
+{
+    fn drop(&mut self) {
+        env::Env::get()
+            .output_commit_coordinator
+            .stage_end(self.final_stage.id);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,5 +1,5 @@
 use std::clone::Clone;
-use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fmt::Debug;
 use std::net::{Ipv4Addr, SocketAddrV4};
 use std::option::Option;
@@ -15,21 +15,19 @@ use crate::partial::{ApproximateActionListener, ApproximateEvaluator, PartialRes
 use crate::rdd::{Rdd, RddBase};
 use crate::scheduler::{
     listener::{JobEndListener, JobStartListener},
-    CompletionEvent, EventQueue, Job, JobListener, JobTracker, LiveListenerBus, NativeScheduler,
-    NoOpListener, ResultTask, Stage, TaskBase, TaskContext, TaskOption, TaskResult, TastEndReason,
+    CompletionEvent, EventQueue, Job, JobCancellation, JobEvents, JobListener, JobTracker,
+    LiveListenerBus, NativeScheduler, NoOpListener, ResultTask, Stage, TaskBase, TaskContext,
+    TaskOption, TaskResult, TastEndReason,
 };
 use crate::serializable_traits::{AnyData, Data, SerFunc};
 use crate::shuffle::ShuffleMapTask;
-use crate::{env, Result};
+use crate::{env, Error, Result, StdResult};
 use dashmap::DashMap;
-use parking_lot::Mutex;
 
 #[derive(Clone, Default)]
 pub(crate) struct LocalScheduler {
     max_failures: usize,
-    attempt_id: Arc<AtomicUsize>,
     resubmit_timeout: u128,
-    poll_timeout: u64,
     event_queues: EventQueue,
     pub(crate) next_job_id: Arc<AtomicUsize>,
     next_run_id: Arc<AtomicUsize>,
@@ -49,7 +47,7 @@ pub(crate) struct LocalScheduler {
     slaves_with_executors: HashSet<String>,
     map_output_tracker: MapOutputTracker,
     // TODO: fix proper locking mechanism
-    scheduler_lock: Arc<Mutex<()>>,
+    scheduler_lock: Arc<tokio::sync::Mutex<()>>,
     live_listener_bus: LiveListenerBus,
 }
 
@@ -59,9 +57,7 @@ impl LocalScheduler {
         live_listener_bus.start().unwrap();
         LocalScheduler {
             max_failures,
-            attempt_id: Arc::new(AtomicUsize::new(0)),
             resubmit_timeout: 2000,
-            poll_timeout: 50,
             event_queues: Arc::new(DashMap::new()),
             next_job_id: Arc::new(AtomicUsize::new(0)),
             next_run_id: Arc::new(AtomicUsize::new(0)),
@@ -80,7 +76,7 @@ impl LocalScheduler {
             job_tasks: HashMap::new(),
             slaves_with_executors: HashSet::new(),
             map_output_tracker: env::Env::get().map_output_tracker.clone(),
-            scheduler_lock: Arc::new(Mutex::new(())),
+            scheduler_lock: Arc::new(tokio::sync::Mutex::new(())),
             live_listener_bus,
         }
     }
@@ -102,10 +98,10 @@ impl LocalScheduler {
         // acquiring lock so that only one job can run at same time this lock is just
         // a temporary patch for preventing multiple jobs to update cache locks which affects
         // construction of dag task graph. dag task graph construction needs to be altered
-        let selfc = self.clone();
-        let _lock = selfc.scheduler_lock.lock();
         env::Env::run_in_async_rt(|| -> Result<PartialResult<R>> {
             futures::executor::block_on(async move {
+                let scheduler_lock = self.scheduler_lock.clone();
+                let _lock = scheduler_lock.lock().await;
                 let partitions: Vec<_> = (0..final_rdd.number_of_splits()).collect();
                 let listener = ApproximateActionListener::new(evaluator, timeout, partitions.len());
                 let jt = JobTracker::from_scheduler(
@@ -114,6 +110,7 @@ impl LocalScheduler {
                     final_rdd.clone(),
                     partitions,
                     listener,
+                    None,
                 )
                 .await?;
                 if final_rdd.number_of_splits() == 0 {
@@ -147,27 +144,51 @@ impl LocalScheduler {
         partitions: Vec<usize>,
         allow_local: bool,
     ) -> Result<Vec<U>>
+    where
+        F: SerFunc((TaskContext, Box<dyn Iterator<Item = T>>)) -> U,
+    {
+        env::Env::run_in_async_rt(|| -> Result<Vec<U>> {
+            futures::executor::block_on(self.run_job_async(
+                func,
+                final_rdd,
+                partitions,
+                allow_local,
+                None,
+            ))
+        })
+    }
+
+    /// Run a job from the async runtime, without blocking a thread while waiting for its
+    /// tasks. The job is stopped as soon as `cancellation` is cancelled.
+    pub async fn run_job_async<T: Data, U: Data, F>(
+        self: Arc<Self>,
+        func: Arc<F>,
+        final_rdd: Arc<dyn Rdd<Item = T>>,
+        partitions: Vec<usize>,
+        allow_local: bool,
+        cancellation: Option<Arc<JobCancellation>>,
+    ) -> Result<Vec<U>>
     where
         F: SerFunc((TaskContext, Box<dyn Iterator<Item = T>>)) -> U,
     {
         // acquiring lock so that only one job can run at same time this lock is just
         // a temporary patch for preventing multiple jobs to update cache locks which affects
         // construction of dag task graph. dag task graph construction needs to be altered
-        let selfc = self.clone();
-        let _lock = selfc.scheduler_lock.lock();
-        env::Env::run_in_async_rt(|| -> Result<Vec<U>> {
-            futures::executor::block_on(async move {
-                let jt = JobTracker::from_scheduler(
-                    &*self,
-                    func,
-                    final_rdd.clone(),
-                    partitions,
-                    NoOpListener,
-                )
-                .await?;
-                self.event_process_loop(allow_local, jt).await
-            })
-        })
+        let scheduler_lock = self.scheduler_lock.clone();
+        let _lock = scheduler_lock.lock().await;
+        if cancellation.as_ref().map_or(false, |c| c.is_cancelled()) {
+            return Err(Error::JobCancelled);
+        }
+        let jt = JobTracker::from_scheduler(
+            &*self,
+            func,
+            final_rdd.clone(),
+            partitions,
+            NoOpListener,
+            cancellation,
+        )
+        .await?;
+        self.event_process_loop(allow_local, jt).await
     }
 
     /// Start the event processing loop for a given job.
@@ -188,7 +209,7 @@ impl LocalScheduler {
             }
         }
 
-        self.event_queues.insert(jt.run_id, VecDeque::new());
+        self.event_queues.insert(jt.run_id, JobEvents::new());
 
         let mut results: Vec<Option<U>> = (0..jt.num_output_parts).map(|_| None).collect();
         let mut fetch_failure_duration = Duration::new(0, 0);
@@ -207,41 +228,53 @@ impl LocalScheduler {
 
         let mut num_finished = 0;
         while num_finished != jt.num_output_parts {
-            let event_option = self.wait_for_event(jt.run_id, self.poll_timeout);
+            let evt = match self
+                .wait_for_event(jt.run_id, jt.cancellation.as_deref())
+                .await
+            {
+                Ok(evt) => evt,
+                Err(err) => {
+                    self.kill_job(jt.clone()).await;
+                    return Err(err);
+                }
+            };
             let start = Instant::now();
-
-            if let Some(evt) = event_option {
-                log::debug!("event starting");
-                let stage = self
-                    .stage_cache
-                    .get(&evt.task.get_stage_id())
-                    .unwrap()
-                    .clone();
-                log::debug!(
-                    "removing stage #{} task from pending task #{}",
-                    stage.id,
-                    evt.task.get_task_id()
-                );
-                jt.pending_tasks
-                    .lock()
-                    .await
-                    .get_mut(&stage)
-                    .unwrap()
-                    .remove(&evt.task);
-                use super::dag_scheduler::TastEndReason::*;
-                match evt.reason {
-                    Success => {
-                        self.on_event_success(evt, &mut results, &mut num_finished, jt.clone())
-                            .await?;
-                    }
-                    FetchFailed(failed_vals) => {
-                        self.on_event_failure(jt.clone(), failed_vals, evt.task.get_stage_id())
-                            .await;
-                        fetch_failure_duration = start.elapsed();
-                    }
-                    Error(error) => panic!("{}", error),
-                    OtherFailure(msg) => panic!("{}", msg),
+            log::debug!("event starting");
+            let stage = self
+                .stage_cache
+                .get(&evt.task.get_stage_id())
+                .unwrap()
+                .clone();
+            log::debug!(
+                "removing stage #{} task from pending task #{}",
+                stage.id,
+                evt.task.get_task_id()
+            );
+            jt.pending_tasks
+                .lock()
+                .await
+                .get_mut(&stage)
+                .unwrap()
+                .remove(&evt.task);
+            if !matches!(evt.reason, TastEndReason::Success) {
+                // The attempt gives up committing its output, if it was authorized to.
+                env::Env::get()
+                    .output_commit_coordinator
+                    .task_failed(evt.task.get_stage_id(), evt.task.get_task_id());
+            }
+            use super::dag_scheduler::TastEndReason::*;
+            match evt.reason {
+                Success => {
+                    self.on_event_success(evt, &mut results, &mut num_finished, jt.clone())
+                        .await?;
+                }
+                FetchFailed(failed_vals) => {
+                    self.on_event_failure(jt.clone(), failed_vals, evt.task.get_stage_id())
+                        .await;
+                    fetch_failure_duration = start.elapsed();
                 }
+                Error(error) => return self.fail_job(jt, error.to_string()).await,
+                OtherFailure(msg) => return self.fail_job(jt, msg).await,
             }
         }
 
@@ -266,7 +299,7 @@ impl LocalScheduler {
     }
 
     fn run_task<T: Data, U: Data, F>(
-        event_queues: Arc<DashMap<usize, VecDeque<CompletionEvent>>>,
+        event_queues: EventQueue,
         task: Vec<u8>,
         _id_in_job: usize,
         attempt_id: usize,
@@ -274,51 +307,53 @@ impl LocalScheduler {
         F: SerFunc((TaskContext, Box<dyn Iterator<Item = T>>)) -> U,
     {
         let des_task: TaskOption = bincode::deserialize(&task).unwrap();
+        if !event_queues.contains_key(&des_task.get_run_id()) {
+            log::debug!(
+                "skipping task #{} of a cancelled job",
+                des_task.get_task_id()
+            );
+            return;
+        }
         let result = des_task.run(attempt_id);
         match des_task {
             TaskOption::ResultTask(tsk) => {
                 let result = match result {
-                    TaskResult::ResultTask(r) => r,
+                    TaskResult::ResultTask(r) => Ok(r.into_box()),
+                    TaskResult::Failed(message) => Err(message),
                     _ => panic!("wrong result type"),
                 };
                 if let Ok(task_final) = tsk.downcast::<ResultTask<T, U, F>>() {
                     let task_final = task_final as Box<dyn TaskBase>;
-                    LocalScheduler::task_ended(
-                        event_queues,
-                        task_final,
-                        TastEndReason::Success,
-                        result.into_box(),
-                    );
+                    LocalScheduler::task_ended(event_queues, task_final, result);
                 }
             }
             TaskOption::ShuffleMapTask(tsk) => {
                 let result = match result {
-                    TaskResult::ShuffleTask(r) => r,
+                    TaskResult::ShuffleTask(r) => Ok(r.into_box()),
+                    TaskResult::Failed(message) => Err(message),
                     _ => panic!("wrong result type"),
                 };
                 if let Ok(task_final) = tsk.downcast::<ShuffleMapTask>() {
                     let task_final = task_final as Box<dyn TaskBase>;
-                    LocalScheduler::task_ended(
-                        event_queues,
-                        task_final,
-                        TastEndReason::Success,
-                        result.into_box(),
-                    );
+                    LocalScheduler::task_ended(event_queues, task_final, result);
                 }
             }
         };
     }
 
+    /// Report the end of a task, with its result or the message of its failure.
     fn task_ended(
-        event_queues: Arc<DashMap<usize, VecDeque<CompletionEvent>>>,
+        event_queues: EventQueue,
         task: Box<dyn TaskBase>,
-        reason: TastEndReason,
-        result: Box<dyn AnyData>,
+        result: StdResult<Box<dyn AnyData>, String>,
         // TODO: accumvalues needs to be done
     ) {
-        let result = Some(result);
+        let (reason, result) = match result {
+            Ok(result) => (TastEndReason::Success, Some(result)),
+            Err(message) => (TastEndReason::OtherFailure(message), None),
+        };
         if let Some(mut queue) = event_queues.get_mut(&(task.get_run_id())) {
-            queue.push_back(CompletionEvent {
+            queue.push(CompletionEvent {
                 task,
                 reason,
                 result,
@@ -342,12 +377,13 @@ impl NativeScheduler for LocalScheduler {
         F: SerFunc((TaskContext, Box<dyn Iterator<Item = T>>)) -> U,
     {
         log::debug!("inside submit task");
-        let my_attempt_id = self.attempt_id.fetch_add(1, Ordering::SeqCst);
+        // Tasks get a new id every time they are submitted, which identifies the attempt.
+        let attempt_id = task.get_task_id();
         let event_queues = self.event_queues.clone();
         let task = bincode::serialize(&task).unwrap();
 
         tokio::task::spawn_blocking(move || {
-            LocalScheduler::run_task::<T, U, F>(event_queues, task, id_in_job, my_attempt_id)
+            LocalScheduler::run_task::<T, U, F>(event_queues, task, id_in_job, attempt_id)
         });
     }
 
@@ -10,11 +10,12 @@ mod job_listener;
 pub(self) mod listener;
 mod live_listener_bus;
 mod local_scheduler;
+mod output_commit_coordinator;
 mod result_task;
 mod stage;
 mod task;
 
-pub(self) use self::base_scheduler::EventQueue;
+pub(self) use self::base_scheduler::{EventQueue, JobEvents};
 pub(self) use self::dag_scheduler::{CompletionEvent, FetchFailedVals, TastEndReason};
 pub(self) use self::job::{Job, JobTracker};
 pub(self) use self::job_listener::NoOpListener;
@@ -23,11 +24,15 @@ pub(self) use self::stage::Stage;
 
 pub(crate) use self::base_scheduler::NativeScheduler;
 pub(crate) use self::distributed_scheduler::DistributedScheduler;
+pub(crate) use self::job::JobCancellation;
 pub(crate) use self::job_listener::JobListener;
 pub(crate) use self::local_scheduler::LocalScheduler;
+pub(crate) use self::output_commit_coordinator::OutputCommitCoordinator;
 pub(crate) use self::result_task::ResultTask;
-pub(crate) use self::task::TaskContext;
-pub(crate) use self::task::{Task, TaskBase, TaskOption, TaskResult};
+pub use self::task::TaskContext;
+pub(crate) use self::task::{
+    catch_task_failure, fail_task, task_failed, Task, TaskBase, TaskOption, TaskResult,
+};
 
 pub trait Scheduler {
     fn start(&self);
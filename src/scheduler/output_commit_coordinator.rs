@@ -0,0 +1,373 @@
+use std::collections::HashMap;
+use std::net::{SocketAddr, TcpStream};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use crate::scheduler::TaskContext;
+use crate::serialized_data_capnp::serialized_data;
+use crate::{Error, NetworkError, Result};
+use capnp::message::ReaderOptions;
+use capnp_futures::serialize as capnp_serialize;
+use parking_lot::Mutex;
+use serde_derive::{Deserialize, Serialize};
+use tokio::{net::TcpListener, stream::StreamExt};
+use tokio_util::compat::{Tokio02AsyncReadCompatExt, Tokio02AsyncWriteCompatExt};
+
+const CAPNP_BUF_READ_OPTS: ReaderOptions = ReaderOptions {
+    traversal_limit_in_words: std::u64::MAX,
+    nesting_limit: 64,
+};
+
+/// Time an attempt waits before asking again while another attempt of its task is authorized
+/// to commit.
+const PENDING_COMMIT_POLL: Duration = Duration::from_millis(20);
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub(crate) enum CommitCoordinatorMessage {
+    CanCommit {
+        stage_id: usize,
+        split_id: usize,
+        attempt_id: usize,
+    },
+    CommitDone {
+        stage_id: usize,
+        split_id: usize,
+        attempt_id: usize,
+    },
+    CommitAborted {
+        stage_id: usize,
+        split_id: usize,
+        attempt_id: usize,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum CommitCoordinatorReply {
+    /// The attempt may commit the output of its partition.
+    Authorized,
+    /// Another attempt is authorized and has not committed yet.
+    Pending {
+        attempt_id: usize,
+    },
+    /// Another attempt committed the output of the partition.
+    Committed {
+        attempt_id: usize,
+    },
+    /// The stage is not running, so its output can't be committed anymore.
+    StageInactive,
+    Ok,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PartitionCommit {
+    Authorized(usize),
+    Committed(usize),
+}
+
+/// Decides which attempt of a task commits the output of its partition, so that the output
+/// of every partition is committed exactly once.
+///
+/// The coordinator runs on the driver, which registers the final stage of every job while
+/// it runs. The first attempt asking to commit a partition is authorized, and keeps the
+/// authorization until it reports its commit as done, or until it aborts its commit or fails,
+/// which lets another attempt commit instead. Executors reach the coordinator of the driver
+/// through the network, like the cache tracker.
+#[derive(Debug)]
+pub(crate) struct OutputCommitCoordinator {
+    is_master: bool,
+    master_addr: SocketAddr,
+    /// Commits of the running stages, by stage id then by partition.
+    stages: Mutex<HashMap<usize, HashMap<usize, PartitionCommit>>>,
+}
+
+impl OutputCommitCoordinator {
+    pub fn new(is_master: bool, master_addr: SocketAddr) -> Arc<Self> {
+        let coordinator = Arc::new(OutputCommitCoordinator {
+            is_master,
+            master_addr: SocketAddr::new(master_addr.ip(), master_addr.port() + 2),
+            stages: Mutex::new(HashMap::new()),
+        });
+        coordinator.clone().server();
+        coordinator
+    }
+
+    /// Start coordinating the commits of a stage.
+    pub fn stage_start(&self, stage_id: usize) {
+        self.stages.lock().entry(stage_id).or_default();
+    }
+
+    /// Stop coordinating the commits of a stage: attempts still running are denied
+    /// committing.
+    pub fn stage_end(&self, stage_id: usize) {
+        self.stages.lock().remove(&stage_id);
+    }
+
+    /// Release the authorization held by an attempt which failed, if any, so that another
+    /// attempt of its task can commit.
+    pub fn task_failed(&self, stage_id: usize, attempt_id: usize) {
+        if let Some(commits) = self.stages.lock().get_mut(&stage_id) {
+            commits.retain(|_, commit| *commit != PartitionCommit::Authorized(attempt_id));
+        }
+    }
+
+    /// Wait until the attempt of a task may commit its output. Returns true if it may, and
+    /// false if another attempt committed the output of the partition.
+    ///
+    /// While another attempt is authorized, this waits for that attempt to either commit or
+    /// fail, as only then is it known whether the partition will be committed.
+    pub fn authorize_commit(&self, context: &TaskContext) -> Result<bool> {
+        loop {
+            let reply = self.ask(CommitCoordinatorMessage::CanCommit {
+                stage_id: context.stage_id,
+                split_id: context.split_id,
+                attempt_id: context.attempt_id,
+            })?;
+            match reply {
+                CommitCoordinatorReply::Authorized => return Ok(true),
+                CommitCoordinatorReply::Committed { attempt_id } => {
+                    log::debug!(
+                        "output of task {} already committed by attempt {}",
+                        context.split_id,
+                        attempt_id
+                    );
+                    return Ok(false);
+                }
+                CommitCoordinatorReply::Pending { .. } => thread::sleep(PENDING_COMMIT_POLL),
+                _ => {
+                    return Err(Error::CommitDenied {
+                        split_id: context.split_id,
+                        attempt_id: context.attempt_id,
+                    })
+                }
+            }
+        }
+    }
+
+    /// Report that an authorized attempt committed its output.
+    pub fn commit_done(&self, context: &TaskContext) -> Result<()> {
+        self.ask(CommitCoordinatorMessage::CommitDone {
+            stage_id: context.stage_id,
+            split_id: context.split_id,
+            attempt_id: context.attempt_id,
+        })?;
+        Ok(())
+    }
+
+    /// Report that an authorized attempt failed committing its output.
+    pub fn commit_aborted(&self, context: &TaskContext) -> Result<()> {
+        self.ask(CommitCoordinatorMessage::CommitAborted {
+            stage_id: context.stage_id,
+            split_id: context.split_id,
+            attempt_id: context.attempt_id,
+        })?;
+        Ok(())
+    }
+
+    fn ask(&self, message: CommitCoordinatorMessage) -> Result<CommitCoordinatorReply> {
+        if self.is_master {
+            Ok(self.process_message(message))
+        } else {
+            self.client(message)
+        }
+    }
+
+    fn process_message(&self, message: CommitCoordinatorMessage) -> CommitCoordinatorReply {
+        let mut stages = self.stages.lock();
+        match message {
+            CommitCoordinatorMessage::CanCommit {
+                stage_id,
+                split_id,
+                attempt_id,
+            } => {
+                let commits = match stages.get_mut(&stage_id) {
+                    Some(commits) => commits,
+                    None => return CommitCoordinatorReply::StageInactive,
+                };
+                match *commits
+                    .entry(split_id)
+                    .or_insert(PartitionCommit::Authorized(attempt_id))
+                {
+                    PartitionCommit::Authorized(authorized) if authorized == attempt_id => {
+                        CommitCoordinatorReply::Authorized
+                    }
+                    PartitionCommit::Authorized(authorized) => CommitCoordinatorReply::Pending {
+                        attempt_id: authorized,
+                    },
+                    PartitionCommit::Committed(committed) => CommitCoordinatorReply::Committed {
+                        attempt_id: committed,
+                    },
+                }
+            }
+            CommitCoordinatorMessage::CommitDone {
+                stage_id,
+                split_id,
+                attempt_id,
+            } => {
+                if let Some(commit) = stages
+                    .get_mut(&stage_id)
+                    .and_then(|commits| commits.get_mut(&split_id))
+                {
+                    if *commit == PartitionCommit::Authorized(attempt_id) {
+                        *commit = PartitionCommit::Committed(attempt_id);
+                    }
+                }
+                CommitCoordinatorReply::Ok
+            }
+            CommitCoordinatorMessage::CommitAborted {
+                stage_id,
+                split_id,
+                attempt_id,
+            } => {
+                if let Some(commits) = stages.get_mut(&stage_id) {
+                    if commits.get(&split_id) == Some(&PartitionCommit::Authorized(attempt_id)) {
+                        commits.remove(&split_id);
+                    }
+                }
+                CommitCoordinatorReply::Ok
+            }
+        }
+    }
+
+    // Executors ask the coordinator of the driver from inside their tasks, so the client is
+    // blocking.
+    fn client(&self, message: CommitCoordinatorMessage) -> Result<CommitCoordinatorReply> {
+        let mut stream =
+            TcpStream::connect(self.master_addr).map_err(|_| NetworkError::ConnectionFailure)?;
+        let bytes = bincode::serialize(&message)?;
+        let mut message = capnp::message::Builder::new_default();
+        let mut data = message.init_root::<serialized_data::Builder>();
+        data.set_msg(&bytes);
+        capnp::serialize::write_message(&mut stream, &message).map_err(Error::OutputWrite)?;
+
+        let message_reader = capnp::serialize::read_message(&mut stream, CAPNP_BUF_READ_OPTS)?;
+        let data = message_reader.get_root::<serialized_data::Reader>()?;
+        Ok(bincode::deserialize(data.get_msg()?)?)
+    }
+
+    /// Only started in the driver, serving the executors.
+    fn server(self: Arc<Self>) {
+        if !self.is_master {
+            return;
+        }
+        log::debug!("output commit coordinator server starting");
+        tokio::spawn(async move {
+            let mut listener = TcpListener::bind(self.master_addr)
+                .await
+                .map_err(NetworkError::TcpListener)?;
+            log::debug!("output commit coordinator server started");
+            while let Some(Ok(mut stream)) = listener.incoming().next().await {
+                let selfc = Arc::clone(&self);
+                tokio::spawn(async move {
+                    let (reader, writer) = stream.split();
+                    let reader = reader.compat();
+                    let writer = writer.compat_write();
+
+                    let message_reader = capnp_serialize::read_message(reader, CAPNP_BUF_READ_OPTS)
+                        .await?
+                        .ok_or_else(|| NetworkError::NoMessageReceived)?;
+                    let data = message_reader.get_root::<serialized_data::Reader>()?;
+                    let message: CommitCoordinatorMessage = bincode::deserialize(data.get_msg()?)?;
+
+                    let reply = bincode::serialize(&selfc.process_message(message))?;
+                    let mut message = capnp::message::Builder::new_default();
+                    let mut data = message.init_root::<serialized_data::Builder>();
+                    data.set_msg(&reply);
+                    // TODO: remove blocking call when possible
+                    futures::executor::block_on(async {
+                        capnp_serialize::write_message(writer, message).await?;
+                        Ok::<_, Error>(())
+                    })?;
+                    Ok::<_, Error>(())
+                });
+            }
+            Err::<(), _>(Error::ExecutorShutdown)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{IpAddr, Ipv4Addr};
+
+    fn coordinator() -> OutputCommitCoordinator {
+        OutputCommitCoordinator {
+            is_master: true,
+            master_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0),
+            stages: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn can_commit(
+        coordinator: &OutputCommitCoordinator,
+        split_id: usize,
+        attempt_id: usize,
+    ) -> CommitCoordinatorReply {
+        coordinator.process_message(CommitCoordinatorMessage::CanCommit {
+            stage_id: 1,
+            split_id,
+            attempt_id,
+        })
+    }
+
+    #[test]
+    fn one_attempt_commits_per_partition() {
+        let coordinator = coordinator();
+        assert_eq!(
+            can_commit(&coordinator, 0, 5),
+            CommitCoordinatorReply::StageInactive
+        );
+
+        coordinator.stage_start(1);
+        assert_eq!(
+            can_commit(&coordinator, 0, 5),
+            CommitCoordinatorReply::Authorized
+        );
+        assert_eq!(
+            can_commit(&coordinator, 1, 6),
+            CommitCoordinatorReply::Authorized
+        );
+        assert_eq!(
+            can_commit(&coordinator, 0, 7),
+            CommitCoordinatorReply::Pending { attempt_id: 5 }
+        );
+
+        // A failed attempt releases its authorization.
+        coordinator.task_failed(1, 5);
+        assert_eq!(
+            can_commit(&coordinator, 0, 7),
+            CommitCoordinatorReply::Authorized
+        );
+        let context = TaskContext::new(1, 0, 7);
+        coordinator.commit_done(&context).unwrap();
+        assert_eq!(
+            can_commit(&coordinator, 0, 5),
+            CommitCoordinatorReply::Committed { attempt_id: 7 }
+        );
+        assert!(!coordinator
+            .authorize_commit(&TaskContext::new(1, 0, 5))
+            .unwrap());
+        coordinator.task_failed(1, 7);
+        assert!(!coordinator
+            .authorize_commit(&TaskContext::new(1, 0, 5))
+            .unwrap());
+
+        // So does an attempt which aborted its commit.
+        coordinator
+            .commit_aborted(&TaskContext::new(1, 1, 6))
+            .unwrap();
+        assert!(coordinator
+            .authorize_commit(&TaskContext::new(1, 1, 8))
+            .unwrap());
+
+        coordinator.stage_end(1);
+        match coordinator.authorize_commit(&TaskContext::new(1, 2, 9)) {
+            Err(Error::CommitDenied {
+                split_id: 2,
+                attempt_id: 9,
+            }) => {}
+            other => panic!("expected a denied commit, got {:?}", other),
+        }
+    }
+}
@@ -4,6 +4,7 @@ use std::net::Ipv4Addr;
 use std::sync::Arc;
 
 use crate::env;
+use crate::error::Result;
 use crate::rdd::Rdd;
 use crate::scheduler::{Task, TaskBase, TaskContext};
 use crate::serializable_traits::{AnyData, Data};
@@ -156,10 +157,9 @@ where
         + Deserialize
         + Clone,
 {
-    fn run(&self, id: usize) -> SerBox<dyn AnyData> {
+    fn run(&self, id: usize) -> Result<SerBox<dyn AnyData>> {
         let split = self.rdd.splits()[self.partition].clone();
         let context = TaskContext::new(self.stage_id, self.partition, id);
-        SerBox::new((self.func)((context, self.rdd.iterator(split).unwrap())))
-            as SerBox<dyn AnyData>
+        Ok(SerBox::new((self.func)((context, self.rdd.iterator(split)?))) as SerBox<dyn AnyData>)
     }
 }
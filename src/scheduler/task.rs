@@ -1,6 +1,10 @@
+use std::any::Any;
+use std::cell::RefCell;
 use std::cmp::Ordering;
 use std::net::Ipv4Addr;
+use std::panic::{self, AssertUnwindSafe};
 
+use crate::error::{Error, Result, StdResult};
 use crate::scheduler::ResultTask;
 use crate::serializable_traits::{AnyData, Data, SerFunc};
 use crate::shuffle::ShuffleMapTask;
@@ -9,6 +13,7 @@ use downcast_rs::{impl_downcast, Downcast};
 use serde_derive::{Deserialize, Serialize};
 use serde_traitobject::{Deserialize, Serialize};
 
+/// Identifies the partition computed by a task and the attempt running it.
 pub struct TaskContext {
     pub stage_id: usize,
     pub split_id: usize,
@@ -62,7 +67,7 @@ impl Ord for dyn TaskBase {
 }
 
 pub(crate) trait Task: TaskBase + Send + Sync + Downcast {
-    fn run(&self, id: usize) -> SerBox<dyn AnyData>;
+    fn run(&self, id: usize) -> Result<SerBox<dyn AnyData>>;
 }
 
 impl_downcast!(Task);
@@ -100,13 +105,67 @@ impl From<ShuffleMapTask> for TaskOption {
 pub(crate) enum TaskResult {
     ResultTask(SerBox<dyn AnyData>),
     ShuffleTask(SerBox<dyn AnyData>),
+    /// The task failed with the given message.
+    Failed(String),
+}
+
+thread_local! {
+    /// Message of the first error failing the task running on this thread.
+    static TASK_ERROR: RefCell<Option<String>> = RefCell::new(None);
+}
+
+/// Fail the running task with an error met while iterating over a partition, which the
+/// iterator can't return. The iterator should end after failing the task: once the task
+/// function returns, the task fails with the first error, which its action returns.
+pub(crate) fn fail_task(error: Error) {
+    log::error!("failing the task: {}", error);
+    TASK_ERROR.with(|task_error| {
+        task_error
+            .borrow_mut()
+            .get_or_insert_with(|| error.to_string());
+    });
+}
+
+/// Whether the running task was failed by `fail_task`.
+pub(crate) fn task_failed() -> bool {
+    TASK_ERROR.with(|task_error| task_error.borrow().is_some())
+}
+
+/// Run the function of a task on this thread, returning the message of the error failing the
+/// task or of its panic instead of its result if it failed.
+pub(crate) fn catch_task_failure<R>(run: impl FnOnce() -> R) -> StdResult<R, String> {
+    TASK_ERROR.with(|task_error| task_error.borrow_mut().take());
+    let result = panic::catch_unwind(AssertUnwindSafe(run));
+    let task_error = TASK_ERROR.with(|task_error| task_error.borrow_mut().take());
+    match (result, task_error) {
+        (Ok(result), None) => Ok(result),
+        (Ok(_), Some(message)) => Err(message),
+        (Err(payload), _) => Err(panic_message(payload)),
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast::<&'static str>() {
+            Ok(message) => (*message).to_owned(),
+            Err(_) => "task panicked".to_owned(),
+        },
+    }
 }
 
 impl TaskOption {
+    /// Run the task. A task which fails, returning an error or panicking, gives a failed
+    /// result instead of taking down the thread running it.
     pub fn run(&self, id: usize) -> TaskResult {
-        match self {
-            TaskOption::ResultTask(tsk) => TaskResult::ResultTask(tsk.run(id)),
-            TaskOption::ShuffleMapTask(tsk) => TaskResult::ShuffleTask(tsk.run(id)),
+        let result = catch_task_failure(|| match self {
+            TaskOption::ResultTask(tsk) => tsk.run(id).map(TaskResult::ResultTask),
+            TaskOption::ShuffleMapTask(tsk) => tsk.run(id).map(TaskResult::ShuffleTask),
+        });
+        match result {
+            Ok(Ok(result)) => result,
+            Ok(Err(error)) => TaskResult::Failed(error.to_string()),
+            Err(message) => TaskResult::Failed(message),
         }
     }
 
@@ -4,6 +4,7 @@ use std::sync::Arc;
 
 use crate::dependency::ShuffleDependencyTrait;
 use crate::env;
+use crate::error::Result;
 use crate::rdd::RddBase;
 use crate::scheduler::{Task, TaskBase};
 use crate::serializable_traits::AnyData;
@@ -84,8 +85,10 @@ impl TaskBase for ShuffleMapTask {
 }
 
 impl Task for ShuffleMapTask {
-    fn run(&self, _id: usize) -> SerBox<dyn AnyData> {
-        SerBox::new(self.dep.do_shuffle_task(self.rdd.clone(), self.partition))
-            as SerBox<dyn AnyData>
+    fn run(&self, _id: usize) -> Result<SerBox<dyn AnyData>> {
+        Ok(
+            SerBox::new(self.dep.do_shuffle_task(self.rdd.clone(), self.partition))
+                as SerBox<dyn AnyData>,
+        )
     }
 }
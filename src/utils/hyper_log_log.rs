@@ -0,0 +1,352 @@
+use std::hash::{Hash, Hasher};
+
+use fasthash::MetroHasher;
+use serde_derive::{Deserialize, Serialize};
+
+const MIN_PRECISION: u8 = 4;
+const MAX_PRECISION: u8 = 18;
+/// Precision of the indices of the sparse representation.
+const SPARSE_PRECISION: u32 = 25;
+/// Bits of a sparse entry holding the rank of the hash bits after its index.
+const RANK_BITS: u32 = 6;
+
+/// A mergeable HyperLogLog++ sketch for estimating the number of distinct elements of a
+/// data set.
+///
+/// Elements are hashed to 64 bits with MetroHash, so no large range correction is required.
+/// As in HyperLogLog++, a sketch starts in a sparse representation, holding an entry for each
+/// register set at a higher precision of 2^25 registers, so that small sketches use little
+/// memory and their cardinality is estimated precisely by linear counting. It converts to
+/// the dense representation of 2^`precision` registers once the entries would take more
+/// memory than the registers. Instead of the empirical bias correction tables of the
+/// original HyperLogLog++ paper the cardinality of a dense sketch is computed with the
+/// improved estimator by Otmar Ertl ("New cardinality estimation algorithms for HyperLogLog
+/// sketches"), which is unbiased over the whole cardinality range.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HyperLogLogPlus {
+    precision: u8,
+    registers: Registers,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+enum Registers {
+    /// Entries of the registers set at the sparse precision, sorted by index with one entry
+    /// per index. An entry holds the index in its high bits and the rank of the remaining
+    /// hash bits in its low `RANK_BITS` bits.
+    Sparse(Vec<u32>),
+    Dense(Vec<u8>),
+}
+
+impl HyperLogLogPlus {
+    /// Create an empty sketch using up to 2^`precision` registers. The relative standard
+    /// deviation of the estimate is about 1.04 / sqrt(2^`precision`).
+    pub fn new(precision: u8) -> Self {
+        assert!(
+            precision >= MIN_PRECISION && precision <= MAX_PRECISION,
+            format!(
+                "precision must be between {} and {} but got {}",
+                MIN_PRECISION, MAX_PRECISION, precision
+            )
+        );
+        HyperLogLogPlus {
+            precision,
+            registers: Registers::Sparse(vec![]),
+        }
+    }
+
+    /// Create an empty sketch with the smallest precision satisfying the requested
+    /// relative standard deviation.
+    pub fn with_relative_sd(relative_sd: f64) -> Self {
+        HyperLogLogPlus::new(HyperLogLogPlus::precision_for(relative_sd))
+    }
+
+    /// The smallest precision satisfying the requested relative standard deviation.
+    pub fn precision_for(relative_sd: f64) -> u8 {
+        let precision = (2.0 * (1.054 / relative_sd).log2()).ceil();
+        assert!(
+            precision >= MIN_PRECISION as f64,
+            format!("relative_sd ({}) must be smaller than 0.37", relative_sd)
+        );
+        assert!(
+            precision <= MAX_PRECISION as f64,
+            format!("relative_sd ({}) must be at least 0.0021", relative_sd)
+        );
+        precision as u8
+    }
+
+    pub fn precision(&self) -> u8 {
+        self.precision
+    }
+
+    /// Add an element to the sketch.
+    pub fn offer<T: Hash + ?Sized>(&mut self, item: &T) {
+        let mut hasher = MetroHasher::default();
+        item.hash(&mut hasher);
+        self.offer_hashed(hasher.finish());
+    }
+
+    fn offer_hashed(&mut self, hash: u64) {
+        match &mut self.registers {
+            Registers::Sparse(entries) => {
+                let index = (hash >> (64 - SPARSE_PRECISION)) as u32;
+                let rank = rank(hash << SPARSE_PRECISION, 64 - SPARSE_PRECISION);
+                insert_entry(entries, index << RANK_BITS | rank);
+                self.convert_if_full();
+            }
+            Registers::Dense(registers) => {
+                let p = self.precision as u32;
+                let index = (hash >> (64 - p)) as usize;
+                let register = &mut registers[index];
+                *register = (*register).max(rank(hash << p, 64 - p) as u8);
+            }
+        }
+    }
+
+    /// Convert a sparse sketch to the dense representation once its entries take more memory
+    /// than the registers.
+    fn convert_if_full(&mut self) {
+        let limit = (1 << self.precision) / std::mem::size_of::<u32>();
+        match &self.registers {
+            Registers::Sparse(entries) if entries.len() > limit => self.to_dense(),
+            _ => {}
+        }
+    }
+
+    fn to_dense(&mut self) {
+        if let Registers::Sparse(entries) = &self.registers {
+            let mut registers = vec![0; 1 << self.precision];
+            set_registers(&mut registers, self.precision, entries);
+            self.registers = Registers::Dense(registers);
+        }
+    }
+
+    /// Merge another sketch into this one. Both sketches must have the same precision.
+    pub fn merge(&mut self, other: &HyperLogLogPlus) {
+        assert_eq!(
+            self.precision, other.precision,
+            "cannot merge sketches with different precision"
+        );
+        if let Registers::Dense(_) = &other.registers {
+            self.to_dense();
+        }
+        match (&mut self.registers, &other.registers) {
+            (Registers::Sparse(entries), Registers::Sparse(others)) => {
+                *entries = merge_entries(entries, others);
+                self.convert_if_full();
+            }
+            (Registers::Dense(registers), Registers::Sparse(others)) => {
+                set_registers(registers, self.precision, others);
+            }
+            (Registers::Dense(registers), Registers::Dense(others)) => registers
+                .iter_mut()
+                .zip(others.iter())
+                .for_each(|(r, o)| *r = (*r).max(*o)),
+            (Registers::Sparse(_), Registers::Dense(_)) => unreachable!(),
+        }
+    }
+
+    /// Estimate the number of distinct elements offered to this sketch.
+    pub fn cardinality(&self) -> u64 {
+        let registers = match &self.registers {
+            Registers::Sparse(entries) => {
+                // linear counting over the registers of the sparse precision
+                let m = (1u64 << SPARSE_PRECISION) as f64;
+                let empty = m - entries.len() as f64;
+                return (m * (m / empty).ln()).round() as u64;
+            }
+            Registers::Dense(registers) => registers,
+        };
+        let m = registers.len() as f64;
+        let q = 64 - self.precision as usize;
+        let mut histogram = vec![0u64; q + 2];
+        registers.iter().for_each(|r| histogram[*r as usize] += 1);
+
+        let mut z = m * tau((m - histogram[q + 1] as f64) / m);
+        for k in (1..=q).rev() {
+            z += histogram[k] as f64;
+            z *= 0.5;
+        }
+        z += m * sigma(histogram[0] as f64 / m);
+        let alpha_inf = 0.5 / std::f64::consts::LN_2;
+        (alpha_inf * m * m / z).round() as u64
+    }
+}
+
+/// The position of the first set bit of the `bits` high bits of `w`, or `bits + 1` if they
+/// are all zero.
+fn rank(w: u64, bits: u32) -> u32 {
+    if w == 0 {
+        bits + 1
+    } else {
+        w.leading_zeros() + 1
+    }
+}
+
+/// Insert an entry in sorted entries, keeping the highest rank of its index.
+fn insert_entry(entries: &mut Vec<u32>, entry: u32) {
+    match entries.binary_search_by_key(&(entry >> RANK_BITS), |e| e >> RANK_BITS) {
+        Ok(i) => entries[i] = entries[i].max(entry),
+        Err(i) => entries.insert(i, entry),
+    }
+}
+
+fn merge_entries(left: &[u32], right: &[u32]) -> Vec<u32> {
+    let mut merged = Vec::with_capacity(left.len() + right.len());
+    let (mut i, mut j) = (0, 0);
+    while i < left.len() && j < right.len() {
+        let (l, r) = (left[i], right[j]);
+        match (l >> RANK_BITS).cmp(&(r >> RANK_BITS)) {
+            std::cmp::Ordering::Less => {
+                merged.push(l);
+                i += 1;
+            }
+            std::cmp::Ordering::Greater => {
+                merged.push(r);
+                j += 1;
+            }
+            std::cmp::Ordering::Equal => {
+                merged.push(l.max(r));
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    merged.extend_from_slice(&left[i..]);
+    merged.extend_from_slice(&right[j..]);
+    merged
+}
+
+/// Set the dense registers from sparse entries. The index of an entry holds the dense index
+/// followed by `SPARSE_PRECISION - precision` bits, which determine the dense rank unless
+/// they are all zero.
+fn set_registers(registers: &mut [u8], precision: u8, entries: &[u32]) {
+    let extra_bits = SPARSE_PRECISION - precision as u32;
+    for entry in entries {
+        let sparse_index = entry >> RANK_BITS;
+        let extra = sparse_index & ((1 << extra_bits) - 1);
+        let rank = if extra == 0 {
+            extra_bits + (entry & ((1 << RANK_BITS) - 1))
+        } else {
+            extra.leading_zeros() - (32 - extra_bits) + 1
+        };
+        let register = &mut registers[(sparse_index >> extra_bits) as usize];
+        *register = (*register).max(rank as u8);
+    }
+}
+
+fn sigma(mut x: f64) -> f64 {
+    if x == 1.0 {
+        return f64::INFINITY;
+    }
+    let mut y = 1.0;
+    let mut z = x;
+    loop {
+        x *= x;
+        let z_old = z;
+        z += x * y;
+        y += y;
+        if z_old == z {
+            return z;
+        }
+    }
+}
+
+fn tau(mut x: f64) -> f64 {
+    if x == 0.0 || x == 1.0 {
+        return 0.0;
+    }
+    let mut y = 1.0;
+    let mut z = 1.0 - x;
+    loop {
+        x = x.sqrt();
+        let z_old = z;
+        y *= 0.5;
+        z -= (1.0 - x).powi(2) * y;
+        if z_old == z {
+            return z / 3.0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_within(estimate: u64, actual: u64, relative_error: f64) {
+        let error = (estimate as f64 - actual as f64).abs() / actual as f64;
+        assert!(
+            error <= relative_error,
+            "estimate {} too far from {}",
+            estimate,
+            actual
+        );
+    }
+
+    #[test]
+    fn empty_sketch() {
+        assert_eq!(HyperLogLogPlus::new(14).cardinality(), 0);
+    }
+
+    #[test]
+    fn estimates_over_the_cardinality_range() {
+        let mut sketch = HyperLogLogPlus::with_relative_sd(0.01);
+        let mut offered = 0u64;
+        for &n in &[10u64, 1_000, 30_000, 300_000] {
+            (offered..n).for_each(|i| sketch.offer(&i));
+            offered = n;
+            assert_within(sketch.cardinality(), n, 0.05);
+        }
+    }
+
+    fn dense(precision: u8) -> HyperLogLogPlus {
+        HyperLogLogPlus {
+            precision,
+            registers: Registers::Dense(vec![0; 1 << precision]),
+        }
+    }
+
+    #[test]
+    fn sparse_converts_to_dense() {
+        let mut sketch = HyperLogLogPlus::new(12);
+        let mut reference = dense(12);
+        for i in 0u64.. {
+            if let Registers::Dense(_) = sketch.registers {
+                // converted once the entries take more memory than the 4096 registers
+                assert!(i > 1024);
+                break;
+            }
+            if i == 1000 {
+                assert_within(sketch.cardinality(), 1000, 0.01);
+                let mut converted = sketch.clone();
+                converted.to_dense();
+                assert_eq!(converted, reference);
+            }
+            sketch.offer(&i);
+            reference.offer(&i);
+        }
+        assert_eq!(sketch, reference);
+    }
+
+    #[test]
+    fn merge_sketches() {
+        let mut first = HyperLogLogPlus::new(12);
+        let mut second = HyperLogLogPlus::new(12);
+        (0..20_000u64).for_each(|i| first.offer(&i));
+        (10_000..30_000u64).for_each(|i| second.offer(&i));
+        first.merge(&second);
+        assert_within(first.cardinality(), 30_000, 0.1);
+
+        // Sparse sketches stay sparse, and merge with dense ones either way.
+        let (mut small, mut other_small) = (HyperLogLogPlus::new(12), HyperLogLogPlus::new(12));
+        (0..300u64).for_each(|i| small.offer(&i));
+        (200..500u64).for_each(|i| other_small.offer(&i));
+        small.merge(&other_small);
+        assert!(matches!(small.registers, Registers::Sparse(_)));
+        assert_within(small.cardinality(), 500, 0.01);
+        let mut large = second.clone();
+        large.merge(&small);
+        small.merge(&second);
+        assert_eq!(small, large);
+        assert_within(small.cardinality(), 20_500, 0.1);
+    }
+}
@@ -7,7 +7,11 @@ use crate::error;
 use rand::Rng;
 
 pub(crate) mod bounded_priority_queue;
+pub(crate) mod hyper_log_log;
+pub(crate) mod quantile_summaries;
 pub(crate) mod random;
+pub(crate) mod stat_counter;
+pub(crate) mod stratified_sampling;
 #[cfg(test)]
 pub(crate) mod test_utils;
 
@@ -0,0 +1,271 @@
+use std::cmp::Ordering;
+
+use serde_derive::{Deserialize, Serialize};
+
+/// Number of samples after which the summary is compressed.
+const COMPRESS_THRESHOLD: usize = 10000;
+/// Number of elements buffered before being inserted into the summary.
+const HEAD_SIZE: usize = 50000;
+
+/// Statistics of one sample of the summary.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+struct Stats {
+    /// The sampled value.
+    value: f64,
+    /// The minimum rank of this element minus the minimum rank of the previous element.
+    g: u64,
+    /// The maximum span of the rank of this element.
+    delta: u64,
+}
+
+/// A mergeable summary of a data set which answers quantile queries within a relative error,
+/// based on the Greenwald-Khanna algorithm ("Space-efficient Online Computation of Quantile
+/// Summaries").
+///
+/// The returned values have a rank within about `relative_error * count` of the requested rank.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct QuantileSummaries {
+    relative_error: f64,
+    sampled: Vec<Stats>,
+    count: u64,
+    /// Elements which are yet to be inserted in the samples.
+    head_sampled: Vec<f64>,
+}
+
+impl QuantileSummaries {
+    pub fn new(relative_error: f64) -> Self {
+        assert!(
+            relative_error > 0.0 && relative_error < 1.0,
+            format!(
+                "relative_error must be in the range (0, 1) but got {}",
+                relative_error
+            )
+        );
+        QuantileSummaries {
+            relative_error,
+            sampled: Vec::new(),
+            count: 0,
+            head_sampled: Vec::new(),
+        }
+    }
+
+    /// Number of values inserted in the summary. NaN values are ignored.
+    pub fn count(&self) -> u64 {
+        self.count + self.head_sampled.len() as u64
+    }
+
+    /// Insert a new value in the summary.
+    pub fn insert(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        self.head_sampled.push(value);
+        if self.head_sampled.len() >= HEAD_SIZE {
+            self.insert_head_buffer();
+            if self.sampled.len() >= COMPRESS_THRESHOLD {
+                self.compress();
+            }
+        }
+    }
+
+    /// Insert the buffered elements in the samples. The buffered elements are sorted once and
+    /// merged with the samples in a single pass.
+    fn insert_head_buffer(&mut self) {
+        if self.head_sampled.is_empty() {
+            return;
+        }
+        let mut sorted = std::mem::replace(&mut self.head_sampled, Vec::new());
+        sorted.sort_by(cmp_f64);
+        let sampled = std::mem::replace(&mut self.sampled, Vec::new());
+        let mut new_samples = Vec::with_capacity(sampled.len() + sorted.len());
+        let mut sample_idx = 0;
+        let mut current_count = self.count;
+        for (ops_idx, &current_sample) in sorted.iter().enumerate() {
+            while sample_idx < sampled.len() && sampled[sample_idx].value <= current_sample {
+                new_samples.push(sampled[sample_idx]);
+                sample_idx += 1;
+            }
+            current_count += 1;
+            // If it is the first or the last one to insert, the delta is 0
+            let delta = if new_samples.is_empty()
+                || (sample_idx == sampled.len() && ops_idx == sorted.len() - 1)
+            {
+                0
+            } else {
+                (2.0 * self.relative_error * current_count as f64).floor() as u64
+            };
+            new_samples.push(Stats {
+                value: current_sample,
+                g: 1,
+                delta,
+            });
+        }
+        new_samples.extend_from_slice(&sampled[sample_idx..]);
+        self.sampled = new_samples;
+        self.count = current_count;
+    }
+
+    /// Compress the summary, collapsing samples as long as the error bounds allow it. This also
+    /// inserts all the buffered elements.
+    pub fn compress(&mut self) {
+        self.insert_head_buffer();
+        let merge_threshold = 2.0 * self.relative_error * self.count as f64;
+        self.sampled = compress_samples(&self.sampled, merge_threshold);
+    }
+
+    /// Merge two summaries together.
+    pub fn merge(&mut self, mut other: QuantileSummaries) {
+        self.compress();
+        other.compress();
+        if other.count == 0 {
+            return;
+        } else if self.count == 0 {
+            *self = other;
+            return;
+        }
+        let merged = merge_samples(&self.sampled, &other.sampled);
+        self.relative_error = self.relative_error.max(other.relative_error);
+        self.count += other.count;
+        let merge_threshold = 2.0 * self.relative_error * self.count as f64;
+        self.sampled = compress_samples(&merged, merge_threshold);
+    }
+
+    /// Run a query for a given quantile. Returns `None` if the summary is empty.
+    ///
+    /// The summary must be compressed before being queried.
+    pub fn query(&self, quantile: f64) -> Option<f64> {
+        assert!(
+            quantile >= 0.0 && quantile <= 1.0,
+            format!(
+                "quantile should be in the range [0, 1] but got {}",
+                quantile
+            )
+        );
+        assert!(
+            self.head_sampled.is_empty(),
+            "cannot query a summary which is not compressed"
+        );
+        let first = self.sampled.first()?;
+        let last = self.sampled.last()?;
+        // Shortcut the extreme quantiles, they can be answered from the min and max.
+        if quantile <= self.relative_error {
+            return Some(first.value);
+        } else if quantile >= 1.0 - self.relative_error {
+            return Some(last.value);
+        }
+        // Find the lowest sample whose rank range is within the error of the target rank.
+        let rank = (quantile * self.count as f64).ceil() as i64;
+        let target_error = (self.relative_error * self.count as f64).ceil() as i64;
+        let mut min_rank = 0i64;
+        for sample in &self.sampled[..self.sampled.len() - 1] {
+            min_rank += sample.g as i64;
+            let max_rank = min_rank + sample.delta as i64;
+            if max_rank - target_error <= rank && rank <= min_rank + target_error {
+                return Some(sample.value);
+            }
+        }
+        Some(last.value)
+    }
+}
+
+/// Merge the samples of two summaries in value order. The rank of a sample is also uncertain
+/// by the rank span of the next greater sample of the other summary, which is added to its
+/// delta so that the error bounds still hold after merging.
+fn merge_samples(first: &[Stats], second: &[Stats]) -> Vec<Stats> {
+    let mut merged = Vec::with_capacity(first.len() + second.len());
+    let (mut i, mut j) = (0, 0);
+    while i < first.len() || j < second.len() {
+        let take_first =
+            j >= second.len() || (i < first.len() && first[i].value <= second[j].value);
+        let (mut sample, next_other) = if take_first {
+            i += 1;
+            (first[i - 1], second.get(j))
+        } else {
+            j += 1;
+            (second[j - 1], first.get(i))
+        };
+        if let Some(next) = next_other {
+            sample.delta += next.g + next.delta - 1;
+        }
+        merged.push(sample);
+    }
+    merged
+}
+
+fn compress_samples(samples: &[Stats], merge_threshold: f64) -> Vec<Stats> {
+    if samples.len() <= 1 {
+        return samples.to_vec();
+    }
+    let mut res = Vec::new();
+    // Start from the last element which is always kept
+    let mut head = samples[samples.len() - 1];
+    for sample in samples[1..samples.len() - 1].iter().rev() {
+        if ((sample.g + head.g + head.delta) as f64) < merge_threshold {
+            head.g += sample.g;
+        } else {
+            res.push(head);
+            head = *sample;
+        }
+    }
+    res.push(head);
+    // The first element is also always kept, so that the minimum is preserved
+    if samples[0].value <= head.value {
+        res.push(samples[0]);
+    }
+    res.reverse();
+    res
+}
+
+fn cmp_f64(a: &f64, b: &f64) -> Ordering {
+    a.partial_cmp(b).unwrap_or(Ordering::Equal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_quantiles(summary: &QuantileSummaries, n: usize) {
+        let eps = summary.relative_error;
+        for &quantile in &[0.0, 0.1, 0.5, 0.9, 0.99, 1.0] {
+            // values are 0..n so the rank of a value is the value itself
+            let value = summary.query(quantile).unwrap();
+            let rank = quantile * n as f64;
+            assert!(
+                (value - rank).abs() <= (eps * n as f64).ceil() + 1.0,
+                "quantile {} returned {}",
+                quantile,
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn empty_summary() {
+        let mut summary = QuantileSummaries::new(0.01);
+        summary.compress();
+        assert_eq!(summary.query(0.5), None);
+    }
+
+    #[test]
+    fn query_within_error() {
+        let n = 120_000;
+        let mut summary = QuantileSummaries::new(0.01);
+        // insert in a shuffled but deterministic order
+        (0..n).for_each(|i| summary.insert(((i * 7919) % n) as f64));
+        summary.compress();
+        assert_eq!(summary.count(), n as u64);
+        check_quantiles(&summary, n);
+    }
+
+    #[test]
+    fn merge_summaries() {
+        let n = 60_000;
+        let mut summaries: Vec<_> = (0..4).map(|_| QuantileSummaries::new(0.01)).collect();
+        (0..n).for_each(|i| summaries[i % 4].insert(i as f64));
+        let mut merged = QuantileSummaries::new(0.01);
+        summaries.into_iter().for_each(|s| merged.merge(s));
+        merged.compress();
+        assert_eq!(merged.count(), n as u64);
+        check_quantiles(&merged, n);
+    }
+}
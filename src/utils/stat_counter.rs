@@ -0,0 +1,190 @@
+use std::fmt::{Display, Formatter};
+
+use serde_derive::{Deserialize, Serialize};
+
+/// A class for tracking the statistics of a set of numbers (count, mean and variance) in a
+/// numerically robust way. Includes support for merging two StatCounters.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StatCounter {
+    /// Running count of our values.
+    n: u64,
+    /// Running mean of our values.
+    mu: f64,
+    /// Running variance numerator (sum of (x - mean)^2).
+    m2: f64,
+    /// Running max of our values.
+    max_value: f64,
+    /// Running min of our values.
+    min_value: f64,
+}
+
+impl Default for StatCounter {
+    fn default() -> Self {
+        StatCounter::new()
+    }
+}
+
+impl StatCounter {
+    /// Initialize the StatCounter with no values.
+    pub fn new() -> Self {
+        StatCounter {
+            n: 0,
+            mu: 0.0,
+            m2: 0.0,
+            max_value: f64::NEG_INFINITY,
+            min_value: f64::INFINITY,
+        }
+    }
+
+    /// Initialize the StatCounter with the given values.
+    pub fn from_values<I: IntoIterator<Item = f64>>(values: I) -> Self {
+        let mut counter = StatCounter::new();
+        counter.merge_values(values);
+        counter
+    }
+
+    /// Add a value into this StatCounter, updating the internal statistics.
+    pub fn merge_value(&mut self, value: f64) {
+        let delta = value - self.mu;
+        self.n += 1;
+        self.mu += delta / self.n as f64;
+        self.m2 += delta * (value - self.mu);
+        self.max_value = self.max_value.max(value);
+        self.min_value = self.min_value.min(value);
+    }
+
+    /// Add multiple values into this StatCounter, updating the internal statistics.
+    pub fn merge_values<I: IntoIterator<Item = f64>>(&mut self, values: I) {
+        values.into_iter().for_each(|v| self.merge_value(v));
+    }
+
+    /// Merge another StatCounter into this one, adding up the internal statistics.
+    pub fn merge(&mut self, other: &StatCounter) {
+        if self.n == 0 {
+            *self = *other;
+        } else if other.n != 0 {
+            let n = self.n as f64;
+            let other_n = other.n as f64;
+            let delta = other.mu - self.mu;
+            // use the formula which is numerically more stable depending on the relative size
+            // of the two sets of values
+            self.mu = if other_n * 10.0 < n {
+                self.mu + (delta * other_n) / (n + other_n)
+            } else if n * 10.0 < other_n {
+                other.mu - (delta * n) / (n + other_n)
+            } else {
+                (self.mu * n + other.mu * other_n) / (n + other_n)
+            };
+            self.m2 += other.m2 + (delta * delta * n * other_n) / (n + other_n);
+            self.n += other.n;
+            self.max_value = self.max_value.max(other.max_value);
+            self.min_value = self.min_value.min(other.min_value);
+        }
+    }
+
+    pub fn count(&self) -> u64 {
+        self.n
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.mu
+    }
+
+    pub fn sum(&self) -> f64 {
+        self.n as f64 * self.mu
+    }
+
+    pub fn max(&self) -> f64 {
+        self.max_value
+    }
+
+    pub fn min(&self) -> f64 {
+        self.min_value
+    }
+
+    /// Return the population variance of the values.
+    pub fn variance(&self) -> f64 {
+        if self.n == 0 {
+            f64::NAN
+        } else {
+            self.m2 / self.n as f64
+        }
+    }
+
+    /// Return the sample variance, which corrects for bias in estimating the variance by dividing
+    /// by N-1 instead of N.
+    pub fn sample_variance(&self) -> f64 {
+        if self.n <= 1 {
+            f64::NAN
+        } else {
+            self.m2 / (self.n - 1) as f64
+        }
+    }
+
+    /// Return the population standard deviation of the values.
+    pub fn stdev(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    /// Return the sample standard deviation of the values, which corrects for bias in estimating
+    /// the variance by dividing by N-1 instead of N.
+    pub fn sample_stdev(&self) -> f64 {
+        self.sample_variance().sqrt()
+    }
+}
+
+impl Display for StatCounter {
+    fn fmt(&self, fmt: &mut Formatter) -> std::fmt::Result {
+        write!(
+            fmt,
+            "(count: {}, mean: {}, stdev: {}, max: {}, min: {})",
+            self.count(),
+            self.mean(),
+            self.stdev(),
+            self.max(),
+            self.min()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx_eq(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn merge_counters() {
+        let values = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0];
+        let full = StatCounter::from_values(values.clone());
+        assert_eq!(full.count(), 10);
+        assert!(approx_eq(full.mean(), 5.5));
+        assert!(approx_eq(full.sum(), 55.0));
+        assert!(approx_eq(full.variance(), 8.25));
+        assert!(approx_eq(full.sample_variance(), 82.5 / 9.0));
+        assert_eq!(full.max(), 10.0);
+        assert_eq!(full.min(), 1.0);
+
+        let mut merged = StatCounter::new();
+        for chunk in values.chunks(3) {
+            merged.merge(&StatCounter::from_values(chunk.iter().copied()));
+        }
+        merged.merge(&StatCounter::new());
+        assert_eq!(merged.count(), full.count());
+        assert!(approx_eq(merged.mean(), full.mean()));
+        assert!(approx_eq(merged.variance(), full.variance()));
+        assert_eq!(merged.max(), full.max());
+        assert_eq!(merged.min(), full.min());
+    }
+
+    #[test]
+    fn empty_counter() {
+        let counter = StatCounter::new();
+        assert_eq!(counter.count(), 0);
+        assert_eq!(counter.sum(), 0.0);
+        assert!(counter.variance().is_nan());
+        assert!(counter.sample_stdev().is_nan());
+    }
+}
@@ -0,0 +1,243 @@
+//! Auxiliary functions and data structures for the sample_by_key methods in PairRdd.
+//!
+//! Essentially, when exact sample size is necessary, we make additional passes over the RDD to
+//! compute the exact threshold value to use for each stratum to guarantee exact sample size with
+//! high probability. This is achieved by maintaining a waitlist of size O(log(s)), where s is the
+//! desired sample size for each stratum.
+//!
+//! Like in simple random sampling, we generate a random value for each item from the uniform
+//! distribution [0.0, 1.0]. All items with values <= min(values of items in the waitlist) are
+//! accepted into the sample instantly. The threshold for instant acceptance is designed so that
+//! s - num_accepted = O(sqrt(s)), where s is again the desired sample size. Thus, by maintaining
+//! a waitlist size = O(sqrt(s)), we will be able to create a sample of the exact size s by adding
+//! a portion of the waitlist to the set of instantly accepted items. The exact threshold
+//! is computed by sorting the values in the waitlist and picking the value at
+//! (s - num_accepted).
+//!
+//! Note that since we use the same seed for the RNG when computing the thresholds and the actual
+//! sample, our computed thresholds are guaranteed to produce the desired sample size.
+//!
+//! For more theoretical background on the sampling techniques used here, please refer to
+//! <http://jmlr.org/proceedings/papers/v28/meng13a.html>
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::serializable_traits::Data;
+use crate::utils::random::get_default_rng_from_seed;
+use rand::Rng;
+use rand_distr::{Distribution, Poisson};
+use rand_pcg::Pcg64;
+use serde_derive::{Deserialize, Serialize};
+
+/// Failure probability of the bounds used for sampling without replacement.
+const DELTA: f64 = 5e-5;
+/// Minimum sampling rate for the upper bound of the binomial distribution.
+const MIN_SAMPLING_RATE: f64 = 1e-10;
+
+/// Object used by the first pass of the exact sampling to compute the acceptance thresholds
+/// of a stratum.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub(crate) struct AcceptanceResult {
+    num_items: u64,
+    num_accepted: u64,
+    wait_list: Vec<f64>,
+    accept_bound: f64,
+    wait_list_bound: f64,
+}
+
+impl AcceptanceResult {
+    fn merge(&mut self, other: AcceptanceResult) {
+        self.num_items += other.num_items;
+        self.num_accepted += other.num_accepted;
+        self.wait_list.extend(other.wait_list);
+        self.accept_bound = self.accept_bound.max(other.accept_bound);
+        self.wait_list_bound = self.wait_list_bound.max(other.wait_list_bound);
+    }
+}
+
+/// Bounds and final threshold used to draw the exact sample of a stratum.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub(crate) struct StratumThreshold {
+    accept_bound: f64,
+    wait_list_bound: f64,
+    threshold: f64,
+}
+
+/// Returns the rng used for the partition, the same sequence of random values is
+/// generated on each pass over the partition.
+pub(crate) fn partition_rng(seed: u64, index: usize) -> Pcg64 {
+    get_default_rng_from_seed(seed.wrapping_add(index as u64))
+}
+
+fn next_poisson(rng: &mut Pcg64, mean: f64) -> u64 {
+    if mean > 0.0 {
+        Poisson::new(mean).unwrap().sample(rng)
+    } else {
+        0
+    }
+}
+
+/// Number of times an item of a stratum sampled with the given fraction is included in the
+/// sample when exact sample sizes are not required.
+pub(crate) fn sample_count(with_replacement: bool, fraction: f64, rng: &mut Pcg64) -> u64 {
+    if fraction <= 0.0 {
+        0
+    } else if with_replacement {
+        next_poisson(rng, fraction)
+    } else if rng.gen::<f64>() < fraction {
+        1
+    } else {
+        0
+    }
+}
+
+/// Update the acceptance result of the stratum of an item. For sampling with replacement the
+/// total count of each stratum must be known beforehand.
+pub(crate) fn update_acceptance_result(
+    result: &mut AcceptanceResult,
+    with_replacement: bool,
+    fraction: f64,
+    stratum_count: u64,
+    rng: &mut Pcg64,
+) {
+    if fraction > 0.0 {
+        if with_replacement {
+            if result.num_items == 0 {
+                let n = stratum_count as f64;
+                let sample_size = (n * fraction).ceil();
+                let lambda_low = poisson_lower_bound(sample_size);
+                let lambda_high = poisson_upper_bound(sample_size);
+                result.accept_bound = lambda_low / n;
+                result.wait_list_bound = (lambda_high - lambda_low) / n;
+            }
+            result.num_accepted += next_poisson(rng, result.accept_bound);
+            let copies_wait_listed = next_poisson(rng, result.wait_list_bound);
+            for _ in 0..copies_wait_listed {
+                result.wait_list.push(rng.gen::<f64>());
+            }
+        } else {
+            result.accept_bound = binomial_lower_bound(DELTA, result.num_items, fraction);
+            result.wait_list_bound = binomial_upper_bound(DELTA, result.num_items, fraction);
+            let x = rng.gen::<f64>();
+            if x < result.accept_bound {
+                result.num_accepted += 1;
+            } else if x < result.wait_list_bound {
+                result.wait_list.push(x);
+            }
+        }
+    }
+    result.num_items += 1;
+}
+
+/// Merge the acceptance results computed on different partitions.
+pub(crate) fn merge_acceptance_results<K: Data + Eq + Hash>(
+    mut results: HashMap<K, AcceptanceResult>,
+    other: HashMap<K, AcceptanceResult>,
+) -> HashMap<K, AcceptanceResult> {
+    other.into_iter().for_each(|(k, v)| {
+        results.entry(k).or_default().merge(v);
+    });
+    results
+}
+
+/// Given the result returned by the first pass, compute the acceptance threshold for each key.
+pub(crate) fn compute_threshold_by_key<K: Data + Eq + Hash>(
+    results: HashMap<K, AcceptanceResult>,
+    fractions: &HashMap<K, f64>,
+) -> HashMap<K, StratumThreshold> {
+    results
+        .into_iter()
+        .map(|(key, mut result)| {
+            let fraction = fractions.get(&key).copied().unwrap_or(0.0);
+            let sample_size = (result.num_items as f64 * fraction).ceil() as u64;
+            let threshold = if result.num_accepted > sample_size {
+                log::warn!(
+                    "pre-accepted too many items for a stratum: {} > {}",
+                    result.num_accepted,
+                    sample_size
+                );
+                result.accept_bound
+            } else {
+                let num_wait_list_accepted = (sample_size - result.num_accepted) as usize;
+                if num_wait_list_accepted >= result.wait_list.len() {
+                    log::warn!(
+                        "wait list too short for a stratum: {} < {}",
+                        result.wait_list.len(),
+                        num_wait_list_accepted
+                    );
+                    result.wait_list_bound
+                } else {
+                    result.wait_list.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                    result.wait_list[num_wait_list_accepted]
+                }
+            };
+            let threshold = StratumThreshold {
+                accept_bound: result.accept_bound,
+                wait_list_bound: result.wait_list_bound,
+                threshold,
+            };
+            (key, threshold)
+        })
+        .collect()
+}
+
+/// Number of times an item is included in the exact sample. It consumes the same random values
+/// as `update_acceptance_result` so that the thresholds computed in the first pass apply.
+pub(crate) fn exact_sample_count(
+    with_replacement: bool,
+    fraction: f64,
+    threshold: &StratumThreshold,
+    rng: &mut Pcg64,
+) -> u64 {
+    if fraction <= 0.0 {
+        0
+    } else if with_replacement {
+        let copies_accepted = next_poisson(rng, threshold.accept_bound);
+        let copies_wait_listed = next_poisson(rng, threshold.wait_list_bound);
+        let copies_from_wait_list = (0..copies_wait_listed)
+            .filter(|_| rng.gen::<f64>() < threshold.threshold)
+            .count() as u64;
+        copies_accepted + copies_from_wait_list
+    } else if rng.gen::<f64>() < threshold.threshold {
+        1
+    } else {
+        0
+    }
+}
+
+/// Returns a threshold `p` such that if we conduct n Bernoulli trials with success rate = `p`,
+/// it is very unlikely to have more than `fraction * n` successes.
+fn binomial_lower_bound(delta: f64, n: u64, fraction: f64) -> f64 {
+    let gamma = -delta.ln() / n as f64 * (2.0 / 3.0);
+    fraction + gamma - (gamma * gamma + 3.0 * gamma * fraction).sqrt()
+}
+
+/// Returns a threshold `p` such that if we conduct n Bernoulli trials with success rate = `p`,
+/// it is very unlikely to have less than `fraction * n` successes.
+fn binomial_upper_bound(delta: f64, n: u64, fraction: f64) -> f64 {
+    let gamma = -delta.ln() / n as f64;
+    (fraction + gamma + (gamma * gamma + 2.0 * gamma * fraction).sqrt())
+        .max(MIN_SAMPLING_RATE)
+        .min(1.0)
+}
+
+/// Returns a lambda such that Pr[X > s] is very small, where X ~ Pois(lambda).
+fn poisson_lower_bound(s: f64) -> f64 {
+    (s - poisson_num_std(s) * s.sqrt()).max(1e-15)
+}
+
+/// Returns a lambda such that Pr[X < s] is very small, where X ~ Pois(lambda).
+fn poisson_upper_bound(s: f64) -> f64 {
+    (s + poisson_num_std(s) * s.sqrt()).max(1e-10)
+}
+
+fn poisson_num_std(s: f64) -> f64 {
+    if s < 6.0 {
+        12.0
+    } else if s < 16.0 {
+        9.0
+    } else {
+        6.0
+    }
+}
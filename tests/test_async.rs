@@ -43,3 +43,68 @@ async fn existing_async_std_rt() -> Result<()> {
     assert_eq!(finally, "finally");
     Ok(())
 }
+
+#[tokio::test(core_threads = 4)]
+async fn async_actions() -> Result<()> {
+    let sc = CONTEXT.clone();
+    let rdd = sc.make_rdd((0..100).collect::<Vec<_>>(), 8);
+
+    let (collected, count) = futures::join!(rdd.collect_async(), rdd.count_async());
+    assert_eq!(collected?, (0..100).collect::<Vec<_>>());
+    assert_eq!(count?, 100);
+
+    let taken = rdd.take_async(15).await?;
+    assert_eq!(taken, (0..15).collect::<Vec<_>>());
+
+    rdd.for_each_async(Fn!(|i: i32| assert!(i < 100))).await?;
+    rdd.for_each_partition_async(Fn!(|iter: Box<dyn Iterator<Item = i32>>| {
+        assert!(iter.count() > 0)
+    }))
+    .await?;
+    Ok(())
+}
+
+#[async_std::test]
+async fn cancel_async_action() -> Result<()> {
+    let sc = CONTEXT.clone();
+    let rdd = sc.make_rdd((0..100).collect::<Vec<_>>(), 8);
+    let action = rdd
+        .map(Fn!(|i: i32| {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+            i
+        }))
+        .take_async(100);
+    action.cancel();
+    assert!(action.is_cancelled());
+    match action.await {
+        Err(Error::JobCancelled) => {}
+        other => panic!("expected a cancelled job, got {:?}", other),
+    }
+    Ok(())
+}
+
+#[tokio::test(core_threads = 4)]
+async fn cancel_running_jobs() -> Result<()> {
+    let sc = CONTEXT.clone();
+    let slow = sc
+        .make_rdd((0..64).collect::<Vec<_>>(), 16)
+        .map(Fn!(|i: i32| {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            i
+        }));
+
+    let count = slow.count_async();
+    let collected = slow.collect_async();
+    let for_each = slow.for_each_async(Fn!(|i: i32| assert!(i < 64)));
+    tokio::time::delay_for(std::time::Duration::from_millis(5)).await;
+    count.cancel();
+    collected.cancel();
+    for_each.cancel();
+    assert!(matches!(count.await, Err(Error::JobCancelled)));
+    assert!(matches!(collected.await, Err(Error::JobCancelled)));
+    assert!(matches!(for_each.await, Err(Error::JobCancelled)));
+
+    // The cancelled jobs don't hold the scheduler.
+    assert_eq!(sc.make_rdd(0..10, 2).count_async().await?, 10);
+    Ok(())
+}
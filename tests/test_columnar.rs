@@ -0,0 +1,120 @@
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+use vega::dataframe::columnar::{batch_to_rows, rows_to_batch, ArrowBatch, RecordBatchRdd};
+use vega::dataframe::*;
+use vega::*;
+
+static CONTEXT: Lazy<Arc<Context>> = Lazy::new(|| Context::new().unwrap());
+
+fn schema() -> Schema {
+    Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("score", DataType::Float64, true),
+        Field::new("tag", DataType::Utf8, true),
+    ])
+}
+
+fn rows() -> Vec<Row> {
+    (0..100)
+        .map(|i: i64| {
+            let score = if i % 10 == 0 {
+                Value::Null
+            } else {
+                (i as f64 / 2.0).into()
+            };
+            vec![i.into(), score, format!("t{}", i % 3).into()]
+        })
+        .collect()
+}
+
+fn batches() -> RecordBatchRdd {
+    let sc = CONTEXT.clone();
+    RecordBatchRdd::from_rows(sc.parallelize(rows(), 4), schema(), 16)
+}
+
+#[test]
+fn test_ipc_round_trip() -> Result<()> {
+    let rows = rows();
+    let batch = ArrowBatch::new(rows_to_batch(&rows, &schema())?);
+    let bytes = bincode::serialize(&batch).unwrap();
+    let decoded: ArrowBatch = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(decoded.num_rows(), 100);
+    assert_eq!(batch_to_rows(&decoded), rows);
+    Ok(())
+}
+
+#[test]
+fn test_batch_kernels() -> Result<()> {
+    let batches = batches();
+    assert_eq!(batches.num_rows()?, 100);
+    assert!(batches
+        .collect()?
+        .iter()
+        .all(|batch| batch.num_rows() <= 16));
+
+    let selected = batches
+        .filter(col("score").gt(lit(40)).and(col("tag").not_eq(lit("t0"))))?
+        .select(vec![
+            col("id"),
+            (col("score") * lit(2)).alias("double"),
+            col("tag").like(lit("%1")).alias("ends_with_one"),
+        ])?;
+    assert_eq!(selected.schema().field(1).name(), "double");
+    let mut rows = selected.to_rows().try_collect()?;
+    rows.sort();
+    let expected = (81..100)
+        .filter(|i| i % 10 != 0 && i % 3 != 0)
+        .map(|i: i64| vec![i.into(), (i as f64).into(), (i % 3 == 1).into()])
+        .collect::<Vec<Row>>();
+    assert_eq!(rows, expected);
+
+    let projected = batches.project(&[2, 0])?;
+    assert_eq!(projected.schema().field(0).name(), "tag");
+    assert_eq!(
+        projected.to_rows().take(1)?,
+        vec![Ok(vec!["t0".into(), 0.into()])]
+    );
+    assert!(batches.project(&[3]).is_err());
+    assert!(batches.filter(col("tag")).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_repartition_and_dataframe() -> Result<()> {
+    let batches = batches().repartition_by(&[2], 3)?;
+    let partitions = batches
+        .to_rows()
+        .glom()
+        .collect()?
+        .into_iter()
+        .map(|rows| {
+            let mut tags = rows
+                .into_iter()
+                .map(|row| row.unwrap()[2].clone())
+                .collect::<Vec<_>>();
+            tags.sort();
+            tags.dedup();
+            tags
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(partitions.len(), 3);
+    assert_eq!(partitions.iter().map(Vec::len).sum::<usize>(), 3);
+
+    let df = batches
+        .to_dataframe()
+        .filter(col("id").lt(lit(10)))?
+        .group_by(vec![col("tag")])?
+        .agg(vec![count(col("score")).alias("n")])?
+        .order_by(vec![col("tag").asc()])?;
+    assert_eq!(
+        df.collect()?,
+        vec![
+            vec!["t0".into(), 3.into()],
+            vec!["t1".into(), 3.into()],
+            vec!["t2".into(), 3.into()],
+        ]
+    );
+    assert_eq!(batches.to_dataframe().count()?, 100);
+    Ok(())
+}
@@ -0,0 +1,153 @@
+use std::fs;
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+use serde_derive::{Deserialize, Serialize};
+use vega::dataframe::{DataType, Value};
+use vega::io::{CsvOptions, SaveMode};
+use vega::*;
+
+static CONTEXT: Lazy<Arc<Context>> = Lazy::new(|| Context::new().unwrap());
+
+#[derive(Clone, Debug, PartialEq, PartialOrd, Serialize, Deserialize)]
+struct Player {
+    id: i64,
+    name: String,
+    score: Option<f64>,
+    active: bool,
+}
+
+fn players() -> String {
+    let mut text = "id,name,score,active\n".to_owned();
+    text += "1,\"Doe, Jane\",12.5,true\n";
+    text += "2,Bob,NA,false\n";
+    for i in 3..20 {
+        text += &format!("{},p{},{},{}\n", i, i, i, i % 2 == 0);
+    }
+    text += "20,late\n";
+    text += "abc,bad,1.0,true\n";
+    text
+}
+
+#[test]
+fn test_read_csv() -> Result<()> {
+    let sc = CONTEXT.clone();
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("players.csv"), players()).unwrap();
+
+    let options = CsvOptions::new()
+        .null_value("NA")
+        .sample_size(10)
+        .permissive(true)
+        .num_partitions_per_host(2);
+    let csv = sc.read_csv(dir.path(), options.clone())?;
+    let types = csv
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| (field.name().to_owned(), field.data_type()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        types,
+        vec![
+            ("id".to_owned(), DataType::Int64),
+            ("name".to_owned(), DataType::Utf8),
+            ("score".to_owned(), DataType::Float64),
+            ("active".to_owned(), DataType::Boolean),
+        ]
+    );
+
+    let mut rows = csv.rows().try_collect()?;
+    rows.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(rows.len(), 19);
+    assert_eq!(
+        rows[1],
+        vec![
+            Value::Int(2),
+            Value::Utf8("Bob".to_owned()),
+            Value::Null,
+            Value::Boolean(false)
+        ]
+    );
+    let mut bad_rows = csv.bad_rows().collect()?;
+    bad_rows.sort_by(|a, b| a.line.cmp(&b.line));
+    assert_eq!(
+        bad_rows
+            .iter()
+            .map(|row| row.line.as_str())
+            .collect::<Vec<_>>(),
+        vec!["20,late", "abc,bad,1.0,true"]
+    );
+    assert_eq!(csv.to_dataframe()?.count()?, 19);
+
+    let mut players = csv.deserialize::<Player>().try_collect()?;
+    players.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(players.len(), 19);
+    assert_eq!(
+        players[0],
+        Player {
+            id: 1,
+            name: "Doe, Jane".to_owned(),
+            score: Some(12.5),
+            active: true,
+        }
+    );
+    assert_eq!(players[1].score, None);
+    Ok(())
+}
+
+#[test]
+fn test_read_malformed_csv() -> Result<()> {
+    let sc = CONTEXT.clone();
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("players.csv"), players()).unwrap();
+
+    let options = CsvOptions::new().null_value("NA").sample_size(10);
+    let csv = sc.read_csv(dir.path(), options)?;
+    match csv.rows().try_collect() {
+        Err(Error::MalformedRecord { line, .. }) => {
+            assert!(line == "20,late" || line == "abc,bad,1.0,true")
+        }
+        other => panic!("expected a malformed row, got {:?}", other),
+    }
+    assert_eq!(csv.rows().count()?, 21);
+    assert!(csv.deserialize::<Player>().try_count().is_err());
+    match csv.to_dataframe()?.count() {
+        Err(Error::TaskFailed(message)) => assert!(message.starts_with("malformed record")),
+        other => panic!("expected a malformed row, got {:?}", other),
+    }
+    Ok(())
+}
+
+#[test]
+fn test_save_as_csv() -> Result<()> {
+    let sc = CONTEXT.clone();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("players");
+    let players = (0..50)
+        .map(|i| Player {
+            id: i,
+            name: format!("p;{}", i),
+            score: if i % 5 == 0 {
+                None
+            } else {
+                Some(i as f64 / 2.0)
+            },
+            active: i % 3 == 0,
+        })
+        .collect::<Vec<_>>();
+    let options = CsvOptions::new().delimiter(b';');
+    sc.parallelize(players.clone(), 3).save_as_csv(
+        &path,
+        options.clone(),
+        SaveMode::ErrorIfExists,
+    )?;
+    assert!(path.join("part-00000.csv").exists());
+
+    let csv = sc.read_csv(&path, options)?;
+    assert_eq!(csv.schema().field(2).data_type(), DataType::Float64);
+    let mut read = csv.deserialize::<Player>().try_collect()?;
+    read.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(read, players);
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use std::net::Ipv4Addr;
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+use serde_derive::{Deserialize, Serialize};
+use vega::io::{DataSource, SourceSplit};
+use vega::*;
+
+static CONTEXT: Lazy<Arc<Context>> = Lazy::new(|| Context::new().unwrap());
+
+/// Numbers from 0 to `end`, read in ranges of `step` numbers.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Ranges {
+    end: u64,
+    step: u64,
+}
+
+impl DataSource for Ranges {
+    type Split = (u64, u64);
+    type Item = u64;
+
+    fn splits(&self) -> Vec<SourceSplit<(u64, u64)>> {
+        (0..self.end)
+            .step_by(self.step as usize)
+            .map(|start| {
+                let end = (start + self.step).min(self.end);
+                SourceSplit::new((start, end))
+                    .size((end - start) * 8)
+                    .preferred_location(Ipv4Addr::LOCALHOST)
+            })
+            .collect()
+    }
+
+    fn read(&self, (start, end): (u64, u64)) -> Result<Box<dyn Iterator<Item = u64>>> {
+        Ok(Box::new(start..end))
+    }
+}
+
+#[test]
+fn test_read_source() -> Result<()> {
+    let sc = CONTEXT.clone();
+    let source = Ranges {
+        end: 1000,
+        step: 150,
+    };
+    let numbers = sc.read_source(source, Fn!(|n: u64| n * 2));
+    assert_eq!(numbers.number_of_splits(), 7);
+    let partitions = numbers.glom().collect()?;
+    assert_eq!(partitions[0], (0..150).map(|n| n * 2).collect::<Vec<_>>());
+    assert_eq!(partitions[6].len(), 100);
+    assert_eq!(
+        partitions.into_iter().flatten().collect::<Vec<_>>(),
+        (0..1000).map(|n| n * 2).collect::<Vec<_>>()
+    );
+    Ok(())
+}
@@ -0,0 +1,178 @@
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+use vega::dataframe::*;
+use vega::*;
+
+static CONTEXT: Lazy<Arc<Context>> = Lazy::new(|| Context::new().unwrap());
+
+fn people() -> DataFrame {
+    let sc = CONTEXT.clone();
+    let schema = Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("name", DataType::Utf8, false),
+        Field::new("age", DataType::Int64, true),
+        Field::new("city", DataType::Utf8, true),
+    ]);
+    let rows = vec![
+        vec![1.into(), "alice".into(), 31.into(), "paris".into()],
+        vec![2.into(), "bob".into(), 17.into(), "london".into()],
+        vec![3.into(), "carol".into(), 45.into(), "paris".into()],
+        vec![4.into(), "dave".into(), Value::Null, "berlin".into()],
+        vec![5.into(), "erin".into(), 25.into(), Value::Null],
+    ];
+    sc.create_dataframe(sc.parallelize(rows, 2), schema)
+}
+
+fn orders() -> DataFrame {
+    let sc = CONTEXT.clone();
+    let schema = Schema::new(vec![
+        Field::new("person_id", DataType::Int64, false),
+        Field::new("amount", DataType::Float64, false),
+    ]);
+    let rows = vec![
+        vec![1.into(), 10.0.into()],
+        vec![1.into(), 5.5.into()],
+        vec![3.into(), 7.0.into()],
+        vec![6.into(), 1.0.into()],
+    ];
+    sc.create_dataframe(sc.parallelize(rows, 3), schema)
+}
+
+#[test]
+fn test_select_filter() -> Result<()> {
+    let adults = people()
+        .filter(col("age").gt_eq(lit(18)).and(lit(true)))?
+        .select(vec![col("name"), (col("age") + lit(1)).alias("next_age")])?;
+    assert_eq!(adults.schema().field(1).name(), "next_age");
+    assert_eq!(adults.schema().field(1).data_type(), DataType::Int64);
+    let mut rows = adults.collect()?;
+    rows.sort();
+    assert_eq!(
+        rows,
+        vec![
+            vec!["alice".into(), 32.into()],
+            vec!["carol".into(), 46.into()],
+            vec!["erin".into(), 26.into()],
+        ]
+    );
+
+    assert!(people().select(vec![col("unknown")]).is_err());
+    assert!(people().filter(col("name") + lit(1)).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_group_by_agg() -> Result<()> {
+    let by_city = people()
+        .group_by(vec![col("city")])?
+        .agg(vec![
+            count(col("id")).alias("n"),
+            avg(col("age")),
+            max(col("name")),
+        ])?
+        .order_by(vec![col("n").desc(), col("city").asc()])?;
+    assert_eq!(
+        by_city.collect()?,
+        vec![
+            vec!["paris".into(), 2.into(), 38.0.into(), "carol".into()],
+            vec![Value::Null, 1.into(), 25.0.into(), "erin".into()],
+            vec!["berlin".into(), 1.into(), Value::Null, "dave".into()],
+            vec!["london".into(), 1.into(), 17.0.into(), "bob".into()],
+        ]
+    );
+
+    // a global aggregation returns a row even without input
+    let empty = people()
+        .filter(col("age").gt(lit(100)))?
+        .agg(vec![count(col("id")), sum(col("age"))])?;
+    assert_eq!(empty.collect()?, vec![vec![0.into(), Value::Null]]);
+
+    // an overflowing integer sum is null, in whichever partition it overflows
+    let sc = CONTEXT.clone();
+    let schema = Schema::new(vec![Field::new("n", DataType::Int64, false)]);
+    for num_partitions in 1..=3 {
+        let rows = vec![vec![i64::MAX.into()], vec![1.into()], vec![1.into()]];
+        let overflowed = sc
+            .create_dataframe(sc.parallelize(rows, num_partitions), schema.clone())
+            .agg(vec![sum(col("n"))])?;
+        assert_eq!(overflowed.collect()?, vec![vec![Value::Null]]);
+    }
+
+    assert!(people()
+        .group_by(vec![col("city")])?
+        .agg(vec![col("age")])
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn test_join() -> Result<()> {
+    let joined = people()
+        .join(&orders(), &[("id", "person_id")], JoinType::Inner)?
+        .group_by(vec![col("name")])?
+        .agg(vec![sum(col("amount")).alias("total")])?
+        .order_by(vec![col("total").desc()])?;
+    assert_eq!(
+        joined.collect()?,
+        vec![
+            vec!["alice".into(), 15.5.into()],
+            vec!["carol".into(), 7.0.into()],
+        ]
+    );
+
+    let full = people()
+        .alias("p")
+        .join(
+            &orders().alias("o"),
+            &[("p.id", "o.person_id")],
+            JoinType::Full,
+        )?
+        .select(vec![col("p.name"), col("o.person_id")])?;
+    let mut rows = full.collect()?;
+    rows.sort();
+    assert_eq!(rows.len(), 7);
+    assert_eq!(rows[0], vec![Value::Null, 6.into()]);
+    assert!(rows.contains(&vec!["bob".into(), Value::Null]));
+    Ok(())
+}
+
+#[test]
+fn test_order_by_limit() -> Result<()> {
+    let sc = CONTEXT.clone();
+    let schema = Schema::new(vec![Field::new("x", DataType::Int64, false)]);
+    let rows = (0..1000)
+        .rev()
+        .map(|x| vec![Value::Int(x % 100)])
+        .collect::<Vec<_>>();
+    let df = sc.create_dataframe(sc.parallelize(rows, 5), schema);
+    let sorted = df.order_by(vec![col("x").asc()])?.collect()?;
+    assert_eq!(sorted.len(), 1000);
+    assert!(sorted.windows(2).all(|w| w[0] <= w[1]));
+
+    let top = df.order_by(vec![col("x").desc()])?.limit(3).collect()?;
+    assert_eq!(top, vec![vec![99.into()]; 3]);
+    Ok(())
+}
+
+#[test]
+fn test_optimizer() -> Result<()> {
+    let query = people()
+        .join(&orders(), &[("id", "person_id")], JoinType::Inner)?
+        .filter(
+            col("age")
+                .gt(lit(10) + lit(10))
+                .and(col("amount").gt(lit(6.0))),
+        )?
+        .select(vec![col("name")])?;
+    let plan = query.optimized_plan()?.to_string();
+    // the predicates are folded and pushed to the scans of each side of the join
+    assert!(!plan.contains("Filter"), "{}", plan);
+    assert!(plan.contains("filters=[(age > 20)]"), "{}", plan);
+    assert!(plan.contains("filters=[(amount > 6)]"), "{}", plan);
+    // only the columns used by the query are read
+    assert!(plan.contains("projection=[0, 1, 2]"), "{}", plan);
+    assert!(plan.contains("projection=[0, 1]"), "{}", plan);
+    assert_eq!(query.collect()?, vec![vec!["carol".into()]]);
+    Ok(())
+}
@@ -0,0 +1,148 @@
+use std::io::{Read, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+use vega::fs::{FileSystem, FileSystemRegistry, MemoryFileSystem};
+use vega::io::{ObjectFileConfig, SaveMode, SUCCESS_MARKER};
+use vega::*;
+
+static CONTEXT: Lazy<Arc<Context>> = Lazy::new(|| Context::new().unwrap());
+
+fn names(path: &str) -> Vec<String> {
+    let file_system = fs::get_file_system(Path::new(path)).unwrap();
+    let mut names = file_system
+        .list_status(Path::new(path))
+        .unwrap()
+        .into_iter()
+        .map(|status| {
+            status
+                .path
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect::<Vec<_>>();
+    names.sort();
+    names
+}
+
+#[test]
+fn test_memory_file_system() -> Result<()> {
+    let sc = CONTEXT.clone();
+    let file_system = fs::get_file_system(Path::new("mem:///"))?;
+    for (name, text) in &[
+        ("a.txt", "1\n2\n3\n"),
+        ("b.txt", "4\n5\n"),
+        ("_SUCCESS", ""),
+    ] {
+        let path = format!("mem:///logs/day=1/{}", name);
+        let mut writer = file_system.create(Path::new(&path), true).unwrap();
+        writer.write_all(text.as_bytes()).unwrap();
+        writer.flush().unwrap();
+    }
+
+    let lines = sc.text_file("mem:///logs/day=*/*.txt", 2);
+    assert_eq!(lines.count()?, 5);
+    let config = LocalFsReaderConfig::new("mem:///logs").recursive(true);
+    let mut read = sc
+        .read_source(config, Fn!(|line: (String, io::PartitionValues)| line))
+        .collect()?;
+    read.sort();
+    assert_eq!(
+        read[0],
+        ("1".to_owned(), vec![("day".to_owned(), "1".to_owned())])
+    );
+
+    let numbers = sc.make_rdd(0..100, 3);
+    numbers.save_as_text_file("mem:///out/text", SaveMode::ErrorIfExists)?;
+    assert_eq!(
+        names("mem:///out/text"),
+        vec![SUCCESS_MARKER, "part-00000", "part-00001", "part-00002"]
+    );
+    assert!(numbers
+        .save_as_text_file("mem:///out/text", SaveMode::ErrorIfExists)
+        .is_err());
+    let mut text = String::new();
+    file_system
+        .open(Path::new("mem:///out/text/part-00000"))
+        .unwrap()
+        .read_to_string(&mut text)
+        .unwrap();
+    assert!(text.starts_with("0\n1\n"));
+
+    numbers.save_as_object_file("mem:///out/objects", ObjectFileConfig::new().block_size(7))?;
+    let mut objects = sc.object_file::<i32, _>("mem:///out/objects")?.collect()?;
+    objects.sort();
+    assert_eq!(objects, (0..100).collect::<Vec<_>>());
+
+    numbers.save_as_json_lines("mem:///out/json", SaveMode::ErrorIfExists)?;
+    let json = sc.read_json_lines::<i32, _>("mem:///out/json", io::JsonLinesOptions::new())?;
+    assert_eq!(json.records().try_count()?, 100);
+
+    // The local file system is reached through `file://` URIs too.
+    let dir = tempfile::tempdir().unwrap();
+    let uri = format!("file://{}", dir.path().join("out").display());
+    numbers.save_as_text_file(&uri, SaveMode::ErrorIfExists)?;
+    assert!(dir.path().join("out").join(SUCCESS_MARKER).exists());
+    assert_eq!(sc.text_file(uri, 2).count()?, 100);
+    Ok(())
+}
+
+#[test]
+fn test_register_file_system() -> Result<()> {
+    #[derive(Clone)]
+    struct ScratchFileSystem(MemoryFileSystem);
+
+    impl FileSystem for ScratchFileSystem {
+        fn scheme(&self) -> &str {
+            "scratch"
+        }
+
+        fn open(&self, path: &Path) -> std::io::Result<Box<dyn fs::ReadSeek>> {
+            self.0.open(path)
+        }
+
+        fn create(&self, path: &Path, overwrite: bool) -> std::io::Result<Box<dyn Write + Send>> {
+            self.0.create(path, overwrite)
+        }
+
+        fn list_status(&self, path: &Path) -> std::io::Result<Vec<fs::FileStatus>> {
+            self.0.list_status(path)
+        }
+
+        fn get_file_status(&self, path: &Path) -> std::io::Result<fs::FileStatus> {
+            self.0.get_file_status(path)
+        }
+
+        fn rename(&self, from: &Path, to: &Path) -> std::io::Result<()> {
+            self.0.rename(from, to)
+        }
+
+        fn delete(&self, path: &Path, recursive: bool) -> std::io::Result<bool> {
+            self.0.delete(path, recursive)
+        }
+
+        fn mkdirs(&self, path: &Path) -> std::io::Result<()> {
+            self.0.mkdirs(path)
+        }
+    }
+
+    let sc = CONTEXT.clone();
+    let path = "scratch:///words";
+    assert!(sc
+        .make_rdd(vec![1, 2], 1)
+        .save_as_text_file(path, SaveMode::Overwrite)
+        .is_err());
+
+    FileSystemRegistry::global().register(Arc::new(ScratchFileSystem(MemoryFileSystem::new())));
+    sc.make_rdd(vec!["a".to_owned(), "b".to_owned()], 2)
+        .save_as_text_file(path, SaveMode::Overwrite)?;
+    assert_eq!(
+        names(path),
+        vec![SUCCESS_MARKER, "part-00000", "part-00001"]
+    );
+    assert_eq!(sc.text_file(path, 1).collect()?.len(), 2);
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use std::fs;
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+use serde_derive::{Deserialize, Serialize};
+use vega::io::{CorruptRecords, JsonLinesOptions, SaveMode};
+use vega::*;
+
+static CONTEXT: Lazy<Arc<Context>> = Lazy::new(|| Context::new().unwrap());
+
+#[derive(Clone, Debug, PartialEq, PartialOrd, Serialize, Deserialize)]
+struct Event {
+    user: String,
+    kind: String,
+    value: Option<i64>,
+}
+
+#[test]
+fn test_read_json_lines() -> Result<()> {
+    let sc = CONTEXT.clone();
+    let dir = tempfile::tempdir().unwrap();
+    let mut text = (0..100)
+        .map(|i| format!(r#"{{"user":"u{}","kind":"click","value":{}}}"#, i % 7, i) + "\n")
+        .collect::<String>();
+    text += "\n{\"user\":\"u1\",\"kind\":\"view\"}\n";
+    text += "{\"user\":\"u2\",\n";
+    text += "not json\n";
+    fs::write(dir.path().join("events.jsonl"), text).unwrap();
+
+    let options = JsonLinesOptions::new()
+        .corrupt_records(CorruptRecords::Drop)
+        .num_partitions_per_host(3);
+    let events = sc.read_json_lines::<Event, _>(dir.path(), options)?;
+    let mut records = events.records().try_collect()?;
+    records.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(records.len(), 101);
+    assert!(records.contains(&Event {
+        user: "u1".to_owned(),
+        kind: "view".to_owned(),
+        value: None,
+    }));
+    let mut corrupt = events
+        .corrupt_records()
+        .map(Fn!(|record: io::CorruptRecord| record.line))
+        .collect()?;
+    corrupt.sort();
+    assert_eq!(corrupt, vec!["not json", "{\"user\":\"u2\","]);
+
+    let options = JsonLinesOptions::new().corrupt_records(CorruptRecords::Fail);
+    let failed = sc.read_json_lines::<Event, _>(dir.path(), options.clone())?;
+    assert_eq!(failed.records().count()?, 103);
+    match failed.records().try_collect() {
+        Err(Error::MalformedRecord { line, .. }) => {
+            assert!(line == "not json" || line == "{\"user\":\"u2\",")
+        }
+        other => panic!("expected a corrupt record, got {:?}", other),
+    }
+
+    let missing = sc.read_json_lines::<Event, _>(dir.path().join("missing"), options);
+    assert!(missing.is_err());
+    Ok(())
+}
+
+#[test]
+fn test_save_as_json_lines() -> Result<()> {
+    let sc = CONTEXT.clone();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("events");
+    let events = (0..60)
+        .map(|i| Event {
+            user: format!("u\"{}\n", i),
+            kind: "click".to_owned(),
+            value: if i % 4 == 0 { None } else { Some(i) },
+        })
+        .collect::<Vec<_>>();
+    sc.parallelize(events.clone(), 4)
+        .save_as_json_lines(&path, SaveMode::ErrorIfExists)?;
+    assert!(path.join("part-00003.jsonl").exists());
+
+    let mut read = sc
+        .read_json_lines::<Event, _>(&path, JsonLinesOptions::new())?
+        .records()
+        .try_collect()?;
+    read.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let mut expected = events;
+    expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(read, expected);
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+use vega::*;
+
+static CONTEXT: Lazy<Arc<Context>> = Lazy::new(|| Context::new().unwrap());
+
+fn approx_eq(a: f64, b: f64) -> bool {
+    (a - b).abs() < 1e-9
+}
+
+#[test]
+fn test_stats() {
+    let sc = CONTEXT.clone();
+    let rdd = sc.make_rdd((1..=10).map(|x| x as f64).collect::<Vec<_>>(), 3);
+    let stats = rdd.stats().unwrap();
+    assert_eq!(stats.count(), 10);
+    assert!(approx_eq(stats.mean(), 5.5));
+    assert!(approx_eq(stats.sum(), 55.0));
+    assert!(approx_eq(stats.variance(), 8.25));
+    assert_eq!(stats.max(), 10.0);
+    assert_eq!(stats.min(), 1.0);
+    assert!(approx_eq(rdd.sum().unwrap(), 55.0));
+    assert!(approx_eq(rdd.sample_variance().unwrap(), 82.5 / 9.0));
+
+    // items convertible to f64 work too
+    let ints = sc.make_rdd(vec![2i32, 4, 4, 4, 5, 5, 7, 9], 2);
+    assert!(approx_eq(ints.mean().unwrap(), 5.0));
+    assert!(approx_eq(ints.stdev().unwrap(), 2.0));
+}
+
+#[test]
+fn test_histogram() {
+    let sc = CONTEXT.clone();
+    let rdd = sc.make_rdd((0..=100).map(|x| x as f64).collect::<Vec<_>>(), 4);
+    let (buckets, counts) = rdd.histogram(2).unwrap();
+    assert_eq!(buckets, vec![0.0, 50.0, 100.0]);
+    assert_eq!(counts, vec![50, 51]);
+
+    let single = sc.make_rdd(vec![1.0, 1.0, 1.0], 2);
+    let (buckets, counts) = single.histogram(4).unwrap();
+    assert_eq!(buckets, vec![1.0, 1.0]);
+    assert_eq!(counts, vec![3]);
+
+    let empty = sc.make_rdd(Vec::<f64>::new(), 4);
+    assert!(empty.histogram(2).is_err());
+    let with_inf = sc.make_rdd(vec![1.0, f64::INFINITY], 2);
+    assert!(with_inf.histogram(2).is_err());
+    let with_nan = sc.make_rdd(vec![1.0, f64::NAN, 3.0], 2);
+    assert!(with_nan.histogram(2).is_err());
+}
+
+#[test]
+fn test_histogram_with_buckets() {
+    let sc = CONTEXT.clone();
+    let rdd = sc.make_rdd(vec![-1.0, 1.0, 5.0, 10.0, 15.0, 20.0, 50.0, 51.0, f64::NAN], 3);
+    // uneven buckets
+    let counts = rdd.histogram_with_buckets(&[1.0, 10.0, 20.0, 50.0]).unwrap();
+    assert_eq!(counts, vec![2, 2, 2]);
+    // evenly spaced buckets
+    let counts = rdd.histogram_with_buckets(&[0.0, 10.0, 20.0, 30.0]).unwrap();
+    assert_eq!(counts, vec![2, 2, 1]);
+}
+
+#[test]
+fn test_sum_and_mean_approx() -> Result<()> {
+    let sc = CONTEXT.clone();
+
+    // this should complete and return the final value, so confidence should be 100%
+    let time_out = std::time::Duration::from_secs(10);
+    let rdd = sc.make_rdd((1..=100).map(|x| x as f64).collect::<Vec<_>>(), 10);
+    let sum = rdd.sum_approx(time_out, Some(0.9))?.get_final_value()?;
+    assert!(approx_eq(sum.mean, 5050.0));
+    assert_eq!(sum.confidence, 1.0);
+    assert_eq!((sum.low, sum.high), (sum.mean, sum.mean));
+    let mean = rdd.mean_approx(time_out, Some(0.9))?.get_final_value()?;
+    assert!(approx_eq(mean.mean, 50.5));
+    assert_eq!(mean.confidence, 1.0);
+    Ok(())
+}
+
+#[test]
+fn test_approx_quantiles() -> Result<()> {
+    let sc = CONTEXT.clone();
+    let n = 10_000;
+    let rdd = sc.make_rdd((0..n).map(|x| x as f64).collect::<Vec<_>>(), 8);
+    let probabilities = [0.0, 0.5, 0.95, 0.99, 1.0];
+    let quantiles = rdd.approx_quantiles(&probabilities, 0.001)?;
+    assert_eq!(quantiles.len(), probabilities.len());
+    for (p, q) in probabilities.iter().zip(quantiles) {
+        assert!((q - p * n as f64).abs() <= 0.002 * n as f64 + 1.0);
+    }
+
+    let empty = sc.make_rdd(Vec::<f64>::new(), 4);
+    assert!(empty.approx_quantiles(&probabilities, 0.01)?.is_empty());
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+use vega::io::{Codec, ObjectFileConfig};
+use vega::*;
+
+static CONTEXT: Lazy<Arc<Context>> = Lazy::new(|| Context::new().unwrap());
+
+#[test]
+fn test_object_file_round_trip() -> Result<()> {
+    let sc = CONTEXT.clone();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("pairs");
+    let items = (0..1000)
+        .map(|i| (format!("key{}", i % 13), vec![i as f64; i % 3]))
+        .collect::<Vec<_>>();
+    let config = ObjectFileConfig::new()
+        .codec(Some(Codec::Gzip))
+        .block_size(64);
+    sc.parallelize(items.clone(), 3)
+        .save_as_object_file(&path, config)?;
+
+    let read = sc.object_file::<(String, Vec<f64>)>(&path)?;
+    assert!(read.number_of_splits() >= 1);
+    let mut read = read.collect()?;
+    read.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let mut expected = items;
+    expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(read, expected);
+
+    // The header records the type of the items.
+    match sc.object_file::<String>(&path) {
+        Err(Error::InvalidObjectFile { .. }) => {}
+        Err(err) => panic!("expected an invalid object file, got {}", err),
+        Ok(_) => panic!("expected an invalid object file"),
+    }
+    Ok(())
+}
+
+#[test]
+fn test_object_file_empty_partitions() -> Result<()> {
+    let sc = CONTEXT.clone();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("numbers");
+    sc.parallelize(vec![1u32, 2, 3], 5)
+        .save_as_object_file(&path, ObjectFileConfig::new())?;
+    let mut read = sc.object_file::<u32>(&path)?.collect()?;
+    read.sort();
+    assert_eq!(read, vec![1, 2, 3]);
+    Ok(())
+}
+
+#[test]
+fn test_object_file_keeps_order() -> Result<()> {
+    let sc = CONTEXT.clone();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("ordered");
+    let items = (0..5000u64).collect::<Vec<_>>();
+    sc.parallelize(items.clone(), 4)
+        .save_as_object_file(&path, ObjectFileConfig::new().block_size(100))?;
+
+    // Every partition reads a contiguous run of blocks, in the order of the files.
+    let read = sc.object_file::<u64>(&path)?;
+    assert!(read.number_of_splits() >= 1);
+    assert_eq!(read.collect()?, items);
+    Ok(())
+}
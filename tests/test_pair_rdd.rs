@@ -133,3 +133,124 @@ fn test_group_by() -> Result<()> {
     assert_eq!(expected, res);
     Ok(())
 }
+
+#[test]
+fn test_count_by_key_approx() -> Result<()> {
+    let sc = CONTEXT.clone();
+    let time_out = std::time::Duration::from_secs(10);
+    let pairs = vec![(1i32, 1.0f64), (2, 2.0), (2, 4.0), (3, 3.0), (3, 3.0), (3, 6.0)];
+    let rdd = sc.make_rdd(pairs, 3);
+
+    let mut counts: Vec<_> = rdd
+        .count_by_key_approx(time_out, Some(0.9))?
+        .get_final_value()?
+        .into_iter()
+        .map(|(k, v)| (k, v.mean))
+        .collect();
+    counts.sort_by(|e1, e2| e1.0.cmp(&e2.0));
+    assert_eq!(counts, vec![(1, 1.0), (2, 2.0), (3, 3.0)]);
+
+    let mut sums: Vec<_> = rdd
+        .sum_by_key_approx(time_out, Some(0.9))?
+        .get_final_value()?
+        .into_iter()
+        .map(|(k, v)| (k, v.mean))
+        .collect();
+    sums.sort_by(|e1, e2| e1.0.cmp(&e2.0));
+    assert_eq!(sums, vec![(1, 1.0), (2, 6.0), (3, 12.0)]);
+
+    let mut means: Vec<_> = rdd
+        .mean_by_key_approx(time_out, Some(0.9))?
+        .get_final_value()?
+        .into_iter()
+        .map(|(k, v)| (k, v.mean))
+        .collect();
+    means.sort_by(|e1, e2| e1.0.cmp(&e2.0));
+    assert_eq!(means, vec![(1, 1.0), (2, 3.0), (3, 4.0)]);
+    Ok(())
+}
+
+#[test]
+fn test_count_approx_distinct_by_key() -> Result<()> {
+    let sc = CONTEXT.clone();
+    // key k has k * 100 distinct values, each repeated twice
+    let pairs: Vec<_> = (1..=5u64)
+        .flat_map(|k| (0..k * 100).chain(0..k * 100).map(move |v| (k, v)))
+        .collect();
+    let mut res = sc
+        .make_rdd(pairs, 4)
+        .count_approx_distinct_by_key(0.05, 2)
+        .collect()?;
+    res.sort_by(|e1, e2| e1.0.cmp(&e2.0));
+    assert_eq!(res.len(), 5);
+    for (k, count) in res {
+        let expected = (k * 100) as f64;
+        assert!((count as f64 - expected).abs() / expected < 0.1);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_approx_quantiles_by_key() -> Result<()> {
+    let sc = CONTEXT.clone();
+    // latencies of two endpoints, "b" being ten times slower than "a"
+    let pairs: Vec<_> = (0..1000)
+        .flat_map(|i| vec![("a".to_string(), i as f64), ("b".to_string(), 10.0 * i as f64)])
+        .collect();
+    let mut res = sc
+        .make_rdd(pairs, 4)
+        .approx_quantiles_by_key(&[0.5, 0.99], 0.001, 2)
+        .collect()?;
+    res.sort_by(|e1, e2| e1.0.cmp(&e2.0));
+    assert_eq!(res.len(), 2);
+    let (a, b) = (&res[0].1, &res[1].1);
+    assert!((a[0] - 500.0).abs() <= 3.0 && (a[1] - 990.0).abs() <= 3.0);
+    assert!((b[0] - 5000.0).abs() <= 30.0 && (b[1] - 9900.0).abs() <= 30.0);
+    Ok(())
+}
+
+#[test]
+fn test_sample_by_key() -> Result<()> {
+    use std::collections::HashMap;
+
+    let sc = CONTEXT.clone();
+    // skewed classes: "a" has 10 times more items than "b"
+    let pairs: Vec<_> = (0..10_000)
+        .map(|i| ("a".to_string(), i))
+        .chain((0..1_000).map(|i| ("b".to_string(), i)))
+        .collect();
+    let rdd = sc.make_rdd(pairs, 8);
+    let mut fractions = HashMap::new();
+    fractions.insert("a".to_string(), 0.1);
+    fractions.insert("b".to_string(), 0.5);
+
+    let count_keys = |sample: Vec<(String, i32)>| {
+        let mut counts = HashMap::new();
+        sample
+            .into_iter()
+            .for_each(|(k, _)| *counts.entry(k).or_insert(0) += 1);
+        counts
+    };
+
+    for &with_replacement in &[false, true] {
+        let sample = rdd
+            .sample_by_key(with_replacement, fractions.clone(), Some(42))
+            .collect()?;
+        let counts = count_keys(sample);
+        assert!((counts["a"] as f64 - 1000.0).abs() < 200.0);
+        assert!((counts["b"] as f64 - 500.0).abs() < 100.0);
+
+        let sample = rdd
+            .sample_by_key_exact(with_replacement, fractions.clone(), Some(42))?
+            .collect()?;
+        let counts = count_keys(sample);
+        assert_eq!(counts["a"], 1000);
+        assert_eq!(counts["b"], 500);
+    }
+
+    // keys missing from the fractions are not sampled
+    fractions.remove("b");
+    let sample = rdd.sample_by_key_exact(false, fractions, Some(42))?.collect()?;
+    assert!(sample.iter().all(|(k, _)| k == "a"));
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+use vega::dataframe::*;
+use vega::io::{ParquetCompression, ParquetWriterConfig};
+use vega::*;
+
+static CONTEXT: Lazy<Arc<Context>> = Lazy::new(|| Context::new().unwrap());
+
+fn people() -> (Vec<Row>, Schema) {
+    let rows = (0..200)
+        .map(|i: i64| {
+            let name = if i % 7 == 0 {
+                Value::Null
+            } else {
+                format!("name{}", i).into()
+            };
+            vec![i.into(), name, (i as f64 * 1.5).into(), (i % 2 == 0).into()]
+        })
+        .collect();
+    let schema = Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("name", DataType::Utf8, true),
+        Field::new("score", DataType::Float64, false),
+        Field::new("even", DataType::Boolean, false),
+    ]);
+    (rows, schema)
+}
+
+#[test]
+fn test_parquet_round_trip() -> Result<()> {
+    let sc = CONTEXT.clone();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("people");
+    let (rows, schema) = people();
+    let df = sc.create_dataframe(sc.parallelize(rows.clone(), 3), schema.clone());
+    let config = ParquetWriterConfig::new()
+        .compression(ParquetCompression::Gzip)
+        .row_group_size(32);
+    df.save_as_parquet(&path, config)?;
+    // Three parts and the success marker.
+    assert_eq!(std::fs::read_dir(&path).unwrap().count(), 4);
+    assert!(path.join(io::SUCCESS_MARKER).exists());
+    assert!(df
+        .save_as_parquet(&path, ParquetWriterConfig::new())
+        .is_err());
+
+    let read = sc.read_parquet(&path)?;
+    assert_eq!(read.schema().len(), 4);
+    assert_eq!(read.schema().field(1).name(), "name");
+    assert!(read.schema().field(1).is_nullable());
+    assert!(!read.schema().field(0).is_nullable());
+    let mut read_rows = read.collect()?;
+    read_rows.sort();
+    assert_eq!(read_rows, rows);
+    assert_eq!(read.count()?, 200);
+    Ok(())
+}
+
+#[test]
+fn test_parquet_pruning() -> Result<()> {
+    let sc = CONTEXT.clone();
+    let dir = tempfile::tempdir().unwrap();
+    let (rows, schema) = people();
+    let df = sc.create_dataframe(sc.parallelize(rows, 2), schema);
+    let config = ParquetWriterConfig::new()
+        .row_group_size(10)
+        .mode(io::SaveMode::Overwrite);
+    df.save_as_parquet(dir.path(), config)?;
+
+    let read = sc.read_parquet(dir.path())?;
+    let df = read
+        .filter(col("id").gt_eq(lit(150)).and(col("name").is_not_null()))?
+        .select(vec![col("name")])?;
+    let plan = df.optimized_plan()?.to_string();
+    assert!(plan.contains("projection=[0, 1]") && plan.contains("filters=["));
+    let mut names = df.collect()?;
+    names.sort();
+    let mut expected = (150..200)
+        .filter(|i| i % 7 != 0)
+        .map(|i| vec![format!("name{}", i).into()])
+        .collect::<Vec<Row>>();
+    expected.sort();
+    assert_eq!(names, expected);
+
+    let df = read.filter(col("score").lt(lit(0)))?;
+    assert_eq!(df.count()?, 0);
+    Ok(())
+}
@@ -175,6 +175,36 @@ fn test_aggregate() {
     )
 }
 
+#[test]
+fn test_tree_aggregate() -> Result<()> {
+    let sc = CONTEXT.clone();
+    let rdd = sc.make_rdd((-1000..1000).collect::<Vec<i64>>(), 40);
+    let seq_fn = Fn!(|acc: (i64, u64), x: i64| (acc.0 + x, acc.1 + 1));
+    let comb_fn = Fn!(|a: (i64, u64), b: (i64, u64)| (a.0 + b.0, a.1 + b.1));
+    for depth in 1..10 {
+        let res = rdd.tree_aggregate((0, 0), seq_fn, comb_fn, depth)?;
+        assert_eq!(res, (-1000, 2000));
+    }
+
+    let empty = sc.make_rdd(Vec::<i64>::new(), 4);
+    assert_eq!(empty.tree_aggregate((0, 0), seq_fn, comb_fn, 2)?, (0, 0));
+    Ok(())
+}
+
+#[test]
+fn test_tree_reduce() -> Result<()> {
+    let sc = CONTEXT.clone();
+    let rdd = sc.make_rdd((-1000..1000).collect::<Vec<i64>>(), 40);
+    for depth in 1..10 {
+        let sum = rdd.tree_reduce(Fn!(|x: i64, y: i64| x + y), depth)?;
+        assert_eq!(sum, Some(-1000));
+    }
+
+    let empty = sc.make_rdd(Vec::<i64>::new(), 4);
+    assert_eq!(empty.tree_reduce(Fn!(|x: i64, y: i64| x + y), 2)?, None);
+    Ok(())
+}
+
 #[test]
 fn test_take() -> Result<()> {
     let sc = CONTEXT.clone();
@@ -481,6 +511,53 @@ fn test_zip() {
     assert_eq!(res, expected);
 }
 
+#[test]
+fn test_zip_partitions() -> Result<()> {
+    let sc = CONTEXT.clone();
+    let first = sc.parallelize(vec![1, 2, 3, 4], 2);
+    let second = sc.parallelize(vec![10, 20, 30, 40, 50, 60], 2);
+    let sums = Fn!(|fst: Box<dyn Iterator<Item = i32>>,
+                    sec: Box<dyn Iterator<Item = i32>>|
+          -> Box<dyn Iterator<Item = i32>> {
+        Box::new(std::iter::once(fst.sum::<i32>() + sec.sum::<i32>()))
+    });
+    let res = first
+        .zip_partitions(second.get_rdd(), sums.clone())?
+        .collect()?;
+    assert_eq!(res, vec![63, 157]);
+
+    let third = sc.parallelize(vec![1, 2, 3], 3);
+    assert!(first.zip_partitions(third.get_rdd(), sums).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_zip_with_index() -> Result<()> {
+    let sc = CONTEXT.clone();
+    let rdd = sc.parallelize(vec!['a', 'b', 'c', 'd', 'e'], 3);
+    let res = rdd.zip_with_index()?.collect()?;
+    let expected = vec![('a', 0), ('b', 1), ('c', 2), ('d', 3), ('e', 4)];
+    assert_eq!(res, expected);
+
+    let empty = sc.parallelize(Vec::<i32>::new(), 2);
+    assert!(empty.zip_with_index()?.collect()?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_zip_with_unique_id() -> Result<()> {
+    let sc = CONTEXT.clone();
+    let rdd = sc.parallelize(vec![1, 2, 3, 4, 5], 2);
+    let res = rdd.zip_with_unique_id().collect()?;
+    let ids: HashSet<u64> = res.iter().map(|(_, id)| *id).collect();
+    assert_eq!(ids.len(), 5);
+    assert_eq!(
+        res.iter().map(|(x, _)| *x).collect::<Vec<_>>(),
+        vec![1, 2, 3, 4, 5]
+    );
+    Ok(())
+}
+
 #[test]
 fn test_intersection_with_num_partitions() {
     let sc = CONTEXT.clone();
@@ -697,3 +774,109 @@ fn test_subtract() {
     assert_eq!(actual,expected_vec)
 
 }
+
+#[test]
+fn test_count_approx_distinct() -> Result<()> {
+    let sc = CONTEXT.clone();
+    let size = 1000u64;
+    let uniform = sc.make_rdd((1..=size).collect::<Vec<_>>(), 10);
+    let err = |relative_sd: f64| -> Result<f64> {
+        let estimate = uniform.count_approx_distinct(relative_sd)? as f64;
+        Ok((estimate - size as f64).abs() / size as f64)
+    };
+    assert!(err(0.2)? < 0.3);
+    assert!(err(0.05)? < 0.1);
+    assert!(err(0.01)? < 0.05);
+
+    // duplicates don't count
+    let duplicated = sc.make_rdd((1..=100u64).chain(1..=100).collect::<Vec<_>>(), 4);
+    let estimate = duplicated.count_approx_distinct(0.05)?;
+    assert!(estimate >= 90 && estimate <= 110);
+    Ok(())
+}
+
+#[test]
+fn test_pipe() -> Result<()> {
+    use std::collections::HashMap;
+
+    let sc = CONTEXT.clone();
+    let rdd = sc.make_rdd(vec![1, 2, 3, 4], 2);
+    let piped = rdd.pipe(&["cat"], HashMap::new()).collect()?;
+    assert_eq!(piped, vec!["1", "2", "3", "4"]);
+
+    // one line of output per partition
+    let counted = rdd.pipe(&["wc", "-l"], HashMap::new()).collect()?;
+    let counted: Vec<_> = counted.iter().map(|c| c.trim().to_string()).collect();
+    assert_eq!(counted, vec!["2", "2"]);
+
+    let formatted = rdd
+        .pipe_with_formatter(
+            &["cat"],
+            HashMap::new(),
+            Fn!(|x: &i32| -> String { format!("item {}", x) }),
+        )
+        .collect()?;
+    assert_eq!(formatted, vec!["item 1", "item 2", "item 3", "item 4"]);
+
+    let mut env = HashMap::new();
+    env.insert("PIPE_TEST_VAR".to_string(), "hello".to_string());
+    let with_env = rdd.pipe(&["printenv", "PIPE_TEST_VAR"], env).collect()?;
+    assert_eq!(with_env, vec!["hello", "hello"]);
+
+    // arguments are passed as they are
+    let echoed = rdd.pipe(&["echo", "a  b"], HashMap::new()).collect()?;
+    assert_eq!(echoed, vec!["a  b", "a  b"]);
+
+    // the command may stop reading its input before the end
+    let first = sc
+        .make_rdd((0..100_000).collect::<Vec<_>>(), 1)
+        .pipe(&["head", "-n", "1"], HashMap::new())
+        .collect()?;
+    assert_eq!(first, vec!["0"]);
+
+    // a non zero exit status fails the job
+    assert!(rdd.pipe(&["false"], HashMap::new()).collect().is_err());
+    Ok(())
+}
+
+#[test]
+fn test_pipe_failing_command() -> Result<()> {
+    use std::collections::HashMap;
+
+    let sc = CONTEXT.clone();
+    let rdd = sc.make_rdd(vec![1, 2, 3, 4], 2);
+    // the exit status is checked once the output is read
+    let failing = rdd.pipe(&["sh", "-c", "cat; exit 3"], HashMap::new());
+    match failing.collect() {
+        Err(Error::TaskFailed(message)) => assert!(message.contains("exited with")),
+        other => panic!("expected a failed task, got {:?}", other),
+    }
+    // a command which can't be started fails the job too
+    assert!(matches!(
+        rdd.pipe(&["vega-missing-command"], HashMap::new()).collect(),
+        Err(Error::TaskFailed(_))
+    ));
+    // and the following jobs run as usual
+    assert_eq!(rdd.count()?, 4);
+    Ok(())
+}
+
+#[test]
+fn test_to_local_iterator() -> Result<()> {
+    let sc = CONTEXT.clone();
+    let rdd = sc.make_rdd((0..100).collect::<Vec<_>>(), 7);
+    let expected: Vec<_> = (0..100).collect();
+
+    let res = rdd.to_local_iterator().collect::<Result<Vec<_>>>()?;
+    assert_eq!(res, expected);
+    for &(window, prefetch) in &[(1, true), (3, false), (3, true), (10, true)] {
+        let res = rdd
+            .to_local_iterator_with_window(window, prefetch)
+            .collect::<Result<Vec<_>>>()?;
+        assert_eq!(res, expected);
+    }
+
+    let empty = sc.make_rdd(Vec::<i32>::new(), 4);
+    assert_eq!(empty.to_local_iterator().count(), 0);
+    Ok(())
+}
@@ -0,0 +1,163 @@
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+use vega::dataframe::*;
+use vega::*;
+
+static CONTEXT: Lazy<Arc<Context>> = Lazy::new(|| {
+    let sc = Context::new().unwrap();
+    let people = vec![
+        vec![1.into(), "alice".into(), 31.into(), "paris".into()],
+        vec![2.into(), "bob".into(), 17.into(), "london".into()],
+        vec![3.into(), "carol".into(), 45.into(), "paris".into()],
+        vec![4.into(), "dave".into(), Value::Null, "berlin".into()],
+        vec![5.into(), "erin".into(), 25.into(), Value::Null],
+    ];
+    sc.register_rdd(
+        "people",
+        sc.parallelize(people, 2),
+        Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, false),
+            Field::new("age", DataType::Int64, true),
+            Field::new("city", DataType::Utf8, true),
+        ]),
+    );
+    let orders = vec![
+        vec![1.into(), 10.0.into()],
+        vec![1.into(), 5.5.into()],
+        vec![3.into(), 7.0.into()],
+        vec![6.into(), 1.0.into()],
+    ];
+    sc.register_rdd(
+        "orders",
+        sc.parallelize(orders, 3),
+        Schema::new(vec![
+            Field::new("person_id", DataType::Int64, false),
+            Field::new("amount", DataType::Float64, false),
+        ]),
+    );
+    sc
+});
+
+fn sorted(mut rows: Vec<Row>) -> Vec<Row> {
+    rows.sort();
+    rows
+}
+
+#[test]
+fn test_select_where() -> Result<()> {
+    let sc = CONTEXT.clone();
+    let df = sc.sql(
+        "SELECT upper(name) AS name, age + 1 next_age FROM people \
+         WHERE age BETWEEN 18 AND 40 AND name LIKE '%e%' ORDER BY 2 DESC",
+    )?;
+    assert_eq!(df.schema().field(0).name(), "name");
+    assert_eq!(df.schema().field(1).name(), "next_age");
+    assert_eq!(
+        df.collect()?,
+        vec![
+            vec!["ALICE".into(), 32.into()],
+            vec!["ERIN".into(), 26.into()],
+        ]
+    );
+
+    let df = sc.sql("SELECT * FROM people WHERE city IS NULL OR id IN (1, 2)")?;
+    assert_eq!(df.schema().len(), 4);
+    assert_eq!(
+        sorted(df.collect()?)
+            .into_iter()
+            .map(|row| row[0].clone())
+            .collect::<Vec<_>>(),
+        vec![1.into(), 2.into(), 5.into()]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_group_by_having() -> Result<()> {
+    let sc = CONTEXT.clone();
+    let df = sc.sql(
+        "SELECT city, count(*) AS n, max(age) FROM people \
+         WHERE city IS NOT NULL GROUP BY people.city HAVING count(*) > 1 OR min(age) < 18 \
+         ORDER BY n DESC",
+    )?;
+    assert_eq!(df.schema().field(2).name(), "MAX(age)");
+    assert_eq!(
+        df.collect()?,
+        vec![
+            vec!["paris".into(), 2.into(), 45.into()],
+            vec!["london".into(), 1.into(), 17.into()],
+        ]
+    );
+
+    let df = sc.sql("SELECT count(DISTINCT city), round(avg(age)) FROM people")?;
+    assert_eq!(df.collect()?, vec![vec![3.into(), 30.0.into()]]);
+
+    let df = sc.sql("SELECT DISTINCT city FROM people WHERE city IS NOT NULL ORDER BY city")?;
+    assert_eq!(
+        df.collect()?,
+        vec![
+            vec!["berlin".into()],
+            vec!["london".into()],
+            vec!["paris".into()],
+        ]
+    );
+
+    assert!(sc
+        .sql("SELECT name, count(*) FROM people GROUP BY city")
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn test_join() -> Result<()> {
+    let sc = CONTEXT.clone();
+    let df = sc.sql(
+        "SELECT p.name, sum(o.amount) AS total FROM people p \
+         JOIN orders o ON o.person_id = p.id AND o.amount > 6 \
+         GROUP BY p.name ORDER BY total DESC",
+    )?;
+    assert_eq!(
+        df.collect()?,
+        vec![
+            vec!["alice".into(), 10.0.into()],
+            vec!["carol".into(), 7.0.into()],
+        ]
+    );
+
+    let df = sc.sql(
+        "SELECT name, coalesce(amount, 0) FROM people \
+         LEFT OUTER JOIN orders ON id = person_id WHERE city = 'paris' OR city IS NULL",
+    )?;
+    assert_eq!(
+        sorted(df.collect()?),
+        vec![
+            vec!["alice".into(), 5.5.into()],
+            vec!["alice".into(), 10.0.into()],
+            vec!["carol".into(), 7.0.into()],
+            vec!["erin".into(), 0.0.into()],
+        ]
+    );
+
+    assert!(sc
+        .sql("SELECT * FROM people LEFT JOIN orders ON id = person_id AND amount > 1")
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn test_errors() -> Result<()> {
+    let sc = CONTEXT.clone();
+    assert!(sc.sql("SELECT * FROM missing").is_err());
+    assert!(sc.sql("SELECT unknown FROM people").is_err());
+    assert!(sc.sql("SELECT name FROM people WHERE").is_err());
+    assert!(sc.sql("SELECT name + 1 FROM people").is_err());
+
+    let df = sc.sql("SELECT name FROM people ORDER BY age DESC LIMIT 2")?;
+    assert_eq!(
+        df.collect()?,
+        vec![vec!["carol".into()], vec!["alice".into()]]
+    );
+    Ok(())
+}
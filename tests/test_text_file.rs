@@ -0,0 +1,197 @@
+use std::fs;
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+use vega::*;
+
+static CONTEXT: Lazy<Arc<Context>> = Lazy::new(|| Context::new().unwrap());
+
+#[test]
+fn test_text_file_splits() -> Result<()> {
+    let sc = CONTEXT.clone();
+    let dir = tempfile::tempdir().unwrap();
+    let lines = (0..1000)
+        .map(|i| "x".repeat(i % 37) + &i.to_string())
+        .collect::<Vec<_>>();
+    fs::write(dir.path().join("a.txt"), lines[..600].join("\n") + "\n").unwrap();
+    fs::write(dir.path().join("b.txt"), lines[600..].join("\r\n")).unwrap();
+    fs::write(dir.path().join("empty.txt"), "").unwrap();
+    fs::write(dir.path().join("_SUCCESS"), "").unwrap();
+
+    let rdd = sc.text_file(dir.path(), 7);
+    assert_eq!(rdd.number_of_splits(), 7);
+    let partitions = rdd.glom().collect()?;
+    assert!(partitions.iter().filter(|lines| !lines.is_empty()).count() > 1);
+    let mut read = partitions.into_iter().flatten().collect::<Vec<_>>();
+    read.sort();
+    let mut expected = lines;
+    expected.sort();
+    assert_eq!(read, expected);
+
+    let single = sc.text_file(dir.path().join("b.txt"), 3);
+    assert_eq!(single.count()?, 400);
+    Ok(())
+}
+
+#[test]
+fn test_compressed_inputs() -> Result<()> {
+    use std::io::Write;
+
+    let sc = CONTEXT.clone();
+    let dir = tempfile::tempdir().unwrap();
+    let text = (0..500).map(|i| format!("{}\n", i)).collect::<String>();
+    let mut gz = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+    gz.write_all(text.as_bytes()).unwrap();
+    fs::write(dir.path().join("a.log.gz"), gz.finish().unwrap()).unwrap();
+    let zst = zstd::stream::encode_all(text.as_bytes(), 0).unwrap();
+    fs::write(dir.path().join("b.log.zst"), &zst).unwrap();
+
+    let lines = sc.text_file(dir.path(), 4);
+    assert_eq!(lines.count()?, 1000);
+    assert_eq!(
+        lines
+            .map(Fn!(|line: String| line.parse::<u32>().unwrap()))
+            .sum()?,
+        2.0 * (0..500).sum::<u32>() as f64
+    );
+
+    // Whole files and lines through the reader configuration, with an explicit codec.
+    let config = LocalFsReaderConfig::new(dir.path().join("b.log.zst"))
+        .codec(io::CodecSelection::Codec(io::Codec::Zstd));
+    let files = sc
+        .read_source(config, Fn!(|bytes: Vec<u8>| bytes.len()))
+        .collect()?;
+    assert_eq!(files, vec![text.len()]);
+    let mut config = LocalFsReaderConfig::new(dir.path()).num_partitions_per_executor(2);
+    config.filter_extension("gz");
+    let numbers = sc
+        .read_source(config, Fn!(|line: String| line.parse::<u64>().unwrap()))
+        .collect()?;
+    assert_eq!(numbers, (0..500).collect::<Vec<_>>());
+
+    // A corrupt file fails the action instead of its task.
+    let corrupt = dir.path().join("corrupt.gz");
+    fs::write(&corrupt, b"not gzip").unwrap();
+    let files = sc.read_source(
+        LocalFsReaderConfig::new(&corrupt),
+        Fn!(|bytes: Vec<u8>| bytes.len()),
+    );
+    assert!(matches!(files.collect(), Err(Error::TaskFailed(_))));
+    assert!(matches!(
+        sc.text_file(&corrupt, 1).count(),
+        Err(Error::TaskFailed(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_partitioned_inputs() -> Result<()> {
+    let sc = CONTEXT.clone();
+    let dir = tempfile::tempdir().unwrap();
+    let write = |path: &str, text: &str| {
+        let path = dir.path().join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, text).unwrap();
+    };
+    write("table/date=2026-01-01/country=fr/part-0", "a\nb\n");
+    write("table/date=2026-01-02/country=de/part-0", "c\n");
+    write("table/date=2026-01-02/country=de/_SUCCESS", "");
+    write("other/top.txt", "d\n");
+
+    let values = |date: &str, country: &str| {
+        vec![
+            ("date".to_owned(), date.to_owned()),
+            ("country".to_owned(), country.to_owned()),
+        ]
+    };
+    let config = LocalFsReaderConfig::new(dir.path().join("table"))
+        .add_path(dir.path().join("other"))
+        .recursive(true)
+        .num_partitions_per_executor(2);
+    let mut lines = sc
+        .read_source(config, Fn!(|line: (String, io::PartitionValues)| line))
+        .collect()?;
+    lines.sort();
+    assert_eq!(
+        lines,
+        vec![
+            ("a".to_owned(), values("2026-01-01", "fr")),
+            ("b".to_owned(), values("2026-01-01", "fr")),
+            ("c".to_owned(), values("2026-01-02", "de")),
+            ("d".to_owned(), vec![]),
+        ]
+    );
+
+    let pattern = dir.path().join("table/date=2026-01-0*/*/part-*");
+    assert_eq!(sc.text_file(pattern, 2).count()?, 3);
+
+    let config = LocalFsReaderConfig::new(dir.path().join("table/*/country=de"));
+    let files = sc
+        .read_source(
+            config,
+            Fn!(|file: (std::path::PathBuf, io::PartitionValues)| file.1),
+        )
+        .collect()?;
+    assert_eq!(files, vec![values("2026-01-02", "de")]);
+    Ok(())
+}
+
+#[test]
+fn test_save_as_text_file() -> Result<()> {
+    use vega::io::{SaveMode, SUCCESS_MARKER};
+
+    let sc = CONTEXT.clone();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("out");
+    let names = || {
+        let mut names = fs::read_dir(&path)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    };
+
+    let rdd = sc.make_rdd(0..100, 4);
+    rdd.save_as_text_file(&path, SaveMode::ErrorIfExists)?;
+    assert_eq!(
+        names(),
+        vec![
+            SUCCESS_MARKER,
+            "part-00000",
+            "part-00001",
+            "part-00002",
+            "part-00003"
+        ]
+    );
+    let mut numbers = sc
+        .text_file(&path, 2)
+        .map(Fn!(|line: String| line.parse::<i32>().unwrap()))
+        .collect()?;
+    numbers.sort();
+    assert_eq!(numbers, (0..100).collect::<Vec<_>>());
+
+    match rdd.save_as_text_file(&path, SaveMode::ErrorIfExists) {
+        Err(Error::OutputExists(existing)) => assert_eq!(existing, path),
+        other => panic!("expected an existing output error, got {:?}", other),
+    }
+
+    let format = Fn!(|i: i32| format!("n{}", i));
+    rdd.save_as_text_file_with(&path, SaveMode::Append, format)?;
+    assert_eq!(names().len(), 9);
+    let lines = sc.text_file(&path, 2).collect()?;
+    assert_eq!(lines.len(), 200);
+    assert_eq!(
+        lines.iter().filter(|line| line.starts_with('n')).count(),
+        100
+    );
+
+    sc.make_rdd(vec!["a".to_owned(), "b".to_owned()], 1)
+        .save_as_text_file(&path, SaveMode::Overwrite)?;
+    assert_eq!(names(), vec![SUCCESS_MARKER, "part-00000"]);
+    assert_eq!(
+        fs::read_to_string(path.join("part-00000")).unwrap(),
+        "a\nb\n"
+    );
+    Ok(())
+}